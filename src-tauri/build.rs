@@ -10,13 +10,51 @@ fn main() {
             .flag("-fobjc-arc")
             .compile("storekit");
         
+        // 编译登录项（SMAppService）桥接代码
+        cc::Build::new()
+            .file("src/autostart.m")
+            .flag("-fobjc-arc")
+            .compile("autostart");
+        
+        // 编译按流量计费网络检测（Network.framework）桥接代码
+        cc::Build::new()
+            .file("src/network.m")
+            .flag("-fobjc-arc")
+            .compile("network");
+        
+        // 编译 AppleScript 命令桥接代码
+        cc::Build::new()
+            .file("src/scripting.m")
+            .flag("-fobjc-arc")
+            .compile("scripting");
+        
+        // 登录项按名称查找随应用打包在 Contents/Library/LaunchAgents 下的 plist，
+        // 缺少文件或打包配置时登录项无法注册，在构建时就报错
+        let agent_plist = "macos/com.filesortify.app.autostart.plist";
+        if !std::path::Path::new(agent_plist).exists() {
+            panic!("Missing {}, required by src/autostart.m", agent_plist);
+        }
+        let bundle_config = std::fs::read_to_string("tauri.conf.json").unwrap_or_default();
+        if !bundle_config.contains("Library/LaunchAgents/com.filesortify.app.autostart.plist") {
+            panic!("tauri.conf.json must bundle {} into Library/LaunchAgents", agent_plist);
+        }
+        
         // 链接系统框架
         println!("cargo:rustc-link-lib=framework=Foundation");
         println!("cargo:rustc-link-lib=framework=StoreKit");
+        println!("cargo:rustc-link-lib=framework=ServiceManagement");
+        println!("cargo:rustc-link-lib=framework=Network");
         
         // 告诉cargo重新构建如果这些文件改变了
         println!("cargo:rerun-if-changed=src/storekit.m");
         println!("cargo:rerun-if-changed=src/storekit.h");
+        println!("cargo:rerun-if-changed=src/autostart.m");
+        println!("cargo:rerun-if-changed=src/autostart.h");
+        println!("cargo:rerun-if-changed=macos/com.filesortify.app.autostart.plist");
+        println!("cargo:rerun-if-changed=src/network.m");
+        println!("cargo:rerun-if-changed=src/network.h");
+        println!("cargo:rerun-if-changed=src/scripting.m");
+        println!("cargo:rerun-if-changed=src/scripting.h");
     }
     
     tauri_build::build()
@@ -0,0 +1,47 @@
+use lazy_static::lazy_static;
+use std::path::PathBuf;
+
+// 环境变量和命令行参数，用于把配置和数据放到其他位置（测试、多配置、管理员部署）
+pub const CONFIG_DIR_ENV: &str = "FILESORTIFY_CONFIG_DIR";
+pub const CONFIG_DIR_ARG: &str = "--config-dir";
+
+lazy_static! {
+    static ref CONFIG_DIR_OVERRIDE: Option<PathBuf> = resolve_override();
+}
+
+/// 应用数据目录：命令行参数优先，其次是环境变量，最后是系统配置目录下的 fileSortify
+pub fn app_data_dir() -> Option<PathBuf> {
+    if let Some(dir) = CONFIG_DIR_OVERRIDE.as_ref() {
+        return Some(dir.clone());
+    }
+    dirs::config_dir().map(|config_dir| config_dir.join("fileSortify"))
+}
+
+fn resolve_override() -> Option<PathBuf> {
+    let dir = config_dir_from_args(std::env::args().skip(1))
+        .or_else(|| std::env::var(CONFIG_DIR_ENV).ok())
+        .filter(|dir| !dir.trim().is_empty())?;
+
+    let dir = PathBuf::from(dir.trim());
+    // 相对路径按启动时的工作目录解析，避免之后工作目录变化导致位置漂移
+    let dir = if dir.is_relative() {
+        std::env::current_dir().map(|cwd| cwd.join(&dir)).unwrap_or(dir)
+    } else {
+        dir
+    };
+    log::info!("Using config directory override: {}", dir.display());
+    Some(dir)
+}
+
+// 支持 --config-dir <dir> 和 --config-dir=<dir> 两种写法
+fn config_dir_from_args(mut args: impl Iterator<Item = String>) -> Option<String> {
+    while let Some(arg) = args.next() {
+        if arg == CONFIG_DIR_ARG {
+            return args.next();
+        }
+        if let Some(value) = arg.strip_prefix(CONFIG_DIR_ARG).and_then(|rest| rest.strip_prefix('=')) {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
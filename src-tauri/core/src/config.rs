@@ -0,0 +1,1050 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use crate::i18n::{t, t_format};
+use crate::config_migration::{self, CURRENT_CONFIG_VERSION};
+use crate::hooks::ScriptHooks;
+
+lazy_static! {
+    // 后台线程和命令会同时读写配置文件，读改写过程需要串行化
+    static ref CONFIG_WRITE_LOCK: Mutex<()> = Mutex::new(());
+    // 统计信息单独保存，整理文件时不改写配置文件，避免触发配置文件监听
+    static ref STATS_WRITE_LOCK: Mutex<()> = Mutex::new(());
+    static ref HOST: RwLock<Option<Box<dyn ConfigHost>>> = RwLock::new(None);
+}
+
+/// 由使用配置的应用提供的扩展：新配置文件使用的格式、管理员下发的托管配置。
+/// 没有注册时使用默认格式，不叠加托管配置
+pub trait ConfigHost: Send + Sync {
+    fn preferred_format(&self) -> ConfigFormat {
+        ConfigFormat::default()
+    }
+    
+    fn apply_managed(&self, _config: &mut Config) {}
+    
+    /// 保存前去掉 apply_managed 叠加的内容；read_user 读取配置文件中用户自己的配置
+    fn strip_managed(&self, _config: &mut Config, _read_user: &dyn Fn() -> Config) {}
+}
+
+/// 注册应用层的配置扩展，在第一次加载配置之前调用
+pub fn set_host(host: impl ConfigHost + 'static) {
+    *HOST.write().unwrap() = Some(Box::new(host));
+}
+
+// 路径配置和状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathConfig {
+    pub id: String,
+    pub path: String,
+    pub name: String,
+    #[serde(rename = "isMonitoring")]
+    pub is_monitoring: bool,
+    #[serde(rename = "autoOrganize")]
+    pub auto_organize: bool,
+    pub stats: PathStats,
+    #[serde(rename = "customCategories")]
+    pub custom_categories: Option<HashMap<String, Vec<String>>>,
+    #[serde(rename = "excludePatterns")]
+    pub exclude_patterns: Option<Vec<String>>,
+    // 可切换的备用规则集，例如“报税季”和“平时”
+    #[serde(rename = "ruleSets", default)]
+    pub rule_sets: Vec<RuleSet>,
+    // 手动指定的规则集ID；为空时按计划自动选择，都不匹配则使用全局分类
+    #[serde(rename = "activeRuleSet", default)]
+    pub active_rule_set: Option<String>,
+    // 监控到新文件时先询问用户，确认后才移动
+    #[serde(rename = "confirmBeforeMove", default)]
+    pub confirm_before_move: bool,
+    // 等待确认的最长时间（秒），超时后按 confirmTimeoutAction 处理
+    #[serde(rename = "confirmTimeoutSecs", default = "default_confirm_timeout_secs")]
+    pub confirm_timeout_secs: u64,
+    #[serde(rename = "confirmTimeoutAction", default)]
+    pub confirm_timeout_action: crate::pending::TimeoutAction,
+    // 关闭后该路径不再弹出系统通知（适合构建输出、相机导入等频繁变化的文件夹）
+    #[serde(rename = "notificationsEnabled", default = "default_notifications_enabled")]
+    pub notifications_enabled: bool,
+    // 恢复监控时（例如应用启动后）先整理监控停止期间下载的文件
+    #[serde(rename = "catchUpOnStart", default = "default_catch_up_on_start")]
+    pub catch_up_on_start: bool,
+    // 补整理时只处理最近几天修改的文件，更早的文件可能是有意留在原处的；为空时不限
+    #[serde(rename = "catchUpMaxAgeDays", default)]
+    pub catch_up_max_age_days: Option<u64>,
+}
+
+fn default_notifications_enabled() -> bool {
+    true
+}
+
+fn default_catch_up_on_start() -> bool {
+    true
+}
+
+fn default_confirm_timeout_secs() -> u64 {
+    120
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleSet {
+    pub id: String,
+    pub name: String,
+    pub categories: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub schedule: Option<RuleSetSchedule>,
+}
+
+/// 规则集的生效时间，各条件之间是“且”的关系，空列表表示不限
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleSetSchedule {
+    // 月份 1-12
+    #[serde(default)]
+    pub months: Vec<u32>,
+    // 星期 1-7（周一为 1）
+    #[serde(default)]
+    pub weekdays: Vec<u32>,
+    // "HH:MM"，结束时间早于开始时间表示跨夜
+    #[serde(rename = "startTime", default)]
+    pub start_time: Option<String>,
+    #[serde(rename = "endTime", default)]
+    pub end_time: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathStats {
+    #[serde(rename = "filesOrganized")]
+    pub files_organized: u64,
+    #[serde(rename = "lastOrganized")]
+    pub last_organized: Option<String>,
+    #[serde(rename = "monitoringSince")]
+    pub monitoring_since: Option<String>,
+    // 按本地日期统计的当天整理数量
+    #[serde(rename = "organizedToday", default)]
+    pub organized_today: u64,
+    #[serde(rename = "organizedTodayDate", default)]
+    pub organized_today_date: Option<chrono::NaiveDate>,
+}
+
+impl PathConfig {
+    pub fn new(path: String, name: Option<String>) -> Self {
+        // 未指定名称时使用文件夹名
+        let name = name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| {
+                Path::new(&path)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone())
+            });
+        
+        PathConfig {
+            id: uuid::Uuid::new_v4().to_string(),
+            path,
+            name,
+            is_monitoring: false,
+            auto_organize: false,
+            stats: PathStats::default(),
+            custom_categories: None,
+            exclude_patterns: None,
+            rule_sets: Vec::new(),
+            active_rule_set: None,
+            confirm_before_move: false,
+            confirm_timeout_secs: default_confirm_timeout_secs(),
+            confirm_timeout_action: crate::pending::TimeoutAction::default(),
+            notifications_enabled: default_notifications_enabled(),
+            catch_up_on_start: default_catch_up_on_start(),
+            catch_up_max_age_days: None,
+        }
+    }
+    
+    /// 当前生效的规则集：手动指定的优先，其次是计划匹配的第一个
+    pub fn current_rule_set(&self, now: &chrono::DateTime<chrono::Local>) -> Option<&RuleSet> {
+        if let Some(active_id) = &self.active_rule_set {
+            return self.rule_sets.iter().find(|r| &r.id == active_id);
+        }
+        self.rule_sets
+            .iter()
+            .find(|r| r.schedule.as_ref().map(|s| s.is_active(now)).unwrap_or(false))
+    }
+}
+
+impl RuleSetSchedule {
+    pub fn is_active(&self, now: &chrono::DateTime<chrono::Local>) -> bool {
+        use chrono::{Datelike, NaiveTime, Timelike};
+        
+        if !self.months.is_empty() && !self.months.contains(&now.month()) {
+            return false;
+        }
+        if !self.weekdays.is_empty() && !self.weekdays.contains(&now.weekday().number_from_monday()) {
+            return false;
+        }
+        
+        let parse = |value: &Option<String>| value.as_deref().and_then(|v| NaiveTime::parse_from_str(v, "%H:%M").ok());
+        let current = NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or_default();
+        match (parse(&self.start_time), parse(&self.end_time)) {
+            (Some(start), Some(end)) if start <= end => current >= start && current < end,
+            (Some(start), Some(end)) => current >= start || current < end,
+            (Some(start), None) => current >= start,
+            (None, Some(end)) => current < end,
+            (None, None) => true,
+        }
+    }
+    
+    fn validate(&self) -> Result<(), String> {
+        for time in [&self.start_time, &self.end_time].into_iter().flatten() {
+            if chrono::NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(t_format("invalid_schedule_time", &[time]));
+            }
+        }
+        if let Some(month) = self.months.iter().find(|m| !(1..=12).contains(*m)) {
+            return Err(t_format("invalid_schedule_month", &[&month.to_string()]));
+        }
+        if let Some(day) = self.weekdays.iter().find(|d| !(1..=7).contains(*d)) {
+            return Err(t_format("invalid_schedule_weekday", &[&day.to_string()]));
+        }
+        Ok(())
+    }
+}
+
+impl PathStats {
+    pub fn record_organized(&mut self, count: u64) {
+        self.files_organized += count;
+        self.last_organized = Some(chrono::Utc::now().to_rfc3339());
+        
+        // 跨天后重新计数
+        let today = chrono::Local::now().date_naive();
+        if self.organized_today_date != Some(today) {
+            self.organized_today = 0;
+            self.organized_today_date = Some(today);
+        }
+        self.organized_today += count;
+    }
+    
+    /// 今天整理的文件数，最后一次整理不在今天时为 0
+    pub fn organized_today(&self) -> u64 {
+        if self.organized_today_date == Some(chrono::Local::now().date_naive()) {
+            self.organized_today
+        } else {
+            0
+        }
+    }
+}
+
+/// 分类的显示元数据，供规则编辑器和通知使用
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CategoryMeta {
+    pub icon: Option<String>,
+    pub color: Option<String>,
+    pub description: Option<String>,
+}
+
+/// 内置分类的稳定ID，显示名称通过 `category_<id>` 翻译键获得
+pub const BUILTIN_CATEGORY_IDS: &[&str] = &[
+    "images", "documents", "spreadsheets", "presentations", "audio",
+    "video", "archives", "programs", "code", "fonts",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // 分类ID -> 扩展名列表；ID与界面语言无关
+    pub categories: HashMap<String, Vec<String>>,
+    // 分类ID -> 显示名称
+    #[serde(rename = "categoryNames", default)]
+    pub category_names: HashMap<String, String>,
+    // 分类ID -> 磁盘上的文件夹名；未设置时沿用已存在的文件夹，与界面语言无关
+    #[serde(rename = "folderNames", default)]
+    pub folder_names: HashMap<String, String>,
+    // 分类ID -> 显示元数据；未设置的字段使用内置默认值
+    #[serde(rename = "categoryMeta", default)]
+    pub category_meta: HashMap<String, CategoryMeta>,
+    pub version: String,
+    pub description: String,
+    pub paths: Option<Vec<PathConfig>>,
+    #[serde(rename = "autoOrganize")]
+    pub auto_organize: Option<bool>,
+    #[serde(rename = "notificationEnabled")]
+    pub notification_enabled: Option<bool>,
+    pub rules: Option<Vec<serde_json::Value>>,
+    // 移动和整理前后运行的用户脚本
+    #[serde(default, skip_serializing_if = "ScriptHooks::is_empty")]
+    pub hooks: ScriptHooks,
+    // 分类ID -> 远程目标；整理到本地后再上传一份
+    #[serde(rename = "categoryDestinations", default, skip_serializing_if = "HashMap::is_empty")]
+    pub category_destinations: HashMap<String, RemoteDestination>,
+    // 整理的文件夹中的符号链接和目录联接如何处理
+    #[serde(rename = "symlinkPolicy", default)]
+    pub symlink_policy: SymlinkPolicy,
+}
+
+/// 符号链接（以及 Windows 的目录联接）的处理方式。链接指向的内容从不读取或遍历
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SymlinkPolicy {
+    // 留在原处，不整理
+    #[default]
+    Skip,
+    // 按链接自身的名称匹配分类，只移动链接，指向的文件不动
+    MoveLink,
+}
+
+/// 分类的远程目标，例如把收据上传到 Google Drive。上传由应用完成，本地整理不受影响
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteDestination {
+    // 提供方 ID，例如 "google_drive"、"dropbox"、"onedrive"
+    pub provider: String,
+    // 远程文件夹，例如 "Receipts/2024"；为空时上传到根目录
+    #[serde(default)]
+    pub folder: String,
+}
+
+/// 配置文件的存储格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigFormat {
+    #[default]
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl ConfigFormat {
+    pub const ALL: [ConfigFormat; 3] = [ConfigFormat::Json, ConfigFormat::Toml, ConfigFormat::Yaml];
+    
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ConfigFormat::Json => "json",
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+        }
+    }
+    
+    /// 根据文件扩展名识别格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "json" => Some(ConfigFormat::Json),
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            _ => None,
+        }
+    }
+    
+    pub fn parse(&self, content: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let value = match self {
+            ConfigFormat::Json => serde_json::from_str(content)?,
+            ConfigFormat::Toml => toml::from_str(content)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(content)?,
+        };
+        Ok(value)
+    }
+    
+    fn serialize(&self, config: &Config) -> Result<String, Box<dyn std::error::Error>> {
+        let content = match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(config)?,
+            ConfigFormat::Toml => toml::to_string_pretty(config)?,
+            ConfigFormat::Yaml => serde_yaml::to_string(config)?,
+        };
+        Ok(content)
+    }
+}
+
+impl Config {
+    /// 加载用户配置，并叠加管理员下发的托管配置
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config = Self::load_user()?;
+        if let Some(host) = HOST.read().unwrap().as_ref() {
+            host.apply_managed(&mut config);
+        }
+        config.apply_stored_stats();
+        Ok(config)
+    }
+    
+    // 用统计文件中的值覆盖配置文件里的统计；统计文件中还没有记录的路径保留配置文件中的旧值
+    fn apply_stored_stats(&mut self) {
+        let stored = Self::load_stats();
+        if stored.is_empty() {
+            return;
+        }
+        for path_config in self.paths.iter_mut().flatten() {
+            if let Some(stats) = stored.get(&path_config.id) {
+                path_config.stats = stats.clone();
+            }
+        }
+    }
+    
+    // 路径ID -> 统计信息
+    fn load_stats() -> HashMap<String, PathStats> {
+        fs::read_to_string(Self::get_stats_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+    
+    fn save_stats(stats: &HashMap<String, PathStats>) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::get_stats_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(stats)?)?;
+        Ok(())
+    }
+    
+    fn get_stats_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("path_stats.json")
+        } else {
+            PathBuf::from("file_organizer_path_stats.json")
+        }
+    }
+    
+    fn load_user() -> Result<Self, Box<dyn std::error::Error>> {
+        let config_path = Self::get_config_path();
+        
+        if config_path.exists() {
+            let content = fs::read_to_string(&config_path)?;
+            let format = ConfigFormat::from_path(&config_path).unwrap_or_default();
+            let raw = format.parse(&content)?;
+            let outcome = config_migration::migrate(raw)?;
+            let config: Config = serde_json::from_value(outcome.value)?;
+            
+            // 升级了旧版本配置时，先备份原文件再写回新格式
+            if outcome.migrated {
+                let backup_path = config_migration::backup_original(&config_path, &outcome.from_version)?;
+                log::info!("Backed up config before migration: {}", backup_path.display());
+                config.save()?;
+            }
+            
+            Ok(config)
+        } else {
+            let config = Self::default();
+            config.save()?;
+            Ok(config)
+        }
+    }
+    
+    /// 读取任意位置的配置文件（用于导入），按扩展名识别格式并在内存中升级版本，不写回磁盘
+    pub fn read_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        let format = ConfigFormat::from_path(path).unwrap_or_default();
+        let outcome = config_migration::migrate(format.parse(&content)?)?;
+        Ok(serde_json::from_value(outcome.value)?)
+    }
+    
+    /// 保存到现有配置文件（保持其格式）；还没有配置文件时使用偏好格式
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config_path = Self::get_config_path();
+        
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        
+        let format = ConfigFormat::from_path(&config_path).unwrap_or_default();
+        // load 返回的配置叠加了托管配置，保存时换回用户自己的值，避免管理员的值永久写入用户配置
+        let content = match HOST.read().unwrap().as_ref() {
+            Some(host) => {
+                let read_user = || {
+                    if config_path.exists() {
+                        Self::read_from(&config_path).unwrap_or_default()
+                    } else {
+                        Self::default()
+                    }
+                };
+                let mut config = self.clone();
+                host.strip_managed(&mut config, &read_user);
+                format.serialize(&config)?
+            }
+            None => format.serialize(self)?,
+        };
+        fs::write(&config_path, content)?;
+        
+        Ok(())
+    }
+    
+    /// 把配置转换为另一种格式保存，并删除其他格式的旧文件
+    pub fn convert_to(format: ConfigFormat) -> Result<(), Box<dyn std::error::Error>> {
+        let config = Self::load()?;
+        let target_path = Self::path_for_format(format);
+        
+        if let Some(parent) = target_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&target_path, format.serialize(&config)?)?;
+        
+        for path in Self::candidate_paths() {
+            if path != target_path && path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// 当前使用的配置文件路径：已存在的配置文件（多个时取最近修改的），否则为偏好格式的路径
+    pub fn get_config_path() -> PathBuf {
+        Self::candidate_paths()
+            .into_iter()
+            .filter(|p| p.exists())
+            .max_by_key(|p| fs::metadata(p).and_then(|m| m.modified()).ok())
+            .unwrap_or_else(|| Self::path_for_format(Self::preferred_format()))
+    }
+    
+    /// 判断文件名是否是某种格式的配置文件
+    pub fn is_config_file(path: &Path) -> bool {
+        Self::candidate_paths()
+            .iter()
+            .any(|candidate| candidate.file_name() == path.file_name())
+    }
+    
+    fn path_for_format(format: ConfigFormat) -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join(format!("config.{}", format.extension()))
+        } else {
+            PathBuf::from(format!("file_organizer_config.{}", format.extension()))
+        }
+    }
+    
+    pub fn candidate_paths() -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = ConfigFormat::ALL.iter().map(|f| Self::path_for_format(*f)).collect();
+        paths.push(Self::path_for_format(ConfigFormat::Yaml).with_extension("yml"));
+        paths
+    }
+    
+    fn preferred_format() -> ConfigFormat {
+        HOST.read()
+            .unwrap()
+            .as_ref()
+            .map(|host| host.preferred_format())
+            .unwrap_or_default()
+    }
+    
+    /// 分类的显示名称；未设置时内置分类使用当前语言翻译，自定义分类使用ID本身
+    pub fn display_name(&self, category_id: &str) -> String {
+        if let Some(name) = self.category_names.get(category_id) {
+            return name.clone();
+        }
+        if BUILTIN_CATEGORY_IDS.contains(&category_id) {
+            return t(&format!("category_{}", category_id));
+        }
+        category_id.to_string()
+    }
+    
+    /// 分类可能对应的文件夹名：固定的文件夹名、显示名称以及内置分类在各语言下的名称
+    pub fn folder_name_candidates(&self, category_id: &str) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        let mut push = |name: String| {
+            if !name.is_empty() && !names.contains(&name) {
+                names.push(name);
+            }
+        };
+        
+        if let Some(name) = self.folder_names.get(category_id) {
+            push(name.clone());
+        }
+        push(self.display_name(category_id));
+        for name in crate::i18n::t_all(&format!("category_{}", category_id)) {
+            push(name);
+        }
+        names
+    }
+    
+    /// 分类在 base 目录下使用的文件夹名
+    ///
+    /// 优先使用固定的文件夹名，其次是已经存在的同分类文件夹（可能是以其他语言创建的），
+    /// 都没有时才使用当前的显示名称，避免切换语言后产生两套文件夹
+    pub fn folder_name(&self, category_id: &str, base: &Path) -> String {
+        if let Some(name) = self.folder_names.get(category_id) {
+            return name.clone();
+        }
+        self.folder_name_candidates(category_id)
+            .into_iter()
+            .find(|name| base.join(name).is_dir())
+            .unwrap_or_else(|| self.display_name(category_id))
+    }
+    
+    /// 分类的元数据：用户设置优先，缺失字段回退到内置分类的默认值
+    pub fn category_meta(&self, category_id: &str) -> CategoryMeta {
+        let defaults = builtin_category_meta(category_id);
+        match self.category_meta.get(category_id) {
+            Some(meta) => CategoryMeta {
+                icon: meta.icon.clone().or(defaults.icon),
+                color: meta.color.clone().or(defaults.color),
+                description: meta.description.clone().or(defaults.description),
+            },
+            None => defaults,
+        }
+    }
+    
+    /// 所有分类的元数据（已合并默认值）
+    pub fn all_category_meta(&self) -> HashMap<String, CategoryMeta> {
+        self.categories
+            .keys()
+            .map(|id| (id.clone(), self.category_meta(id)))
+            .collect()
+    }
+    
+    pub fn set_category_meta(&mut self, category_id: &str, meta: CategoryMeta) -> Result<(), String> {
+        if !self.categories.contains_key(category_id) {
+            return Err(t_format("category_not_found", &[category_id]));
+        }
+        if let Some(color) = &meta.color {
+            if !is_valid_color(color) {
+                return Err(t_format("invalid_category_color", &[color]));
+            }
+        }
+        
+        if meta == CategoryMeta::default() {
+            self.category_meta.remove(category_id);
+        } else {
+            self.category_meta.insert(category_id.to_string(), meta);
+        }
+        Ok(())
+    }
+    
+    pub fn destination_for(&self, category_id: &str) -> Option<&RemoteDestination> {
+        self.category_destinations.get(category_id)
+    }
+    
+    /// 设置或清除分类的远程目标，提供方是否可用由调用方检查
+    pub fn set_category_destination(&mut self, category_id: &str, destination: Option<RemoteDestination>) -> Result<(), String> {
+        if !self.categories.contains_key(category_id) {
+            return Err(t_format("category_not_found", &[category_id]));
+        }
+        match destination {
+            Some(mut destination) => {
+                destination.folder = destination.folder.trim().trim_matches('/').to_string();
+                self.category_destinations.insert(category_id.to_string(), destination);
+            }
+            None => {
+                self.category_destinations.remove(category_id);
+            }
+        }
+        Ok(())
+    }
+    
+    pub fn add_category(&mut self, name: String, extensions: Vec<String>) {
+        self.categories.insert(name, extensions);
+    }
+    
+    pub fn remove_category(&mut self, name: &str) -> bool {
+        self.category_names.remove(name);
+        self.folder_names.remove(name);
+        self.category_meta.remove(name);
+        self.category_destinations.remove(name);
+        self.categories.remove(name).is_some()
+    }
+    
+    pub fn update_category(&mut self, name: String, extensions: Vec<String>) -> bool {
+        match self.categories.get_mut(&name) {
+            Some(existing) => {
+                *existing = extensions;
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// 把扩展名归入指定分类，并从原来所属的分类中移除；返回原分类ID
+    pub fn assign_extension(&mut self, extension: &str, category_id: &str) -> Result<Option<String>, String> {
+        let trimmed = extension.trim().trim_start_matches('.').to_lowercase();
+        if trimmed.is_empty() {
+            return Err(t_format("invalid_extension", &[extension]));
+        }
+        let extension = format!(".{}", trimmed);
+        if !self.categories.contains_key(category_id) {
+            return Err(t_format("category_not_found", &[category_id]));
+        }
+        
+        let mut previous = None;
+        for (id, extensions) in self.categories.iter_mut() {
+            if id != category_id && extensions.iter().any(|e| e.eq_ignore_ascii_case(&extension)) {
+                extensions.retain(|e| !e.eq_ignore_ascii_case(&extension));
+                previous = Some(id.clone());
+            }
+        }
+        if let Some(extensions) = self.categories.get_mut(category_id) {
+            if !extensions.iter().any(|e| e.eq_ignore_ascii_case(&extension)) {
+                extensions.push(extension);
+            }
+        }
+        Ok(previous)
+    }
+    
+    // 路径管理
+    
+    pub fn list_paths(&self) -> Vec<PathConfig> {
+        self.paths.clone().unwrap_or_default()
+    }
+    
+    /// 所有已配置路径今天整理的文件总数
+    pub fn files_organized_today(&self) -> u64 {
+        self.paths.iter().flatten().map(|path| path.stats.organized_today()).sum()
+    }
+    
+    pub fn find_path(&self, id: &str) -> Option<&PathConfig> {
+        self.paths.as_ref()?.iter().find(|p| p.id == id)
+    }
+    
+    pub fn find_path_mut(&mut self, id: &str) -> Option<&mut PathConfig> {
+        self.paths.as_mut()?.iter_mut().find(|p| p.id == id)
+    }
+    
+    /// 校验路径存在、是文件夹，并且不与其他已配置路径重复或互相嵌套
+    pub fn validate_path(&self, path: &str, ignore_id: Option<&str>) -> Result<PathBuf, String> {
+        let candidate = Path::new(path);
+        if !candidate.exists() {
+            return Err(t_format("path_not_found", &[path]));
+        }
+        if !candidate.is_dir() {
+            return Err(t_format("path_not_directory", &[path]));
+        }
+        let candidate = candidate
+            .canonicalize()
+            .map_err(|e| t_format("path_resolve_failed", &[path, &e.to_string()]))?;
+        
+        for existing in self.paths.iter().flatten() {
+            if Some(existing.id.as_str()) == ignore_id {
+                continue;
+            }
+            // 已配置但当前不存在的路径按原样比较
+            let existing_path = Path::new(&existing.path)
+                .canonicalize()
+                .unwrap_or_else(|_| PathBuf::from(&existing.path));
+            
+            if candidate == existing_path {
+                return Err(t_format("path_already_added", &[path]));
+            }
+            if candidate.starts_with(&existing_path) || existing_path.starts_with(&candidate) {
+                return Err(t_format("path_nested", &[path, &existing.path]));
+            }
+        }
+        
+        Ok(candidate)
+    }
+    
+    /// 按文件夹路径查找路径配置（兼容尾部斜杠、符号链接等写法差异）
+    pub fn find_path_by_folder(&self, folder_path: &Path) -> Option<&PathConfig> {
+        let target = normalize_path(folder_path);
+        self.paths
+            .as_ref()?
+            .iter()
+            .find(|p| normalize_path(Path::new(&p.path)) == target)
+    }
+    
+    /// 某个文件夹是否显示通知：全局关闭时都不显示，未配置的文件夹默认显示
+    pub fn notifications_enabled_for(&self, folder_path: &Path) -> bool {
+        self.notification_enabled != Some(false)
+            && self
+                .find_path_by_folder(folder_path)
+                .map(|p| p.notifications_enabled)
+                .unwrap_or(true)
+    }
+    
+    /// 文件名是否匹配某个文件夹的排除模式；模式支持 * 和 ?，不区分大小写
+    pub fn is_excluded(&self, folder_path: &Path, file_name: &str) -> bool {
+        let file_name = file_name.to_lowercase();
+        self.find_path_by_folder(folder_path)
+            .and_then(|p| p.exclude_patterns.as_ref())
+            .map(|patterns| {
+                patterns
+                    .iter()
+                    .map(|pattern| pattern.trim())
+                    .filter(|pattern| !pattern.is_empty())
+                    .any(|pattern| matches_pattern(&pattern.to_lowercase(), &file_name))
+            })
+            .unwrap_or(false)
+    }
+    
+    /// 某个文件夹当前应使用的分类规则：有生效的规则集时用规则集，否则用全局分类
+    pub fn categories_for(&self, folder_path: &Path) -> &HashMap<String, Vec<String>> {
+        let now = chrono::Local::now();
+        self.find_path_by_folder(folder_path)
+            .and_then(|p| p.current_rule_set(&now))
+            .map(|r| &r.categories)
+            .unwrap_or(&self.categories)
+    }
+    
+    /// 读改写方式更新配置，与其他写入者互斥，避免并发写入时互相覆盖
+    pub fn update<F>(update: F) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut Config),
+    {
+        let _guard = CONFIG_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut config = Self::load()?;
+        update(&mut config);
+        config.save()
+    }
+    
+    /// 读改写方式更新某个文件夹的统计信息；该文件夹未配置时返回 None。
+    /// 统计写入单独的统计文件，不改写配置文件
+    pub fn update_path_stats<F>(folder_path: &Path, update: F) -> Result<Option<PathStats>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&mut PathStats),
+    {
+        let _guard = STATS_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let config = Self::load()?;
+        
+        let path_config = match config.find_path_by_folder(folder_path) {
+            Some(path_config) => path_config,
+            None => return Ok(None),
+        };
+        let mut stats = path_config.stats.clone();
+        update(&mut stats);
+        
+        let mut stored = Self::load_stats();
+        // 顺便清理已删除路径的统计
+        stored.retain(|id, _| config.paths.iter().flatten().any(|p| &p.id == id));
+        stored.insert(path_config.id.clone(), stats.clone());
+        Self::save_stats(&stored)?;
+        Ok(Some(stats))
+    }
+    
+    pub fn add_path(&mut self, path: String, name: Option<String>) -> Result<PathConfig, String> {
+        self.validate_path(&path, None)?;
+        
+        let path_config = PathConfig::new(path, name);
+        self.paths.get_or_insert_with(Vec::new).push(path_config.clone());
+        Ok(path_config)
+    }
+    
+    pub fn remove_path(&mut self, id: &str) -> Result<PathConfig, String> {
+        let paths = self.paths.get_or_insert_with(Vec::new);
+        let pos = paths
+            .iter()
+            .position(|p| p.id == id)
+            .ok_or_else(|| t("path_config_not_found"))?;
+        Ok(paths.remove(pos))
+    }
+    
+    /// 切换路径的规则集；传 None 恢复按计划自动选择
+    pub fn set_active_rule_set(&mut self, path_id: &str, rule_set_id: Option<String>) -> Result<PathConfig, String> {
+        let path_config = self
+            .find_path_mut(path_id)
+            .ok_or_else(|| t("path_config_not_found"))?;
+        if let Some(id) = &rule_set_id {
+            if !path_config.rule_sets.iter().any(|r| &r.id == id) {
+                return Err(t_format("rule_set_not_found", &[id]));
+            }
+        }
+        path_config.active_rule_set = rule_set_id;
+        Ok(path_config.clone())
+    }
+    
+    fn validate_rule_sets(path_config: &PathConfig) -> Result<(), String> {
+        for rule_set in &path_config.rule_sets {
+            if let Some(schedule) = &rule_set.schedule {
+                schedule.validate()?;
+            }
+        }
+        if let Some(id) = &path_config.active_rule_set {
+            if !path_config.rule_sets.iter().any(|r| &r.id == id) {
+                return Err(t_format("rule_set_not_found", &[id]));
+            }
+        }
+        Ok(())
+    }
+    
+    pub fn update_path(&mut self, updated: PathConfig) -> Result<PathConfig, String> {
+        let current_path = self
+            .find_path(&updated.id)
+            .map(|p| p.path.clone())
+            .ok_or_else(|| t("path_config_not_found"))?;
+        
+        // 修改了路径本身时重新校验
+        if current_path != updated.path {
+            self.validate_path(&updated.path, Some(&updated.id))?;
+        }
+        Self::validate_rule_sets(&updated)?;
+        
+        if let Some(existing) = self.find_path_mut(&updated.id) {
+            *existing = updated.clone();
+        }
+        Ok(updated)
+    }
+}
+
+// 内置分类的默认图标和颜色
+fn builtin_category_meta(category_id: &str) -> CategoryMeta {
+    let (icon, color) = match category_id {
+        "images" => ("🖼️", "#4F9DDE"),
+        "documents" => ("📄", "#5B6BC0"),
+        "spreadsheets" => ("📊", "#2E9E5B"),
+        "presentations" => ("📽️", "#E07B39"),
+        "audio" => ("🎵", "#C2509B"),
+        "video" => ("🎬", "#D9534F"),
+        "archives" => ("🗜️", "#8D6E63"),
+        "programs" => ("💿", "#607D8B"),
+        "code" => ("💻", "#7E57C2"),
+        "fonts" => ("🔤", "#26A69A"),
+        _ => return CategoryMeta::default(),
+    };
+    
+    CategoryMeta {
+        icon: Some(icon.to_string()),
+        color: Some(color.to_string()),
+        description: Some(t(&format!("category_{}_description", category_id))),
+    }
+}
+
+// 颜色只接受 #RGB 或 #RRGGBB 格式
+fn is_valid_color(color: &str) -> bool {
+    let hex = match color.strip_prefix('#') {
+        Some(hex) => hex,
+        None => return false,
+    };
+    (hex.len() == 3 || hex.len() == 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+// 简单的通配符匹配：* 匹配任意多个字符，? 匹配一个字符
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // 最近一个 * 的位置，以及它当时对应到名称的位置
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = star {
+            // 让 * 多匹配一个字符后重试
+            p = star_p + 1;
+            n = star_n + 1;
+            star = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn normalize_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.components().collect())
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut categories = HashMap::new();
+        
+        categories.insert("images".to_string(), vec![
+            ".jpg".to_string(), ".jpeg".to_string(), ".png".to_string(), 
+            ".gif".to_string(), ".bmp".to_string(), ".svg".to_string(), 
+            ".webp".to_string(), ".tiff".to_string(), ".ico".to_string()
+        ]);
+        
+        categories.insert("documents".to_string(), vec![
+            ".pdf".to_string(), ".doc".to_string(), ".docx".to_string(), 
+            ".txt".to_string(), ".rtf".to_string(), ".pages".to_string(), 
+            ".odt".to_string(), ".epub".to_string()
+        ]);
+        
+        categories.insert("spreadsheets".to_string(), vec![
+            ".xls".to_string(), ".xlsx".to_string(), ".csv".to_string(), 
+            ".numbers".to_string(), ".ods".to_string()
+        ]);
+        
+        categories.insert("presentations".to_string(), vec![
+            ".ppt".to_string(), ".pptx".to_string(), ".key".to_string(), 
+            ".odp".to_string()
+        ]);
+        
+        categories.insert("audio".to_string(), vec![
+            ".mp3".to_string(), ".wav".to_string(), ".aac".to_string(), 
+            ".flac".to_string(), ".m4a".to_string(), ".ogg".to_string(), 
+            ".wma".to_string()
+        ]);
+        
+        categories.insert("video".to_string(), vec![
+            ".mp4".to_string(), ".avi".to_string(), ".mov".to_string(), 
+            ".mkv".to_string(), ".wmv".to_string(), ".flv".to_string(), 
+            ".webm".to_string(), ".m4v".to_string()
+        ]);
+        
+        categories.insert("archives".to_string(), vec![
+            ".zip".to_string(), ".rar".to_string(), ".7z".to_string(), 
+            ".tar".to_string(), ".gz".to_string(), ".bz2".to_string(), 
+            ".xz".to_string()
+        ]);
+        
+        categories.insert("programs".to_string(), vec![
+            ".dmg".to_string(), ".pkg".to_string(), ".app".to_string(), 
+            ".exe".to_string(), ".deb".to_string(), ".rpm".to_string()
+        ]);
+        
+        categories.insert("code".to_string(), vec![
+            ".py".to_string(), ".js".to_string(), ".html".to_string(), 
+            ".css".to_string(), ".java".to_string(), ".cpp".to_string(), 
+            ".c".to_string(), ".php".to_string(), ".rb".to_string(), 
+            ".go".to_string(), ".rs".to_string()
+        ]);
+        
+        categories.insert("fonts".to_string(), vec![
+            ".ttf".to_string(), ".otf".to_string(), ".woff".to_string(), 
+            ".woff2".to_string()
+        ]);
+        
+        // 显示名称在创建时按当前语言固定下来，之后切换语言不会改变文件夹名
+        let category_names = BUILTIN_CATEGORY_IDS
+            .iter()
+            .map(|id| (id.to_string(), t(&format!("category_{}", id))))
+            .collect();
+        
+        Config {
+            categories,
+            category_names,
+            folder_names: HashMap::new(),
+            category_meta: HashMap::new(),
+            version: CURRENT_CONFIG_VERSION.to_string(),
+            description: t("config_file_description"),
+            paths: Some(vec![]),
+            auto_organize: None,
+            notification_enabled: None,
+            rules: None,
+            hooks: ScriptHooks::default(),
+            category_destinations: HashMap::new(),
+            symlink_policy: SymlinkPolicy::default(),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_config() -> Config {
+        let mut config = Config::default();
+        let mut path = PathConfig::new("/tmp/downloads".to_string(), None);
+        path.stats.files_organized = 3;
+        config.paths = Some(vec![path]);
+        config.category_names.insert("images".to_string(), "Pictures".to_string());
+        config.folder_names.insert("images".to_string(), "Bilder".to_string());
+        config.category_meta.insert(
+            "images".to_string(),
+            CategoryMeta { icon: Some("🖼".to_string()), color: Some("#ff0000".to_string()), description: None },
+        );
+        config
+    }
+
+    #[test]
+    fn round_trips_every_format() {
+        let config = sample_config();
+        let expected = serde_json::to_value(&config).unwrap();
+
+        for format in ConfigFormat::ALL {
+            let content = format.serialize(&config).unwrap();
+            let outcome = config_migration::migrate(format.parse(&content).unwrap()).unwrap();
+            assert!(!outcome.migrated, "{:?} should already be current", format);
+            let parsed: Config = serde_json::from_value(outcome.value).unwrap();
+            assert_eq!(serde_json::to_value(&parsed).unwrap(), expected, "{:?} round trip", format);
+        }
+    }
+
+    #[test]
+    fn recognizes_format_from_extension() {
+        assert_eq!(ConfigFormat::from_path(Path::new("config.JSON")), Some(ConfigFormat::Json));
+        assert_eq!(ConfigFormat::from_path(Path::new("config.toml")), Some(ConfigFormat::Toml));
+        assert_eq!(ConfigFormat::from_path(Path::new("config.yml")), Some(ConfigFormat::Yaml));
+        assert_eq!(ConfigFormat::from_path(Path::new("config.ini")), None);
+    }
+}
@@ -0,0 +1,262 @@
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 当前配置文件的 schema 版本
+pub const CURRENT_CONFIG_VERSION: &str = "2.0";
+
+// 单步迁移：把 from 版本的配置升级到 to 版本
+type MigrationFn = fn(&mut Value) -> Result<(), String>;
+
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    apply: MigrationFn,
+}
+
+// 迁移步骤必须按版本顺序排列，每一步只负责相邻两个版本之间的升级
+const MIGRATIONS: &[Migration] = &[
+    Migration { from: "0", to: "1.0", apply: migrate_0_to_1_0 },
+    Migration { from: "1.0", to: "2.0", apply: migrate_1_0_to_2_0 },
+];
+
+/// 迁移结果
+pub struct MigrationOutcome {
+    pub value: Value,
+    pub from_version: String,
+    pub migrated: bool,
+}
+
+/// 将原始配置逐步升级到当前版本
+pub fn migrate(mut value: Value) -> Result<MigrationOutcome, String> {
+    if !value.is_object() {
+        return Err("Config root must be a JSON object".to_string());
+    }
+
+    let from_version = read_version(&value);
+    let mut version = from_version.clone();
+
+    if compare_versions(&version, CURRENT_CONFIG_VERSION) == std::cmp::Ordering::Greater {
+        // 来自更新版本的配置，原样加载，避免破坏新版本写入的数据
+        log::warn!(
+            "Config version {} is newer than supported version {}",
+            version, CURRENT_CONFIG_VERSION
+        );
+        return Ok(MigrationOutcome { value, from_version, migrated: false });
+    }
+
+    while compare_versions(&version, CURRENT_CONFIG_VERSION) == std::cmp::Ordering::Less {
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| compare_versions(m.from, &version) != std::cmp::Ordering::Greater
+                && compare_versions(m.to, &version) == std::cmp::Ordering::Greater)
+            .ok_or_else(|| format!("No migration path from config version {}", version))?;
+
+        (step.apply)(&mut value)?;
+        value["version"] = json!(step.to);
+        log::info!("Migrated config from version {} to {}", version, step.to);
+        version = step.to.to_string();
+    }
+
+    let migrated = version != from_version;
+    Ok(MigrationOutcome { value, from_version, migrated })
+}
+
+/// 迁移前备份原始配置文件，返回备份路径
+pub fn backup_original(config_path: &Path, from_version: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let file_name = config_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("config");
+    let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S");
+    let backup_path = config_path.with_file_name(format!(
+        "{}.v{}.{}.bak",
+        file_name, from_version, timestamp
+    ));
+    fs::copy(config_path, &backup_path)?;
+    Ok(backup_path)
+}
+
+fn read_version(value: &Value) -> String {
+    match value.get("version") {
+        Some(Value::String(v)) if !v.trim().is_empty() => v.trim().to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        // 没有版本号的配置来自最早的版本
+        _ => "0".to_string(),
+    }
+}
+
+/// 按数字段比较版本号，例如 "1.10" > "1.9"
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        v.split('.').map(|p| p.trim().parse::<u64>().unwrap_or(0)).collect()
+    };
+    let (pa, pb) = (parse(a), parse(b));
+    let len = pa.len().max(pb.len());
+    for i in 0..len {
+        let x = pa.get(i).copied().unwrap_or(0);
+        let y = pb.get(i).copied().unwrap_or(0);
+        if x != y {
+            return x.cmp(&y);
+        }
+    }
+    std::cmp::Ordering::Equal
+}
+
+// 0 -> 1.0：早期配置只有 categories，补齐后来成为必填的字段
+fn migrate_0_to_1_0(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Config root must be a JSON object")?;
+
+    if !obj.get("categories").map(|c| c.is_object()).unwrap_or(false) {
+        obj.insert("categories".to_string(), json!({}));
+    }
+    if !obj.get("description").map(|d| d.is_string()).unwrap_or(false) {
+        obj.insert("description".to_string(), json!(crate::i18n::t("config_file_description")));
+    }
+    if !obj.get("paths").map(|p| p.is_array()).unwrap_or(false) {
+        obj.insert("paths".to_string(), json!([]));
+    }
+
+    // 早期版本允许扩展名不带点，统一为 ".ext" 小写形式
+    if let Some(categories) = obj.get_mut("categories").and_then(|c| c.as_object_mut()) {
+        for extensions in categories.values_mut() {
+            if let Some(list) = extensions.as_array_mut() {
+                for ext in list.iter_mut() {
+                    if let Some(s) = ext.as_str() {
+                        let s = s.trim().to_lowercase();
+                        let normalized = if s.starts_with('.') { s } else { format!(".{}", s) };
+                        *ext = json!(normalized);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// 1.0 -> 2.0：分类键从本地化名称改为稳定ID，原名称保存到 categoryNames 以保留已有文件夹
+fn migrate_1_0_to_2_0(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Config root must be a JSON object")?;
+    let old_categories = match obj.remove("categories") {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+    let mut names = match obj.remove("categoryNames") {
+        Some(Value::Object(map)) => map,
+        _ => Map::new(),
+    };
+
+    // 按名称排序保证结果稳定；当前语言的名称优先作为显示名称
+    let mut entries: Vec<(String, Value)> = old_categories.into_iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut categories = Map::new();
+    for (name, extensions) in entries {
+        let id = builtin_id_for_name(&name).unwrap_or_else(|| name.clone());
+
+        match categories.get_mut(&id) {
+            // 切换过语言的旧配置里同一个内置分类可能出现两次（如 "Images" 和 "图片"），合并扩展名
+            Some(Value::Array(existing)) => {
+                if let Value::Array(list) = extensions {
+                    for ext in list {
+                        if !existing.contains(&ext) {
+                            existing.push(ext);
+                        }
+                    }
+                }
+                let current_name = crate::i18n::t(&format!("category_{}", id));
+                if name == current_name {
+                    names.insert(id.clone(), json!(name));
+                }
+            }
+            _ => {
+                categories.insert(id.clone(), extensions);
+                if id != name {
+                    names.insert(id, json!(name));
+                }
+            }
+        }
+    }
+
+    obj.insert("categories".to_string(), Value::Object(categories));
+    obj.insert("categoryNames".to_string(), Value::Object(names));
+    Ok(())
+}
+
+fn builtin_id_for_name(name: &str) -> Option<String> {
+    crate::config::BUILTIN_CATEGORY_IDS
+        .iter()
+        .find(|id| {
+            **id == name
+                || crate::i18n::t_all(&format!("category_{}", id))
+                    .iter()
+                    .any(|translated| translated == name)
+        })
+        .map(|id| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn compares_versions_by_numeric_segment() {
+        assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+        assert_eq!(compare_versions("2", "2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("0", "1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn migrates_unversioned_config_to_current_version() {
+        let raw = json!({
+            "categories": { "Images": ["JPG", ".Png"], "Receipts": ["pdf"] },
+            "paths": []
+        });
+        let outcome = migrate(raw).unwrap();
+
+        assert!(outcome.migrated);
+        assert_eq!(outcome.from_version, "0");
+        assert_eq!(outcome.value["version"], json!(CURRENT_CONFIG_VERSION));
+        // 内置分类的本地化名称换成稳定ID，扩展名统一为带点的小写形式
+        assert_eq!(outcome.value["categories"]["images"], json!([".jpg", ".png"]));
+        assert_eq!(outcome.value["categories"]["Receipts"], json!([".pdf"]));
+        assert_eq!(outcome.value["categoryNames"]["images"], json!("Images"));
+        assert!(outcome.value["description"].is_string());
+    }
+
+    #[test]
+    fn merges_builtin_categories_saved_under_several_languages() {
+        let raw = json!({
+            "version": "1.0",
+            "categories": { "Images": [".jpg"], "图片": [".jpg", ".heic"] },
+            "paths": [],
+            "description": ""
+        });
+        let outcome = migrate(raw).unwrap();
+
+        assert_eq!(outcome.value["categories"].as_object().unwrap().len(), 1);
+        let mut extensions: Vec<String> = serde_json::from_value(outcome.value["categories"]["images"].clone()).unwrap();
+        extensions.sort();
+        assert_eq!(extensions, vec![".heic", ".jpg"]);
+    }
+
+    #[test]
+    fn leaves_current_and_newer_configs_untouched() {
+        let current = json!({ "version": CURRENT_CONFIG_VERSION, "categories": { "images": [".jpg"] } });
+        let outcome = migrate(current.clone()).unwrap();
+        assert!(!outcome.migrated);
+        assert_eq!(outcome.value, current);
+
+        let newer = json!({ "version": "99.0", "categories": {}, "futureField": true });
+        let outcome = migrate(newer.clone()).unwrap();
+        assert!(!outcome.migrated);
+        assert_eq!(outcome.value, newer);
+    }
+
+    #[test]
+    fn rejects_non_object_config() {
+        assert!(migrate(json!([])).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::i18n::now_timestamps;
+
+// 移动失败后立即重试的间隔：杀毒软件和浏览器通常在下载完成后的几秒内释放文件
+const RENAME_RETRY_DELAYS: [Duration; 4] = [
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+];
+
+/// 文件仍被占用时放入延后队列，按这些间隔在后台再试，全部失败后放弃并留在原处
+pub const DEFERRED_RETRY_DELAYS: [Duration; 5] = [
+    Duration::from_secs(30),
+    Duration::from_secs(60),
+    Duration::from_secs(2 * 60),
+    Duration::from_secs(5 * 60),
+    Duration::from_secs(15 * 60),
+];
+
+// Windows 错误码：文件被其他进程打开或锁定
+const ERROR_SHARING_VIOLATION: i32 = 32;
+const ERROR_LOCK_VIOLATION: i32 = 33;
+// errno：文件或设备忙
+const EBUSY: i32 = 16;
+// 等待重试期间检查是否已取消的间隔
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 文件是在哪种整理中延后的，重试时按同样的方式移动
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeferredOrigin {
+    // 监控到的新文件，停止监控时不再重试
+    Monitor,
+    // 手动整理，重试成功时记录撤销历史
+    Manual,
+}
+
+/// 因为被占用而延后整理的文件，发送给前端显示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeferredFile {
+    pub file_name: String,
+    pub path: String,
+    pub folder_path: String,
+    pub origin: DeferredOrigin,
+    // 已经重试的次数
+    pub attempts: u32,
+    pub timestamp: String, // ISO-8601
+    pub display_time: String, // 按当前语言格式化的时间
+}
+
+lazy_static! {
+    // 文件路径 -> 延后的文件和取消标记
+    static ref DEFERRED: Mutex<HashMap<PathBuf, (DeferredFile, Arc<AtomicBool>)>> = Mutex::new(HashMap::new());
+}
+
+/// 错误是否表示文件正被其他程序占用，稍后可能成功
+pub fn is_locked(error: &io::Error) -> bool {
+    match error.raw_os_error() {
+        Some(code) if cfg!(windows) => code == ERROR_SHARING_VIOLATION || code == ERROR_LOCK_VIOLATION,
+        Some(code) => code == EBUSY,
+        None => false,
+    }
+}
+
+/// 移动文件；文件被占用时按递增的间隔重试几次，仍失败时返回最后一次的错误
+pub fn rename_with_retry(from: &Path, to: &Path) -> io::Result<()> {
+    let mut delays = RENAME_RETRY_DELAYS.iter();
+    loop {
+        match fs::rename(from, to) {
+            Err(e) if is_locked(&e) => match delays.next() {
+                Some(delay) => std::thread::sleep(*delay),
+                None => return Err(e),
+            },
+            result => return result,
+        }
+    }
+}
+
+/// 登记延后的文件，返回文件和重试时检查的取消标记；已经在队列中时返回 None，避免同一个文件开多个重试
+pub fn register(path: &Path, folder_path: &Path, origin: DeferredOrigin) -> Option<(DeferredFile, Arc<AtomicBool>)> {
+    let mut deferred = DEFERRED.lock().unwrap();
+    if deferred.contains_key(path) {
+        return None;
+    }
+    let (timestamp, display_time) = now_timestamps();
+    let file = DeferredFile {
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: path.to_string_lossy().to_string(),
+        folder_path: folder_path.to_string_lossy().to_string(),
+        origin,
+        attempts: 0,
+        timestamp,
+        display_time,
+    };
+    let cancelled = Arc::new(AtomicBool::new(false));
+    deferred.insert(path.to_path_buf(), (file.clone(), cancelled.clone()));
+    Some((file, cancelled))
+}
+
+/// 记录一次重试
+pub fn record_attempt(path: &Path) {
+    if let Some((file, _)) = DEFERRED.lock().unwrap().get_mut(path) {
+        file.attempts += 1;
+    }
+}
+
+/// 取消文件夹中指定来源的延后文件，不再重试，返回被取消的文件
+pub fn cancel(folder_path: &Path, origin: DeferredOrigin) -> Vec<DeferredFile> {
+    let folder_path = folder_path.to_string_lossy();
+    let mut deferred = DEFERRED.lock().unwrap();
+    let paths: Vec<PathBuf> = deferred
+        .iter()
+        .filter(|(_, (file, _))| file.origin == origin && file.folder_path == folder_path)
+        .map(|(path, _)| path.clone())
+        .collect();
+    paths
+        .iter()
+        .filter_map(|path| deferred.remove(path))
+        .map(|(file, cancelled)| {
+            cancelled.store(true, Ordering::Relaxed);
+            file
+        })
+        .collect()
+}
+
+/// 等待下一次重试，期间被取消时提前返回 false
+pub fn wait_unless_cancelled(delay: Duration, cancelled: &AtomicBool) -> bool {
+    let mut remaining = delay;
+    while !remaining.is_zero() {
+        if cancelled.load(Ordering::Relaxed) {
+            return false;
+        }
+        let step = remaining.min(CANCEL_CHECK_INTERVAL);
+        std::thread::sleep(step);
+        remaining -= step;
+    }
+    !cancelled.load(Ordering::Relaxed)
+}
+
+pub fn is_deferred(path: &Path) -> bool {
+    DEFERRED.lock().unwrap().contains_key(path)
+}
+
+pub fn remove(path: &Path) {
+    DEFERRED.lock().unwrap().remove(path);
+}
+
+/// 所有延后的文件，按时间从早到晚
+pub fn list() -> Vec<DeferredFile> {
+    let mut files: Vec<DeferredFile> = DEFERRED.lock().unwrap().values().map(|(file, _)| file.clone()).collect();
+    files.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cancel_only_drops_files_of_that_origin_and_folder() {
+        let folder = std::env::temp_dir().join("filesortify-deferred-cancel");
+        let other_folder = std::env::temp_dir().join("filesortify-deferred-other");
+        let (_, monitored) = register(&folder.join("a.zip"), &folder, DeferredOrigin::Monitor).unwrap();
+        let (_, manual) = register(&folder.join("b.zip"), &folder, DeferredOrigin::Manual).unwrap();
+        let (_, elsewhere) = register(&other_folder.join("c.zip"), &other_folder, DeferredOrigin::Monitor).unwrap();
+
+        let cancelled = cancel(&folder, DeferredOrigin::Monitor);
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].file_name, "a.zip");
+        assert!(monitored.load(Ordering::Relaxed));
+        assert!(!is_deferred(&folder.join("a.zip")));
+        assert!(!manual.load(Ordering::Relaxed));
+        assert!(is_deferred(&folder.join("b.zip")));
+        assert!(!elsewhere.load(Ordering::Relaxed));
+
+        remove(&folder.join("b.zip"));
+        remove(&other_folder.join("c.zip"));
+    }
+
+    #[test]
+    fn wait_returns_early_when_cancelled() {
+        let cancelled = AtomicBool::new(false);
+        assert!(wait_unless_cancelled(Duration::from_millis(10), &cancelled));
+        cancelled.store(true, Ordering::Relaxed);
+        let started = std::time::Instant::now();
+        assert!(!wait_unless_cancelled(Duration::from_secs(30), &cancelled));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn registers_a_file_once() {
+        let folder = std::env::temp_dir().join("filesortify-deferred-once");
+        let path = folder.join("setup.exe");
+        assert!(register(&path, &folder, DeferredOrigin::Manual).is_some());
+        assert!(register(&path, &folder, DeferredOrigin::Monitor).is_none());
+        assert_eq!(list().iter().find(|file| file.path == path.to_string_lossy()).unwrap().origin, DeferredOrigin::Manual);
+        remove(&path);
+    }
+}
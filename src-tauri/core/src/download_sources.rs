@@ -0,0 +1,72 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// 报告后一直没有被整理（例如不在整理的文件夹中）的来源信息，超过这个时间后丢弃
+const MAX_AGE: Duration = Duration::from_secs(30 * 60);
+const MAX_ENTRIES: usize = 500;
+
+/// 浏览器扩展报告的下载来源，整理时附在 file-organized 事件中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadSource {
+    pub url: String,
+    // url 的主机名，例如 "github.com"
+    pub domain: Option<String>,
+    pub referrer: Option<String>,
+}
+
+lazy_static! {
+    // 下载完成的文件路径 -> 来源和报告时间
+    static ref SOURCES: Mutex<HashMap<PathBuf, (DownloadSource, Instant)>> = Mutex::new(HashMap::new());
+}
+
+/// 记录浏览器扩展报告的已完成下载
+pub fn record(path: &Path, url: &str, referrer: Option<String>) -> DownloadSource {
+    let source = DownloadSource {
+        url: url.to_string(),
+        domain: domain(url),
+        referrer: referrer.filter(|referrer| !referrer.trim().is_empty()),
+    };
+
+    let mut sources = SOURCES.lock().unwrap();
+    sources.retain(|_, (_, reported_at)| reported_at.elapsed() < MAX_AGE);
+    if sources.len() >= MAX_ENTRIES {
+        if let Some(oldest) = sources.iter().min_by_key(|(_, (_, reported_at))| *reported_at).map(|(path, _)| path.clone()) {
+            sources.remove(&oldest);
+        }
+    }
+    sources.insert(path.to_path_buf(), (source.clone(), Instant::now()));
+    source
+}
+
+/// 取出文件的来源信息；文件被整理后不再需要
+pub fn take(path: &Path) -> Option<DownloadSource> {
+    SOURCES
+        .lock()
+        .unwrap()
+        .remove(path)
+        .filter(|(_, reported_at)| reported_at.elapsed() < MAX_AGE)
+        .map(|(source, _)| source)
+}
+
+/// URL 的主机名（小写，不含端口和用户信息）
+pub fn domain(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, rest)| rest)?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit_once('@').map(|(_, host)| host).unwrap_or(authority);
+    let host = if host.starts_with('[') {
+        // IPv6 地址保留方括号内的部分
+        host.split(']').next().map(|host| host.trim_start_matches('['))?
+    } else {
+        host.split(':').next()?
+    };
+    let host = host.trim().trim_end_matches('.').to_lowercase();
+    if host.is_empty() {
+        None
+    } else {
+        Some(host)
+    }
+}
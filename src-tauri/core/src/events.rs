@@ -0,0 +1,53 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::config::PathStats;
+use crate::deferred::DeferredFile;
+use crate::file_organizer::{FileOrganizedEvent, LogMessage, SessionSummary, UndoAction};
+use crate::pending::{Decision, PendingMove};
+use crate::permissions::PermissionIssue;
+
+/// 整理过程中产生的事件，以及免费版额度这类由宿主决定的策略。
+/// 核心库不依赖 Tauri：桌面应用把事件转发给前端和托盘，命令行版本只记录统计
+pub trait OrganizerEvents: Send + Sync {
+    fn log(&self, _message: &LogMessage) {}
+
+    fn file_organized(&self, _event: &FileOrganizedEvent) {}
+
+    fn file_undone(&self, _event: &serde_json::Value) {}
+
+    // 一次手动整理完成，或监控停止时
+    fn session_finished(&self, _summary: &SessionSummary) {}
+
+    // 因为权限问题无法移动文件，例如 macOS 没有允许访问下载文件夹
+    fn permission_denied(&self, _issue: &PermissionIssue) {}
+
+    // 文件被其他程序占用，放入延后队列稍后重试
+    fn file_deferred(&self, _file: &DeferredFile) {}
+
+    // 延后的文件已经移动，或者不再重试（被移走、仍被占用、停止了监控）
+    fn file_deferred_resolved(&self, _path: &str, _moved: bool) {}
+
+    // 手动整理时延后的文件在重试中移动了，宿主把它加入撤销历史并按手动整理计数
+    fn deferred_manual_move(&self, _action: &UndoAction) {}
+
+    fn path_stats_updated(&self, _folder_path: &Path, _stats: &PathStats) {}
+
+    fn file_pending(&self, _pending: &PendingMove) {}
+
+    fn file_pending_resolved(&self, _id: &str, _decision: Decision) {}
+
+    // 统计指标，例如整理的文件数
+    fn metric(&self, _name: &str, _value: u64) {}
+
+    // 监控中自动移动文件之前调用，返回 false 时文件留在原处（免费版今天的额度已用完）
+    fn allow_monitored_move(&self) -> bool {
+        true
+    }
+
+    // 监控中自动移动了一个文件
+    fn monitored_file_moved(&self) {}
+}
+
+/// 在整理器和监控线程之间共享的事件处理
+pub type SharedEvents = Arc<dyn OrganizerEvents>;
@@ -0,0 +1,1317 @@
+use notify::{Watcher, RecursiveMode, Event, EventKind};
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::fs;
+use serde::{Deserialize, Serialize};
+use std::thread::JoinHandle;
+use std::collections::VecDeque;
+use chrono;
+use rand;
+
+use crate::config::{Config, PathConfig, SymlinkPolicy};
+use crate::deferred::{self, DeferredOrigin};
+use crate::download_sources::{self, DownloadSource};
+use crate::events::SharedEvents;
+use crate::hooks::{self, HookContext, HookStage};
+use crate::in_use;
+use crate::i18n::{t, t_format, now_timestamps};
+use crate::pending;
+use crate::permissions;
+use crate::unmatched;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LogMessage {
+    pub message: String,
+    pub log_type: String,
+    pub timestamp: String, // ISO-8601
+    #[serde(default)]
+    pub display_time: String, // 按当前语言格式化的时间
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileOrganizedEvent {
+    pub file_name: String,
+    pub actual_file_name: String, // 实际移动后的文件名（可能被重命名）
+    pub category: String,
+    pub category_id: String,
+    pub category_icon: Option<String>,
+    pub category_color: Option<String>,
+    pub timestamp: String, // ISO-8601
+    #[serde(default)]
+    pub display_time: String, // 按当前语言格式化的时间
+    pub folder_path: String,
+    pub original_path: String, // 原始完整路径
+    pub moved_to_path: String, // 实际移动到的完整路径
+    #[serde(default = "default_notify")]
+    pub notify: bool, // 该路径关闭通知时为 false，前端只记录不弹出提示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<DownloadSource>, // 浏览器扩展报告的下载来源
+}
+
+fn default_notify() -> bool {
+    true
+}
+
+/// 整理前的预览：按当前规则会移动哪些文件，不实际移动
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct OrganizePreview {
+    pub folder_path: String,
+    pub moves: Vec<PreviewMove>,
+    pub unmatched_count: usize, // 没有匹配规则、会留在原处的文件数
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PreviewMove {
+    pub file_name: String,
+    pub category: String,
+    pub category_id: String,
+    pub destination_folder: String, // 分类文件夹的完整路径
+}
+
+/// 一次手动整理或一段监控结束时的汇总
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SessionSummary {
+    pub folder_path: String,
+    pub kind: SessionKind,
+    pub files_moved: u64,
+    pub unmatched_count: u64,
+    pub started_at: String, // ISO-8601
+    pub finished_at: String, // ISO-8601
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionKind {
+    Organize,
+    Monitoring,
+}
+
+// 监控期间的计数，在监控线程和等待确认的线程之间共享
+#[derive(Default)]
+struct SessionCounters {
+    moved: AtomicU64,
+    unmatched: AtomicU64,
+}
+
+// 监控中移动一个文件的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MoveOutcome {
+    Moved,
+    // 文件仍被占用，已放入延后队列
+    Locked,
+    // 没有移动：额度用完、脚本中止或移动失败
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UndoAction {
+    pub id: String,
+    pub file_name: String,
+    pub original_path: PathBuf,
+    pub moved_to_path: PathBuf,
+    pub category: String,
+    pub timestamp: String, // ISO-8601
+    #[serde(default)]
+    pub display_time: String, // 按当前语言格式化的时间
+    pub downloads_path: PathBuf,
+    pub source: String, // "manual" or "monitoring"
+}
+
+#[derive(Debug, Clone)]
+pub struct UndoHistory {
+    actions: VecDeque<UndoAction>,
+    max_size: usize,
+}
+
+impl UndoHistory {
+    pub fn new(max_size: usize) -> Self {
+        Self {
+            actions: VecDeque::new(),
+            max_size,
+        }
+    }
+
+    pub fn add_action(&mut self, action: UndoAction) {
+        if self.actions.len() >= self.max_size {
+            self.actions.pop_front();
+        }
+        self.actions.push_back(action);
+    }
+
+    pub fn get_latest_actions(&self, count: usize) -> Vec<UndoAction> {
+        self.actions
+            .iter()
+            .rev()
+            .take(count)
+            .cloned()
+            .collect()
+    }
+
+    pub fn remove_action(&mut self, action_id: &str) -> Option<UndoAction> {
+        if let Some(pos) = self.actions.iter().position(|a| a.id == action_id) {
+            self.actions.remove(pos)
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.actions.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+}
+
+pub struct FileSortify {
+    pub downloads_path: PathBuf,
+    pub config: Config,
+    pub monitoring_stop_signal: Option<Arc<AtomicBool>>,
+    pub monitoring_thread: Option<JoinHandle<()>>,
+    pub events: Option<SharedEvents>,
+    pub undo_history: UndoHistory,
+    // 当前监控会话的计数，监控线程和手动移动共用
+    counters: Arc<SessionCounters>,
+}
+
+// 事件处理由宿主提供，没有 Debug 实现，输出时跳过
+impl std::fmt::Debug for FileSortify {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileSortify")
+            .field("downloads_path", &self.downloads_path)
+            .field("config", &self.config)
+            .field("monitoring_stop_signal", &self.monitoring_stop_signal)
+            .field("monitoring_thread", &self.monitoring_thread)
+            .field("undo_history", &self.undo_history)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Clone for FileSortify {
+    fn clone(&self) -> Self {
+        Self {
+            downloads_path: self.downloads_path.clone(),
+            config: self.config.clone(),
+            monitoring_stop_signal: None, // 新实例不继承监控状态
+            monitoring_thread: None, // 新实例不继承线程句柄
+            events: self.events.clone(),
+            undo_history: self.undo_history.clone(),
+            counters: Arc::new(SessionCounters::default()),
+        }
+    }
+}
+
+impl FileSortify {
+    pub fn new(downloads_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let downloads_path = PathBuf::from(downloads_path);
+        let config = Config::load()?;
+        let undo_history = UndoHistory::new(50); // 最多保存50个撤销操作
+        Ok(FileSortify {
+            downloads_path,
+            config,
+            monitoring_stop_signal: None,
+            monitoring_thread: None,
+            events: None,
+            undo_history,
+            counters: Arc::new(SessionCounters::default()),
+        })
+    }
+
+    /// 整理时把日志、整理结果等事件交给宿主处理
+    pub fn with_events(mut self, events: SharedEvents) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn emit_log(&self, message: &str, log_type: &str) {
+        Self::emit_log_to(&self.events, message, log_type);
+    }
+
+    fn emit_log_to(events: &Option<SharedEvents>, message: &str, log_type: &str) {
+        if let Some(events) = events {
+            let (timestamp, display_time) = now_timestamps();
+            let log_message = LogMessage {
+                message: message.to_string(),
+                log_type: log_type.to_string(),
+                timestamp,
+                display_time,
+            };
+            
+            events.log(&log_message);
+        }
+        
+        // 同时保留原有的日志输出
+        match log_type {
+            "error" => log::error!("{}", message),
+            "warning" => log::warn!("{}", message),
+            "success" => log::info!("{}", message),
+            _ => log::info!("{}", message),
+        }
+    }
+
+    // 权限问题通知界面并换成给用户看的说明，其他错误原样返回
+    fn permission_error(events: &Option<SharedEvents>, error: std::io::Error, path: &Path, downloads_path: &Path) -> Box<dyn std::error::Error> {
+        match permissions::diagnose(&error, path, downloads_path) {
+            Some(issue) => {
+                if let Some(events) = events {
+                    events.permission_denied(&issue);
+                }
+                issue.message.into()
+            }
+            None => error.into(),
+        }
+    }
+
+    /// 更新配置中的路径统计，并通知前端
+    fn record_organized_stats(events: &Option<SharedEvents>, downloads_path: &Path, count: u64) {
+        if let Some(events) = events {
+            events.metric("files_organized", count);
+        }
+        match Config::update_path_stats(downloads_path, |stats| stats.record_organized(count)) {
+            Ok(Some(stats)) => {
+                if let Some(events) = events {
+                    events.path_stats_updated(downloads_path, &stats);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => log::error!("Failed to update path stats: {}", e),
+        }
+    }
+
+    fn emit_file_organized(&self, original_file_name: &str, actual_file_name: &str, category_id: &str, category: &str, original_path: &Path, moved_to_path: &Path) {
+        if let Some(events) = &self.events {
+            let meta = self.config.category_meta(category_id);
+            let (timestamp, display_time) = now_timestamps();
+            let event = FileOrganizedEvent {
+                file_name: original_file_name.to_string(),
+                actual_file_name: actual_file_name.to_string(),
+                category: category.to_string(),
+                category_id: category_id.to_string(),
+                category_icon: meta.icon,
+                category_color: meta.color,
+                timestamp,
+                display_time,
+                folder_path: self.downloads_path.to_string_lossy().to_string(),
+                original_path: original_path.to_string_lossy().to_string(),
+                moved_to_path: moved_to_path.to_string_lossy().to_string(),
+                notify: self.config.notifications_enabled_for(&self.downloads_path),
+                source: download_sources::take(original_path),
+            };
+            events.file_organized(&event);
+        }
+    }
+    
+    pub fn organize_existing_files(&mut self) -> Result<usize, Box<dyn std::error::Error>> {
+        self.organize_existing_files_up_to(None)
+    }
+    
+    // 最多移动 limit 个文件，免费版用今天剩余的额度作为上限
+    pub fn organize_existing_files_up_to(&mut self, limit: Option<usize>) -> Result<usize, Box<dyn std::error::Error>> {
+        self.create_folders()?;
+        self.run_session_hook(HookStage::BeforeSession, None)?;
+        
+        let started_at = chrono::Utc::now().to_rfc3339();
+        let mut files_moved = 0;
+        let mut unmatched_count = 0;
+        
+        let entries = fs::read_dir(&self.downloads_path)
+            .map_err(|e| Self::permission_error(&self.events, e, &self.downloads_path, &self.downloads_path))?;
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+            
+            // 跳过隐藏文件和文件夹，符号链接按策略处理
+            if path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with('.'))
+                .unwrap_or(false)
+                || !Self::is_organizable(&path, &self.config, &|message, log_type| self.emit_log(message, log_type)) {
+                continue;
+            }
+            
+            if let Some(category) = self.get_file_category(&path) {
+                if limit.map(|limit| files_moved >= limit).unwrap_or(false) {
+                    self.emit_log(&t("free_tier_limit_stopped"), "warning");
+                    break;
+                }
+                if self.move_file(&path, &category, true)? { // 手动整理时记录撤销历史
+                    files_moved += 1;
+                }
+            } else {
+                if let Some(file_name) = path.file_name() {
+                    self.emit_log(&t_format("skip_unmatched_file", &[&format!("{:?}", file_name)]), "info");
+                }
+                unmatched::record(&self.downloads_path, &path);
+                unmatched_count += 1;
+            }
+        }
+        unmatched::flush();
+        
+        self.emit_log(&t_format("organize_complete_moved_count", &[&files_moved.to_string()]), "success");
+        if files_moved > 0 {
+            Self::record_organized_stats(&self.events, &self.downloads_path, files_moved as u64);
+        }
+        self.run_session_hook(HookStage::AfterSession, Some(files_moved as u64))?;
+        if let Some(events) = &self.events {
+            events.session_finished(&SessionSummary {
+                folder_path: self.downloads_path.to_string_lossy().to_string(),
+                kind: SessionKind::Organize,
+                files_moved: files_moved as u64,
+                unmatched_count,
+                started_at,
+                finished_at: chrono::Utc::now().to_rfc3339(),
+            });
+        }
+        Ok(files_moved)
+    }
+    
+    // 浏览器扩展报告下载完成的文件：下载已经确定完成，不需要等待文件写入，直接按规则整理。
+    // 返回移动记录；文件不在这个文件夹中、已经被移走或没有匹配的分类时返回 None
+    pub fn organize_completed_download(&mut self, path: &Path) -> Result<Option<UndoAction>, Box<dyn std::error::Error>> {
+        if path.parent() != Some(self.downloads_path.as_path())
+            || !Self::is_organizable(path, &self.config, &|message, log_type| self.emit_log(message, log_type))
+        {
+            return Ok(None);
+        }
+        let Some(category) = self.get_file_category(path) else {
+            unmatched::record(&self.downloads_path, path);
+            unmatched::flush();
+            return Ok(None);
+        };
+        // “询问我”模式的文件留给监控询问，免费版今天的额度用完后不移动
+        if self.config.find_path_by_folder(&self.downloads_path).map(|p| p.confirm_before_move).unwrap_or(false)
+            || !self.events.as_ref().map(|events| events.allow_monitored_move()).unwrap_or(true)
+        {
+            return Ok(None);
+        }
+
+        self.create_folders()?;
+        if !self.move_file(path, &category, true)? {
+            return Ok(None);
+        }
+        if let Some(events) = &self.events {
+            events.monitored_file_moved();
+        }
+        Self::record_organized_stats(&self.events, &self.downloads_path, 1);
+        Ok(self.undo_history.get_latest_actions(1).into_iter().next())
+    }
+    
+    // 列出整理时会移动的文件，与 organize_existing_files 使用相同的跳过和匹配规则
+    pub fn preview(&self) -> Result<OrganizePreview, Box<dyn std::error::Error>> {
+        let mut preview = OrganizePreview {
+            folder_path: self.downloads_path.to_string_lossy().to_string(),
+            moves: Vec::new(),
+            unmatched_count: 0,
+        };
+        
+        for entry in fs::read_dir(&self.downloads_path)? {
+            let path = entry?.path();
+            if path.file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.starts_with('.'))
+                .unwrap_or(false)
+                || !Self::is_organizable(&path, &self.config, &|_, _| {}) {
+                continue;
+            }
+            
+            match self.get_file_category(&path) {
+                Some(category_id) => {
+                    let folder = self.config.folder_name(&category_id, &self.downloads_path);
+                    preview.moves.push(PreviewMove {
+                        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+                        category: self.config.display_name(&category_id),
+                        destination_folder: self.downloads_path.join(folder).to_string_lossy().to_string(),
+                        category_id,
+                    });
+                }
+                None => preview.unmatched_count += 1,
+            }
+        }
+        Ok(preview)
+    }
+    
+    /// 开始监控；先在监控线程中整理监控停止期间下载的文件（路径关闭了补整理时跳过）
+    pub fn start_monitoring(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.start_watching(true)
+    }
+    
+    fn start_watching(&mut self, catch_up: bool) -> Result<(), Box<dyn std::error::Error>> {
+        // 如果已经在监控，先停止；重启监控时延后的文件继续重试
+        if self.monitoring_stop_signal.is_some() {
+            self.stop_watching();
+        }
+        self.run_session_hook(HookStage::BeforeSession, None)?;
+
+        let (tx, rx) = channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(&self.downloads_path, RecursiveMode::NonRecursive)?;
+
+        let stop_signal = Arc::new(AtomicBool::new(false));
+        self.monitoring_stop_signal = Some(stop_signal.clone());
+
+        let config = self.config.clone();
+        let events = self.events.clone();
+        let downloads_path = self.downloads_path.clone();
+        self.counters = Arc::new(SessionCounters::default());
+        let counters = self.counters.clone();
+        let started_at = chrono::Utc::now().to_rfc3339();
+
+        // 用于去重的文件处理记录
+        let mut last_processed: std::collections::HashMap<PathBuf, std::time::Instant> = std::collections::HashMap::new();
+
+        let handle = std::thread::spawn(move || {
+            // watcher必须在这个线程中保持活跃
+            let _watcher = watcher;
+
+            // 创建一个辅助函数来发送日志
+            let emit_log = |message: &str, log_type: &str| Self::emit_log_to(&events, message, log_type);
+
+            if catch_up {
+                Self::catch_up(&config, &downloads_path, &events, &counters, &emit_log, &stop_signal);
+            }
+
+            loop {
+                // 检查停止信号
+                if stop_signal.load(Ordering::Relaxed) {
+                    emit_log(&t("monitor_stop_signal_received"), "info");
+                    break;
+                }
+
+                match rx.recv_timeout(Duration::from_millis(100)) {
+                    Ok(event) => {
+                        match event {
+                            Ok(Event { kind, paths, .. }) => {
+                                match kind {
+                                    // 处理文件创建事件
+                                    EventKind::Create(_) => {
+                                        emit_log(&t_format("file_create_event_detected", &[&paths.len().to_string()]), "info");
+                                        for path in paths {
+                                            Self::process_file_event(&path, &config, &downloads_path, &mut last_processed, &events, &counters, false);
+                                        }
+                                    }
+                                    // 处理文件修改事件（用于处理下载完成的文件）
+                                    EventKind::Modify(_) => {
+                                        emit_log(&t_format("file_modify_event_detected", &[&paths.len().to_string()]), "info");
+                                        for path in paths {
+                                            Self::process_file_event(&path, &config, &downloads_path, &mut last_processed, &events, &counters, true);
+                                        }
+                                    }
+                                    // 处理文件重命名/移动事件（用于处理临时文件重命名为最终文件）
+                                    EventKind::Other => {
+                                        emit_log(&t_format("file_other_event_detected", &[&paths.len().to_string()]), "info");
+                                        for path in paths {
+                                            Self::process_file_event(&path, &config, &downloads_path, &mut last_processed, &events, &counters, true);
+                                        }
+                                    }
+                                    _ => {
+                                        // emit_log(&format!("忽略其他类型事件: {:?}", kind), "info");
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                emit_log(&t_format("event_process_error", &[&format!("{:?}", e)]), "error");
+                            }
+                        }
+                    }
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {
+                        // 超时是正常的，继续循环
+                        continue;
+                    }
+                    Err(e) => {
+                        emit_log(&t_format("monitor_error", &[&format!("{:?}", e)]), "error");
+                        break;
+                    }
+                }
+            }
+
+            let files_moved = counters.moved.load(Ordering::Relaxed);
+            let context = HookContext {
+                folder: Some(downloads_path.as_path()),
+                files_moved: Some(files_moved),
+                ..Default::default()
+            };
+            if let Err(e) = hooks::run(&config.hooks, HookStage::AfterSession, &context) {
+                emit_log(&t_format("hook_failed", &[HookStage::AfterSession.as_str(), &e.message]), "warning");
+            }
+
+            if let Some(events) = &events {
+                events.session_finished(&SessionSummary {
+                    folder_path: downloads_path.to_string_lossy().to_string(),
+                    kind: SessionKind::Monitoring,
+                    files_moved,
+                    unmatched_count: counters.unmatched.load(Ordering::Relaxed),
+                    started_at,
+                    finished_at: chrono::Utc::now().to_rfc3339(),
+                });
+            }
+        });
+
+        self.monitoring_thread = Some(handle);
+        self.emit_log(&t("monitor_started"), "success");
+        Ok(())
+    }
+    
+    /// 替换配置；如果正在监控，则用新配置重启监控线程（不再补整理）
+    pub fn reload_config(&mut self, config: Config) -> Result<(), Box<dyn std::error::Error>> {
+        self.config = config;
+        if self.monitoring_stop_signal.is_some() {
+            self.start_watching(false)?;
+        }
+        Ok(())
+    }
+    
+    pub fn stop_monitoring(&mut self) {
+        self.stop_watching();
+        // 监控到的被占用文件不再重试，手动整理延后的文件不受影响
+        for file in deferred::cancel(&self.downloads_path, DeferredOrigin::Monitor) {
+            if let Some(events) = &self.events {
+                events.file_deferred_resolved(&file.path, false);
+            }
+        }
+    }
+
+    fn stop_watching(&mut self) {
+        if let Some(stop_signal) = &self.monitoring_stop_signal {
+            stop_signal.store(true, Ordering::Relaxed);
+            self.emit_log(&t("monitor_stop_signal_sent"), "info");
+        }
+        
+        // 等待线程结束
+        if let Some(handle) = self.monitoring_thread.take() {
+            // 给线程一些时间来响应停止信号
+            std::thread::sleep(Duration::from_millis(200));
+            if let Err(e) = handle.join() {
+                self.emit_log(&t_format("join_monitor_thread_error", &[&format!("{:?}", e)]), "error");
+            }
+        }
+        
+        // 清理资源
+        self.monitoring_stop_signal = None;
+        
+        self.emit_log(&t("monitor_stopped"), "success");
+    }
+    
+    // 整理开始和结束时的脚本；整理前的脚本按 abort 策略失败时不开始整理
+    fn run_session_hook(&self, stage: HookStage, files_moved: Option<u64>) -> Result<(), Box<dyn std::error::Error>> {
+        let context = HookContext {
+            folder: Some(self.downloads_path.as_path()),
+            files_moved,
+            ..Default::default()
+        };
+        if let Err(e) = hooks::run(&self.config.hooks, stage, &context) {
+            let message = t_format("hook_failed", &[stage.as_str(), &e.message]);
+            self.emit_log(&message, "warning");
+            if e.abort {
+                return Err(message.into());
+            }
+        }
+        Ok(())
+    }
+    
+    fn create_folders(&self) -> Result<(), Box<dyn std::error::Error>> {
+        // 创建所有分类文件夹（不再区分“其他”）
+        for category_id in self.config.categories_for(&self.downloads_path).keys() {
+            let folder = self.config.folder_name(category_id, &self.downloads_path);
+            let category_path = self.downloads_path.join(&folder);
+            if !category_path.exists() {
+                fs::create_dir_all(&category_path)
+                    .map_err(|e| Self::permission_error(&self.events, e, &category_path, &self.downloads_path))?;
+                self.emit_log(&t_format("create_folder", &[&folder]), "info");
+            }
+        }
+        Ok(())
+    }
+    
+    // 是否整理这个路径：只整理普通文件。符号链接和目录联接从不跟随，按策略跳过或只移动链接本身
+    fn is_organizable(path: &Path, config: &Config, emit_log: &dyn Fn(&str, &str)) -> bool {
+        match fs::symlink_metadata(path) {
+            // Windows 的目录联接也报告为符号链接
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                if config.symlink_policy == SymlinkPolicy::MoveLink {
+                    return true;
+                }
+                emit_log(&t_format("skip_symlink", &[&path.file_name().unwrap_or_default().to_string_lossy()]), "info");
+                false
+            }
+            Ok(metadata) if metadata.is_file() => {
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+                let excluded = path.parent().map(|folder| config.is_excluded(folder, &file_name)).unwrap_or(false);
+                if excluded {
+                    emit_log(&t_format("skip_excluded_file", &[&file_name]), "info");
+                }
+                !excluded
+            }
+            _ => false,
+        }
+    }
+    
+    // 路径上是否已经有文件、文件夹或链接；不跟随链接，失效的链接也算已占用
+    fn path_taken(path: &Path) -> bool {
+        fs::symlink_metadata(path).is_ok()
+    }
+    
+    fn get_file_category(&self, file_path: &Path) -> Option<String> {
+        Self::get_file_category_static(file_path, &self.config, &self.downloads_path)
+    }
+    
+    fn get_file_category_static(file_path: &Path, config: &Config, downloads_path: &Path) -> Option<String> {
+        let extension = file_path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| format!(".{}", ext.to_lowercase()));
+        if let Some(ext) = extension {
+            // 路径配置了规则集时按当前生效的规则集匹配
+            for (category, extensions) in config.categories_for(downloads_path) {
+                if extensions.contains(&ext) {
+                    return Some(category.clone());
+                }
+            }
+        }
+        // 没有匹配到规则时返回 None
+        None
+    }
+    
+    fn move_file(&mut self, source_path: &Path, category_id: &str, record_undo: bool) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.move_file_outcome(source_path, category_id, record_undo)? == MoveOutcome::Moved)
+    }
+
+    fn move_file_outcome(&mut self, source_path: &Path, category_id: &str, record_undo: bool) -> Result<MoveOutcome, Box<dyn std::error::Error>> {
+        let filename = source_path.file_name()
+            .ok_or("Failed to get file name")?;
+        // 文件正被其他程序打开（例如仍在写入）时不移动，放入延后队列
+        if in_use::is_in_use(source_path) {
+            Self::defer_locked_file(source_path, &self.config, &self.downloads_path, &self.events, &self.counters, DeferredOrigin::Manual);
+            return Ok(MoveOutcome::Locked);
+        }
+        let category_name = self.config.display_name(category_id);
+        let category = category_name.as_str();
+        let destination_folder = self.downloads_path.join(self.config.folder_name(category_id, &self.downloads_path));
+        let mut destination_path = destination_folder.join(filename);
+        
+        // 如果目标文件已存在，添加数字后缀
+        let mut counter = 1;
+        let original_destination = destination_path.clone();
+        while Self::path_taken(&destination_path) {
+            if let Some(stem) = original_destination.file_stem().and_then(|s| s.to_str()) {
+                if let Some(ext) = original_destination.extension().and_then(|e| e.to_str()) {
+                    destination_path = destination_folder.join(format!("{}_{}.{}", stem, counter, ext));
+                } else {
+                    destination_path = destination_folder.join(format!("{}_{}", stem, counter));
+                }
+            }
+            counter += 1;
+        }
+        
+        let context = HookContext {
+            folder: Some(self.downloads_path.as_path()),
+            file: Some(source_path),
+            category: Some(category),
+            destination: Some(destination_path.as_path()),
+            files_moved: None,
+        };
+        if let Err(e) = hooks::run(&self.config.hooks, HookStage::BeforeMove, &context) {
+            self.emit_log(&t_format("hook_failed", &[HookStage::BeforeMove.as_str(), &e.message]), "warning");
+            if e.abort {
+                return Ok(MoveOutcome::Skipped);
+            }
+        }
+        
+        // 执行文件移动；文件仍被占用时放入延后队列，稍后在后台重试
+        if let Err(e) = deferred::rename_with_retry(source_path, &destination_path) {
+            if deferred::is_locked(&e) {
+                Self::defer_locked_file(source_path, &self.config, &self.downloads_path, &self.events, &self.counters, DeferredOrigin::Manual);
+                return Ok(MoveOutcome::Locked);
+            }
+            return Err(Self::permission_error(&self.events, e, source_path, &self.downloads_path));
+        }
+        
+        if let Err(e) = hooks::run(&self.config.hooks, HookStage::AfterMove, &context) {
+            self.emit_log(&t_format("hook_failed", &[HookStage::AfterMove.as_str(), &e.message]), "warning");
+        }
+        
+        // 只在手动整理时记录撤销历史
+        if record_undo {
+            let (timestamp, display_time) = now_timestamps();
+            let undo_action = UndoAction {
+                id: format!("{}-{}", chrono::Local::now().timestamp_millis(), rand::random::<u32>()),
+                file_name: filename.to_string_lossy().to_string(),
+                original_path: source_path.to_path_buf(),
+                moved_to_path: destination_path.clone(),
+                category: category.to_string(),
+                timestamp,
+                display_time,
+                downloads_path: self.downloads_path.clone(),
+                source: "manual".to_string(),
+            };
+            
+            self.undo_history.add_action(undo_action);
+        }
+        
+        // 发送日志和事件
+        if let Some(filename) = source_path.file_name() {
+            if let Some(filename_str) = filename.to_str() {
+                // 获取实际移动后的文件名
+                let actual_filename = destination_path.file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(filename_str);
+                
+                self.emit_log(&t_format("move_file_success", &[actual_filename, category]), "success");
+                self.emit_file_organized(filename_str, actual_filename, category_id, category, source_path, &destination_path);
+            }
+        }
+        
+        Ok(MoveOutcome::Moved)
+    }
+    
+    fn move_file_static(source_path: &Path, folder: &str, downloads_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let filename = source_path.file_name()
+            .ok_or("Failed to get file name")?;
+        let destination_folder = downloads_path.join(folder);
+        // 规则集按计划切换后，新分类的文件夹可能还不存在
+        fs::create_dir_all(&destination_folder)?;
+        let mut destination_path = destination_folder.join(filename);
+        // 如果目标文件已存在，添加数字后缀
+        let mut counter = 1;
+        let original_destination = destination_path.clone();
+        while Self::path_taken(&destination_path) {
+            if let Some(stem) = original_destination.file_stem().and_then(|s| s.to_str()) {
+                if let Some(ext) = original_destination.extension().and_then(|e| e.to_str()) {
+                    destination_path = destination_folder.join(format!("{}_{}.{}", stem, counter, ext));
+                } else {
+                    destination_path = destination_folder.join(format!("{}_{}", stem, counter));
+                }
+            }
+            counter += 1;
+        }
+        deferred::rename_with_retry(source_path, &destination_path)?;
+        // 返回实际的目标路径
+        log::info!("Moved file: {:?} -> {:?}", filename, destination_path.file_name());
+        Ok(destination_path)
+    }
+    
+    // 恢复监控时整理监控停止期间（例如应用关闭时）下载的文件，跳过和确认规则与监控到的新文件相同
+    fn catch_up(
+        config: &Config,
+        downloads_path: &Path,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+        emit_log: &dyn Fn(&str, &str),
+        stop_signal: &AtomicBool,
+    ) {
+        let path_config = config.find_path_by_folder(downloads_path);
+        if !path_config.map(|p| p.catch_up_on_start).unwrap_or(true) {
+            return;
+        }
+        let max_age = path_config
+            .and_then(|p| p.catch_up_max_age_days)
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+        let entries = match fs::read_dir(downloads_path) {
+            Ok(entries) => entries,
+            Err(e) => {
+                emit_log(&t_format("catch_up_failed", &[&e.to_string()]), "error");
+                return;
+            }
+        };
+
+        let moved_before = counters.moved.load(Ordering::Relaxed);
+        for entry in entries.flatten() {
+            if stop_signal.load(Ordering::Relaxed) {
+                return;
+            }
+            let path = entry.path();
+            let file_name = match path.file_name().and_then(|n| n.to_str()) {
+                Some(name) => name,
+                None => continue,
+            };
+            if Self::should_skip_file(file_name, false)
+                || !Self::is_organizable(&path, config, emit_log)
+                || deferred::is_deferred(&path)
+                || pending::is_pending(&path)
+            {
+                continue;
+            }
+            // 不跟随符号链接，按链接本身的修改时间判断
+            let too_old = max_age
+                .zip(entry.metadata().and_then(|metadata| metadata.modified()).ok())
+                .map(|(max_age, modified)| modified.elapsed().map(|age| age > max_age).unwrap_or(false))
+                .unwrap_or(false);
+            if too_old {
+                continue;
+            }
+
+            match Self::get_file_category_static(&path, config, downloads_path) {
+                Some(category_id) => {
+                    match config.find_path_by_folder(downloads_path).filter(|p| p.confirm_before_move) {
+                        Some(path_config) => {
+                            Self::ask_before_move(&path, category_id, path_config, config, downloads_path, events, counters);
+                        }
+                        None => {
+                            Self::move_and_report(&path, &category_id, config, downloads_path, events, counters, emit_log);
+                        }
+                    }
+                }
+                None => {
+                    unmatched::record(downloads_path, &path);
+                    counters.unmatched.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        unmatched::flush();
+
+        let moved = counters.moved.load(Ordering::Relaxed) - moved_before;
+        if moved > 0 {
+            emit_log(&t_format("catch_up_complete", &[&moved.to_string()]), "success");
+        }
+    }
+
+    // 统一的文件事件处理方法
+    fn process_file_event(
+        path: &Path,
+        config: &Config,
+        downloads_path: &Path,
+        last_processed: &mut std::collections::HashMap<PathBuf, std::time::Instant>,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+        is_modify_event: bool,
+    ) {
+        let emit_log = |message: &str, log_type: &str| Self::emit_log_to(events, message, log_type);
+        // 只处理文件，跳过目录；符号链接按策略处理
+        if !Self::is_organizable(path, config, &emit_log) {
+            return;
+        }
+        // 被占用而延后的文件由延后队列重试
+        if deferred::is_deferred(path) {
+            return;
+        }
+
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name,
+            None => return,
+        };
+
+        // 优化的文件过滤逻辑
+        if Self::should_skip_file(file_name, is_modify_event) {
+            return;
+        }
+
+        // 检查去重机制
+        let now = std::time::Instant::now();
+        if let Some(last_time) = last_processed.get(path) {
+            let duration = now.duration_since(*last_time);
+            // 根据事件类型调整去重时间
+            let skip_duration = if is_modify_event {
+                Duration::from_secs(2) // 修改事件允许更频繁的处理
+            } else {
+                Duration::from_secs(5) // 创建事件保持原来的去重时间
+            };
+            
+            if duration < skip_duration {
+                emit_log(&t_format("file_recently_processed_skip", &[&format!("{:?}", path.file_name()), &format!("{:?}", duration)]), "info");
+                return;
+            }
+        }
+
+        emit_log(&t_format("start_processing_file", &[&format!("{:?}", path.file_name())]), "info");
+        last_processed.insert(path.to_path_buf(), now);
+
+        // 等待文件写入完成，修改事件可以缩短等待时间
+        let wait_time = if is_modify_event {
+            Duration::from_millis(500)
+        } else {
+            Duration::from_secs(1)
+        };
+        std::thread::sleep(wait_time);
+
+        // 尝试分类和移动文件
+        if let Some(category_id) = Self::get_file_category_static(path, config, downloads_path) {
+            // “询问我”模式：在单独的线程里等待确认，不阻塞监控循环
+            if let Some(path_config) = config.find_path_by_folder(downloads_path).filter(|p| p.confirm_before_move) {
+                if pending::is_pending(path) {
+                    return;
+                }
+                Self::ask_before_move(path, category_id, path_config, config, downloads_path, events, counters);
+                return;
+            }
+            Self::move_and_report(path, &category_id, config, downloads_path, events, counters, &emit_log);
+        } else {
+            emit_log(&t_format("new_file_unmatched", &[&format!("{:?}", file_name)]), "info");
+            unmatched::record(downloads_path, path);
+            unmatched::flush();
+            counters.unmatched.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // 发出待确认事件，等待用户决定后再移动；超时按路径设置处理
+    fn ask_before_move(
+        path: &Path,
+        category_id: String,
+        path_config: &PathConfig,
+        config: &Config,
+        downloads_path: &Path,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+    ) {
+        let timeout_action = path_config.confirm_timeout_action;
+        let file_name = path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let category = config.display_name(&category_id);
+        let folder = config.folder_name(&category_id, downloads_path);
+        let proposed_path = downloads_path.join(&folder).join(&file_name);
+        let timeout = Duration::from_secs(path_config.confirm_timeout_secs.max(5));
+        let (timestamp, display_time) = now_timestamps();
+        let expires_at = chrono::Local::now() + chrono::Duration::seconds(timeout.as_secs() as i64);
+
+        let pending_move = pending::PendingMove {
+            id: format!("{}-{}", chrono::Local::now().timestamp_millis(), rand::random::<u32>()),
+            file_name: file_name.clone(),
+            category: category.clone(),
+            category_id: category_id.clone(),
+            folder_path: downloads_path.to_string_lossy().to_string(),
+            original_path: path.to_string_lossy().to_string(),
+            proposed_path: proposed_path.to_string_lossy().to_string(),
+            timestamp,
+            display_time,
+            expires_at: expires_at.to_rfc3339(),
+            timeout_action,
+        };
+
+        // 先登记再开线程，随后到达的同一文件事件不会重复询问
+        let id = pending_move.id.clone();
+        let decisions = pending::register(events, pending_move);
+
+        let path = path.to_path_buf();
+        let config = config.clone();
+        let downloads_path = downloads_path.to_path_buf();
+        let events = events.clone();
+        let counters = counters.clone();
+        std::thread::spawn(move || {
+            let emit_log = |message: &str, log_type: &str| Self::emit_log_to(&events, message, log_type);
+            emit_log(&t_format("file_pending_confirmation", &[&file_name, &category]), "info");
+
+            let should_move = match pending::wait(&events, &id, decisions, timeout) {
+                pending::Decision::Confirm => true,
+                pending::Decision::Deny => {
+                    emit_log(&t_format("pending_move_denied", &[&file_name]), "info");
+                    false
+                }
+                pending::Decision::Timeout => {
+                    emit_log(&t_format("pending_move_timeout", &[&file_name]), "warning");
+                    timeout_action == pending::TimeoutAction::Move
+                }
+            };
+
+            // 等待期间文件可能已经被用户移走或删除
+            if should_move && Self::is_organizable(&path, &config, &emit_log) {
+                Self::move_and_report(&path, &category_id, &config, &downloads_path, &events, &counters, &emit_log);
+            }
+        });
+    }
+
+    // 移动监控到的文件，并发送日志、统计和整理事件
+    fn move_and_report(
+        path: &Path,
+        category_id: &str,
+        config: &Config,
+        downloads_path: &Path,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+        emit_log: &dyn Fn(&str, &str),
+    ) -> MoveOutcome {
+        let file_name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name,
+            None => return MoveOutcome::Skipped,
+        };
+        // 免费版今天的额度用完后不再移动，文件留在原处
+        if !events.as_ref().map(|events| events.allow_monitored_move()).unwrap_or(true) {
+            emit_log(&t_format("free_tier_file_skipped", &[file_name]), "warning");
+            return MoveOutcome::Skipped;
+        }
+        // 文件正被其他程序打开（例如仍在写入）时不移动，放入延后队列
+        if in_use::is_in_use(path) {
+            Self::defer_locked_file(path, config, downloads_path, events, counters, DeferredOrigin::Monitor);
+            return MoveOutcome::Locked;
+        }
+        let category_id = category_id.to_string();
+        let category = config.display_name(&category_id);
+        let folder = config.folder_name(&category_id, downloads_path);
+        let planned_path = downloads_path.join(&folder).join(file_name);
+        let context = HookContext {
+            folder: Some(downloads_path),
+            file: Some(path),
+            category: Some(&category),
+            destination: Some(planned_path.as_path()),
+            files_moved: None,
+        };
+        if let Err(e) = hooks::run(&config.hooks, HookStage::BeforeMove, &context) {
+            emit_log(&t_format("hook_failed", &[HookStage::BeforeMove.as_str(), &e.message]), "warning");
+            if e.abort {
+                return MoveOutcome::Skipped;
+            }
+        }
+        match Self::move_file_static(path, &folder, downloads_path) {
+            Ok(actual_path) => {
+                let context = HookContext {
+                    destination: Some(actual_path.as_path()),
+                    ..context
+                };
+                if let Err(e) = hooks::run(&config.hooks, HookStage::AfterMove, &context) {
+                    emit_log(&t_format("hook_failed", &[HookStage::AfterMove.as_str(), &e.message]), "warning");
+                }
+                if let Some(events) = events {
+                    events.monitored_file_moved();
+                }
+                counters.moved.fetch_add(1, Ordering::Relaxed);
+                // 获取实际的文件名
+                let actual_filename = actual_path.file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(file_name);
+                
+                emit_log(&t_format("new_file_categorized", &[actual_filename, &category]), "success");
+                Self::record_organized_stats(events, downloads_path, 1);
+
+                // 发送文件整理事件
+                if let Some(events) = events {
+                    let meta = config.category_meta(&category_id);
+                    let (timestamp, display_time) = now_timestamps();
+                    let event = FileOrganizedEvent {
+                        file_name: file_name.to_string(),
+                        actual_file_name: actual_filename.to_string(),
+                        category: category.clone(),
+                        category_id: category_id.clone(),
+                        category_icon: meta.icon,
+                        category_color: meta.color,
+                        timestamp,
+                        display_time,
+                        folder_path: downloads_path.to_string_lossy().to_string(),
+                        original_path: path.to_string_lossy().to_string(),
+                        moved_to_path: actual_path.to_string_lossy().to_string(),
+                        notify: config.notifications_enabled_for(downloads_path),
+                        source: download_sources::take(path),
+                    };
+                    events.file_organized(&event);
+                }
+                MoveOutcome::Moved
+            }
+            Err(e) => {
+                let io_error = e.downcast_ref::<std::io::Error>();
+                if io_error.map(deferred::is_locked).unwrap_or(false) {
+                    Self::defer_locked_file(path, config, downloads_path, events, counters, DeferredOrigin::Monitor);
+                    return MoveOutcome::Locked;
+                }
+                match io_error.and_then(|io_error| permissions::diagnose(io_error, path, downloads_path)) {
+                    Some(issue) => {
+                        if let Some(events) = events {
+                            events.permission_denied(&issue);
+                        }
+                        emit_log(&issue.message, "error");
+                    }
+                    None => emit_log(&t_format("move_file_failed", &[&format!("{:?}", e)]), "error"),
+                }
+                MoveOutcome::Skipped
+            }
+        }
+    }
+
+    // 文件仍被占用或正被其他程序打开：放入延后队列，在后台按递增的间隔重试，不影响其他文件的整理。
+    // 重试按原来的方式移动：监控到的文件计入监控额度，停止监控时不再重试；手动整理的文件记录撤销历史。
+    // 已经在队列中的文件（重试时再次被占用）不重复登记
+    fn defer_locked_file(
+        path: &Path,
+        config: &Config,
+        downloads_path: &Path,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+        origin: DeferredOrigin,
+    ) {
+        let Some((file, cancelled)) = deferred::register(path, downloads_path, origin) else {
+            return;
+        };
+        Self::emit_log_to(events, &t_format("file_locked_deferred", &[&file.file_name]), "warning");
+        if let Some(events) = events {
+            events.file_deferred(&file);
+        }
+
+        let path = path.to_path_buf();
+        let config = config.clone();
+        let downloads_path = downloads_path.to_path_buf();
+        let events = events.clone();
+        let counters = counters.clone();
+        std::thread::spawn(move || {
+            let emit_log = |message: &str, log_type: &str| Self::emit_log_to(&events, message, log_type);
+            let mut outcome = MoveOutcome::Locked;
+            for delay in deferred::DEFERRED_RETRY_DELAYS {
+                // 停止监控时已经从队列中移除并通知过界面
+                if !deferred::wait_unless_cancelled(delay, &cancelled) {
+                    return;
+                }
+                deferred::record_attempt(&path);
+                // 等待期间文件可能已经被用户移走或删除
+                if !Self::is_organizable(&path, &config, &|_, _| {}) {
+                    outcome = MoveOutcome::Skipped;
+                    break;
+                }
+                let Some(category_id) = Self::get_file_category_static(&path, &config, &downloads_path) else {
+                    outcome = MoveOutcome::Skipped;
+                    break;
+                };
+                outcome = match origin {
+                    DeferredOrigin::Monitor => {
+                        Self::move_and_report(&path, &category_id, &config, &downloads_path, &events, &counters, &emit_log)
+                    }
+                    DeferredOrigin::Manual => Self::retry_manual_move(&path, &category_id, &config, &downloads_path, &events, &counters),
+                };
+                if outcome != MoveOutcome::Locked {
+                    break;
+                }
+            }
+
+            if outcome == MoveOutcome::Locked {
+                emit_log(&t_format("file_locked_gave_up", &[&file.file_name]), "error");
+            }
+            deferred::remove(&path);
+            if let Some(events) = &events {
+                events.file_deferred_resolved(&file.path, outcome == MoveOutcome::Moved);
+            }
+        });
+    }
+
+    // 重试手动整理时延后的文件：与手动整理相同，不占用监控额度，记录撤销历史并交给宿主保存
+    fn retry_manual_move(
+        path: &Path,
+        category_id: &str,
+        config: &Config,
+        downloads_path: &Path,
+        events: &Option<SharedEvents>,
+        counters: &Arc<SessionCounters>,
+    ) -> MoveOutcome {
+        let mut organizer = FileSortify {
+            downloads_path: downloads_path.to_path_buf(),
+            config: config.clone(),
+            monitoring_stop_signal: None,
+            monitoring_thread: None,
+            events: events.clone(),
+            undo_history: UndoHistory::new(1),
+            counters: counters.clone(),
+        };
+        match organizer.move_file_outcome(path, category_id, true) {
+            Ok(MoveOutcome::Moved) => {
+                Self::record_organized_stats(events, downloads_path, 1);
+                if let (Some(events), Some(action)) = (events, organizer.undo_history.get_latest_actions(1).first()) {
+                    events.deferred_manual_move(action);
+                }
+                MoveOutcome::Moved
+            }
+            Ok(outcome) => outcome,
+            Err(e) => {
+                Self::emit_log_to(events, &t_format("move_file_failed", &[&e.to_string()]), "error");
+                MoveOutcome::Skipped
+            }
+        }
+    }
+
+    // 优化的文件过滤逻辑
+    fn should_skip_file(file_name: &str, is_modify_event: bool) -> bool {
+        // 始终跳过的文件类型
+        if file_name.starts_with("._") || // macOS 资源分叉文件
+           file_name == ".DS_Store" || // macOS 系统文件
+           file_name.starts_with("~$") || // Office 临时文件
+           file_name.ends_with(".tmp") && file_name.len() < 10 || // 短的tmp文件通常是真正的临时文件
+           file_name.ends_with(".part") && !is_modify_event // Firefox下载文件，但修改事件时可能已完成
+        {
+            return true;
+        }
+
+        // 对于创建事件，跳过一些临时格式
+        if !is_modify_event && file_name.starts_with('.') && !Self::is_likely_final_file(file_name) {
+            return true;
+        }
+
+        false
+    }
+
+    // 判断以点开头的文件是否可能是最终文件
+    fn is_likely_final_file(file_name: &str) -> bool {
+        // 一些以点开头但是正常文件的情况
+        let normal_dot_files = [
+            ".env", ".gitignore", ".gitattributes", ".editorconfig",
+            ".eslintrc", ".prettierrc", ".babelrc", ".npmrc"
+        ];
+        
+        for normal_file in &normal_dot_files {
+            if file_name.starts_with(normal_file) {
+                return true;
+            }
+        }
+
+        // 如果文件有明确的扩展名，很可能是最终文件
+        if let Some(dot_pos) = file_name.rfind('.') {
+            if dot_pos > 1 && dot_pos < file_name.len() - 1 {
+                let extension = &file_name[dot_pos..];
+                // 检查是否是常见的文件扩展名
+                return matches!(extension, ".txt" | ".pdf" | ".jpg" | ".png" | ".mp4" | ".zip" | ".doc" | ".docx");
+            }
+        }
+
+        false
+    }
+    
+    // 撤销操作相关方法
+    pub fn get_undo_history(&self, count: usize) -> Vec<UndoAction> {
+        self.undo_history.get_latest_actions(count)
+    }
+    
+    pub fn undo_action(&mut self, action_id: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let action = self.undo_history.remove_action(action_id)
+            .ok_or("Undo action not found")?;
+        
+        // 检查目标文件是否还存在
+        if !Self::path_taken(&action.moved_to_path) {
+            return Err(format!("File {} has been deleted or moved", action.file_name).into());
+        }
+        
+        // 检查原始路径是否被占用
+        if Self::path_taken(&action.original_path) {
+            return Err(format!("Original location {} is occupied", action.original_path.display()).into());
+        }
+        
+        // 执行撤销（将文件移回原位置）
+        fs::rename(&action.moved_to_path, &action.original_path)?;
+        
+        let message = t_format("undo_action_success", &[&action.file_name]);
+        self.emit_log(&message, "success");
+        
+        // 发送撤销事件
+        if let Some(events) = &self.events {
+            let (timestamp, display_time) = now_timestamps();
+            let undo_event = serde_json::json!({
+                "action_id": action.id,
+                "file_name": action.file_name,
+                "original_path": action.original_path,
+                "category": action.category,
+                "timestamp": timestamp,
+                "display_time": display_time
+            });
+            
+            events.file_undone(&undo_event);
+        }
+        
+        Ok(message)
+    }
+    
+    pub fn clear_undo_history(&mut self) {
+        self.undo_history.clear();
+        self.emit_log(&t("undo_history_cleared"), "info");
+    }
+    
+    pub fn get_undo_history_count(&self) -> usize {
+        self.undo_history.len()
+    }
+}
\ No newline at end of file
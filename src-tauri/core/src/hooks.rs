@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// 整理过程中运行用户脚本的时机
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookStage {
+    BeforeMove,
+    AfterMove,
+    BeforeSession,
+    AfterSession,
+}
+
+impl HookStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookStage::BeforeMove => "before_move",
+            HookStage::AfterMove => "after_move",
+            HookStage::BeforeSession => "before_session",
+            HookStage::AfterSession => "after_session",
+        }
+    }
+
+    fn is_before(&self) -> bool {
+        matches!(self, HookStage::BeforeMove | HookStage::BeforeSession)
+    }
+}
+
+/// 脚本失败（退出码不为 0、超时或无法启动）时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum FailurePolicy {
+    // 记录警告后继续整理
+    #[default]
+    Continue,
+    // 移动前脚本失败时不移动这个文件，整理前脚本失败时不开始整理或监控；移动后和整理后的脚本失败只记录警告
+    Abort,
+}
+
+/// 一个用户脚本。macOS 和 Linux 上用 sh -c 运行，文件路径、分类和目标路径依次作为 $1、$2、$3；
+/// Windows 上用 PowerShell 运行，通过 $env:FILESORTIFY_FILE 等环境变量读取
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptHook {
+    pub command: String,
+    #[serde(rename = "timeoutSecs", default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    #[serde(rename = "onFailure", default)]
+    pub on_failure: FailurePolicy,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+/// 移动每个文件前后、每次整理（手动整理或一段监控）开始和结束时运行的脚本
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ScriptHooks {
+    #[serde(rename = "beforeMove", default, skip_serializing_if = "Option::is_none")]
+    pub before_move: Option<ScriptHook>,
+    #[serde(rename = "afterMove", default, skip_serializing_if = "Option::is_none")]
+    pub after_move: Option<ScriptHook>,
+    #[serde(rename = "beforeSession", default, skip_serializing_if = "Option::is_none")]
+    pub before_session: Option<ScriptHook>,
+    #[serde(rename = "afterSession", default, skip_serializing_if = "Option::is_none")]
+    pub after_session: Option<ScriptHook>,
+}
+
+impl ScriptHooks {
+    pub fn is_empty(&self) -> bool {
+        self.before_move.is_none() && self.after_move.is_none() && self.before_session.is_none() && self.after_session.is_none()
+    }
+
+    fn get(&self, stage: HookStage) -> Option<&ScriptHook> {
+        match stage {
+            HookStage::BeforeMove => self.before_move.as_ref(),
+            HookStage::AfterMove => self.after_move.as_ref(),
+            HookStage::BeforeSession => self.before_session.as_ref(),
+            HookStage::AfterSession => self.after_session.as_ref(),
+        }
+        .filter(|hook| !hook.command.trim().is_empty())
+    }
+}
+
+/// 传给脚本的信息，同时通过环境变量 FILESORTIFY_* 提供
+#[derive(Debug, Clone, Default)]
+pub struct HookContext<'a> {
+    pub folder: Option<&'a Path>,
+    pub file: Option<&'a Path>,
+    pub category: Option<&'a str>,
+    // 移动前为计划的目标路径（重名时实际路径会带数字后缀），移动后为实际路径
+    pub destination: Option<&'a Path>,
+    pub files_moved: Option<u64>,
+}
+
+/// 脚本失败；abort 为 true 时调用方应跳过这个文件或这次整理
+#[derive(Debug, Clone)]
+pub struct HookError {
+    pub message: String,
+    pub abort: bool,
+}
+
+/// 运行该时机配置的脚本，没有配置时直接返回
+pub fn run(hooks: &ScriptHooks, stage: HookStage, context: &HookContext) -> Result<(), HookError> {
+    let Some(hook) = hooks.get(stage) else {
+        return Ok(());
+    };
+    run_hook(hook, stage, context).map_err(|message| HookError {
+        message,
+        abort: hook.on_failure == FailurePolicy::Abort && stage.is_before(),
+    })
+}
+
+fn run_hook(hook: &ScriptHook, stage: HookStage, context: &HookContext) -> Result<(), String> {
+    let text = |path: Option<&Path>| path.map(|path| path.to_string_lossy().to_string()).unwrap_or_default();
+    let file = text(context.file);
+    let category = context.category.unwrap_or_default().to_string();
+    let destination = text(context.destination);
+
+    let mut command = shell_command(&hook.command, &[&file, &category, &destination]);
+    command
+        .env("FILESORTIFY_HOOK", stage.as_str())
+        .env("FILESORTIFY_FOLDER", text(context.folder))
+        .env("FILESORTIFY_FILE", &file)
+        .env("FILESORTIFY_CATEGORY", &category)
+        .env("FILESORTIFY_DESTINATION", &destination)
+        .env("FILESORTIFY_FILES_MOVED", context.files_moved.map(|count| count.to_string()).unwrap_or_default())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    if let Some(folder) = context.folder {
+        command.current_dir(folder);
+    }
+
+    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    let timeout = Duration::from_secs(hook.timeout_secs.max(1));
+    let started = Instant::now();
+    loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) if status.success() => return Ok(()),
+            Some(status) => return Err(format!("exit status {}", status.code().map(|code| code.to_string()).unwrap_or_else(|| "unknown".to_string()))),
+            None if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("timed out after {}s", timeout.as_secs()));
+            }
+            None => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+}
+
+#[cfg(windows)]
+fn shell_command(script: &str, _args: &[&str]) -> Command {
+    let mut command = Command::new("powershell");
+    command.args(["-NoProfile", "-NonInteractive", "-ExecutionPolicy", "Bypass", "-Command", script]);
+    command
+}
+
+#[cfg(not(windows))]
+fn shell_command(script: &str, args: &[&str]) -> Command {
+    let mut command = Command::new("sh");
+    // sh -c 之后的第一个参数是 $0
+    command.arg("-c").arg(script).arg("filesortify").args(args);
+    command
+}
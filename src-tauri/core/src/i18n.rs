@@ -0,0 +1,381 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::hash::Hash; // 添加这一行导入Hash trait
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)] // 添加Hash
+pub enum Language {
+    #[default]
+    English,
+    Chinese,
+    Spanish,
+    French,
+    German,
+    Japanese,
+    Korean,
+    Portuguese,
+}
+
+impl From<&str> for Language {
+    fn from(s: &str) -> Self {
+        // 只看主语言标签，"pt-BR"、"zh_CN" 等都能识别
+        let lower = s.to_lowercase();
+        let primary = lower.split(['-', '_']).next().unwrap_or("");
+        match primary {
+            "zh" | "chinese" | "中文" => Language::Chinese,
+            "es" | "spanish" | "español" => Language::Spanish,
+            "fr" | "french" | "français" => Language::French,
+            "de" | "german" | "deutsch" => Language::German,
+            "ja" | "japanese" | "日本語" => Language::Japanese,
+            "ko" | "korean" | "한국어" => Language::Korean,
+            "pt" | "portuguese" | "português" => Language::Portuguese,
+            _ => Language::English,
+        }
+    }
+}
+
+// 内置语言文件，编译时打包进程序
+const BUNDLED_LOCALES: &[(Language, &str)] = &[
+    (Language::English, include_str!("../../locales/en.json")),
+    (Language::Chinese, include_str!("../../locales/zh.json")),
+    (Language::Spanish, include_str!("../../locales/es.json")),
+    (Language::French, include_str!("../../locales/fr.json")),
+    (Language::German, include_str!("../../locales/de.json")),
+    (Language::Japanese, include_str!("../../locales/ja.json")),
+    (Language::Korean, include_str!("../../locales/ko.json")),
+    (Language::Portuguese, include_str!("../../locales/pt.json")),
+];
+
+lazy_static! {
+    static ref CURRENT_LANGUAGE: RwLock<Language> = RwLock::new(Language::default());
+    
+    static ref TRANSLATIONS: RwLock<HashMap<Language, HashMap<String, String>>> = RwLock::new(load_translations());
+}
+
+impl Language {
+    pub const ALL: [Language; 8] = [
+        Language::English,
+        Language::Chinese,
+        Language::Spanish,
+        Language::French,
+        Language::German,
+        Language::Japanese,
+        Language::Korean,
+        Language::Portuguese,
+    ];
+    
+    /// 语言代码，同时也是语言文件名
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::Chinese => "zh",
+            Language::Spanish => "es",
+            Language::French => "fr",
+            Language::German => "de",
+            Language::Japanese => "ja",
+            Language::Korean => "ko",
+            Language::Portuguese => "pt",
+        }
+    }
+    
+    /// 语言自身的名称，用于语言选择列表
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "中文",
+            Language::Spanish => "Español",
+            Language::French => "Français",
+            Language::German => "Deutsch",
+            Language::Japanese => "日本語",
+            Language::Korean => "한국어",
+            Language::Portuguese => "Português",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    pub code: String,
+    pub name: String,
+}
+
+/// 支持的语言列表
+pub fn supported_languages() -> Vec<LanguageInfo> {
+    Language::ALL
+        .iter()
+        .map(|lang| LanguageInfo {
+            code: lang.code().to_string(),
+            name: lang.native_name().to_string(),
+        })
+        .collect()
+}
+
+/// 根据系统区域设置选择最接近的支持语言，无法识别时使用英文
+pub fn detect_system_language() -> Language {
+    match sys_locale::get_locale() {
+        Some(locale) => {
+            log::info!("Detected system locale: {}", locale);
+            Language::from(locale.as_str())
+        }
+        None => Language::default(),
+    }
+}
+
+/// 用户自定义语言文件目录，其中的 `<语言代码>.json` 会覆盖或补充内置翻译
+pub fn locale_override_dir() -> Option<PathBuf> {
+    crate::app_paths::app_data_dir().map(|dir| dir.join("locales"))
+}
+
+// 先加载内置翻译，再叠加覆盖目录中的同名语言文件
+fn load_translations() -> HashMap<Language, HashMap<String, String>> {
+    let mut translations = HashMap::new();
+    
+    for (lang, content) in BUNDLED_LOCALES {
+        let strings = serde_json::from_str::<HashMap<String, String>>(content).unwrap_or_else(|e| {
+            log::error!("Invalid bundled locale {}: {}", lang.code(), e);
+            HashMap::new()
+        });
+        translations.insert(*lang, strings);
+    }
+    
+    if let Some(dir) = locale_override_dir() {
+        for lang in Language::ALL {
+            let path = dir.join(format!("{}.json", lang.code()));
+            if !path.exists() {
+                continue;
+            }
+            match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| serde_json::from_str::<HashMap<String, String>>(&content).map_err(|e| e.to_string()))
+            {
+                Ok(overrides) => translations.entry(lang).or_insert_with(HashMap::new).extend(overrides),
+                Err(e) => log::error!("Failed to load locale override {}: {}", path.display(), e),
+            }
+        }
+    }
+    
+    translations
+}
+
+/// 重新读取语言文件（修改覆盖目录中的文件后无需重启）
+pub fn reload_translations() {
+    let translations = load_translations();
+    *TRANSLATIONS.write().unwrap() = translations;
+}
+
+/// 设置当前语言
+pub fn set_language(lang: Language) {
+    let mut current = CURRENT_LANGUAGE.write().unwrap();
+    *current = lang;
+}
+
+/// 获取当前语言
+pub fn get_language() -> Language {
+    *CURRENT_LANGUAGE.read().unwrap()
+}
+
+/// 翻译函数，类似前端的t()
+///
+/// 回退顺序：当前语言 → 英文 → 键名
+pub fn t(key: &str) -> String {
+    let lang = get_language();
+    let translations = TRANSLATIONS.read().unwrap();
+    
+    if let Some(text) = translations.get(&lang).and_then(|strings| strings.get(key)) {
+        return text.to_string();
+    }
+    
+    if lang != Language::English {
+        if let Some(text) = translations.get(&Language::English).and_then(|strings| strings.get(key)) {
+            log::debug!("Missing {} translation for key: {}", lang.code(), key);
+            return text.to_string();
+        }
+    }
+    
+    log::warn!("Missing translation for key: {}", key);
+    key.to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingTranslations {
+    pub language: String,
+    pub total: usize,
+    pub missing: Vec<String>,
+}
+
+// 复数变体（`<键>.one` 等）因语言而异，不参与缺失检查
+fn is_plural_variant(key: &str) -> bool {
+    matches!(
+        key.rsplit_once('.').map(|(_, category)| category),
+        Some("zero" | "one" | "two" | "few" | "many" | "other")
+    )
+}
+
+/// 以英文为基准，列出某个语言缺少的翻译键
+pub fn missing_translations(lang: Language) -> MissingTranslations {
+    let translations = TRANSLATIONS.read().unwrap();
+    let empty = HashMap::new();
+    let reference = translations.get(&Language::English).unwrap_or(&empty);
+    let strings = translations.get(&lang).unwrap_or(&empty);
+    
+    let mut missing: Vec<String> = reference
+        .keys()
+        .filter(|key| !is_plural_variant(key) && !strings.contains_key(key.as_str()))
+        .cloned()
+        .collect();
+    missing.sort();
+    
+    MissingTranslations {
+        language: lang.code().to_string(),
+        total: reference.keys().filter(|key| !is_plural_variant(key)).count(),
+        missing,
+    }
+}
+
+/// 获取某个键在所有语言下的翻译，用于识别旧数据中已被本地化的名称
+pub fn t_all(key: &str) -> Vec<String> {
+    TRANSLATIONS
+        .read()
+        .unwrap()
+        .values()
+        .filter_map(|translations| translations.get(key).cloned())
+        .collect()
+}
+
+// 语言文件未提供或格式无效时使用的时间格式
+const DEFAULT_DATETIME_FORMAT: &str = "%Y/%m/%d %H:%M:%S";
+
+/// 按当前语言的 `datetime_format`（strftime 格式）格式化时间，用于界面显示
+pub fn format_datetime(time: &chrono::DateTime<chrono::Local>) -> String {
+    use chrono::format::{Item, StrftimeItems};
+    
+    let format = t("datetime_format");
+    // 覆盖目录中的格式可能写错，无效时回退到默认格式，避免格式化时 panic
+    let valid = format != "datetime_format" && !StrftimeItems::new(&format).any(|item| matches!(item, Item::Error));
+    let format = if valid { format.as_str() } else { DEFAULT_DATETIME_FORMAT };
+    time.format(format).to_string()
+}
+
+/// 当前时间的 ISO-8601 字符串和按当前语言格式化的显示字符串
+pub fn now_timestamps() -> (String, String) {
+    let now = chrono::Local::now();
+    (now.to_rfc3339(), format_datetime(&now))
+}
+
+/// 语言的复数规则，返回 CLDR 复数类别（one / few / many / other）
+fn plural_category(lang: Language, count: u64) -> &'static str {
+    match lang {
+        // 中日韩没有语法上的复数变化
+        Language::Chinese | Language::Japanese | Language::Korean => "other",
+        // 法语和葡萄牙语中 0 也用单数
+        Language::French | Language::Portuguese => {
+            if count <= 1 { "one" } else { "other" }
+        }
+        Language::English | Language::Spanish | Language::German => {
+            if count == 1 { "one" } else { "other" }
+        }
+    }
+}
+
+// 复数形式以 `<键>.<类别>` 存放，每种语言内缺少对应类别时依次回退到 `<键>.other` 和 `<键>` 本身；
+// 语言之间与 t 相同：当前语言 → 英文 → 键名
+fn plural_template(
+    translations: &HashMap<Language, HashMap<String, String>>,
+    lang: Language,
+    key: &str,
+    count: u64,
+) -> String {
+    let lookup = |lang: Language| {
+        let strings = translations.get(&lang)?;
+        strings
+            .get(&format!("{}.{}", key, plural_category(lang, count)))
+            .or_else(|| strings.get(&format!("{}.other", key)))
+            .or_else(|| strings.get(key))
+            .cloned()
+    };
+    lookup(lang)
+        .or_else(|| if lang != Language::English { lookup(Language::English) } else { None })
+        .unwrap_or_else(|| {
+            log::warn!("Missing translation for key: {}", key);
+            key.to_string()
+        })
+}
+
+/// 带格式化参数的翻译函数
+///
+/// 第一个参数是数量时，按当前语言的复数规则选择对应的文案
+pub fn t_format(key: &str, args: &[&str]) -> String {
+    let template = match args.first().and_then(|arg| arg.parse::<u64>().ok()) {
+        Some(count) => plural_template(&TRANSLATIONS.read().unwrap(), get_language(), key, count),
+        None => t(key),
+    };
+    let mut result = template.clone();
+    
+    for (i, arg) in args.iter().enumerate() {
+        // 支持 {0} 风格
+        result = result.replace(&format!("{{{}}}", i), arg);
+        // 兼容 {} 顺序占位符，每次只替换一个
+        if result.contains("{}") {
+            result = result.replacen("{}", arg, 1);
+        }
+    }
+    
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn translations(entries: &[(Language, &[(&str, &str)])]) -> HashMap<Language, HashMap<String, String>> {
+        entries
+            .iter()
+            .map(|(lang, strings)| {
+                let strings = strings.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+                (*lang, strings)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn english_uses_one_only_for_a_single_item() {
+        let translations = translations(&[(
+            Language::English,
+            &[("files_moved.one", "{0} file moved"), ("files_moved.other", "{0} files moved")],
+        )]);
+        assert_eq!(plural_template(&translations, Language::English, "files_moved", 0), "{0} files moved");
+        assert_eq!(plural_template(&translations, Language::English, "files_moved", 1), "{0} file moved");
+        assert_eq!(plural_template(&translations, Language::English, "files_moved", 2), "{0} files moved");
+    }
+
+    #[test]
+    fn french_treats_zero_as_singular() {
+        let translations = translations(&[(
+            Language::French,
+            &[("files_moved.one", "{0} fichier déplacé"), ("files_moved.other", "{0} fichiers déplacés")],
+        )]);
+        assert_eq!(plural_template(&translations, Language::French, "files_moved", 0), "{0} fichier déplacé");
+        assert_eq!(plural_template(&translations, Language::French, "files_moved", 1), "{0} fichier déplacé");
+        assert_eq!(plural_template(&translations, Language::French, "files_moved", 2), "{0} fichiers déplacés");
+    }
+
+    #[test]
+    fn missing_plural_forms_fall_back_to_english_then_key() {
+        let translations = translations(&[
+            (
+                Language::English,
+                &[("files_moved.one", "{0} file moved"), ("files_moved.other", "{0} files moved")],
+            ),
+            (Language::French, &[("files_skipped", "{0} fichier(s) ignoré(s)")]),
+        ]);
+        // 当前语言有普通翻译时优先使用
+        assert_eq!(plural_template(&translations, Language::French, "files_skipped", 3), "{0} fichier(s) ignoré(s)");
+        // 当前语言完全没有时使用英文，并按英文规则选择类别
+        assert_eq!(plural_template(&translations, Language::French, "files_moved", 0), "{0} files moved");
+        assert_eq!(plural_template(&translations, Language::French, "files_moved", 1), "{0} file moved");
+        assert_eq!(plural_template(&translations, Language::French, "unknown_key", 1), "unknown_key");
+    }
+}
@@ -0,0 +1,102 @@
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+// 超过这个时间没有修改的文件视为已经写完，不再检查。
+// Linux 和 macOS 的检查要遍历所有进程，对每个文件都检查开销太大
+const RECENT_WRITE_WINDOW: Duration = Duration::from_secs(10 * 60);
+
+/// 文件是否正被其他程序打开，移动前检查，避免移走仍在写入的文件（例如不使用临时文件名的下载工具）。
+/// 只检查最近修改过的文件。各平台的检查方式不同：Windows 尝试独占打开；
+/// Linux 查找以写入方式打开它的进程；macOS 使用 lsof。无法判断时按未被占用处理
+pub fn is_in_use(path: &Path) -> bool {
+    // 符号链接只移动链接本身，不检查指向的文件
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_file() && recently_modified(&metadata) => is_open_elsewhere(path),
+        _ => false,
+    }
+}
+
+// 无法读取修改时间时按最近修改处理，照常检查
+fn recently_modified(metadata: &fs::Metadata) -> bool {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_none_or(|age| age < RECENT_WRITE_WINDOW)
+}
+
+#[cfg(windows)]
+fn is_open_elsewhere(path: &Path) -> bool {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    // 不与其他句柄共享地打开：任何程序打开着这个文件时都会因共享冲突失败
+    match fs::OpenOptions::new().read(true).share_mode(0).open(path) {
+        Ok(_) => false,
+        Err(e) => crate::deferred::is_locked(&e),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn is_open_elsewhere(path: &Path) -> bool {
+    let Ok(target) = fs::canonicalize(path) else {
+        return false;
+    };
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return false;
+    };
+    let own_pid = std::process::id().to_string();
+    for process in processes.flatten() {
+        let pid = process.file_name().to_string_lossy().to_string();
+        if pid == own_pid || !pid.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+        // 其他用户的进程没有权限读取，跳过
+        let Ok(fds) = fs::read_dir(process.path().join("fd")) else {
+            continue;
+        };
+        for fd in fds.flatten() {
+            if fs::read_link(fd.path()).map(|link| link == target).unwrap_or(false)
+                && opened_for_writing(&process.path().join("fdinfo").join(fd.file_name()))
+            {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// fdinfo 中的 flags 是八进制的打开标志，O_WRONLY 为 1，O_RDWR 为 2
+#[cfg(target_os = "linux")]
+fn opened_for_writing(fdinfo: &Path) -> bool {
+    fs::read_to_string(fdinfo)
+        .ok()
+        .and_then(|info| {
+            info.lines()
+                .find_map(|line| line.strip_prefix("flags:"))
+                .and_then(|flags| u32::from_str_radix(flags.trim(), 8).ok())
+        })
+        .map(|flags| flags & 0o3 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "macos")]
+fn is_open_elsewhere(path: &Path) -> bool {
+    // -F a 输出每个打开方式的访问模式：r 只读，w 写入，u 读写；没有进程打开时输出为空
+    let output = match std::process::Command::new("/usr/sbin/lsof")
+        .args(["-w", "-F", "a", "--"])
+        .arg(path)
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return false,
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .any(|line| line == "aw" || line == "au")
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+fn is_open_elsewhere(_path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,17 @@
+//! FileSortify 的整理核心：配置、分类规则和文件整理，不依赖 Tauri。
+//! 桌面应用和命令行版本都使用这里的实现，界面相关的事件通过 events::OrganizerEvents 交给宿主处理
+
+pub mod app_paths;
+pub mod config;
+pub mod config_migration;
+pub mod deferred;
+pub mod download_sources;
+pub mod events;
+pub mod file_organizer;
+pub mod hooks;
+pub mod i18n;
+pub mod in_use;
+pub mod pending;
+pub mod permissions;
+pub mod presets;
+pub mod unmatched;
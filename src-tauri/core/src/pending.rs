@@ -0,0 +1,122 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use crate::events::SharedEvents;
+use crate::i18n::t;
+
+/// 等待确认超时后的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeoutAction {
+    // 保留在原处，不移动
+    #[default]
+    Skip,
+    // 按建议的位置移动
+    Move,
+}
+
+/// 用户对待确认文件的决定
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Confirm,
+    Deny,
+    Timeout,
+}
+
+impl Decision {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Decision::Confirm => "confirm",
+            Decision::Deny => "deny",
+            Decision::Timeout => "timeout",
+        }
+    }
+}
+
+/// 发送给前端的待确认移动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingMove {
+    pub id: String,
+    pub file_name: String,
+    pub category: String,
+    pub category_id: String,
+    pub folder_path: String,
+    pub original_path: String,
+    pub proposed_path: String, // 建议的目标路径（重名时实际移动可能加数字后缀）
+    pub timestamp: String, // ISO-8601
+    pub display_time: String, // 按当前语言格式化的时间
+    pub expires_at: String, // ISO-8601，超过后按 timeout_action 处理
+    pub timeout_action: TimeoutAction,
+}
+
+struct Waiting {
+    pending: PendingMove,
+    sender: Sender<Decision>,
+}
+
+lazy_static! {
+    // 待确认 ID -> 等待中的请求
+    static ref WAITING: Mutex<HashMap<String, Waiting>> = Mutex::new(HashMap::new());
+}
+
+/// 文件是否已经在等待确认，避免同一个文件的多次修改事件重复询问
+pub fn is_pending(original_path: &Path) -> bool {
+    let original_path = original_path.to_string_lossy();
+    WAITING
+        .lock()
+        .unwrap()
+        .values()
+        .any(|w| w.pending.original_path == original_path)
+}
+
+/// 所有等待确认的移动，按时间从早到晚（窗口重新打开时用来恢复列表）
+pub fn list() -> Vec<PendingMove> {
+    let mut pending: Vec<PendingMove> = WAITING
+        .lock()
+        .unwrap()
+        .values()
+        .map(|w| w.pending.clone())
+        .collect();
+    pending.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+    pending
+}
+
+/// 登记待确认的移动并通知宿主，返回用于接收决定的通道
+pub fn register(events: &Option<SharedEvents>, pending: PendingMove) -> Receiver<Decision> {
+    let (tx, rx) = channel();
+    WAITING.lock().unwrap().insert(pending.id.clone(), Waiting { pending: pending.clone(), sender: tx });
+
+    if let Some(events) = events {
+        events.file_pending(&pending);
+    }
+    rx
+}
+
+/// 阻塞等待前端的决定，超时返回 Decision::Timeout
+pub fn wait(events: &Option<SharedEvents>, id: &str, decisions: Receiver<Decision>, timeout: Duration) -> Decision {
+    let decision = match decisions.recv_timeout(timeout) {
+        Ok(decision) => decision,
+        Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => Decision::Timeout,
+    };
+    WAITING.lock().unwrap().remove(id);
+
+    // 通知前端移除这条待确认项（超时或在其他窗口中处理的情况）
+    if let Some(events) = events {
+        events.file_pending_resolved(id, decision);
+    }
+
+    decision
+}
+
+/// 前端确认或拒绝一个待确认的移动
+pub fn resolve(id: &str, confirm: bool) -> Result<(), String> {
+    let waiting = WAITING.lock().unwrap();
+    let entry = waiting.get(id).ok_or_else(|| t("pending_move_not_found"))?;
+    let decision = if confirm { Decision::Confirm } else { Decision::Deny };
+    // 等待的线程已经超时退出时发送会失败，按找不到处理
+    entry.sender.send(decision).map_err(|_| t("pending_move_not_found"))
+}
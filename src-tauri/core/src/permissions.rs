@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+use crate::i18n::t_format;
+
+// 系统设置中对应的隐私设置页
+const FULL_DISK_ACCESS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles";
+const FILES_AND_FOLDERS_URL: &str = "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders";
+
+// errno：EPERM 在 macOS 上通常表示被隐私保护（TCC）拒绝，EACCES 是普通的文件权限不足
+const EPERM: i32 = 1;
+const EACCES: i32 = 13;
+const EROFS: i32 = 30;
+
+/// 权限问题的类型，界面据此给出对应的处理步骤
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionIssueKind {
+    // macOS 的桌面、文稿或下载文件夹需要在“文件与文件夹”中允许访问
+    ProtectedFolder,
+    // macOS 的外置或网络宗卷需要允许访问“可移除的宗卷”或“网络宗卷”
+    ExternalVolume,
+    // 其他受保护的位置（例如 iCloud 云盘、邮件附件）需要“完全磁盘访问权限”
+    FullDiskAccess,
+    // 宗卷以只读方式装载
+    ReadOnlyVolume,
+    // 文件或文件夹的权限不允许当前用户修改
+    AccessDenied,
+}
+
+/// 移动文件时遇到的权限问题，通过 OrganizerEvents::permission_denied 交给界面引导用户授权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionIssue {
+    pub kind: PermissionIssueKind,
+    // 无法访问的文件或文件夹
+    pub path: String,
+    // 正在整理的文件夹
+    pub folder_path: String,
+    // 受保护的系统文件夹："desktop"、"documents" 或 "downloads"
+    pub protected_folder: Option<String>,
+    // 打开系统设置中对应页面的地址，只有 macOS 有
+    pub settings_url: Option<String>,
+    // 给用户看的说明，代替原始的系统错误
+    pub message: String,
+}
+
+/// 判断错误是否为权限问题，以及需要用户做什么；其他错误返回 None
+pub fn diagnose(error: &io::Error, path: &Path, folder_path: &Path) -> Option<PermissionIssue> {
+    // Windows 的错误码含义不同，只按错误类型判断
+    let code = if cfg!(unix) { error.raw_os_error() } else { None };
+    if code != Some(EROFS) && code != Some(EPERM) && code != Some(EACCES) && error.kind() != io::ErrorKind::PermissionDenied {
+        return None;
+    }
+
+    let mut protected_folder = None;
+    let kind = if code == Some(EROFS) {
+        PermissionIssueKind::ReadOnlyVolume
+    } else if cfg!(target_os = "macos") && code == Some(EPERM) {
+        if folder_path.starts_with("/Volumes") {
+            PermissionIssueKind::ExternalVolume
+        } else if let Some(folder) = protected_home_folder(folder_path) {
+            protected_folder = Some(folder.to_string());
+            PermissionIssueKind::ProtectedFolder
+        } else {
+            PermissionIssueKind::FullDiskAccess
+        }
+    } else {
+        PermissionIssueKind::AccessDenied
+    };
+
+    let path_text = path.to_string_lossy().to_string();
+    let message = match kind {
+        PermissionIssueKind::ProtectedFolder => t_format("permission_protected_folder", &[&folder_path.to_string_lossy()]),
+        PermissionIssueKind::ExternalVolume => t_format("permission_external_volume", &[&folder_path.to_string_lossy()]),
+        PermissionIssueKind::FullDiskAccess => t_format("permission_full_disk_access", &[&path_text]),
+        PermissionIssueKind::ReadOnlyVolume => t_format("permission_read_only_volume", &[&path_text]),
+        PermissionIssueKind::AccessDenied => t_format("permission_access_denied", &[&path_text]),
+    };
+
+    Some(PermissionIssue {
+        kind,
+        path: path_text,
+        folder_path: folder_path.to_string_lossy().to_string(),
+        protected_folder,
+        settings_url: settings_url(kind).map(str::to_string),
+        message,
+    })
+}
+
+/// 系统设置中可以授权的页面，只有 macOS 有
+pub fn settings_url(kind: PermissionIssueKind) -> Option<&'static str> {
+    if !cfg!(target_os = "macos") {
+        return None;
+    }
+    match kind {
+        PermissionIssueKind::ProtectedFolder | PermissionIssueKind::ExternalVolume => Some(FILES_AND_FOLDERS_URL),
+        PermissionIssueKind::FullDiskAccess => Some(FULL_DISK_ACCESS_URL),
+        PermissionIssueKind::ReadOnlyVolume | PermissionIssueKind::AccessDenied => None,
+    }
+}
+
+// 受 macOS 隐私保护的用户文件夹
+fn protected_home_folder(path: &Path) -> Option<&'static str> {
+    let home = dirs::home_dir()?;
+    [("Desktop", "desktop"), ("Documents", "documents"), ("Downloads", "downloads")]
+        .into_iter()
+        .find(|(name, _)| path.starts_with(home.join(name)))
+        .map(|(_, id)| id)
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::config::{CategoryMeta, Config};
+use crate::i18n::{t, t_format};
+
+// 预设中的一个分类；ID 已存在时只追加扩展名
+struct PresetCategory {
+    id: &'static str,
+    extensions: &'static [&'static str],
+    icon: Option<&'static str>,
+    color: Option<&'static str>,
+}
+
+struct Preset {
+    id: &'static str,
+    categories: &'static [PresetCategory],
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        id: "developer",
+        categories: &[
+            PresetCategory {
+                id: "code",
+                extensions: &[".ts", ".tsx", ".jsx", ".json", ".yaml", ".yml", ".toml", ".sh", ".swift", ".kt", ".vue"],
+                icon: None,
+                color: None,
+            },
+            PresetCategory {
+                id: "databases",
+                extensions: &[".sql", ".sqlite", ".db", ".dump"],
+                icon: Some("🗄️"),
+                color: Some("#546E7A"),
+            },
+        ],
+    },
+    Preset {
+        id: "designer",
+        categories: &[
+            PresetCategory {
+                id: "design",
+                extensions: &[".psd", ".ai", ".sketch", ".fig", ".xd", ".indd", ".afdesign", ".afphoto", ".eps"],
+                icon: Some("🎨"),
+                color: Some("#EC407A"),
+            },
+            PresetCategory {
+                id: "fonts",
+                extensions: &[".eot", ".pfb", ".pfm"],
+                icon: None,
+                color: None,
+            },
+        ],
+    },
+    Preset {
+        id: "photographer",
+        categories: &[
+            PresetCategory {
+                id: "raw_photos",
+                extensions: &[".cr2", ".cr3", ".nef", ".arw", ".dng", ".orf", ".rw2", ".raf", ".srw"],
+                icon: Some("📷"),
+                color: Some("#FFA726"),
+            },
+            PresetCategory {
+                id: "images",
+                extensions: &[".heic", ".heif", ".avif"],
+                icon: None,
+                color: None,
+            },
+            PresetCategory {
+                id: "photo_sidecars",
+                extensions: &[".xmp", ".lrcat", ".cos"],
+                icon: Some("🏷️"),
+                color: Some("#BDBDBD"),
+            },
+        ],
+    },
+    Preset {
+        id: "student",
+        categories: &[
+            PresetCategory {
+                id: "notes",
+                extensions: &[".md", ".one", ".enex", ".ipynb"],
+                icon: Some("📝"),
+                color: Some("#FFCA28"),
+            },
+            PresetCategory {
+                id: "documents",
+                extensions: &[".tex", ".bib", ".mobi", ".azw3"],
+                icon: None,
+                color: None,
+            },
+        ],
+    },
+    Preset {
+        id: "accountant",
+        categories: &[
+            PresetCategory {
+                id: "finance",
+                extensions: &[".qbw", ".qbb", ".ofx", ".qfx", ".qif", ".xbrl"],
+                icon: Some("💰"),
+                color: Some("#43A047"),
+            },
+            PresetCategory {
+                id: "spreadsheets",
+                extensions: &[".xlsm", ".xlsb", ".tsv"],
+                icon: None,
+                color: None,
+            },
+        ],
+    },
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetApplyReport {
+    pub preset: String,
+    pub added_categories: Vec<String>,
+    // 分类ID -> 本次新增的扩展名
+    pub added_extensions: HashMap<String, Vec<String>>,
+    // 已属于其他分类而被跳过的扩展名，避免同一扩展名匹配到多个分类
+    pub skipped_extensions: Vec<String>,
+}
+
+pub fn list_presets() -> Vec<PresetInfo> {
+    PRESETS
+        .iter()
+        .map(|preset| PresetInfo {
+            id: preset.id.to_string(),
+            name: t(&format!("preset_{}", preset.id)),
+            description: t(&format!("preset_{}_description", preset.id)),
+            categories: preset.categories.iter().map(|c| c.id.to_string()).collect(),
+        })
+        .collect()
+}
+
+/// 在内置预设中查找包含该扩展名的分类，作为未匹配扩展名的建议
+pub fn suggest_category(extension: &str) -> Option<String> {
+    PRESETS
+        .iter()
+        .flat_map(|preset| preset.categories.iter())
+        .find(|category| category.extensions.iter().any(|e| e.eq_ignore_ascii_case(extension)))
+        .map(|category| category.id.to_string())
+}
+
+/// 把预设合并进现有配置，不覆盖用户已有的分类和扩展名
+pub fn apply_preset(config: &mut Config, preset_id: &str) -> Result<PresetApplyReport, String> {
+    let preset = PRESETS
+        .iter()
+        .find(|p| p.id == preset_id)
+        .ok_or_else(|| t_format("preset_not_found", &[preset_id]))?;
+
+    let mut report = PresetApplyReport {
+        preset: preset.id.to_string(),
+        added_categories: Vec::new(),
+        added_extensions: HashMap::new(),
+        skipped_extensions: Vec::new(),
+    };
+
+    for preset_category in preset.categories {
+        let category_id = preset_category.id.to_string();
+
+        if !config.categories.contains_key(&category_id) {
+            config.categories.insert(category_id.clone(), Vec::new());
+            config
+                .category_names
+                .entry(category_id.clone())
+                .or_insert_with(|| t(&format!("category_{}", preset_category.id)));
+            if preset_category.icon.is_some() || preset_category.color.is_some() {
+                config.category_meta.entry(category_id.clone()).or_insert(CategoryMeta {
+                    icon: preset_category.icon.map(|s| s.to_string()),
+                    color: preset_category.color.map(|s| s.to_string()),
+                    description: None,
+                });
+            }
+            report.added_categories.push(category_id.clone());
+        }
+
+        for ext in preset_category.extensions {
+            let ext = ext.to_string();
+            let owner = config
+                .categories
+                .iter()
+                .find(|(_, exts)| exts.iter().any(|e| e.eq_ignore_ascii_case(&ext)))
+                .map(|(id, _)| id.clone());
+
+            match owner {
+                Some(owner) if owner == category_id => {}
+                Some(_) => report.skipped_extensions.push(ext),
+                None => {
+                    if let Some(extensions) = config.categories.get_mut(&category_id) {
+                        extensions.push(ext.clone());
+                    }
+                    report.added_extensions.entry(category_id.clone()).or_default().push(ext);
+                }
+            }
+        }
+
+        // 扩展名全部被其他分类占用时，不保留空分类
+        if report.added_categories.contains(&category_id) && !report.added_extensions.contains_key(&category_id) {
+            config.remove_category(&category_id);
+            report.added_categories.retain(|id| id != &category_id);
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,165 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::presets;
+
+lazy_static! {
+    // 监控线程和命令可能同时更新目录文件
+    static ref CATALOG_LOCK: Mutex<()> = Mutex::new(());
+    // 还没有写入文件的记录，整理结束时由 flush 一次合并写入；计数是增量
+    static ref PENDING: Mutex<UnmatchedCatalog> = Mutex::new(UnmatchedCatalog::default());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ExtensionRecord {
+    count: u64,
+    last_seen: String,
+    sample_file: String,
+}
+
+/// 整理时遇到的、没有匹配任何分类的扩展名，按文件夹分别统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct UnmatchedCatalog {
+    // 文件夹路径 -> 扩展名 -> 记录
+    folders: HashMap<String, HashMap<String, ExtensionRecord>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnmatchedExtension {
+    pub extension: String,
+    pub count: u64,
+    pub last_seen: String,
+    pub sample_file: String,
+    // 内置预设中包含该扩展名的分类，可作为建议
+    pub suggested_category: Option<String>,
+}
+
+impl UnmatchedCatalog {
+    fn load() -> Self {
+        fs::read_to_string(Self::get_catalog_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::get_catalog_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn get_catalog_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("unmatched_extensions.json")
+        } else {
+            PathBuf::from("file_organizer_unmatched_extensions.json")
+        }
+    }
+}
+
+/// 记录一个未匹配的文件；没有扩展名的文件不记录。
+/// 记录先保存在内存中，调用 flush 后才写入文件
+pub fn record(folder: &Path, file_path: &Path) {
+    let extension = match file_path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) => format!(".{}", ext.to_lowercase()),
+        None => return,
+    };
+    let file_name = file_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    let record = pending
+        .folders
+        .entry(folder.to_string_lossy().to_string())
+        .or_default()
+        .entry(extension)
+        .or_default();
+    record.count += 1;
+    record.last_seen = chrono::Utc::now().to_rfc3339();
+    record.sample_file = file_name;
+}
+
+/// 把内存中的记录合并到目录文件；没有新记录时不读写文件
+pub fn flush() {
+    let _guard = CATALOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = flush_locked() {
+        log::error!("Failed to save unmatched extensions: {}", e);
+    }
+}
+
+// 调用方需持有 CATALOG_LOCK
+fn flush_locked() -> Result<(), Box<dyn std::error::Error>> {
+    let pending = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    if pending.folders.is_empty() {
+        return Ok(());
+    }
+    let mut catalog = UnmatchedCatalog::load();
+    for (folder, records) in pending.folders {
+        let folder_records = catalog.folders.entry(folder).or_default();
+        for (extension, record) in records {
+            let entry = folder_records.entry(extension).or_default();
+            entry.count += record.count;
+            entry.last_seen = record.last_seen;
+            entry.sample_file = record.sample_file;
+        }
+    }
+    catalog.save()
+}
+
+/// 列出未匹配的扩展名，按出现次数降序；不指定文件夹时汇总所有文件夹
+pub fn list(folder: Option<&str>) -> Vec<UnmatchedExtension> {
+    let _guard = CATALOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = flush_locked() {
+        log::error!("Failed to save unmatched extensions: {}", e);
+    }
+    let catalog = UnmatchedCatalog::load();
+
+    let mut merged: HashMap<String, ExtensionRecord> = HashMap::new();
+    for (path, records) in &catalog.folders {
+        if folder.map(|f| f != path).unwrap_or(false) {
+            continue;
+        }
+        for (extension, record) in records {
+            let entry = merged.entry(extension.clone()).or_default();
+            entry.count += record.count;
+            if record.last_seen > entry.last_seen {
+                entry.last_seen = record.last_seen.clone();
+                entry.sample_file = record.sample_file.clone();
+            }
+        }
+    }
+
+    let mut result: Vec<UnmatchedExtension> = merged
+        .into_iter()
+        .map(|(extension, record)| UnmatchedExtension {
+            suggested_category: presets::suggest_category(&extension),
+            extension,
+            count: record.count,
+            last_seen: record.last_seen,
+            sample_file: record.sample_file,
+        })
+        .collect();
+    result.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.extension.cmp(&b.extension)));
+    result
+}
+
+/// 扩展名已分配到分类后，从所有文件夹的记录中移除
+pub fn forget(extension: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let _guard = CATALOG_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    flush_locked()?;
+    let mut catalog = UnmatchedCatalog::load();
+    for records in catalog.folders.values_mut() {
+        records.remove(extension);
+    }
+    catalog.folders.retain(|_, records| !records.is_empty());
+    catalog.save()
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::file_organizer::FileOrganizedEvent;
+
+// 只保留最近 5 周的记录，足够生成上一周的汇总
+const RETENTION_DAYS: i64 = 35;
+
+lazy_static! {
+    // 读写 activity.jsonl 时加锁，同时记录上次清理过期记录的日期
+    static ref FILE_LOCK: Mutex<Option<NaiveDate>> = Mutex::new(None);
+}
+
+/// 一个被整理的文件，按行追加保存在应用数据目录的 activity.jsonl 中。
+/// 界面中的整理历史保存在前端且只保留最近的记录，每周汇总使用这里的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    pub timestamp: DateTime<Utc>,
+    pub category_id: String,
+    pub category: String,
+    // 移动的字节数
+    pub size: u64,
+    // 目标文件夹中已经有内容相同的同名文件
+    #[serde(default)]
+    pub duplicate: bool,
+}
+
+/// 在后台记录整理事件；需要读取文件大小，重名时还要比较内容，不阻塞整理
+pub fn record(event: &FileOrganizedEvent) {
+    let event = event.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let moved_to = Path::new(&event.moved_to_path);
+        let entry = ActivityEntry {
+            timestamp: DateTime::parse_from_rfc3339(&event.timestamp)
+                .map(|timestamp| timestamp.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            category_id: event.category_id.clone(),
+            category: event.category.clone(),
+            // 不跟随符号链接，移动链接时只计链接本身
+            size: fs::symlink_metadata(moved_to).map(|metadata| metadata.len()).unwrap_or(0),
+            duplicate: is_duplicate(&event, moved_to),
+        };
+        if let Err(e) = append(&entry) {
+            log::warn!("Failed to record activity: {}", e);
+        }
+    });
+}
+
+/// 读取 since 之后的记录
+pub fn since(since: DateTime<Utc>) -> Vec<ActivityEntry> {
+    let _guard = FILE_LOCK.lock().unwrap();
+    read()
+        .into_iter()
+        .filter(|entry| entry.timestamp >= since)
+        .collect()
+}
+
+/// 删除全部记录，例如清除整理历史时
+pub fn clear() {
+    let _guard = FILE_LOCK.lock().unwrap();
+    let _ = fs::remove_file(activity_path());
+}
+
+fn activity_path() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("activity.jsonl"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_activity.jsonl"))
+}
+
+fn append(entry: &ActivityEntry) -> Result<(), Box<dyn std::error::Error>> {
+    let mut last_pruned = FILE_LOCK.lock().unwrap();
+    let path = activity_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    drop(file);
+
+    // 每天第一次记录时清理过期的记录
+    let today = Utc::now().date_naive();
+    if *last_pruned != Some(today) {
+        prune(&path)?;
+        *last_pruned = Some(today);
+    }
+    Ok(())
+}
+
+fn read() -> Vec<ActivityEntry> {
+    fs::read_to_string(activity_path())
+        .map(|content| content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+        .unwrap_or_default()
+}
+
+fn prune(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let cutoff = Utc::now() - chrono::Duration::days(RETENTION_DAYS);
+    let mut content = String::new();
+    for entry in read().into_iter().filter(|entry| entry.timestamp >= cutoff) {
+        content.push_str(&serde_json::to_string(&entry)?);
+        content.push('\n');
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+// 文件因重名被改名时，比较目标文件夹中原来的同名文件与移动后的文件内容是否相同
+fn is_duplicate(event: &FileOrganizedEvent, moved_to: &Path) -> bool {
+    if event.actual_file_name == event.file_name {
+        return false;
+    }
+    let Some(existing) = moved_to.parent().map(|dir| dir.join(&event.file_name)) else {
+        return false;
+    };
+    match (fs::symlink_metadata(&existing), fs::symlink_metadata(moved_to)) {
+        // 符号链接不读取指向的内容
+        (Ok(existing_metadata), Ok(moved_metadata))
+            if existing_metadata.is_file() && moved_metadata.is_file() && existing_metadata.len() == moved_metadata.len() =>
+        {
+            let existing_hash = file_hash(&existing);
+            existing_hash.is_some() && existing_hash == file_hash(moved_to)
+        }
+        _ => false,
+    }
+}
+
+fn file_hash(path: &Path) -> Option<Vec<u8>> {
+    let mut file = fs::File::open(path).ok()?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).ok()?;
+    Some(hasher.finalize().to_vec())
+}
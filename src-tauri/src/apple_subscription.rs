@@ -1,7 +1,22 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use base64::Engine;
 use chrono::{DateTime, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
+use x509_parser::prelude::*;
+
+// App Store Server API
+const SERVER_API_PRODUCTION_URL: &str = "https://api.storekit.itunes.apple.com";
+const SERVER_API_SANDBOX_URL: &str = "https://api.storekit-sandbox.itunes.apple.com";
+
+// Apple Root CA - G3 证书的 SHA-256 指纹，签名交易的证书链必须以它结尾
+const APPLE_ROOT_CA_G3_SHA256: &str = "63343abfb89a6a03ebb57e9b3f5fa7be7c4f5c756f3017b3a8c488c3653e9179";
+// 叶子证书和中间证书必须带有的 Apple 扩展
+const LEAF_CERT_OID: &str = "1.2.840.113635.100.6.11.1";
+const INTERMEDIATE_CERT_OID: &str = "1.2.840.113635.100.6.2.1";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppleReceiptData {
@@ -54,27 +69,122 @@ pub struct ApplePendingRenewal {
 pub struct AppleSubscriptionStatus {
     pub is_active: bool,
     pub product_id: String,
+    pub original_transaction_id: String,
     pub expires_date: Option<DateTime<Utc>>,
     pub is_trial: bool,
     pub is_cancelled: bool,
     pub auto_renew_status: bool,
 }
 
+/// App Store Server API 凭据（App Store Connect 中生成的 App 内购买密钥）
+#[derive(Debug, Clone)]
+pub struct AppStoreServerApiConfig {
+    pub issuer_id: String,
+    pub key_id: String,
+    pub private_key: String, // .p8 密钥文件的 PEM 内容
+    pub sandbox: bool,
+}
+
+impl AppStoreServerApiConfig {
+    /// 从环境变量读取凭据，未配置时返回 None
+    pub fn from_env() -> Option<Self> {
+        let issuer_id = std::env::var("APPLE_ISSUER_ID").ok()?;
+        let key_id = std::env::var("APPLE_KEY_ID").ok()?;
+        let private_key = std::fs::read_to_string(std::env::var("APPLE_PRIVATE_KEY_PATH").ok()?).ok()?;
+        let sandbox = std::env::var("APPLE_STOREKIT_SANDBOX").map(|v| v == "1" || v == "true").unwrap_or(false);
+        Some(Self { issuer_id, key_id, private_key, sandbox })
+    }
+}
+
+/// StoreKit 2 签名交易（JWSTransaction）解码后的内容，时间均为毫秒时间戳
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsTransaction {
+    pub transaction_id: String,
+    pub original_transaction_id: String,
+    pub bundle_id: String,
+    pub product_id: String,
+    pub purchase_date: i64,
+    #[serde(default)]
+    pub expires_date: Option<i64>,
+    #[serde(default)]
+    pub revocation_date: Option<i64>,
+    // 1 表示首次优惠（包括免费试用）
+    #[serde(default)]
+    pub offer_type: Option<i32>,
+    #[serde(rename = "type")]
+    pub transaction_type: String,
+    #[serde(default)]
+    pub environment: Option<String>,
+    pub signed_date: i64,
+}
+
+/// 签名的续订信息（JWSRenewalInfo）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JwsRenewalInfo {
+    pub original_transaction_id: String,
+    pub product_id: String,
+    pub auto_renew_status: i32,
+    #[serde(default)]
+    pub expiration_intent: Option<i32>,
+}
+
+// GET /inApps/v1/subscriptions/{transactionId} 的响应
+#[derive(Debug, Deserialize)]
+struct SubscriptionStatusesResponse {
+    data: Vec<SubscriptionGroupStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubscriptionGroupStatus {
+    last_transactions: Vec<LastTransaction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastTransaction {
+    original_transaction_id: String,
+    // 1 有效，2 已过期，3 扣款重试中，4 扣款宽限期，5 已撤销
+    status: i32,
+    signed_transaction_info: String,
+    signed_renewal_info: String,
+}
+
+// App Store Server API 身份令牌的内容
+#[derive(Debug, Serialize)]
+struct ServerApiClaims {
+    iss: String,
+    iat: i64,
+    exp: i64,
+    aud: &'static str,
+    bid: String,
+}
+
 pub struct AppleSubscriptionValidator {
     client: Client,
     shared_secret: String,
     bundle_id: String,
+    server_api: Option<AppStoreServerApiConfig>,
 }
 
 impl AppleSubscriptionValidator {
     pub fn new(shared_secret: String, bundle_id: String) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::http::client(),
             shared_secret,
             bundle_id,
+            server_api: None,
         }
     }
 
+    /// 启用 App Store Server API，用于查询 StoreKit 2 交易的最新状态
+    pub fn with_server_api(mut self, config: AppStoreServerApiConfig) -> Self {
+        self.server_api = Some(config);
+        self
+    }
+
     /// 验证App Store收据
     pub async fn verify_receipt(&self, receipt_data: &str) -> Result<AppleVerificationResponse, Box<dyn std::error::Error>> {
         let request_body = AppleReceiptData {
@@ -171,6 +281,7 @@ impl AppleSubscriptionValidator {
         Ok(AppleSubscriptionStatus {
             is_active,
             product_id: latest_transaction.product_id.clone(),
+            original_transaction_id: latest_transaction.original_transaction_id.clone(),
             expires_date,
             is_trial,
             is_cancelled,
@@ -184,11 +295,173 @@ impl AppleSubscriptionValidator {
         matches!(product_id, "com.fileSortify.monthly" | "com.fileSortify.yearly")
     }
 
-    /// 验证收据并返回订阅状态
+    /// 验证收据并返回订阅状态。
+    /// StoreKit 2 提供的是签名交易（JWS），旧版 StoreKit 提供的是 base64 收据，后者仍使用 verifyReceipt
     pub async fn validate_subscription(&self, receipt_data: &str) -> Result<AppleSubscriptionStatus, Box<dyn std::error::Error>> {
+        if is_jws(receipt_data) {
+            let transaction = self.parse_signed_transaction(receipt_data)?;
+            if self.server_api.is_some() {
+                // 本地的交易可能已经续费或被撤销，以服务端的最新状态为准
+                return self.validate_transaction(&transaction.original_transaction_id).await;
+            }
+            return Ok(self.status_from_transaction(&transaction, None, None));
+        }
+
         let verification_response = self.verify_receipt(receipt_data).await?;
         self.get_subscription_status(&verification_response)
     }
+
+    /// 通过 App Store Server API 查询交易所在订阅的最新状态
+    pub async fn validate_transaction(&self, transaction_id: &str) -> Result<AppleSubscriptionStatus, Box<dyn std::error::Error>> {
+        let statuses = self.get_subscription_statuses(transaction_id).await?;
+
+        let mut latest: Option<(JwsTransaction, JwsRenewalInfo, i32)> = None;
+        for last in statuses.data.iter().flat_map(|group| group.last_transactions.iter()) {
+            let transaction = self.parse_signed_transaction(&last.signed_transaction_info)?;
+            if transaction.original_transaction_id != last.original_transaction_id
+                || !self.is_subscription_product(&transaction.product_id)
+            {
+                continue;
+            }
+            let renewal: JwsRenewalInfo = self.verify_jws(&last.signed_renewal_info)?;
+            let newer = latest
+                .as_ref()
+                .map(|(current, _, _)| transaction.purchase_date > current.purchase_date)
+                .unwrap_or(true);
+            if newer {
+                latest = Some((transaction, renewal, last.status));
+            }
+        }
+
+        let (transaction, renewal, status) = latest.ok_or("No subscription transactions found")?;
+        Ok(self.status_from_transaction(&transaction, Some(&renewal), Some(status)))
+    }
+
+    /// 验证并解析签名交易，交易必须属于本应用
+    pub fn parse_signed_transaction(&self, jws: &str) -> Result<JwsTransaction, Box<dyn std::error::Error>> {
+        let transaction: JwsTransaction = self.verify_jws(jws)?;
+        if transaction.bundle_id != self.bundle_id {
+            return Err(format!("Transaction belongs to another app: {}", transaction.bundle_id).into());
+        }
+        Ok(transaction)
+    }
+
+    /// 验证 JWS：头部 x5c 证书链必须由 Apple Root CA - G3 签发，再用叶子证书的公钥验证签名
+    pub fn verify_jws<T: DeserializeOwned>(&self, jws: &str) -> Result<T, Box<dyn std::error::Error>> {
+        let header = jsonwebtoken::decode_header(jws)?;
+        if header.alg != Algorithm::ES256 {
+            return Err(format!("Unexpected JWS algorithm: {:?}", header.alg).into());
+        }
+        let chain = header.x5c.ok_or("JWS header has no certificate chain")?;
+        if chain.len() != 3 {
+            return Err(format!("Unexpected certificate chain length: {}", chain.len()).into());
+        }
+
+        let ders = chain
+            .iter()
+            .map(|cert| base64::engine::general_purpose::STANDARD.decode(cert))
+            .collect::<Result<Vec<_>, _>>()?;
+        if format!("{:x}", Sha256::digest(&ders[2])) != APPLE_ROOT_CA_G3_SHA256 {
+            return Err("JWS certificate chain is not rooted in Apple Root CA - G3".into());
+        }
+
+        let certs = ders
+            .iter()
+            .map(|der| parse_x509_certificate(der).map(|(_, cert)| cert))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (leaf, intermediate, root) = (&certs[0], &certs[1], &certs[2]);
+        if !has_extension(leaf, LEAF_CERT_OID) || !has_extension(intermediate, INTERMEDIATE_CERT_OID) {
+            return Err("JWS certificates are not App Store signing certificates".into());
+        }
+        leaf.verify_signature(Some(intermediate.public_key()))?;
+        intermediate.verify_signature(Some(root.public_key()))?;
+        root.verify_signature(None)?;
+
+        let key = DecodingKey::from_ec_der(&leaf.public_key().subject_public_key.data);
+        let mut validation = Validation::new(Algorithm::ES256);
+        // 交易内容不是标准 JWT，没有 exp 等字段
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+        Ok(jsonwebtoken::decode::<T>(jws, &key, &validation)?.claims)
+    }
+
+    // 生产环境找不到交易时（沙盒购买）改查沙盒环境
+    async fn get_subscription_statuses(&self, transaction_id: &str) -> Result<SubscriptionStatusesResponse, Box<dyn std::error::Error>> {
+        let config = self.server_api.as_ref().ok_or("App Store Server API is not configured")?;
+        let token = self.server_api_token(config)?;
+
+        let base_urls: &[&str] = if config.sandbox {
+            &[SERVER_API_SANDBOX_URL]
+        } else {
+            &[SERVER_API_PRODUCTION_URL, SERVER_API_SANDBOX_URL]
+        };
+        for base_url in base_urls {
+            let response = self
+                .client
+                .get(&format!("{}/inApps/v1/subscriptions/{}", base_url, transaction_id))
+                .bearer_auth(&token)
+                .send()
+                .await?;
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                continue;
+            }
+            if !response.status().is_success() {
+                return Err(format!("App Store Server API request failed: {}", response.status()).into());
+            }
+            return Ok(response.json().await?);
+        }
+        Err(format!("Transaction not found: {}", transaction_id).into())
+    }
+
+    // 用 App 内购买密钥签发的 ES256 令牌，有效期不能超过 60 分钟
+    fn server_api_token(&self, config: &AppStoreServerApiConfig) -> Result<String, Box<dyn std::error::Error>> {
+        let now = Utc::now().timestamp();
+        let claims = ServerApiClaims {
+            iss: config.issuer_id.clone(),
+            iat: now,
+            exp: now + 20 * 60,
+            aud: "appstoreconnect-v1",
+            bid: self.bundle_id.clone(),
+        };
+        let mut header = Header::new(Algorithm::ES256);
+        header.kid = Some(config.key_id.clone());
+        let key = EncodingKey::from_ec_pem(config.private_key.as_bytes())?;
+        Ok(jsonwebtoken::encode(&header, &claims, &key)?)
+    }
+
+    // api_status 为 App Store Server API 返回的订阅状态，扣款重试和宽限期内仍视为有效
+    fn status_from_transaction(
+        &self,
+        transaction: &JwsTransaction,
+        renewal: Option<&JwsRenewalInfo>,
+        api_status: Option<i32>,
+    ) -> AppleSubscriptionStatus {
+        let expires_date = transaction.expires_date.and_then(DateTime::from_timestamp_millis);
+        let is_cancelled = transaction.revocation_date.is_some();
+        let is_active = match api_status {
+            Some(status) => matches!(status, 1 | 4) && !is_cancelled,
+            None => expires_date.map(|expires| expires > Utc::now()).unwrap_or(false) && !is_cancelled,
+        };
+
+        AppleSubscriptionStatus {
+            is_active,
+            product_id: transaction.product_id.clone(),
+            original_transaction_id: transaction.original_transaction_id.clone(),
+            expires_date,
+            is_trial: transaction.offer_type == Some(1),
+            is_cancelled,
+            auto_renew_status: renewal.map(|renewal| renewal.auto_renew_status == 1).unwrap_or(false),
+        }
+    }
+}
+
+// JWS 紧凑格式为 header.payload.signature，base64 收据不含“.”
+fn is_jws(data: &str) -> bool {
+    data.split('.').count() == 3
+}
+
+fn has_extension(cert: &X509Certificate, oid: &str) -> bool {
+    cert.extensions().iter().any(|ext| ext.oid.to_id_string() == oid)
 }
 
 /// Apple订阅产品配置
@@ -198,6 +471,18 @@ pub struct AppleSubscriptionConfig {
     pub yearly_product_id: String,
     pub shared_secret: String,
     pub bundle_id: String,
+    pub server_api: Option<AppStoreServerApiConfig>,
+}
+
+impl AppleSubscriptionConfig {
+    /// 按配置创建验证器，配置了 App Store Server API 凭据时启用 StoreKit 2 交易查询
+    pub fn validator(&self) -> AppleSubscriptionValidator {
+        let validator = AppleSubscriptionValidator::new(self.shared_secret.clone(), self.bundle_id.clone());
+        match &self.server_api {
+            Some(config) => validator.with_server_api(config.clone()),
+            None => validator,
+        }
+    }
 }
 
 impl Default for AppleSubscriptionConfig {
@@ -208,6 +493,7 @@ impl Default for AppleSubscriptionConfig {
             shared_secret: std::env::var("APPLE_SHARED_SECRET")
                 .unwrap_or_else(|_| "your-app-specific-shared-secret".to_string()),
             bundle_id: "com.fileSortify.tool".to_string(),
+            server_api: AppStoreServerApiConfig::from_env(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,22 @@
+#ifndef AUTOSTART_H
+#define AUTOSTART_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// SMAppService 登录代理（macOS 13+），使用应用内打包的 LaunchAgent plist
+// 返回值：-1 系统不支持，0 未注册，1 已启用，2 需要用户在系统设置中批准，3 找不到应用
+int autostart_sm_status(void);
+// 失败时把错误信息写入 error（UTF-8，以 0 结尾）
+bool autostart_sm_register(char* error, size_t error_len);
+bool autostart_sm_unregister(char* error, size_t error_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // AUTOSTART_H
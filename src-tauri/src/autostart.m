@@ -0,0 +1,74 @@
+#import <Foundation/Foundation.h>
+#import <ServiceManagement/ServiceManagement.h>
+#import "autostart.h"
+
+// 随应用打包在 Contents/Library/LaunchAgents 下，启动参数为 --hidden --autostart
+static NSString *const AgentPlistName = @"com.filesortify.app.autostart.plist";
+
+API_AVAILABLE(macos(13.0))
+static SMAppService *autostart_service(void) {
+    return [SMAppService agentServiceWithPlistName:AgentPlistName];
+}
+
+static void copy_error(NSError *error, const char *fallback, char *buffer, size_t buffer_len) {
+    if (buffer == NULL || buffer_len == 0) {
+        return;
+    }
+    const char *message = error != nil ? [[error localizedDescription] UTF8String] : fallback;
+    if (message == NULL) {
+        message = fallback;
+    }
+    strlcpy(buffer, message, buffer_len);
+}
+
+int autostart_sm_status(void) {
+    if (@available(macOS 13.0, *)) {
+        switch (autostart_service().status) {
+            case SMAppServiceStatusEnabled:
+                return 1;
+            case SMAppServiceStatusRequiresApproval:
+                return 2;
+            case SMAppServiceStatusNotFound:
+                return 3;
+            case SMAppServiceStatusNotRegistered:
+            default:
+                return 0;
+        }
+    }
+    return -1;
+}
+
+bool autostart_sm_register(char *error, size_t error_len) {
+    if (@available(macOS 13.0, *)) {
+        NSError *err = nil;
+        if ([autostart_service() registerAndReturnError:&err]) {
+            return true;
+        }
+        copy_error(err, "SMAppService register failed", error, error_len);
+        return false;
+    }
+    copy_error(nil, "SMAppService requires macOS 13 or later", error, error_len);
+    return false;
+}
+
+bool autostart_sm_unregister(char *error, size_t error_len) {
+    if (@available(macOS 13.0, *)) {
+        // 早先注册为主应用登录项的一并移除，避免开机启动两次
+        SMAppService *main_service = [SMAppService mainAppService];
+        if (main_service.status == SMAppServiceStatusEnabled || main_service.status == SMAppServiceStatusRequiresApproval) {
+            [main_service unregisterAndReturnError:nil];
+        }
+        // 没有注册过时直接视为成功
+        if (autostart_service().status == SMAppServiceStatusNotRegistered) {
+            return true;
+        }
+        NSError *err = nil;
+        if ([autostart_service() unregisterAndReturnError:&err]) {
+            return true;
+        }
+        copy_error(err, "SMAppService unregister failed", error, error_len);
+        return false;
+    }
+    copy_error(nil, "SMAppService requires macOS 13 or later", error, error_len);
+    return false;
+}
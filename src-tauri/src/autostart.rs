@@ -1,241 +1,820 @@
-use std::process::Command;
-use std::path::PathBuf;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+const APP_NAME: &str = "FileSortify";
+
+/// 开机启动的实际状态（以系统中的登记为准，而不是设置里最后保存的值）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStartStatus {
+    Enabled,
+    Disabled,
+    // macOS：已注册，但需要用户在“系统设置 > 登录项”中批准
+    RequiresApproval,
+    // 已登记，但被用户在系统中关闭（任务管理器启动项、桌面环境的自启动设置）
+    DisabledBySystem,
+    // 已登记，但指向的不是当前程序（应用被移动或更新后）
+    Broken,
+}
+
+/// 开机启动状态的详细信息，供设置界面显示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoStartReport {
+    pub status: AutoStartStatus,
+    pub scope: AutoStartScope,
+    pub method: AutoStartMethod,
+    // 系统中登记的启动命令，没有登记时为空
+    pub registered_command: Option<Vec<String>>,
+    pub expected_command: Vec<String>,
+}
+
+/// 开机启动的登记范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStartScope {
+    CurrentUser,
+    // 本机所有用户（仅 Windows，需要管理员权限），适合共用的实验室、办公电脑
+    AllUsers,
+}
+
+impl Default for AutoStartScope {
+    fn default() -> Self {
+        AutoStartScope::CurrentUser
+    }
+}
+
+/// Linux 上开机启动的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AutoStartMethod {
+    // XDG Autostart desktop 文件，由桌面环境在会话启动时拉起
+    Desktop,
+    // systemd --user 服务，用户登录后即启动，不依赖桌面环境的自启动阶段
+    Systemd,
+}
+
+impl Default for AutoStartMethod {
+    fn default() -> Self {
+        AutoStartMethod::Desktop
+    }
+}
+
+lazy_static! {
+    static ref METHOD: RwLock<AutoStartMethod> = RwLock::new(AutoStartMethod::default());
+}
+
+/// 更新 Linux 上使用的开机启动方式（启动时和设置修改后调用），其他平台忽略
+pub fn set_method(method: AutoStartMethod) {
+    *METHOD.write().unwrap() = method;
+}
+
+/// 各平台开机启动实现的统一接口
+trait AutoStartBackend {
+    fn enable(&self, scope: AutoStartScope) -> Result<(), String>;
+    fn disable(&self, scope: AutoStartScope) -> Result<(), String>;
+    fn status(&self, scope: AutoStartScope) -> Result<AutoStartStatus, String>;
+    // 系统中登记的启动命令（程序路径和参数），没有登记时返回 None
+    fn registered_command(&self, scope: AutoStartScope) -> Result<Option<Vec<String>>, String>;
+    // 当前程序应该登记的启动命令
+    fn expected_command(&self) -> Result<Vec<String>, String>;
+}
 
 pub struct AutoStart;
 
 impl AutoStart {
-    pub fn enable() -> Result<(), String> {
-        #[cfg(target_os = "macos")]
-        {
-            Self::enable_macos()
-        }
-        
-        #[cfg(target_os = "windows")]
-        {
-            Self::enable_windows()
+    pub fn enable(scope: AutoStartScope) -> Result<(), String> {
+        backend().enable(scope)
+    }
+
+    pub fn disable(scope: AutoStartScope) -> Result<(), String> {
+        backend().disable(scope)
+    }
+
+    pub fn status(scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+        let status = backend().status(scope)?;
+        if status != AutoStartStatus::Disabled && Self::has_drifted(scope)? {
+            return Ok(AutoStartStatus::Broken);
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            Self::enable_linux()
+        Ok(status)
+    }
+
+    pub fn report(scope: AutoStartScope) -> Result<AutoStartReport, String> {
+        let backend = backend();
+        Ok(AutoStartReport {
+            status: Self::status(scope)?,
+            scope,
+            method: *METHOD.read().unwrap(),
+            registered_command: backend.registered_command(scope)?,
+            expected_command: backend.expected_command()?,
+        })
+    }
+
+    pub fn is_enabled(scope: AutoStartScope) -> Result<bool, String> {
+        Ok(Self::status(scope)? == AutoStartStatus::Enabled)
+    }
+
+    /// 登记的启动命令是否已经不指向当前程序（应用被移动、更新后路径变化，或缺少启动参数）
+    pub fn has_drifted(scope: AutoStartScope) -> Result<bool, String> {
+        let backend = backend();
+        match backend.registered_command(scope)? {
+            Some(registered) => Ok(!same_command(&registered, &backend.expected_command()?)),
+            None => Ok(false),
         }
     }
-    
-    pub fn disable() -> Result<(), String> {
-        #[cfg(target_os = "macos")]
-        {
-            Self::disable_macos()
+
+    /// 启动时检查登记是否失效，失效则重新登记；返回是否进行了修复
+    pub fn repair_if_drifted(scope: AutoStartScope) -> Result<bool, String> {
+        if !Self::has_drifted(scope)? {
+            return Ok(false);
         }
-        
+        log::warn!("Autostart entry does not point to the current executable, repairing");
+        // 所有用户的登记需要管理员权限，启动时不弹出提权确认，权限不足直接报告失败
+        let result = match scope {
+            AutoStartScope::AllUsers => Self::apply_all_users(true),
+            AutoStartScope::CurrentUser => backend().enable(scope),
+        };
+        result.map(|_| true)
+    }
+
+    /// 提权后的辅助进程（--autostart-all-users=enable|disable）执行的操作
+    pub fn apply_all_users(enable: bool) -> Result<(), String> {
         #[cfg(target_os = "windows")]
         {
-            Self::disable_windows()
+            windows::apply_all_users(enable)
         }
-        
-        #[cfg(target_os = "linux")]
+
+        #[cfg(not(target_os = "windows"))]
         {
-            Self::disable_linux()
+            let _ = enable;
+            Err(ALL_USERS_UNSUPPORTED.to_string())
         }
     }
-    
-    pub fn is_enabled() -> Result<bool, String> {
-        #[cfg(target_os = "macos")]
-        {
-            Self::is_enabled_macos()
-        }
-        
-        #[cfg(target_os = "windows")]
-        {
-            Self::is_enabled_windows()
+}
+
+#[cfg(not(target_os = "windows"))]
+const ALL_USERS_UNSUPPORTED: &str = "All-users autostart is only available on Windows";
+
+fn backend() -> &'static dyn AutoStartBackend {
+    #[cfg(target_os = "macos")]
+    {
+        &macos::MacAutoStart
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        &windows::WindowsAutoStart
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        match *METHOD.read().unwrap() {
+            AutoStartMethod::Desktop => &linux::LinuxAutoStart,
+            AutoStartMethod::Systemd => &linux::SystemdAutoStart,
         }
-        
-        #[cfg(target_os = "linux")]
-        {
-            Self::is_enabled_linux()
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        &Unsupported
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn current_exe() -> Result<std::path::PathBuf, String> {
+    std::env::current_exe().map_err(|e| format!("Failed to get app path: {}", e))
+}
+
+// 当前可执行文件加上开机启动参数
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn default_command() -> Result<Vec<String>, String> {
+    Ok(std::iter::once(current_exe()?.to_string_lossy().to_string())
+        .chain(crate::cli::AUTOSTART_ARGS.iter().map(|arg| arg.to_string()))
+        .collect())
+}
+
+// 程序路径尽量按实际文件比较（大小写、符号链接），参数逐个比较
+fn same_command(registered: &[String], expected: &[String]) -> bool {
+    match (registered.split_first(), expected.split_first()) {
+        (Some((registered_exe, registered_args)), Some((expected_exe, expected_args))) => {
+            let same_exe = match (std::fs::canonicalize(registered_exe), std::fs::canonicalize(expected_exe)) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => registered_exe == expected_exe,
+            };
+            same_exe && registered_args == expected_args
         }
+        _ => false,
     }
-    
-    #[cfg(target_os = "macos")]
-    fn enable_macos() -> Result<(), String> {
-        let app_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get app path: {}", e))?;
-        
-        // Resolve app bundle path (.../Contents/MacOS/FileSortify -> .../FileSortify.app)
-        let app_bundle = app_path
-            .parent() // MacOS
-            .and_then(|p| p.parent()) // Contents
-            .and_then(|p| p.parent()) // FileSortify.app
-            .ok_or("Failed to resolve app bundle path")?;
-        
-        let plist_content = format!(r#"<?xml version="1.0" encoding="UTF-8"?>
-<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
-<plist version="1.0">
-<dict>
-    <key>Label</key>
-    <string>com.filesortify.app</string>
-    <key>ProgramArguments</key>
-    <array>
-        <string>open</string>
-        <string>{}</string>
-    </array>
-    <key>RunAtLoad</key>
-    <true/>
-    <key>KeepAlive</key>
-    <false/>
-</dict>
-</plist>"#, app_bundle.display());
-        
-        let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-        let launch_agents_dir = home_dir.join("Library/LaunchAgents");
-        std::fs::create_dir_all(&launch_agents_dir)
-            .map_err(|e| format!("Failed to create LaunchAgents directory: {}", e))?;
-        
-        let plist_path = launch_agents_dir.join("com.filesortify.app.plist");
-        std::fs::write(&plist_path, plist_content)
-            .map_err(|e| format!("Failed to write plist file: {}", e))?;
-        
-        // load plist
-        let output = Command::new("launchctl")
-            .args(&["load", plist_path.to_str().unwrap()])
-            .output()
-            .map_err(|e| format!("Failed to execute launchctl load: {}", e))?;
-        
-        if !output.status.success() {
-            return Err(format!("launchctl load failed: {}", String::from_utf8_lossy(&output.stderr)));
-        }
-        
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+struct Unsupported;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+impl AutoStartBackend for Unsupported {
+    fn enable(&self, _scope: AutoStartScope) -> Result<(), String> {
+        Err("Auto start is not supported on this platform".to_string())
+    }
+
+    fn disable(&self, _scope: AutoStartScope) -> Result<(), String> {
         Ok(())
     }
-    
-    #[cfg(target_os = "macos")]
-    fn disable_macos() -> Result<(), String> {
+
+    fn status(&self, _scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+        Ok(AutoStartStatus::Disabled)
+    }
+
+    fn registered_command(&self, _scope: AutoStartScope) -> Result<Option<Vec<String>>, String> {
+        Ok(None)
+    }
+
+    fn expected_command(&self) -> Result<Vec<String>, String> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use super::{default_command, AutoStartBackend, AutoStartScope, AutoStartStatus, ALL_USERS_UNSUPPORTED};
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+    use std::path::PathBuf;
+
+    extern "C" {
+        fn autostart_sm_status() -> i32;
+        fn autostart_sm_register(error: *mut c_char, error_len: usize) -> bool;
+        fn autostart_sm_unregister(error: *mut c_char, error_len: usize) -> bool;
+    }
+
+    const LAUNCH_AGENT_LABEL: &str = "com.filesortify.app";
+
+    /// macOS 13+ 通过 SMAppService 注册应用内打包的登录代理（登录项不能带启动参数）；
+    /// 更早的系统写 LaunchAgent，登录时由 launchd 直接启动可执行文件
+    pub struct MacAutoStart;
+
+    // SMAppService 状态码，见 autostart.h
+    const SM_UNSUPPORTED: i32 = -1;
+    const SM_ENABLED: i32 = 1;
+    const SM_REQUIRES_APPROVAL: i32 = 2;
+
+    fn sm_call(f: unsafe extern "C" fn(*mut c_char, usize) -> bool) -> Result<(), String> {
+        let mut buffer = [0 as c_char; 512];
+        if unsafe { f(buffer.as_mut_ptr(), buffer.len()) } {
+            Ok(())
+        } else {
+            let message = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+            Err(message.to_string_lossy().to_string())
+        }
+    }
+
+    fn launch_agent_path() -> Result<PathBuf, String> {
         let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-        let plist_path = home_dir.join("Library/LaunchAgents/com.filesortify.app.plist");
-        
+        Ok(home_dir.join("Library/LaunchAgents").join(format!("{}.plist", LAUNCH_AGENT_LABEL)))
+    }
+
+    // 旧版本写入的 LaunchAgent（通过 open 启动），改用登录项后需要清理，避免启动两次
+    fn remove_launch_agent() -> Result<(), String> {
+        let plist_path = launch_agent_path()?;
         if plist_path.exists() {
-            // unload plist
-            let output = Command::new("launchctl")
-                .args(&["unload", plist_path.to_str().unwrap()])
-                .output()
-                .map_err(|e| format!("Failed to execute launchctl unload: {}", e))?;
-            
-            // remove plist file
             std::fs::remove_file(&plist_path)
                 .map_err(|e| format!("Failed to remove plist file: {}", e))?;
         }
-        
         Ok(())
     }
-    
-    #[cfg(target_os = "macos")]
-    fn is_enabled_macos() -> Result<bool, String> {
-        let home_dir = dirs::home_dir().ok_or("Failed to get home directory")?;
-        let plist_path = home_dir.join("Library/LaunchAgents/com.filesortify.app.plist");
-        Ok(plist_path.exists())
+
+    fn write_launch_agent() -> Result<(), String> {
+        let mut agent = plist::Dictionary::new();
+        agent.insert("Label".to_string(), LAUNCH_AGENT_LABEL.into());
+        agent.insert(
+            "ProgramArguments".to_string(),
+            plist::Value::Array(default_command()?.into_iter().map(plist::Value::from).collect()),
+        );
+        agent.insert("RunAtLoad".to_string(), true.into());
+        agent.insert("KeepAlive".to_string(), false.into());
+        agent.insert("ProcessType".to_string(), "Interactive".into());
+
+        let plist_path = launch_agent_path()?;
+        if let Some(parent) = plist_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create LaunchAgents directory: {}", e))?;
+        }
+        // 登录时 launchd 会自动加载该目录下的 plist，不需要调用 launchctl
+        plist::Value::Dictionary(agent)
+            .to_file_xml(&plist_path)
+            .map_err(|e| format!("Failed to write plist file: {}", e))
     }
-    
-    #[cfg(target_os = "windows")]
-    fn enable_windows() -> Result<(), String> {
-        let app_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get app path: {}", e))?;
-        
-        let output = Command::new("reg")
-            .args(&[
-                "add",
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run",
-                "/v",
-                "FileSortify",
-                "/t",
-                "REG_SZ",
-                "/d",
-                &format!("\"{}\"", app_path.display()),
-                "/f"
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute reg add: {}", e))?;
-        
-        if !output.status.success() {
-            return Err(format!("Registry add failed: {}", String::from_utf8_lossy(&output.stderr)));
-        }
-        
-        Ok(())
+
+    impl AutoStartBackend for MacAutoStart {
+        fn enable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Err(ALL_USERS_UNSUPPORTED.to_string());
+            }
+            if unsafe { autostart_sm_status() } == SM_UNSUPPORTED {
+                return write_launch_agent();
+            }
+            sm_call(autostart_sm_register)?;
+            remove_launch_agent()
+        }
+
+        fn disable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(());
+            }
+            if unsafe { autostart_sm_status() } != SM_UNSUPPORTED {
+                sm_call(autostart_sm_unregister)?;
+            }
+            remove_launch_agent()
+        }
+
+        fn status(&self, scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(AutoStartStatus::Disabled);
+            }
+            match unsafe { autostart_sm_status() } {
+                SM_ENABLED => Ok(AutoStartStatus::Enabled),
+                SM_REQUIRES_APPROVAL => Ok(AutoStartStatus::RequiresApproval),
+                // 未注册登录项时，可能仍有旧版本写入的 LaunchAgent
+                _ => Ok(if launch_agent_path()?.exists() {
+                    AutoStartStatus::Enabled
+                } else {
+                    AutoStartStatus::Disabled
+                }),
+            }
+        }
+
+        fn registered_command(&self, scope: AutoStartScope) -> Result<Option<Vec<String>>, String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(None);
+            }
+            // 登录代理的程序路径相对于应用包，随应用移动，不会失效
+            if matches!(unsafe { autostart_sm_status() }, SM_ENABLED | SM_REQUIRES_APPROVAL) {
+                return self.expected_command().map(Some);
+            }
+            let plist_path = launch_agent_path()?;
+            if !plist_path.exists() {
+                return Ok(None);
+            }
+            // 旧版本写入的是 ["open", 应用包路径]，同样视为需要修复
+            let agent = plist::Value::from_file(&plist_path)
+                .map_err(|e| format!("Failed to read plist file: {}", e))?;
+            let arguments = agent
+                .as_dictionary()
+                .and_then(|dict| dict.get("ProgramArguments"))
+                .and_then(|value| value.as_array())
+                .map(|values| values.iter().filter_map(|v| v.as_string().map(str::to_string)).collect())
+                .unwrap_or_default();
+            Ok(Some(arguments))
+        }
+
+        fn expected_command(&self) -> Result<Vec<String>, String> {
+            default_command()
+        }
     }
-    
-    #[cfg(target_os = "windows")]
-    fn disable_windows() -> Result<(), String> {
-        let output = Command::new("reg")
-            .args(&[
-                "delete",
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run",
-                "/v",
-                "FileSortify",
-                "/f"
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute reg delete: {}", e))?;
-        
-        // ignore missing key error
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use super::{current_exe, default_command, AutoStartBackend, AutoStartScope, AutoStartStatus, APP_NAME};
+    use crate::cli::{ALL_USERS_AUTOSTART_ARG, AUTOSTART_ARGS};
+    use std::io::ErrorKind;
+    use winreg::enums::{HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_SET_VALUE};
+    use winreg::RegKey;
+
+    const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+    // 任务管理器“启动”页中的开关保存在这里，奇数首字节表示被用户禁用
+    const STARTUP_APPROVED_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\StartupApproved\\Run";
+
+    /// 通过注册表 API 写入 HKCU\...\Run；所有用户时写入 HKLM\...\Run（需要管理员权限）
+    pub struct WindowsAutoStart;
+
+    fn root(scope: AutoStartScope) -> HKEY {
+        match scope {
+            AutoStartScope::CurrentUser => HKEY_CURRENT_USER,
+            AutoStartScope::AllUsers => HKEY_LOCAL_MACHINE,
+        }
+    }
+
+    fn write_entry(scope: AutoStartScope) -> std::io::Result<()> {
+        let app_path = current_exe().map_err(|e| std::io::Error::new(ErrorKind::Other, e))?;
+        let hive = RegKey::predef(root(scope));
+        let (run_key, _) = hive.create_subkey(RUN_KEY)?;
+        run_key.set_value(APP_NAME, &format!("\"{}\" {}", app_path.display(), AUTOSTART_ARGS.join(" ")))?;
+
+        // 清除任务管理器中的禁用标记，否则重新开启后仍不会启动
+        if let Ok(approved) = hive.open_subkey_with_flags(STARTUP_APPROVED_KEY, KEY_SET_VALUE) {
+            let _ = approved.delete_value(APP_NAME);
+        }
         Ok(())
     }
-    
-    #[cfg(target_os = "windows")]
-    fn is_enabled_windows() -> Result<bool, String> {
-        let output = Command::new("reg")
-            .args(&[
-                "query",
-                "HKCU\\Software\\Microsoft\\Windows\\CurrentVersion\\Run",
-                "/v",
-                "FileSortify"
-            ])
-            .output()
-            .map_err(|e| format!("Failed to execute reg query: {}", e))?;
-        
-        Ok(output.status.success())
-    }
-    
-    #[cfg(target_os = "linux")]
-    fn enable_linux() -> Result<(), String> {
-        let app_path = std::env::current_exe()
-            .map_err(|e| format!("Failed to get app path: {}", e))?;
-        
-        let desktop_content = format!(r#"[Desktop Entry]
+
+    fn delete_entry(scope: AutoStartScope) -> std::io::Result<()> {
+        let run_key = match RegKey::predef(root(scope)).open_subkey_with_flags(RUN_KEY, KEY_SET_VALUE) {
+            Ok(key) => key,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        match run_key.delete_value(APP_NAME) {
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    // 当前进程没有管理员权限时，以 runas 重新启动自身完成 HKLM 的修改
+    fn run_elevated(enable: bool) -> Result<(), String> {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::processthreadsapi::GetExitCodeProcess;
+        use winapi::um::shellapi::{ShellExecuteExW, SEE_MASK_NOCLOSEPROCESS, SHELLEXECUTEINFOW};
+        use winapi::um::synchapi::WaitForSingleObject;
+        use winapi::um::winbase::INFINITE;
+        use winapi::um::winuser::SW_HIDE;
+
+        let wide = |s: &OsStr| s.encode_wide().chain(std::iter::once(0)).collect::<Vec<u16>>();
+        let exe = wide(current_exe()?.as_os_str());
+        let verb = wide(OsStr::new("runas"));
+        let params = wide(OsStr::new(&format!(
+            "{}={}",
+            ALL_USERS_AUTOSTART_ARG,
+            if enable { "enable" } else { "disable" }
+        )));
+
+        let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+        info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+        info.fMask = SEE_MASK_NOCLOSEPROCESS;
+        info.lpVerb = verb.as_ptr();
+        info.lpFile = exe.as_ptr();
+        info.lpParameters = params.as_ptr();
+        info.nShow = SW_HIDE;
+
+        // 用户在 UAC 对话框中取消时也会失败
+        if unsafe { ShellExecuteExW(&mut info) } == 0 || info.hProcess.is_null() {
+            return Err(format!("Administrator permission was not granted: {}", std::io::Error::last_os_error()));
+        }
+        let mut exit_code: u32 = 1;
+        unsafe {
+            WaitForSingleObject(info.hProcess, INFINITE);
+            GetExitCodeProcess(info.hProcess, &mut exit_code);
+            CloseHandle(info.hProcess);
+        }
+        if exit_code == 0 {
+            Ok(())
+        } else {
+            Err(format!("Failed to update all-users autostart (exit code {})", exit_code))
+        }
+    }
+
+    // 写 HKLM 失败且原因是权限不足时，申请提权后重试
+    fn with_elevation(scope: AutoStartScope, enable: bool, result: std::io::Result<()>) -> Result<(), String> {
+        match result {
+            Ok(()) => Ok(()),
+            Err(e) if scope == AutoStartScope::AllUsers && e.kind() == ErrorKind::PermissionDenied => run_elevated(enable),
+            Err(e) => Err(format!("Failed to update registry: {}", e)),
+        }
+    }
+
+    /// 提权后的辅助进程调用：直接修改 HKLM
+    pub fn apply_all_users(enable: bool) -> Result<(), String> {
+        let result = if enable {
+            write_entry(AutoStartScope::AllUsers)
+        } else {
+            delete_entry(AutoStartScope::AllUsers)
+        };
+        result.map_err(|e| format!("Failed to update registry: {}", e))
+    }
+
+    impl AutoStartBackend for WindowsAutoStart {
+        fn enable(&self, scope: AutoStartScope) -> Result<(), String> {
+            with_elevation(scope, true, write_entry(scope))
+        }
+
+        fn disable(&self, scope: AutoStartScope) -> Result<(), String> {
+            with_elevation(scope, false, delete_entry(scope))
+        }
+
+        fn status(&self, scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+            let hive = RegKey::predef(root(scope));
+            let registered = hive
+                .open_subkey(RUN_KEY)
+                .and_then(|key| key.get_value::<String, _>(APP_NAME));
+            match registered {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => return Ok(AutoStartStatus::Disabled),
+                Err(e) => return Err(format!("Failed to read registry value: {}", e)),
+            }
+
+            // 所有用户的启动项也可能被当前用户在任务管理器中关闭
+            let disabled_by_user = [hive, RegKey::predef(HKEY_CURRENT_USER)].iter().any(|hive| {
+                hive.open_subkey(STARTUP_APPROVED_KEY)
+                    .and_then(|key| key.get_raw_value(APP_NAME))
+                    .map(|value| value.bytes.first().map(|b| b % 2 == 1).unwrap_or(false))
+                    .unwrap_or(false)
+            });
+            Ok(if disabled_by_user {
+                AutoStartStatus::DisabledBySystem
+            } else {
+                AutoStartStatus::Enabled
+            })
+        }
+
+        fn registered_command(&self, scope: AutoStartScope) -> Result<Option<Vec<String>>, String> {
+            let registered = RegKey::predef(root(scope))
+                .open_subkey(RUN_KEY)
+                .and_then(|key| key.get_value::<String, _>(APP_NAME));
+            match registered {
+                Ok(command) => Ok(Some(split_command(&command))),
+                Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(format!("Failed to read registry value: {}", e)),
+            }
+        }
+
+        fn expected_command(&self) -> Result<Vec<String>, String> {
+            default_command()
+        }
+    }
+
+    // Run 值的格式为 "程序路径" 参数...；旧版本写入的值只有带引号的路径
+    fn split_command(command: &str) -> Vec<String> {
+        let command = command.trim();
+        let (exe, rest) = match command.strip_prefix('"').and_then(|rest| rest.split_once('"')) {
+            Some((exe, rest)) => (exe.to_string(), rest),
+            None => match command.split_once(' ') {
+                Some((exe, rest)) => (exe.to_string(), rest),
+                None => (command.to_string(), ""),
+            },
+        };
+        std::iter::once(exe)
+            .chain(rest.split_whitespace().map(str::to_string))
+            .collect()
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::{current_exe, AutoStartBackend, AutoStartScope, AutoStartStatus, ALL_USERS_UNSUPPORTED, APP_NAME};
+    use crate::cli::AUTOSTART_ARGS;
+    use std::path::{Path, PathBuf};
+
+    const DESKTOP_FILE: &str = "filesortify.desktop";
+
+    /// 按 XDG Autostart 规范在 $XDG_CONFIG_HOME/autostart 写入 desktop 文件
+    pub struct LinuxAutoStart;
+
+    fn desktop_path() -> Result<PathBuf, String> {
+        // dirs::config_dir 已经遵循 $XDG_CONFIG_HOME
+        let config_dir = dirs::config_dir().ok_or("Failed to get config directory")?;
+        Ok(config_dir.join("autostart").join(DESKTOP_FILE))
+    }
+
+    // AppImage 运行时 current_exe 指向临时挂载点，需要登记 AppImage 文件本身
+    fn executable() -> Result<String, String> {
+        if let Some(appimage) = std::env::var_os("APPIMAGE") {
+            return Ok(appimage.to_string_lossy().to_string());
+        }
+        Ok(current_exe()?.to_string_lossy().to_string())
+    }
+
+    // desktop 文件 Exec 字段（以及 systemd 的 ExecStart）的引号规则：用双引号包住，并转义 " ` $ \
+    fn quote_exec(arg: &str) -> String {
+        let mut quoted = String::from("\"");
+        for c in arg.chars() {
+            if matches!(c, '"' | '`' | '$' | '\\') {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('"');
+        // desktop 文件中 % 是字段代码前缀
+        quoted.replace('%', "%%")
+    }
+
+    // 先写临时文件再替换，避免桌面环境或 systemd 读到写了一半的文件
+    fn write_atomic(path: &Path, content: &str) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+        }
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+        std::fs::write(&temp_path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        std::fs::rename(&temp_path, path)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    fn remove_if_exists(path: &Path) -> Result<(), String> {
+        match std::fs::remove_file(path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+
+    fn expected_command() -> Result<Vec<String>, String> {
+        Ok(std::iter::once(executable()?)
+            .chain(AUTOSTART_ARGS.iter().map(|arg| arg.to_string()))
+            .collect())
+    }
+
+    // 按 Exec 字段的引号规则拆分参数，是 quote_exec 的逆过程
+    fn split_exec(exec: &str) -> Vec<String> {
+        let exec = exec.replace("%%", "%");
+        let mut args = Vec::new();
+        let mut current = String::new();
+        let mut in_quotes = false;
+        let mut chars = exec.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => in_quotes = !in_quotes,
+                '\\' if in_quotes => {
+                    if let Some(next) = chars.next() {
+                        current.push(next);
+                    }
+                }
+                ' ' | '\t' if !in_quotes => {
+                    if !current.is_empty() {
+                        args.push(std::mem::take(&mut current));
+                    }
+                }
+                _ => current.push(c),
+            }
+        }
+        if !current.is_empty() {
+            args.push(current);
+        }
+        args
+    }
+
+    impl AutoStartBackend for LinuxAutoStart {
+        fn enable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Err(ALL_USERS_UNSUPPORTED.to_string());
+            }
+            let desktop_content = format!(r#"[Desktop Entry]
 Type=Application
-Name=FileSortify
+Name={}
 Exec={}
+Terminal=false
 Hidden=false
 NoDisplay=false
 X-GNOME-Autostart-enabled=true
-"#, app_path.display());
-        
-        let config_dir = dirs::config_dir().ok_or("Failed to get config directory")?;
-        let autostart_dir = config_dir.join("autostart");
-        std::fs::create_dir_all(&autostart_dir)
-            .map_err(|e| format!("Failed to create autostart directory: {}", e))?;
-        
-        let desktop_path = autostart_dir.join("filesortify.desktop");
-        std::fs::write(&desktop_path, desktop_content)
-            .map_err(|e| format!("Failed to write desktop file: {}", e))?;
-        
-        Ok(())
-    }
-    
-    #[cfg(target_os = "linux")]
-    fn disable_linux() -> Result<(), String> {
-        let config_dir = dirs::config_dir().ok_or("Failed to get config directory")?;
-        let desktop_path = config_dir.join("autostart/filesortify.desktop");
-        
-        if desktop_path.exists() {
-            std::fs::remove_file(&desktop_path)
-                .map_err(|e| format!("Failed to remove desktop file: {}", e))?;
+"#, APP_NAME, expected_command()?.iter().map(|arg| quote_exec(arg)).collect::<Vec<_>>().join(" "));
+
+            write_atomic(&desktop_path()?, &desktop_content)?;
+            // 两种方式只保留一种，避免启动两次
+            SystemdAutoStart.disable(scope)
+        }
+
+        fn disable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(());
+            }
+            remove_if_exists(&desktop_path()?)
+        }
+
+        fn status(&self, scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(AutoStartStatus::Disabled);
+            }
+            let desktop_path = desktop_path()?;
+            let content = match std::fs::read_to_string(&desktop_path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(AutoStartStatus::Disabled),
+                Err(e) => return Err(format!("Failed to read desktop file: {}", e)),
+            };
+
+            // 桌面环境的“启动应用程序”设置会把这两个键改成关闭
+            let disabled = content.lines().map(str::trim).any(|line| {
+                line.eq_ignore_ascii_case("Hidden=true")
+                    || line.eq_ignore_ascii_case("X-GNOME-Autostart-enabled=false")
+            });
+            Ok(if disabled {
+                AutoStartStatus::DisabledBySystem
+            } else {
+                AutoStartStatus::Enabled
+            })
+        }
+
+        fn registered_command(&self, scope: AutoStartScope) -> Result<Option<Vec<String>>, String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(None);
+            }
+            let content = match std::fs::read_to_string(desktop_path()?) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(format!("Failed to read desktop file: {}", e)),
+            };
+            Ok(Some(
+                content
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("Exec="))
+                    .map(split_exec)
+                    .unwrap_or_default(),
+            ))
+        }
+
+        fn expected_command(&self) -> Result<Vec<String>, String> {
+            expected_command()
         }
-        
-        Ok(())
     }
-    
-    #[cfg(target_os = "linux")]
-    fn is_enabled_linux() -> Result<bool, String> {
+
+    const SERVICE_FILE: &str = "filesortify.service";
+    // 与 systemctl --user enable 的效果相同：在 default.target.wants 中建立指向服务文件的链接
+    const WANTED_BY: &str = "default.target";
+
+    /// systemd --user 服务：用户登录后由 systemd 启动，不依赖桌面环境的自启动阶段
+    pub struct SystemdAutoStart;
+
+    fn systemd_user_dir() -> Result<PathBuf, String> {
         let config_dir = dirs::config_dir().ok_or("Failed to get config directory")?;
-        let desktop_path = config_dir.join("autostart/filesortify.desktop");
-        Ok(desktop_path.exists())
+        Ok(config_dir.join("systemd").join("user"))
+    }
+
+    fn service_path() -> Result<PathBuf, String> {
+        Ok(systemd_user_dir()?.join(SERVICE_FILE))
+    }
+
+    fn wants_link_path() -> Result<PathBuf, String> {
+        Ok(systemd_user_dir()?.join(format!("{}.wants", WANTED_BY)).join(SERVICE_FILE))
+    }
+
+    impl AutoStartBackend for SystemdAutoStart {
+        fn enable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Err(ALL_USERS_UNSUPPORTED.to_string());
+            }
+            // 图形会话还没就绪时程序会启动失败，由 systemd 稍后重试
+            let service_content = format!(r#"[Unit]
+Description={} file organizer
+
+[Service]
+Type=simple
+ExecStart={}
+Restart=on-failure
+RestartSec=10
+
+[Install]
+WantedBy={}
+"#, APP_NAME, expected_command()?.iter().map(|arg| quote_exec(arg)).collect::<Vec<_>>().join(" "), WANTED_BY);
+
+            let service_path = service_path()?;
+            write_atomic(&service_path, &service_content)?;
+
+            let link_path = wants_link_path()?;
+            if let Some(parent) = link_path.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory {}: {}", parent.display(), e))?;
+            }
+            remove_if_exists(&link_path)?;
+            std::os::unix::fs::symlink(&service_path, &link_path)
+                .map_err(|e| format!("Failed to enable systemd service: {}", e))?;
+
+            // 两种方式只保留一种，避免启动两次
+            LinuxAutoStart.disable(scope)
+        }
+
+        fn disable(&self, scope: AutoStartScope) -> Result<(), String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(());
+            }
+            remove_if_exists(&wants_link_path()?)?;
+            remove_if_exists(&service_path()?)
+        }
+
+        fn status(&self, scope: AutoStartScope) -> Result<AutoStartStatus, String> {
+            if scope == AutoStartScope::AllUsers || !service_path()?.exists() {
+                return Ok(AutoStartStatus::Disabled);
+            }
+            // 服务文件还在但链接被移除，说明用户执行过 systemctl --user disable
+            Ok(if wants_link_path()?.exists() {
+                AutoStartStatus::Enabled
+            } else {
+                AutoStartStatus::DisabledBySystem
+            })
+        }
+
+        fn registered_command(&self, scope: AutoStartScope) -> Result<Option<Vec<String>>, String> {
+            if scope == AutoStartScope::AllUsers {
+                return Ok(None);
+            }
+            let content = match std::fs::read_to_string(service_path()?) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(format!("Failed to read systemd service: {}", e)),
+            };
+            Ok(Some(
+                content
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix("ExecStart="))
+                    .map(split_exec)
+                    .unwrap_or_default(),
+            ))
+        }
+
+        fn expected_command(&self) -> Result<Vec<String>, String> {
+            expected_command()
+        }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const HISTORY_NAME: &str = "history.json";
+const BACKUP_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    pub created_at: String,
+    pub files: Vec<String>,
+    pub has_history: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreResult {
+    pub manifest: BackupManifest,
+    pub restored_files: Vec<String>,
+    // 整理历史保存在前端，恢复后交还给前端重新写入
+    pub history: Option<serde_json::Value>,
+}
+
+/// 应用数据目录（配置、设置、订阅等文件所在位置）
+pub fn app_data_dir() -> PathBuf {
+    crate::app_paths::app_data_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn default_backup_dir() -> PathBuf {
+    app_data_dir().join("backups")
+}
+
+/// 把应用数据目录下的所有数据文件和前端传入的历史记录打包成一个 zip
+pub fn create_backup(
+    destination: Option<PathBuf>,
+    history: Option<serde_json::Value>,
+    app_version: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let data_dir = app_data_dir();
+    let destination = match destination {
+        Some(path) => path,
+        None => {
+            let file_name = format!(
+                "filesortify-backup-{}.zip",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            );
+            default_backup_dir().join(file_name)
+        }
+    };
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let files = data_files(&data_dir)?;
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: app_version.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files: files
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect(),
+        has_history: history.is_some(),
+    };
+
+    let mut writer = zip::ZipWriter::new(File::create(&destination)?);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    writer.start_file(MANIFEST_NAME, options)?;
+    writer.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    for (path, name) in files.iter().zip(manifest.files.iter()) {
+        writer.start_file(format!("data/{}", name), options)?;
+        writer.write_all(&fs::read(path)?)?;
+    }
+
+    if let Some(history) = &history {
+        writer.start_file(HISTORY_NAME, options)?;
+        writer.write_all(serde_json::to_string(history)?.as_bytes())?;
+    }
+
+    writer.finish()?;
+    Ok(destination)
+}
+
+/// 从备份恢复。先解压到临时目录并校验，再整体替换，任何一步失败都会回滚
+pub fn restore_backup(archive_path: &Path) -> Result<RestoreResult, Box<dyn std::error::Error>> {
+    let data_dir = app_data_dir();
+    let staging_dir = data_dir.join(".restore-staging");
+    let previous_dir = data_dir.join(".restore-previous");
+    for dir in [&staging_dir, &previous_dir] {
+        if dir.exists() {
+            fs::remove_dir_all(dir)?;
+        }
+    }
+    fs::create_dir_all(&staging_dir)?;
+
+    let staged = match stage_archive(archive_path, &staging_dir) {
+        Ok(staged) => staged,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging_dir);
+            return Err(e);
+        }
+    };
+    let (manifest, history) = staged;
+
+    // 先把现有文件移到旁边，失败时可以原样放回
+    fs::create_dir_all(&previous_dir)?;
+    let current_files = data_files(&data_dir)?;
+    let mut moved: Vec<(PathBuf, PathBuf)> = Vec::new();
+    let mut installed: Vec<PathBuf> = Vec::new();
+
+    let result = (|| -> Result<(), Box<dyn std::error::Error>> {
+        for path in &current_files {
+            let aside = previous_dir.join(path.file_name().ok_or("Invalid file name")?);
+            fs::rename(path, &aside)?;
+            moved.push((aside, path.clone()));
+        }
+        for name in &manifest.files {
+            let target = data_dir.join(name);
+            fs::rename(staging_dir.join(name), &target)?;
+            installed.push(target);
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        for path in &installed {
+            let _ = fs::remove_file(path);
+        }
+        for (aside, original) in &moved {
+            let _ = fs::rename(aside, original);
+        }
+        let _ = fs::remove_dir_all(&staging_dir);
+        let _ = fs::remove_dir_all(&previous_dir);
+        return Err(e);
+    }
+
+    let _ = fs::remove_dir_all(&staging_dir);
+    let _ = fs::remove_dir_all(&previous_dir);
+
+    Ok(RestoreResult {
+        restored_files: manifest.files.clone(),
+        manifest,
+        history,
+    })
+}
+
+// 解压并校验备份内容
+fn stage_archive(
+    archive_path: &Path,
+    staging_dir: &Path,
+) -> Result<(BackupManifest, Option<serde_json::Value>), Box<dyn std::error::Error>> {
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+
+    let manifest: BackupManifest = {
+        let mut entry = archive.by_name(MANIFEST_NAME).map_err(|_| "Backup manifest is missing")?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        serde_json::from_str(&content)?
+    };
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!("Unsupported backup format version: {}", manifest.format_version).into());
+    }
+
+    for name in &manifest.files {
+        // 只接受单层文件名，防止压缩包中的路径穿越
+        if Path::new(name).components().count() != 1 || name.starts_with('.') {
+            return Err(format!("Invalid file name in backup: {}", name).into());
+        }
+        let mut entry = archive
+            .by_name(&format!("data/{}", name))
+            .map_err(|_| format!("File missing from backup: {}", name))?;
+        let mut bytes = Vec::new();
+        entry.read_to_end(&mut bytes)?;
+        fs::write(staging_dir.join(name), bytes)?;
+    }
+
+    // 配置文件必须能被解析，避免恢复出一个无法启动的状态
+    for name in &manifest.files {
+        let path = staging_dir.join(name);
+        if name.starts_with("config.") {
+            if let Some(format) = crate::config::ConfigFormat::from_path(&path) {
+                let content = fs::read_to_string(&path)?;
+                format.parse(&content).map_err(|e| format!("Invalid config in backup: {}", e))?;
+            }
+        }
+    }
+
+    let history = if manifest.has_history {
+        let mut entry = archive.by_name(HISTORY_NAME).map_err(|_| "History is missing from backup")?;
+        let mut content = String::new();
+        entry.read_to_string(&mut content)?;
+        Some(serde_json::from_str(&content)?)
+    } else {
+        None
+    };
+
+    Ok((manifest, history))
+}
+
+// 数据目录下需要备份的文件：只取顶层的普通文件，跳过隐藏文件和迁移备份
+pub fn data_files(data_dir: &Path) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+    let mut files = Vec::new();
+    if !data_dir.exists() {
+        return Ok(files);
+    }
+    for entry in fs::read_dir(data_dir)? {
+        let path = entry?.path();
+        let name = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) => name.to_string(),
+            None => continue,
+        };
+        if !path.is_file() || name.starts_with('.') || name.ends_with(".bak") || name.ends_with(".tmp") {
+            continue;
+        }
+        files.push(path);
+    }
+    files.sort();
+    Ok(files)
+}
@@ -0,0 +1,58 @@
+use crate::app_paths::CONFIG_DIR_ARG;
+
+/// 本次启动不显示主窗口
+pub const HIDDEN_ARG: &str = "--hidden";
+/// 由开机启动拉起，用于在日志和统计中区分
+pub const AUTOSTART_ARG: &str = "--autostart";
+/// 登记开机启动时附加的参数：开机时不抢占焦点
+pub const AUTOSTART_ARGS: [&str; 2] = [HIDDEN_ARG, AUTOSTART_ARG];
+/// 文件管理器右键菜单：--organize <文件夹>，整理该文件夹（应用已运行时转给已运行的实例）
+pub const ORGANIZE_ARG: &str = "--organize";
+/// 提权后的辅助进程：--autostart-all-users=enable|disable，修改所有用户的开机启动后退出
+pub const ALL_USERS_AUTOSTART_ARG: &str = "--autostart-all-users";
+
+/// 启动参数
+#[derive(Debug, Clone, Default)]
+pub struct CliArgs {
+    pub hidden: bool,
+    pub autostart: bool,
+    pub all_users_autostart: Option<bool>,
+    pub organize: Option<String>,
+}
+
+impl CliArgs {
+    pub fn from_env() -> Self {
+        Self::parse(std::env::args().skip(1))
+    }
+
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                HIDDEN_ARG => parsed.hidden = true,
+                AUTOSTART_ARG => parsed.autostart = true,
+                ORGANIZE_ARG => parsed.organize = args.next(),
+                _ if arg.starts_with(ORGANIZE_ARG) && arg[ORGANIZE_ARG.len()..].starts_with('=') => {
+                    parsed.organize = Some(arg[ORGANIZE_ARG.len() + 1..].to_string());
+                }
+                // 配置目录由 app_paths 处理，这里只跳过它的值
+                CONFIG_DIR_ARG => {
+                    args.next();
+                }
+                _ if arg.starts_with(CONFIG_DIR_ARG) && arg[CONFIG_DIR_ARG.len()..].starts_with('=') => {}
+                _ if arg.starts_with(ALL_USERS_AUTOSTART_ARG) => {
+                    match arg[ALL_USERS_AUTOSTART_ARG.len()..].strip_prefix('=') {
+                        Some("enable") => parsed.all_users_autostart = Some(true),
+                        Some("disable") => parsed.all_users_autostart = Some(false),
+                        _ => log::warn!("Ignoring invalid argument: {}", arg),
+                    }
+                }
+                // 旧版 macOS 从 Finder 启动时会附带进程序列号
+                _ if arg.starts_with("-psn_") => {}
+                _ => log::warn!("Ignoring unknown argument: {}", arg),
+            }
+        }
+        parsed
+    }
+}
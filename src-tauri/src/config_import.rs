@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::config::{Config, PathConfig};
+use crate::i18n::t_format;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryEntry {
+    pub id: String,
+    pub name: String,
+    pub extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryChange {
+    pub id: String,
+    pub name: String,
+    pub added_extensions: Vec<String>,
+    pub removed_extensions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathChange {
+    pub path: String,
+    pub name: String,
+    // true 表示本地还没有这个路径
+    pub is_new: bool,
+}
+
+/// 导入文件与当前配置之间的差异，供前端展示合并预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigImportDiff {
+    pub added_categories: Vec<CategoryEntry>,
+    pub changed_categories: Vec<CategoryChange>,
+    pub removed_categories: Vec<CategoryEntry>,
+    pub paths: Vec<PathChange>,
+}
+
+/// 用户在预览中选择要应用的部分
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportSelection {
+    // 要新增或更新的分类ID
+    #[serde(default)]
+    pub categories: Vec<String>,
+    // 要删除的本地分类ID（导入文件中不存在的分类）
+    #[serde(default)]
+    pub remove_categories: Vec<String>,
+    // 要新增或更新的路径
+    #[serde(default)]
+    pub paths: Vec<String>,
+}
+
+pub fn diff(local: &Config, imported: &Config) -> ConfigImportDiff {
+    let mut added_categories = Vec::new();
+    let mut changed_categories = Vec::new();
+    let mut removed_categories = Vec::new();
+
+    let mut ids: Vec<&String> = imported.categories.keys().collect();
+    ids.sort();
+    for id in ids {
+        let imported_exts = &imported.categories[id];
+        match local.categories.get(id) {
+            None => added_categories.push(CategoryEntry {
+                id: id.clone(),
+                name: imported.display_name(id),
+                extensions: imported_exts.clone(),
+            }),
+            Some(local_exts) => {
+                let local_set: BTreeSet<String> = local_exts.iter().map(|e| e.to_lowercase()).collect();
+                let imported_set: BTreeSet<String> = imported_exts.iter().map(|e| e.to_lowercase()).collect();
+                if local_set != imported_set {
+                    changed_categories.push(CategoryChange {
+                        id: id.clone(),
+                        name: local.display_name(id),
+                        added_extensions: imported_set.difference(&local_set).cloned().collect(),
+                        removed_extensions: local_set.difference(&imported_set).cloned().collect(),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut local_ids: Vec<&String> = local.categories.keys().collect();
+    local_ids.sort();
+    for id in local_ids {
+        if !imported.categories.contains_key(id) {
+            removed_categories.push(CategoryEntry {
+                id: id.clone(),
+                name: local.display_name(id),
+                extensions: local.categories[id].clone(),
+            });
+        }
+    }
+
+    let local_paths = local.list_paths();
+    let paths = imported
+        .list_paths()
+        .into_iter()
+        .filter_map(|imported_path| {
+            match local_paths.iter().find(|p| p.path == imported_path.path) {
+                None => Some(PathChange {
+                    path: imported_path.path.clone(),
+                    name: imported_path.name.clone(),
+                    is_new: true,
+                }),
+                Some(local_path) if path_settings_differ(local_path, &imported_path) => Some(PathChange {
+                    path: imported_path.path.clone(),
+                    name: imported_path.name.clone(),
+                    is_new: false,
+                }),
+                Some(_) => None,
+            }
+        })
+        .collect();
+
+    ConfigImportDiff {
+        added_categories,
+        changed_categories,
+        removed_categories,
+        paths,
+    }
+}
+
+/// 按用户的选择把导入的配置合并进当前配置
+pub fn apply(local: &mut Config, imported: &Config, selection: &ImportSelection) -> Result<(), String> {
+    for id in &selection.categories {
+        let extensions = imported
+            .categories
+            .get(id)
+            .ok_or_else(|| t_format("category_not_found", &[id]))?;
+        local.categories.insert(id.clone(), extensions.clone());
+        if let Some(name) = imported.category_names.get(id) {
+            local.category_names.insert(id.clone(), name.clone());
+        }
+        if let Some(meta) = imported.category_meta.get(id) {
+            local.category_meta.insert(id.clone(), meta.clone());
+        }
+    }
+
+    for id in &selection.remove_categories {
+        local.remove_category(id);
+    }
+
+    for path in &selection.paths {
+        let imported_path = imported
+            .list_paths()
+            .into_iter()
+            .find(|p| &p.path == path)
+            .ok_or_else(|| t_format("import_path_not_found", &[path]))?;
+
+        // 已存在的路径保留本地的ID、监控状态和统计，只更新规则相关字段
+        let existing_id = local.list_paths().into_iter().find(|p| &p.path == path).map(|p| p.id);
+        match existing_id {
+            Some(id) => {
+                if let Some(local_path) = local.find_path_mut(&id) {
+                    local_path.name = imported_path.name.clone();
+                    local_path.auto_organize = imported_path.auto_organize;
+                    local_path.custom_categories = imported_path.custom_categories.clone();
+                    local_path.exclude_patterns = imported_path.exclude_patterns.clone();
+                }
+            }
+            None => {
+                local.validate_path(path, None)?;
+                let mut new_path = PathConfig::new(path.clone(), Some(imported_path.name.clone()));
+                new_path.auto_organize = imported_path.auto_organize;
+                new_path.custom_categories = imported_path.custom_categories.clone();
+                new_path.exclude_patterns = imported_path.exclude_patterns.clone();
+                local.paths.get_or_insert_with(Vec::new).push(new_path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn path_settings_differ(local: &PathConfig, imported: &PathConfig) -> bool {
+    local.name != imported.name
+        || local.auto_organize != imported.auto_organize
+        || serde_json::to_value(&local.custom_categories).ok() != serde_json::to_value(&imported.custom_categories).ok()
+        || local.exclude_patterns != imported.exclude_patterns
+}
@@ -0,0 +1,491 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{CategoryMeta, Config};
+use crate::i18n::{t, t_format};
+
+type Categories = HashMap<String, Vec<String>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackend {
+    Folder,  // 用户指定的同步文件夹（iCloud Drive、Dropbox 等）
+    Server,  // 复用 webhook 服务器
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncSettings {
+    pub enabled: bool,
+    pub backend: SyncBackend,
+    pub folder_path: Option<String>,
+    // 服务端同步时用于标识同一用户多台设备的同步密钥
+    pub sync_key: Option<String>,
+    pub last_synced_at: Option<String>,
+}
+
+impl SyncSettings {
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        let settings_path = Self::get_settings_path();
+
+        if settings_path.exists() {
+            let content = fs::read_to_string(&settings_path)?;
+            let settings: SyncSettings = serde_json::from_str(&content)?;
+            Ok(settings)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let settings_path = Self::get_settings_path();
+
+        if let Some(parent) = settings_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(&settings_path, content)?;
+
+        Ok(())
+    }
+
+    fn get_settings_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("sync.json")
+        } else {
+            PathBuf::from("file_organizer_sync.json")
+        }
+    }
+
+    // 上次同步成功后的分类快照，作为三方合并的基准
+    fn get_base_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("sync_base.json")
+        } else {
+            PathBuf::from("file_organizer_sync_base.json")
+        }
+    }
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        SyncSettings {
+            enabled: false,
+            backend: SyncBackend::Folder,
+            folder_path: None,
+            sync_key: None,
+            last_synced_at: None,
+        }
+    }
+}
+
+/// 参与同步的分类设置：扩展名、显示名称、磁盘上的文件夹名和显示元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncedCategories {
+    pub categories: Categories,
+    // 旧版本同步的数据只有 categories
+    #[serde(rename = "categoryNames", default)]
+    pub category_names: HashMap<String, String>,
+    #[serde(rename = "folderNames", default)]
+    pub folder_names: HashMap<String, String>,
+    #[serde(rename = "categoryMeta", default)]
+    pub category_meta: HashMap<String, CategoryMeta>,
+}
+
+impl SyncedCategories {
+    fn from_config(config: &Config) -> Self {
+        SyncedCategories {
+            categories: config.categories.clone(),
+            category_names: config.category_names.clone(),
+            folder_names: config.folder_names.clone(),
+            category_meta: config.category_meta.clone(),
+        }
+    }
+
+    fn apply_to(&self, config: &mut Config) {
+        config.categories = self.categories.clone();
+        config.category_names = self.category_names.clone();
+        config.folder_names = self.folder_names.clone();
+        config.category_meta = self.category_meta.clone();
+    }
+
+    // 扩展名不区分大小写和顺序，其余设置按值比较
+    fn same_as(&self, other: &SyncedCategories) -> bool {
+        same_categories(&self.categories, &other.categories)
+            && self.category_names == other.category_names
+            && self.folder_names == other.folder_names
+            && self.category_meta == other.category_meta
+    }
+}
+
+/// 远端保存的同步数据。路径配置与设备相关，不参与同步
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncedConfig {
+    #[serde(flatten)]
+    pub settings: SyncedCategories,
+    pub updated_at: String,
+    pub device_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    Unchanged,
+    Pushed,
+    Pulled,
+    Merged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncReport {
+    pub action: SyncAction,
+    // 两端都修改过的分类：扩展名已合并，名称和元数据保留本地的值
+    pub conflicts: Vec<String>,
+    pub synced_at: String,
+}
+
+/// 执行一次同步：拉取远端、与本地做三方合并，再把结果写回两端
+pub async fn sync(settings: &mut SyncSettings, server_url: &str, device_id: &str) -> Result<SyncReport, String> {
+    if !settings.enabled {
+        return Err(t("sync_disabled"));
+    }
+
+    let local = Config::load()
+        .map(|config| SyncedCategories::from_config(&config))
+        .map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let remote = fetch_remote(settings, server_url).await?.map(|r| r.settings);
+    let base = load_base();
+
+    let (merged, conflicts) = match (&remote, &base) {
+        (None, _) => (local.clone(), Vec::new()),
+        (Some(remote), None) => {
+            // 首次同步没有基准，按两边都修改过处理
+            merge_settings(&SyncedCategories::default(), &local, remote)
+        }
+        (Some(remote), Some(base)) => merge_settings(base, &local, remote),
+    };
+
+    let local_changed = !merged.same_as(&local);
+    let remote_changed = remote.as_ref().map(|r| !merged.same_as(r)).unwrap_or(true);
+
+    if local_changed {
+        Config::update(|config| merged.apply_to(config))
+            .map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    }
+
+    let synced_at = chrono::Utc::now().to_rfc3339();
+    if remote_changed {
+        let payload = SyncedConfig {
+            settings: merged.clone(),
+            updated_at: synced_at.clone(),
+            device_id: device_id.to_string(),
+        };
+        push_remote(settings, server_url, &payload).await?;
+    }
+
+    save_base(&merged).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+    settings.last_synced_at = Some(synced_at.clone());
+    settings.save().map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+
+    let action = match (local_changed, remote_changed) {
+        (false, false) => SyncAction::Unchanged,
+        (false, true) => SyncAction::Pushed,
+        (true, false) => SyncAction::Pulled,
+        (true, true) => SyncAction::Merged,
+    };
+
+    Ok(SyncReport { action, conflicts, synced_at })
+}
+
+/// 三方合并同步的分类设置，返回合并结果和两端都修改过的分类ID
+pub fn merge_settings(
+    base: &SyncedCategories,
+    local: &SyncedCategories,
+    remote: &SyncedCategories,
+) -> (SyncedCategories, Vec<String>) {
+    let (categories, mut conflicts) = merge_categories(&base.categories, &local.categories, &remote.categories);
+    let merged = SyncedCategories {
+        categories,
+        category_names: merge_values(&base.category_names, &local.category_names, &remote.category_names, &mut conflicts),
+        folder_names: merge_values(&base.folder_names, &local.folder_names, &remote.folder_names, &mut conflicts),
+        category_meta: merge_values(&base.category_meta, &local.category_meta, &remote.category_meta, &mut conflicts),
+    };
+    conflicts.sort();
+    conflicts.dedup();
+    (merged, conflicts)
+}
+
+/// 三方合并分类：只有一端修改时取修改后的版本，两端都修改时合并扩展名并记录冲突
+pub fn merge_categories(base: &Categories, local: &Categories, remote: &Categories) -> (Categories, Vec<String>) {
+    let mut names: BTreeSet<&String> = BTreeSet::new();
+    names.extend(base.keys());
+    names.extend(local.keys());
+    names.extend(remote.keys());
+
+    let mut merged = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for name in names {
+        let b = base.get(name).map(|v| extension_set(v));
+        let l = local.get(name).map(|v| extension_set(v));
+        let r = remote.get(name).map(|v| extension_set(v));
+
+        let result = if l == r || r == b {
+            local.get(name).cloned()
+        } else if l == b {
+            remote.get(name).cloned()
+        } else {
+            conflicts.push(name.clone());
+            let mut union: Vec<String> = Vec::new();
+            for ext in local.get(name).into_iter().chain(remote.get(name)).flatten() {
+                if !union.contains(ext) {
+                    union.push(ext.clone());
+                }
+            }
+            Some(union)
+        };
+
+        if let Some(extensions) = result {
+            merged.insert(name.clone(), extensions);
+        }
+    }
+
+    (merged, conflicts)
+}
+
+// 三方合并单值设置（名称、元数据）：只有一端修改时取修改后的值，两端改成不同的值时保留本地的值并记录冲突
+fn merge_values<V: Clone + PartialEq>(
+    base: &HashMap<String, V>,
+    local: &HashMap<String, V>,
+    remote: &HashMap<String, V>,
+    conflicts: &mut Vec<String>,
+) -> HashMap<String, V> {
+    let mut ids: BTreeSet<&String> = BTreeSet::new();
+    ids.extend(base.keys());
+    ids.extend(local.keys());
+    ids.extend(remote.keys());
+
+    let mut merged = HashMap::new();
+    for id in ids {
+        let (b, l, r) = (base.get(id), local.get(id), remote.get(id));
+        let result = if l == r || r == b {
+            l
+        } else if l == b {
+            r
+        } else {
+            conflicts.push(id.clone());
+            l.or(r)
+        };
+        if let Some(value) = result {
+            merged.insert(id.clone(), value.clone());
+        }
+    }
+    merged
+}
+
+fn extension_set(extensions: &[String]) -> BTreeSet<String> {
+    extensions.iter().map(|e| e.to_lowercase()).collect()
+}
+
+fn same_categories(a: &Categories, b: &Categories) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|(name, exts)| {
+            b.get(name).map(|other| extension_set(exts) == extension_set(other)).unwrap_or(false)
+        })
+}
+
+fn load_base() -> Option<SyncedCategories> {
+    let content = fs::read_to_string(SyncSettings::get_base_path()).ok()?;
+    serde_json::from_str(&content).ok().or_else(|| {
+        // 旧版本的基准只保存了分类扩展名
+        serde_json::from_str(&content).ok().map(|categories| SyncedCategories {
+            categories,
+            ..Default::default()
+        })
+    })
+}
+
+fn save_base(settings: &SyncedCategories) -> Result<(), Box<dyn std::error::Error>> {
+    let base_path = SyncSettings::get_base_path();
+    if let Some(parent) = base_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&base_path, serde_json::to_string_pretty(settings)?)?;
+    Ok(())
+}
+
+fn sync_file_path(settings: &SyncSettings) -> Result<PathBuf, String> {
+    let folder = settings
+        .folder_path
+        .as_ref()
+        .filter(|f| !f.trim().is_empty())
+        .ok_or_else(|| t("sync_folder_not_set"))?;
+    Ok(PathBuf::from(folder).join("FileSortify").join("config-sync.json"))
+}
+
+// 同步密钥作为查询参数发送，由 reqwest 负责编码
+fn server_sync_key(settings: &SyncSettings) -> Result<&str, String> {
+    settings
+        .sync_key
+        .as_deref()
+        .filter(|k| !k.trim().is_empty())
+        .ok_or_else(|| t("sync_key_not_set"))
+}
+
+async fn fetch_remote(settings: &SyncSettings, server_url: &str) -> Result<Option<SyncedConfig>, String> {
+    match settings.backend {
+        SyncBackend::Folder => {
+            let path = sync_file_path(settings)?;
+            if !path.exists() {
+                return Ok(None);
+            }
+            let content = fs::read_to_string(&path).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+            serde_json::from_str(&content)
+                .map(Some)
+                .map_err(|e| t_format("sync_failed", &[&e.to_string()]))
+        }
+        SyncBackend::Server => {
+            let sync_key = server_sync_key(settings)?;
+            let response = crate::http::client()
+                .get(format!("{}/api/config-sync", server_url))
+                .query(&[("syncKey", sync_key)])
+                .send()
+                .await
+                .map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Ok(None);
+            }
+            if !response.status().is_success() {
+                return Err(t_format("sync_failed", &[&response.status().to_string()]));
+            }
+            response
+                .json::<SyncedConfig>()
+                .await
+                .map(Some)
+                .map_err(|e| t_format("sync_failed", &[&e.to_string()]))
+        }
+    }
+}
+
+async fn push_remote(settings: &SyncSettings, server_url: &str, payload: &SyncedConfig) -> Result<(), String> {
+    match settings.backend {
+        SyncBackend::Folder => {
+            let path = sync_file_path(settings)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+            }
+            let content = serde_json::to_string_pretty(payload).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+            // 先写临时文件再替换，避免网盘客户端同步到写了一半的文件
+            let tmp_path = path.with_extension("json.tmp");
+            fs::write(&tmp_path, content).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+            fs::rename(&tmp_path, &path).map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+            Ok(())
+        }
+        SyncBackend::Server => {
+            let sync_key = server_sync_key(settings)?;
+            let response = crate::http::client()
+                .put(format!("{}/api/config-sync", server_url))
+                .query(&[("syncKey", sync_key)])
+                .json(payload)
+                .send()
+                .await
+                .map_err(|e| t_format("sync_failed", &[&e.to_string()]))?;
+
+            if !response.status().is_success() {
+                return Err(t_format("sync_failed", &[&response.status().to_string()]));
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(entries: &[(&str, &[&str])]) -> Categories {
+        entries
+            .iter()
+            .map(|(name, exts)| (name.to_string(), exts.iter().map(|e| e.to_string()).collect()))
+            .collect()
+    }
+
+    fn names(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn takes_the_side_that_changed() {
+        let base = categories(&[("images", &[".jpg"]), ("video", &[".mp4"])]);
+        let local = categories(&[("images", &[".jpg", ".png"]), ("video", &[".mp4"])]);
+        let remote = categories(&[("images", &[".jpg"])]);
+
+        let (merged, conflicts) = merge_categories(&base, &local, &remote);
+        assert_eq!(merged, categories(&[("images", &[".jpg", ".png"])]));
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn unions_extensions_changed_on_both_sides() {
+        let base = categories(&[("images", &[".jpg"])]);
+        let local = categories(&[("images", &[".jpg", ".png"])]);
+        let remote = categories(&[("images", &[".JPG", ".heic"])]);
+
+        let (merged, conflicts) = merge_categories(&base, &local, &remote);
+        assert_eq!(merged, categories(&[("images", &[".jpg", ".png", ".JPG", ".heic"])]));
+        assert_eq!(conflicts, vec!["images".to_string()]);
+    }
+
+    #[test]
+    fn ignores_extension_case_and_order() {
+        let a = categories(&[("images", &[".JPG", ".png"])]);
+        let b = categories(&[("images", &[".png", ".jpg"])]);
+        assert!(same_categories(&a, &b));
+        assert!(!same_categories(&a, &categories(&[("images", &[".png"])])));
+    }
+
+    #[test]
+    fn merges_names_folders_and_metadata() {
+        let base = SyncedCategories {
+            category_names: names(&[("images", "Images"), ("video", "Video")]),
+            folder_names: names(&[("images", "Images")]),
+            ..Default::default()
+        };
+        let local = SyncedCategories {
+            category_names: names(&[("images", "Photos"), ("video", "Clips")]),
+            folder_names: names(&[("images", "Images")]),
+            ..Default::default()
+        };
+        let remote = SyncedCategories {
+            category_names: names(&[("images", "Images"), ("video", "Movies")]),
+            folder_names: names(&[("images", "Bilder")]),
+            category_meta: HashMap::from([(
+                "images".to_string(),
+                CategoryMeta { icon: Some("🖼".to_string()), ..Default::default() },
+            )]),
+            ..Default::default()
+        };
+
+        let (merged, conflicts) = merge_settings(&base, &local, &remote);
+        // 只有本地改了显示名称，只有远端改了文件夹名和元数据
+        assert_eq!(merged.category_names["images"], "Photos");
+        assert_eq!(merged.folder_names["images"], "Bilder");
+        assert_eq!(merged.category_meta["images"].icon.as_deref(), Some("🖼"));
+        // 两端都改了的名称保留本地的值
+        assert_eq!(merged.category_names["video"], "Clips");
+        assert_eq!(conflicts, vec!["video".to_string()]);
+    }
+
+    #[test]
+    fn reads_sync_data_written_by_older_versions() {
+        let remote: SyncedConfig = serde_json::from_str(
+            r#"{"categories": {"images": [".jpg"]}, "updated_at": "2024-01-01T00:00:00Z", "device_id": "a"}"#,
+        )
+        .unwrap();
+        assert_eq!(remote.settings.categories, categories(&[("images", &[".jpg"])]));
+        assert!(remote.settings.category_names.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::fs;
+use std::sync::mpsc::channel;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::Config;
+use crate::file_organizer::LogMessage;
+use crate::i18n::{t, t_format, now_timestamps};
+use crate::AppState;
+
+/// 监听 config.json 的外部修改（手动编辑、网盘同步等），并热加载到正在运行的整理器
+pub fn start(app_handle: AppHandle) -> Result<(), Box<dyn std::error::Error>> {
+    let watch_dir = Config::get_config_path()
+        .parent()
+        .ok_or("Failed to resolve config directory")?
+        .to_path_buf();
+    fs::create_dir_all(&watch_dir)?;
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    // 监听目录而不是文件本身：很多编辑器和同步工具是以"写临时文件再替换"的方式保存的
+    watcher.watch(&watch_dir, RecursiveMode::NonRecursive)?;
+
+    std::thread::spawn(move || {
+        // watcher必须在这个线程中保持活跃
+        let _watcher = watcher;
+        let mut last_content = fs::read_to_string(Config::get_config_path()).ok();
+        let mut last_fingerprint = Config::load().ok().map(|c| rules_fingerprint(&c));
+
+        while let Ok(event) = rx.recv() {
+            let touches_config = match &event {
+                Ok(Event { kind, paths, .. }) => {
+                    matches!(kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Other)
+                        && paths.iter().any(|p| Config::is_config_file(p))
+                }
+                Err(_) => false,
+            };
+            if !touches_config {
+                continue;
+            }
+
+            // 去抖：保存一次文件通常会产生多个事件，等写入稳定后再读取
+            std::thread::sleep(Duration::from_millis(500));
+            while rx.try_recv().is_ok() {}
+
+            // 配置可能被转换为其他格式，每次重新定位文件
+            let config_path = Config::get_config_path();
+            let content = match fs::read_to_string(&config_path) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            if last_content.as_deref() == Some(content.as_str()) {
+                continue;
+            }
+
+            match Config::load() {
+                Ok(config) => {
+                    // 迁移可能会重写文件，以重新读取的内容为准
+                    last_content = fs::read_to_string(&config_path).ok().or(Some(content));
+                    
+                    // 只有统计信息变化（应用自身写入）时不需要重启监控
+                    let fingerprint = rules_fingerprint(&config);
+                    if last_fingerprint.as_ref() == Some(&fingerprint) {
+                        continue;
+                    }
+                    last_fingerprint = Some(fingerprint);
+                    apply_config(&app_handle, config);
+                }
+                Err(e) => {
+                    // 文件可能处于编辑中途的无效状态，保留当前配置继续运行
+                    last_content = Some(content);
+                    emit_log(&app_handle, &t_format("config_reload_failed", &[&e.to_string()]), "error");
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// 去掉路径统计后的配置内容，用于判断分类规则是否真正发生变化
+fn rules_fingerprint(config: &Config) -> serde_json::Value {
+    let mut value = serde_json::to_value(config).unwrap_or_default();
+    if let Some(paths) = value.get_mut("paths").and_then(|p| p.as_array_mut()) {
+        for path in paths {
+            if let Some(obj) = path.as_object_mut() {
+                obj.remove("stats");
+            }
+        }
+    }
+    value
+}
+
+fn apply_config(app_handle: &AppHandle, config: Config) {
+    let state = app_handle.state::<AppState>();
+    let mut organizers = state.organizers.blocking_lock();
+
+    for (folder_path, organizer) in organizers.iter_mut() {
+        match organizer.reload_config(config.clone()) {
+            Ok(()) => crate::metrics::record_watcher_restart(),
+            Err(e) => emit_log(
+                app_handle,
+                &t_format("config_reload_restart_failed", &[folder_path, &e.to_string()]),
+                "error",
+            ),
+        }
+    }
+    drop(organizers);
+
+    // 托盘菜单中的路径列表随配置更新
+    crate::refresh_tray_menu(app_handle);
+
+    emit_log(app_handle, &t("config_reloaded"), "success");
+    if let Err(e) = app_handle.emit("config-reloaded", &config) {
+        eprintln!("Failed to emit config reloaded event: {}", e);
+    }
+}
+
+fn emit_log(app_handle: &AppHandle, message: &str, log_type: &str) {
+    let (timestamp, display_time) = now_timestamps();
+    let log_message = LogMessage {
+        message: message.to_string(),
+        log_type: log_type.to_string(),
+        timestamp,
+        display_time,
+    };
+    if let Err(e) = app_handle.emit("log-message", &log_message) {
+        eprintln!("Failed to emit log message: {}", e);
+    }
+
+    match log_type {
+        "error" => log::error!("{}", message),
+        _ => log::info!("{}", message),
+    }
+}
@@ -0,0 +1,138 @@
+use crate::i18n::t;
+
+// 右键菜单中显示的名称，Windows 上注册表项的名称
+#[cfg(target_os = "windows")]
+const MENU_KEY: &str = "FileSortify";
+
+/// 文件管理器右键菜单“用 FileSortify 整理”的登记。
+/// Windows 在 Explorer 的文件夹和文件夹空白处菜单中注册命令，用 --organize 启动应用；
+/// macOS 由随应用打包的 Finder Sync 扩展提供菜单，扩展通过 filesortify://organize 链接调用应用，
+/// 这里只负责启用或停用扩展
+pub struct ContextMenu;
+
+impl ContextMenu {
+    pub fn enable() -> Result<(), String> {
+        platform::enable()
+    }
+
+    pub fn disable() -> Result<(), String> {
+        platform::disable()
+    }
+
+    pub fn is_enabled() -> Result<bool, String> {
+        platform::is_enabled()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::MENU_KEY;
+    use crate::cli::ORGANIZE_ARG;
+    use crate::i18n::t;
+    use std::io::ErrorKind;
+    use winreg::enums::HKEY_CURRENT_USER;
+    use winreg::RegKey;
+
+    // 右键文件夹，%1 是文件夹路径
+    const DIRECTORY_KEY: &str = "Software\\Classes\\Directory\\shell";
+    // 在文件夹空白处右键，%V 是当前文件夹
+    const BACKGROUND_KEY: &str = "Software\\Classes\\Directory\\Background\\shell";
+
+    fn entries() -> [(&'static str, &'static str); 2] {
+        [(DIRECTORY_KEY, "%1"), (BACKGROUND_KEY, "%V")]
+    }
+
+    pub fn enable() -> Result<(), String> {
+        let app_path = super::current_exe()?;
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        for (parent, placeholder) in entries() {
+            let write = || -> std::io::Result<()> {
+                let (key, _) = hkcu.create_subkey(format!("{}\\{}", parent, MENU_KEY))?;
+                key.set_value("MUIVerb", &t("context_menu_sort"))?;
+                key.set_value("Icon", &format!("\"{}\",0", app_path.display()))?;
+                let (command, _) = key.create_subkey("command")?;
+                command.set_value("", &format!("\"{}\" {} \"{}\"", app_path.display(), ORGANIZE_ARG, placeholder))?;
+                Ok(())
+            };
+            write().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn disable() -> Result<(), String> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        for (parent, _) in entries() {
+            match hkcu.delete_subkey_all(format!("{}\\{}", parent, MENU_KEY)) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.to_string()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_enabled() -> Result<bool, String> {
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        Ok(hkcu.open_subkey(format!("{}\\{}\\command", DIRECTORY_KEY, MENU_KEY)).is_ok())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::process::Command;
+
+    // 随应用打包在 Contents/PlugIns 中的 Finder Sync 扩展
+    const EXTENSION_ID: &str = "com.fileSortify.tool.FinderSync";
+
+    fn pluginkit(args: &[&str]) -> Result<String, String> {
+        let output = Command::new("pluginkit").args(args).output().map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    pub fn enable() -> Result<(), String> {
+        pluginkit(&["-e", "use", "-i", EXTENSION_ID]).map(|_| ())
+    }
+
+    pub fn disable() -> Result<(), String> {
+        pluginkit(&["-e", "ignore", "-i", EXTENSION_ID]).map(|_| ())
+    }
+
+    // pluginkit -m 的输出中已启用的扩展以 + 开头，没有安装扩展时输出为空
+    pub fn is_enabled() -> Result<bool, String> {
+        let output = pluginkit(&["-m", "-i", EXTENSION_ID])?;
+        Ok(output.lines().any(|line| line.trim_start().starts_with('+')))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use crate::i18n::t;
+
+    pub fn enable() -> Result<(), String> {
+        Err(t("context_menu_unsupported"))
+    }
+
+    pub fn disable() -> Result<(), String> {
+        Ok(())
+    }
+
+    pub fn is_enabled() -> Result<bool, String> {
+        Ok(false)
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn current_exe() -> Result<std::path::PathBuf, String> {
+    std::env::current_exe().map_err(|e| e.to_string())
+}
+
+/// 检查右键菜单传来的路径：必须是存在的文件夹
+pub fn validate_folder(path: &str) -> Result<std::path::PathBuf, String> {
+    let path = std::path::PathBuf::from(path.trim());
+    if path.as_os_str().is_empty() || !path.is_dir() {
+        return Err(t("context_menu_invalid_folder"));
+    }
+    Ok(path)
+}
@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::config::{self, Config, ConfigFormat, ConfigHost, PathStats};
+use crate::deferred::DeferredFile;
+use crate::events::OrganizerEvents;
+use crate::file_organizer::{FileSortify, FileOrganizedEvent, LogMessage, SessionSummary, UndoAction};
+use crate::free_tier;
+use crate::managed_config::ManagedConfig;
+use crate::pending::{Decision, PendingMove};
+use crate::permissions::PermissionIssue;
+use crate::settings::GeneralSettings;
+
+/// 向 filesortify-core 注册应用层的配置扩展：配置文件格式来自通用设置，并叠加托管配置。
+/// 需要在第一次加载配置之前调用
+pub fn install() {
+    config::set_host(AppConfigHost);
+}
+
+struct AppConfigHost;
+
+impl ConfigHost for AppConfigHost {
+    fn preferred_format(&self) -> ConfigFormat {
+        GeneralSettings::load().map(|s| s.config_format).unwrap_or_default()
+    }
+
+    fn apply_managed(&self, config: &mut Config) {
+        if let Some(managed) = ManagedConfig::load() {
+            managed.apply_to_config(config);
+        }
+    }
+
+    fn strip_managed(&self, config: &mut Config, read_user: &dyn Fn() -> Config) {
+        if let Some(managed) = ManagedConfig::load() {
+            managed.strip_from_config(config, &read_user());
+        }
+    }
+}
+
+/// 把整理事件转发给前端、托盘、Webhook 和远程目标，监控中按免费版额度限制自动移动
+pub struct TauriEvents {
+    app_handle: AppHandle,
+}
+
+impl OrganizerEvents for TauriEvents {
+    fn log(&self, message: &LogMessage) {
+        if let Err(e) = self.app_handle.emit("log-message", message) {
+            eprintln!("Failed to emit log message: {}", e);
+        }
+        if message.log_type == "error" {
+            crate::tray_status::report_error(&self.app_handle);
+            crate::webhooks::error(&message.message);
+            crate::metrics::record_error();
+        }
+    }
+
+    fn file_organized(&self, event: &FileOrganizedEvent) {
+        if let Err(e) = self.app_handle.emit("file-organized", event) {
+            eprintln!("Failed to emit file organized event: {}", e);
+        }
+        crate::metrics::record_file_organized();
+        crate::activity::record(event);
+        crate::webhooks::file_organized(event);
+        crate::destinations::on_file_organized(&self.app_handle, event);
+    }
+
+    fn permission_denied(&self, issue: &PermissionIssue) {
+        // 界面据此显示授权引导，macOS 上可以直接打开系统设置中对应的页面
+        if let Err(e) = self.app_handle.emit("permission-denied", issue) {
+            eprintln!("Failed to emit permission denied event: {}", e);
+        }
+    }
+
+    fn file_undone(&self, event: &serde_json::Value) {
+        if let Err(e) = self.app_handle.emit("file-undone", event) {
+            eprintln!("Failed to emit undo event: {}", e);
+        }
+    }
+
+    fn session_finished(&self, summary: &SessionSummary) {
+        crate::webhooks::session_finished(summary);
+    }
+
+    fn path_stats_updated(&self, folder_path: &Path, stats: &PathStats) {
+        let event = serde_json::json!({
+            "folder_path": folder_path.to_string_lossy(),
+            "stats": stats,
+        });
+        if let Err(e) = self.app_handle.emit("path-stats-updated", &event) {
+            eprintln!("Failed to emit path stats event: {}", e);
+        }
+        crate::tray_status::update_organized_today(&self.app_handle);
+    }
+
+    fn file_pending(&self, pending: &PendingMove) {
+        if let Err(e) = self.app_handle.emit("file-pending", pending) {
+            eprintln!("Failed to emit file pending event: {}", e);
+        }
+    }
+
+    fn file_pending_resolved(&self, id: &str, decision: Decision) {
+        let event = serde_json::json!({ "id": id, "decision": decision.as_str() });
+        if let Err(e) = self.app_handle.emit("file-pending-resolved", &event) {
+            eprintln!("Failed to emit file pending resolved event: {}", e);
+        }
+    }
+
+    fn file_deferred(&self, file: &DeferredFile) {
+        if let Err(e) = self.app_handle.emit("file-deferred", file) {
+            eprintln!("Failed to emit file deferred event: {}", e);
+        }
+    }
+
+    fn file_deferred_resolved(&self, path: &str, moved: bool) {
+        let event = serde_json::json!({ "path": path, "moved": moved });
+        if let Err(e) = self.app_handle.emit("file-deferred-resolved", &event) {
+            eprintln!("Failed to emit file deferred resolved event: {}", e);
+        }
+    }
+
+    fn deferred_manual_move(&self, action: &UndoAction) {
+        // 与手动整理一样计入免费版的每日额度，并加入该文件夹的撤销历史
+        if free_tier::is_limited_blocking(&self.app_handle) {
+            free_tier::record_files(1);
+        }
+        let app_handle = self.app_handle.clone();
+        let action = action.clone();
+        tauri::async_runtime::spawn(async move {
+            let state = app_handle.state::<crate::AppState>();
+            let folder = action.downloads_path.to_string_lossy().to_string();
+            if let Some(organizer) = state.organizers.lock().await.get_mut(&folder) {
+                organizer.undo_history.add_action(action);
+            }
+        });
+    }
+
+    fn metric(&self, name: &str, value: u64) {
+        crate::telemetry::record(name, value);
+    }
+
+    fn allow_monitored_move(&self) -> bool {
+        if !free_tier::is_limited_blocking(&self.app_handle) {
+            return true;
+        }
+        match free_tier::remaining_today() {
+            Ok(_) => true,
+            Err(e) => {
+                let _ = self.app_handle.emit("free-tier-limit-reached", &e);
+                false
+            }
+        }
+    }
+
+    fn monitored_file_moved(&self) {
+        if free_tier::is_limited_blocking(&self.app_handle) {
+            free_tier::record_files(1);
+        }
+    }
+}
+
+/// 命令行版本的事件处理：只记录统计，试用期结束时按免费版额度限制监控中的自动移动
+pub struct HeadlessEvents {
+    pub limited: bool,
+}
+
+impl OrganizerEvents for HeadlessEvents {
+    fn metric(&self, name: &str, value: u64) {
+        crate::telemetry::record(name, value);
+    }
+
+    fn allow_monitored_move(&self) -> bool {
+        !self.limited || free_tier::remaining_today().is_ok()
+    }
+
+    fn monitored_file_moved(&self) {
+        if self.limited {
+            free_tier::record_files(1);
+        }
+    }
+}
+
+/// 整理器把事件发送到应用窗口
+pub trait WithAppHandle {
+    fn with_app_handle(self, app_handle: AppHandle) -> Self;
+}
+
+impl WithAppHandle for FileSortify {
+    fn with_app_handle(self, app_handle: AppHandle) -> Self {
+        self.with_events(Arc::new(TauriEvents { app_handle }))
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// 一次崩溃的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub created_at: String,
+    // 用户同意后上传过的报告不再提示
+    #[serde(default)]
+    pub sent: bool,
+}
+
+pub fn crash_dir() -> Option<PathBuf> {
+    crate::app_paths::app_data_dir().map(|dir| dir.join("crashes"))
+}
+
+fn report_path(id: &str) -> Option<PathBuf> {
+    // ID 由我们生成，只包含字母、数字和连字符，防止路径穿越
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    crash_dir().map(|dir| dir.join(format!("{}.json", id)))
+}
+
+/// 安装 panic hook：把崩溃信息写到本地，然后交给默认的 hook 继续处理
+pub fn install(app_version: &'static str) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+            s.to_string()
+        } else if let Some(s) = info.payload().downcast_ref::<String>() {
+            s.clone()
+        } else {
+            "Unknown panic".to_string()
+        };
+        let now = chrono::Local::now();
+        let report = CrashReport {
+            id: format!("crash-{}-{}", now.format("%Y%m%d%H%M%S"), rand::random::<u32>()),
+            app_version: app_version.to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            thread: std::thread::current().name().unwrap_or("unnamed").to_string(),
+            message,
+            location: info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            created_at: now.to_rfc3339(),
+            sent: false,
+        };
+
+        match save(&report) {
+            Ok(path) => log::error!("Panic: {} (crash report saved to {})", report.message, path.display()),
+            Err(e) => log::error!("Panic: {} (failed to save crash report: {})", report.message, e),
+        }
+        log::logger().flush();
+
+        default_hook(info);
+    }));
+}
+
+fn save(report: &CrashReport) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = report_path(&report.id).ok_or("Crash report directory is unavailable")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(report)?)?;
+    Ok(path)
+}
+
+/// 本地保存的崩溃报告，按时间从新到旧；include_sent 为 false 时只返回还没上传过的
+pub fn list(include_sent: bool) -> Vec<CrashReport> {
+    let Some(dir) = crash_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(&dir) else { return Vec::new() };
+
+    let mut reports: Vec<CrashReport> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<CrashReport>(&content).ok())
+        .filter(|report| include_sent || !report.sent)
+        .collect();
+    reports.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    reports
+}
+
+fn load(id: &str) -> Result<CrashReport, Box<dyn std::error::Error + Send + Sync>> {
+    let path = report_path(id).ok_or_else(|| format!("Invalid crash report id: {}", id))?;
+    let content = fs::read_to_string(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// 用户同意后上传崩溃报告，成功后标记为已发送
+pub async fn upload(id: &str, server_url: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let mut report = load(id)?;
+
+    let response = crate::http::client()
+        .post(&format!("{}/api/crash-reports", server_url))
+        .json(&report)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("Crash report upload failed: {}", response.status()).into());
+    }
+
+    report.sent = true;
+    save(&report).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 删除崩溃报告（用户选择不发送时）
+pub fn dismiss(id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = report_path(id).ok_or_else(|| format!("Invalid crash report id: {}", id))?;
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
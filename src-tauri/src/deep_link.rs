@@ -0,0 +1,139 @@
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_deep_link::DeepLinkExt;
+
+use crate::i18n::{t, t_format};
+
+pub const SCHEME: &str = "filesortify";
+
+/// 支付完成后 Creem 结账页跳转回应用的地址
+pub const PAYMENT_SUCCESS_URL: &str = "filesortify://payment/success";
+/// Finder Sync 扩展的右键菜单调用应用整理文件夹：filesortify://organize?path=<文件夹>
+pub const ORGANIZE_HOST: &str = "organize";
+/// 预览整理结果并在窗口中确认：filesortify://preview?path=<文件夹>
+pub const PREVIEW_HOST: &str = "preview";
+/// 显示主窗口的某个页面：filesortify://show?view=<页面>，view=rules 时可以带 pathId 打开规则编辑器
+pub const SHOW_HOST: &str = "show";
+
+// show 链接可以打开的页面，前端按 deep-link-show 事件切换
+const SHOW_VIEWS: [&str; 4] = ["main", "settings", "history", "rules"];
+
+/// 从 filesortify:// 链接解析出的操作，参数已经过检查
+#[derive(Debug, Clone)]
+enum DeepLinkAction {
+    PaymentSuccess,
+    Organize { path: String },
+    Preview { path: String },
+    Show { view: String, path_id: Option<String> },
+}
+
+/// 处理应用运行期间和通过链接启动时收到的 filesortify:// 链接
+pub fn setup(app: &AppHandle) {
+    // macOS 和安装包在安装时注册 scheme，Linux（AppImage）和 Windows 开发模式需要运行时注册
+    #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+    if let Err(e) = app.deep_link().register_all() {
+        log::warn!("Failed to register {}:// URL scheme: {}", SCHEME, e);
+    }
+
+    let handle = app.clone();
+    app.deep_link().on_open_url(move |event| {
+        for url in event.urls() {
+            handle_url(&handle, &url);
+        }
+    });
+
+    match app.deep_link().get_current() {
+        Ok(Some(urls)) => {
+            for url in urls {
+                handle_url(app, &url);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to read launch deep link: {}", e),
+    }
+}
+
+fn handle_url(app: &AppHandle, url: &Url) {
+    if url.scheme() != SCHEME {
+        return;
+    }
+    log::info!("Received deep link: {}", url);
+
+    let action = match parse(url) {
+        Ok(action) => action,
+        Err(message) => {
+            // 链接可能来自网页或启动器，不合法时提示原因
+            log::warn!("Rejected deep link {}: {}", url, message);
+            let _ = tauri_plugin_notification::NotificationExt::notification(app)
+                .builder()
+                .title(&t("deep_link_rejected_title"))
+                .body(&message)
+                .show();
+            return;
+        }
+    };
+    crate::telemetry::record_feature("deep_link");
+
+    match action {
+        DeepLinkAction::PaymentSuccess => {
+            show_main_window(app);
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                crate::payment::confirm(app).await;
+            });
+        }
+        DeepLinkAction::Organize { path } => crate::organize_from_context_menu(app, &path),
+        DeepLinkAction::Preview { path } => crate::drop_target::handle_drop(app, vec![path.into()]),
+        DeepLinkAction::Show { view, path_id } => {
+            if view == "rules" {
+                if let Err(e) = crate::rules_editor::open(app, path_id) {
+                    log::error!("Failed to open rules editor from deep link: {}", e);
+                }
+                return;
+            }
+            show_main_window(app);
+            let _ = app.emit("deep-link-show", &serde_json::json!({ "view": view }));
+        }
+    }
+}
+
+// 检查链接的操作和参数，文件夹必须存在
+fn parse(url: &Url) -> Result<DeepLinkAction, String> {
+    let query = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let folder = || {
+        let path = query("path").ok_or_else(|| t_format("deep_link_missing_parameter", &["path"]))?;
+        crate::context_menu::validate_folder(&path).map(|path| path.to_string_lossy().to_string())
+    };
+
+    match (url.host_str(), url.path()) {
+        (Some("payment"), "/success") => Ok(DeepLinkAction::PaymentSuccess),
+        (Some(ORGANIZE_HOST), _) => Ok(DeepLinkAction::Organize { path: folder()? }),
+        (Some(PREVIEW_HOST), _) => Ok(DeepLinkAction::Preview { path: folder()? }),
+        (Some(SHOW_HOST), _) => {
+            let view = query("view").unwrap_or_else(|| "main".to_string());
+            if !SHOW_VIEWS.contains(&view.as_str()) {
+                return Err(t_format("deep_link_invalid_parameter", &["view", &view]));
+            }
+            let path_id = query("pathId");
+            if let Some(path_id) = &path_id {
+                if !crate::config::Config::load().map(|config| config.find_path(path_id).is_some()).unwrap_or(false) {
+                    return Err(t_format("deep_link_invalid_parameter", &["pathId", path_id]));
+                }
+            }
+            Ok(DeepLinkAction::Show { view, path_id })
+        }
+        _ => Err(t_format("deep_link_unknown_action", &[url.host_str().unwrap_or_default()])),
+    }
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
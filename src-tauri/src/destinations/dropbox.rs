@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+use tauri::AppHandle;
+
+use super::oauth::{self, OAuthClient};
+use super::queue::PendingUpload;
+use super::{check_response, read_file, remote_path, DestinationKind, DestinationProvider};
+
+const UPLOAD_URL: &str = "https://content.dropboxapi.com/2/files/upload";
+
+// token_access_type=offline 才会返回刷新令牌
+const CLIENT: OAuthClient = OAuthClient {
+    authorize_url: "https://www.dropbox.com/oauth2/authorize",
+    token_url: "https://api.dropboxapi.com/oauth2/token",
+    client_id: option_env!("FILESORTIFY_DROPBOX_CLIENT_ID"),
+    client_secret: None,
+    scopes: "files.content.write",
+    extra_params: &[("token_access_type", "offline")],
+};
+
+pub struct DropboxProvider;
+
+#[async_trait]
+impl DestinationProvider for DropboxProvider {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::Dropbox
+    }
+
+    fn is_configured(&self) -> bool {
+        CLIENT.client_id.is_some()
+    }
+
+    async fn connect(&self, app: &AppHandle, _credentials: Option<serde_json::Value>) -> Result<(), String> {
+        oauth::connect(app, self.kind(), &CLIENT).await
+    }
+
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String> {
+        let token = oauth::access_token(self.kind(), &CLIENT).await?;
+        let (name, bytes) = read_file(&upload.file).await?;
+        let arg = serde_json::json!({
+            "path": remote_path(&upload.folder, &name),
+            "mode": "add",
+            "autorename": true,
+        });
+
+        let response = crate::http::client()
+            .post(UPLOAD_URL)
+            .bearer_auth(&token)
+            .header("Dropbox-API-Arg", ascii_json(&arg.to_string()))
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        check_response(response).await.map(|_| ())
+    }
+}
+
+// HTTP 头只能包含 ASCII，JSON 中的其他字符按 Dropbox 的要求转为 \uXXXX
+fn ascii_json(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            let mut units = [0u16; 2];
+            for unit in c.encode_utf16(&mut units) {
+                escaped.push_str(&format!("\\u{:04x}", unit));
+            }
+        }
+    }
+    escaped
+}
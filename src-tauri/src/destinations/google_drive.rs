@@ -0,0 +1,131 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use tauri::AppHandle;
+
+use super::oauth::{self, OAuthClient};
+use super::queue::PendingUpload;
+use super::{check_response, read_file, DestinationKind, DestinationProvider};
+
+const API_URL: &str = "https://www.googleapis.com/drive/v3/files";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/drive/v3/files?uploadType=resumable";
+const FOLDER_MIME_TYPE: &str = "application/vnd.google-apps.folder";
+
+// drive.file 只能访问应用自己创建的文件和文件夹
+const CLIENT: OAuthClient = OAuthClient {
+    authorize_url: "https://accounts.google.com/o/oauth2/v2/auth",
+    token_url: "https://oauth2.googleapis.com/token",
+    client_id: option_env!("FILESORTIFY_GOOGLE_CLIENT_ID"),
+    client_secret: option_env!("FILESORTIFY_GOOGLE_CLIENT_SECRET"),
+    scopes: "https://www.googleapis.com/auth/drive.file",
+    extra_params: &[("access_type", "offline"), ("prompt", "consent")],
+};
+
+#[derive(Deserialize)]
+struct DriveFile {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct FileList {
+    files: Vec<DriveFile>,
+}
+
+pub struct GoogleDriveProvider;
+
+#[async_trait]
+impl DestinationProvider for GoogleDriveProvider {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::GoogleDrive
+    }
+
+    fn is_configured(&self) -> bool {
+        CLIENT.client_id.is_some()
+    }
+
+    async fn connect(&self, app: &AppHandle, _credentials: Option<serde_json::Value>) -> Result<(), String> {
+        oauth::connect(app, self.kind(), &CLIENT).await
+    }
+
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String> {
+        let token = oauth::access_token(self.kind(), &CLIENT).await?;
+        let (name, bytes) = read_file(&upload.file).await?;
+        let parent = folder_id(&token, &upload.folder).await?;
+
+        // 可续传上传：先提交元数据拿到上传地址，再上传内容
+        let response = crate::http::client()
+            .post(UPLOAD_URL)
+            .bearer_auth(&token)
+            .header("X-Upload-Content-Length", bytes.len())
+            .json(&serde_json::json!({ "name": name, "parents": [parent] }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let response = check_response(response).await?;
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .ok_or_else(|| "Missing upload location".to_string())?
+            .to_string();
+
+        let response = crate::http::client()
+            .put(location)
+            .bearer_auth(&token)
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        check_response(response).await.map(|_| ())
+    }
+}
+
+// 按路径逐级查找文件夹，不存在时创建，返回最后一级的 ID；空路径为“我的云端硬盘”根目录
+async fn folder_id(token: &str, folder: &str) -> Result<String, String> {
+    let mut parent = "root".to_string();
+    for name in folder.split('/').filter(|name| !name.is_empty()) {
+        parent = match find_folder(token, &parent, name).await? {
+            Some(id) => id,
+            None => create_folder(token, &parent, name).await?,
+        };
+    }
+    Ok(parent)
+}
+
+async fn find_folder(token: &str, parent: &str, name: &str) -> Result<Option<String>, String> {
+    let query = format!(
+        "name = '{}' and mimeType = '{}' and '{}' in parents and trashed = false",
+        name.replace('\\', "\\\\").replace('\'', "\\'"),
+        FOLDER_MIME_TYPE,
+        parent
+    );
+    let response = crate::http::client()
+        .get(API_URL)
+        .bearer_auth(token)
+        .query(&[("q", query.as_str()), ("fields", "files(id)"), ("pageSize", "1")])
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let list = check_response(response)
+        .await?
+        .json::<FileList>()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(list.files.into_iter().next().map(|file| file.id))
+}
+
+async fn create_folder(token: &str, parent: &str, name: &str) -> Result<String, String> {
+    let response = crate::http::client()
+        .post(API_URL)
+        .bearer_auth(token)
+        .query(&[("fields", "id")])
+        .json(&serde_json::json!({ "name": name, "mimeType": FOLDER_MIME_TYPE, "parents": [parent] }))
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    let file = check_response(response)
+        .await?
+        .json::<DriveFile>()
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(file.id)
+}
@@ -0,0 +1,341 @@
+pub mod dropbox;
+pub mod google_drive;
+pub mod oauth;
+pub mod onedrive;
+pub mod queue;
+pub mod s3;
+pub mod webdav;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::config::{Config, RemoteDestination};
+use crate::file_organizer::FileOrganizedEvent;
+use crate::i18n::{t, t_format};
+use queue::PendingUpload;
+
+// 凭据保存在系统钥匙串中，条目名为 "destination-<提供方 ID>"
+const KEYRING_SERVICE: &str = "com.fileSortify.tool";
+// 上传失败后在本次运行中重试的次数，间隔按 4、8 秒递增；仍然失败时留到下次启动继续
+const MAX_ATTEMPTS: u32 = 3;
+
+/// 可以作为分类远程目标的服务
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DestinationKind {
+    GoogleDrive,
+    Dropbox,
+    #[serde(rename = "onedrive")]
+    OneDrive,
+    // S3 兼容的对象存储，例如 MinIO
+    S3,
+    // WebDAV 服务器，例如 Nextcloud
+    #[serde(rename = "webdav")]
+    WebDav,
+}
+
+impl DestinationKind {
+    pub const ALL: [DestinationKind; 5] = [
+        DestinationKind::GoogleDrive,
+        DestinationKind::Dropbox,
+        DestinationKind::OneDrive,
+        DestinationKind::S3,
+        DestinationKind::WebDav,
+    ];
+
+    /// 配置文件中 RemoteDestination.provider 使用的 ID
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DestinationKind::GoogleDrive => "google_drive",
+            DestinationKind::Dropbox => "dropbox",
+            DestinationKind::OneDrive => "onedrive",
+            DestinationKind::S3 => "s3",
+            DestinationKind::WebDav => "webdav",
+        }
+    }
+
+    pub fn parse(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.as_str() == id)
+    }
+
+    fn keyring_entry(&self) -> String {
+        format!("destination-{}", self.as_str())
+    }
+}
+
+/// 远程目标的账户连接和上传
+#[async_trait]
+pub trait DestinationProvider: Send + Sync {
+    fn kind(&self) -> DestinationKind;
+
+    /// 构建时是否提供了这个服务的应用凭据（例如 OAuth 客户端 ID）
+    fn is_configured(&self) -> bool;
+
+    /// 连接账户并把凭据保存到钥匙串。OAuth 服务在浏览器中授权，忽略 credentials；
+    /// S3 和 WebDAV 使用用户填写的 credentials，保存前先检查能否访问
+    async fn connect(&self, app: &AppHandle, credentials: Option<serde_json::Value>) -> Result<(), String>;
+
+    /// 把文件上传到远程文件夹（相对于账户根目录，以 / 分隔），不覆盖远程的同名文件。
+    /// 支持续传的服务在每个分块完成后更新并保存 upload.progress
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String>;
+}
+
+pub fn provider(kind: DestinationKind) -> Box<dyn DestinationProvider> {
+    match kind {
+        DestinationKind::GoogleDrive => Box::new(google_drive::GoogleDriveProvider),
+        DestinationKind::Dropbox => Box::new(dropbox::DropboxProvider),
+        DestinationKind::OneDrive => Box::new(onedrive::OneDriveProvider),
+        DestinationKind::S3 => Box::new(s3::S3Provider),
+        DestinationKind::WebDav => Box::new(webdav::WebDavProvider),
+    }
+}
+
+/// 读取钥匙串中的凭据，没有连接时返回 None
+pub fn load_credentials<T: DeserializeOwned>(kind: DestinationKind) -> Result<Option<T>, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, &kind.keyring_entry()).map_err(|e| e.to_string())?;
+    match entry.get_password() {
+        Ok(json) => serde_json::from_str(&json).map(Some).map_err(|e| e.to_string()),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+pub fn save_credentials<T: Serialize>(kind: DestinationKind, credentials: &T) -> Result<(), String> {
+    let json = serde_json::to_string(credentials).map_err(|e| e.to_string())?;
+    keyring::Entry::new(KEYRING_SERVICE, &kind.keyring_entry())
+        .and_then(|entry| entry.set_password(&json))
+        .map_err(|e| e.to_string())
+}
+
+fn delete_credentials(kind: DestinationKind) -> Result<(), String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, &kind.keyring_entry()).map_err(|e| e.to_string())?;
+    match entry.delete_password() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn is_connected(kind: DestinationKind) -> bool {
+    keyring::Entry::new(KEYRING_SERVICE, &kind.keyring_entry())
+        .and_then(|entry| entry.get_password())
+        .is_ok()
+}
+
+// 上传结果，通过 destination-upload 事件发给前端
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct UploadStatus {
+    provider: DestinationKind,
+    folder: String,
+    file_name: String,
+    category_id: String,
+    success: bool,
+    error: Option<String>,
+}
+
+/// 文件被整理到设置了远程目标的分类后，在后台上传移动后的文件；失败只记录，不影响本地整理
+pub fn on_file_organized(app: &AppHandle, event: &FileOrganizedEvent) {
+    let Ok(config) = Config::load() else {
+        return;
+    };
+    let Some(destination) = config.destination_for(&event.category_id).cloned() else {
+        return;
+    };
+    let Some(kind) = DestinationKind::parse(&destination.provider) else {
+        log::warn!("Unknown destination provider for category {}: {}", event.category_id, destination.provider);
+        return;
+    };
+    // 移动的是符号链接时不上传，不读取链接指向的内容
+    let is_link = std::fs::symlink_metadata(&event.moved_to_path)
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_link {
+        log::info!("Skipping upload of symbolic link {}", event.moved_to_path);
+        return;
+    }
+
+    // 先记入队列，上传途中退出应用时下次启动继续
+    let upload = PendingUpload::new(kind, &destination.folder, event.moved_to_path.clone().into(), &event.category_id);
+    upload.save_progress();
+    tauri::async_runtime::spawn(run_upload(app.clone(), upload));
+}
+
+/// 启动时继续上次没有完成的上传，超过 7 天的放弃
+pub fn resume_pending(app: &AppHandle) {
+    for upload in queue::list() {
+        if upload.is_expired() {
+            log::warn!("Giving up upload of {} to {}", upload.file.display(), upload.provider.as_str());
+            queue::remove(&upload.id);
+            continue;
+        }
+        tauri::async_runtime::spawn(run_upload(app.clone(), upload));
+    }
+}
+
+async fn run_upload(app: AppHandle, mut upload: PendingUpload) {
+    let provider = provider(upload.provider);
+    let mut attempt = 1;
+    let result = loop {
+        // 文件在上传前被移走或删除时不再上传
+        if !upload.file.is_file() {
+            log::warn!("Skipping upload of {}: file no longer exists", upload.file.display());
+            queue::remove(&upload.id);
+            return;
+        }
+        match provider.upload(&mut upload).await {
+            Ok(()) => break Ok(()),
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                log::debug!("Upload of {} failed ({}), retry {} of {}", upload.file.display(), e, attempt, MAX_ATTEMPTS - 1);
+                attempt += 1;
+                tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    match &result {
+        Ok(()) => {
+            queue::remove(&upload.id);
+            crate::telemetry::record_feature("destination_upload");
+        }
+        Err(e) => log::error!("Failed to upload {} to {}: {}", upload.file.display(), upload.provider.as_str(), e),
+    }
+    let status = UploadStatus {
+        provider: upload.provider,
+        folder: upload.folder,
+        file_name: upload.file.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default(),
+        category_id: upload.category_id,
+        success: result.is_ok(),
+        error: result.err().map(|e| t_format("destination_upload_failed", &[&e])),
+    };
+    if let Err(e) = app.emit("destination-upload", &status) {
+        eprintln!("Failed to emit destination upload event: {}", e);
+    }
+}
+
+/// 远程目标的连接状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DestinationStatus {
+    pub provider: DestinationKind,
+    // 这个版本是否支持该服务
+    pub configured: bool,
+    pub connected: bool,
+}
+
+fn parse_kind(id: &str) -> Result<DestinationKind, String> {
+    DestinationKind::parse(id).ok_or_else(|| t_format("destination_unknown_provider", &[id]))
+}
+
+// Tauri命令：连接远程目标的账户；OAuth 服务在浏览器中授权，S3 和 WebDAV 使用填写的凭据
+#[tauri::command]
+pub async fn connect_destination(
+    app: AppHandle,
+    provider: String,
+    credentials: Option<serde_json::Value>,
+) -> Result<(), String> {
+    let provider = self::provider(parse_kind(&provider)?);
+    if !provider.is_configured() {
+        return Err(t("destination_not_configured"));
+    }
+    provider.connect(&app, credentials).await?;
+    crate::telemetry::record_feature("destination_connect");
+    Ok(())
+}
+
+// Tauri命令：断开账户并删除钥匙串中的凭据，使用它的分类设置保留
+#[tauri::command]
+pub async fn disconnect_destination(provider: String) -> Result<(), String> {
+    delete_credentials(parse_kind(&provider)?)
+}
+
+// Tauri命令：获取各远程目标的连接状态
+#[tauri::command]
+pub async fn get_destination_status() -> Result<Vec<DestinationStatus>, String> {
+    Ok(DestinationKind::ALL
+        .into_iter()
+        .map(|kind| DestinationStatus {
+            provider: kind,
+            configured: provider(kind).is_configured(),
+            connected: is_connected(kind),
+        })
+        .collect())
+}
+
+// Tauri命令：设置或清除分类的远程目标
+#[tauri::command]
+pub async fn set_category_destination(
+    category_id: String,
+    destination: Option<RemoteDestination>,
+) -> Result<Option<RemoteDestination>, String> {
+    if let Some(destination) = &destination {
+        parse_kind(&destination.provider)?;
+    }
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    config.set_category_destination(&category_id, destination)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(config.destination_for(&category_id).cloned())
+}
+
+// 读取整个文件用于上传
+async fn read_file(file: &Path) -> Result<(String, Vec<u8>), String> {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| t_format("path_not_found", &[&file.to_string_lossy()]))?;
+    let bytes = tokio::fs::read(file).await.map_err(|e| e.to_string())?;
+    Ok((name, bytes))
+}
+
+// 非 2xx 响应转为包含状态码和响应内容的错误
+async fn check_response(response: reqwest::Response) -> Result<reqwest::Response, String> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    Err(format!("HTTP {}: {}", status, body))
+}
+
+// 对远程路径的每一段做百分号编码，保留分隔用的 /
+fn encode_path(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            segment
+                .bytes()
+                .map(|byte| match byte {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+                    _ => format!("%{:02X}", byte),
+                })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// 远程文件夹和文件名拼成以 / 开头的路径
+fn remote_path(folder: &str, name: &str) -> String {
+    if folder.is_empty() {
+        format!("/{}", name)
+    } else {
+        format!("/{}/{}", folder, name)
+    }
+}
+
+// 解析 S3、WebDAV 等需要用户填写的凭据
+fn parse_credentials<T: DeserializeOwned>(credentials: Option<serde_json::Value>) -> Result<T, String> {
+    let credentials = credentials.ok_or_else(|| t("destination_credentials_required"))?;
+    serde_json::from_value(credentials).map_err(|e| t_format("destination_invalid_credentials", &[&e.to_string()]))
+}
+
+// 重名时使用的文件名，例如 "receipt (1).pdf"
+fn numbered_name(name: &str, number: u32) -> String {
+    let path = Path::new(name);
+    match (path.file_stem(), path.extension()) {
+        (Some(stem), Some(extension)) => format!("{} ({}).{}", stem.to_string_lossy(), number, extension.to_string_lossy()),
+        _ => format!("{} ({})", name, number),
+    }
+}
@@ -0,0 +1,191 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Url};
+use tokio::net::TcpListener;
+
+use super::DestinationKind;
+use crate::i18n::{t, t_format};
+use crate::loopback;
+
+// 浏览器中授权最多等待 5 分钟
+const AUTHORIZE_TIMEOUT_SECS: u64 = 5 * 60;
+const CALLBACK_PATH: &str = "/oauth/callback";
+// 访问令牌到期前这么久就刷新，避免上传途中过期
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// 提供方的 OAuth 应用信息（授权码 + PKCE，桌面应用不保存客户端密钥）
+pub struct OAuthClient {
+    pub authorize_url: &'static str,
+    pub token_url: &'static str,
+    pub client_id: Option<&'static str>,
+    // Google 的桌面应用仍需要客户端密钥，但它不被视为机密
+    pub client_secret: Option<&'static str>,
+    pub scopes: &'static str,
+    // 提供方需要的额外授权参数，例如获取刷新令牌
+    pub extra_params: &'static [(&'static str, &'static str)],
+}
+
+/// 保存在系统钥匙串中的令牌
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    // 访问令牌的过期时间（Unix 秒）
+    pub expires_at: Option<i64>,
+}
+
+impl OAuthTokens {
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| chrono::Utc::now().timestamp() + REFRESH_MARGIN_SECS >= expires_at)
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+impl TokenResponse {
+    fn into_tokens(self, previous_refresh_token: Option<String>) -> OAuthTokens {
+        OAuthTokens {
+            access_token: self.access_token,
+            // 刷新时提供方不一定返回新的刷新令牌
+            refresh_token: self.refresh_token.or(previous_refresh_token),
+            expires_at: self.expires_in.map(|secs| chrono::Utc::now().timestamp() + secs),
+        }
+    }
+}
+
+// 在浏览器中完成授权：打开授权页，在本机随机端口上等待回调，然后用授权码换取令牌
+async fn authorize(app: &AppHandle, client: &OAuthClient) -> Result<OAuthTokens, String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let client_id = client.client_id.ok_or_else(|| t("destination_not_configured"))?;
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await.map_err(|e| e.to_string())?;
+    let redirect_uri = format!("http://127.0.0.1:{}{}", listener.local_addr().map_err(|e| e.to_string())?.port(), CALLBACK_PATH);
+
+    let state = uuid::Uuid::new_v4().simple().to_string();
+    let verifier = format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple());
+    let challenge = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+
+    let mut url = Url::parse(client.authorize_url).map_err(|e| e.to_string())?;
+    url.query_pairs_mut()
+        .append_pair("client_id", client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", client.scopes)
+        .append_pair("state", &state)
+        .append_pair("code_challenge", &challenge)
+        .append_pair("code_challenge_method", "S256")
+        .extend_pairs(client.extra_params.iter());
+    app.opener()
+        .open_url(url.as_str(), None::<String>)
+        .map_err(|e| t_format("destination_connect_failed", &[&e.to_string()]))?;
+
+    let code = tokio::time::timeout(
+        tokio::time::Duration::from_secs(AUTHORIZE_TIMEOUT_SECS),
+        wait_for_code(&listener, &state),
+    )
+    .await
+    .map_err(|_| t("destination_connect_timeout"))??;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code".to_string()),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client_id.to_string()),
+        ("code_verifier", verifier),
+    ];
+    if let Some(secret) = client.client_secret {
+        form.push(("client_secret", secret.to_string()));
+    }
+    request_tokens(client, &form, None).await
+}
+
+/// 授权并把令牌保存到钥匙串
+pub async fn connect(app: &AppHandle, kind: DestinationKind, client: &OAuthClient) -> Result<(), String> {
+    let tokens = authorize(app, client).await?;
+    super::save_credentials(kind, &tokens)
+}
+
+/// 读取钥匙串中的令牌，过期时刷新并保存，返回可用的访问令牌
+pub async fn access_token(kind: DestinationKind, client: &OAuthClient) -> Result<String, String> {
+    let tokens = super::load_credentials::<OAuthTokens>(kind)?.ok_or_else(|| t("destination_not_connected"))?;
+    let (tokens, refreshed) = fresh_tokens(client, tokens).await?;
+    if refreshed {
+        super::save_credentials(kind, &tokens)?;
+    }
+    Ok(tokens.access_token)
+}
+
+// 访问令牌未过期时原样返回，否则用刷新令牌换新的；第二个值表示令牌是否有变化
+async fn fresh_tokens(client: &OAuthClient, tokens: OAuthTokens) -> Result<(OAuthTokens, bool), String> {
+    if !tokens.is_expired() {
+        return Ok((tokens, false));
+    }
+    let refresh_token = tokens.refresh_token.clone().ok_or_else(|| t("destination_not_connected"))?;
+    let client_id = client.client_id.ok_or_else(|| t("destination_not_configured"))?;
+
+    let mut form = vec![
+        ("grant_type", "refresh_token".to_string()),
+        ("refresh_token", refresh_token.clone()),
+        ("client_id", client_id.to_string()),
+    ];
+    if let Some(secret) = client.client_secret {
+        form.push(("client_secret", secret.to_string()));
+    }
+    Ok((request_tokens(client, &form, Some(refresh_token)).await?, true))
+}
+
+async fn request_tokens(
+    client: &OAuthClient,
+    form: &[(&str, String)],
+    previous_refresh_token: Option<String>,
+) -> Result<OAuthTokens, String> {
+    let response = crate::http::client()
+        .post(client.token_url)
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("HTTP {}: {}", status, body));
+    }
+    let tokens = response.json::<TokenResponse>().await.map_err(|e| e.to_string())?;
+    Ok(tokens.into_tokens(previous_refresh_token))
+}
+
+// 等待带正确 state 的回调，返回授权码；用户拒绝授权时返回错误
+async fn wait_for_code(listener: &TcpListener, state: &str) -> Result<String, String> {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            continue;
+        };
+        let Some(url) = loopback::read_target(&mut stream).await else {
+            continue;
+        };
+        let query = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.to_string());
+        if url.path() != CALLBACK_PATH || query("state").as_deref() != Some(state) {
+            loopback::respond(&mut stream, "404 Not Found", loopback::HTML_CONTENT_TYPE, "").await;
+            continue;
+        }
+
+        let result = match (query("code"), query("error")) {
+            (Some(code), _) => Ok(code),
+            (None, error) => Err(t_format("destination_connect_failed", &[&error.unwrap_or_default()])),
+        };
+        let page = match &result {
+            Ok(_) => loopback::html_page(&t("destination_connected_title"), &t("destination_connected_body")),
+            Err(message) => loopback::html_page(&t("destination_connect_failed_title"), message),
+        };
+        loopback::respond(&mut stream, "200 OK", loopback::HTML_CONTENT_TYPE, &page).await;
+        return result;
+    }
+}
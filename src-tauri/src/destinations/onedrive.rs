@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use tauri::AppHandle;
+
+use super::oauth::{self, OAuthClient};
+use super::queue::PendingUpload;
+use super::{check_response, encode_path, read_file, remote_path, DestinationKind, DestinationProvider};
+
+const DRIVE_URL: &str = "https://graph.microsoft.com/v1.0/me/drive/root:";
+
+// offline_access 才会返回刷新令牌；common 同时支持个人账户和工作或学校账户
+const CLIENT: OAuthClient = OAuthClient {
+    authorize_url: "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+    token_url: "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+    client_id: option_env!("FILESORTIFY_MICROSOFT_CLIENT_ID"),
+    client_secret: None,
+    scopes: "Files.ReadWrite offline_access",
+    extra_params: &[],
+};
+
+pub struct OneDriveProvider;
+
+#[async_trait]
+impl DestinationProvider for OneDriveProvider {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::OneDrive
+    }
+
+    fn is_configured(&self) -> bool {
+        CLIENT.client_id.is_some()
+    }
+
+    async fn connect(&self, app: &AppHandle, _credentials: Option<serde_json::Value>) -> Result<(), String> {
+        oauth::connect(app, self.kind(), &CLIENT).await
+    }
+
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String> {
+        let token = oauth::access_token(self.kind(), &CLIENT).await?;
+        let (name, bytes) = read_file(&upload.file).await?;
+        // 路径中不存在的文件夹由 OneDrive 自动创建
+        let url = format!(
+            "{}{}:/content?@microsoft.graph.conflictBehavior=rename",
+            DRIVE_URL,
+            encode_path(&remote_path(&upload.folder, &name))
+        );
+
+        let response = crate::http::client()
+            .put(url)
+            .bearer_auth(&token)
+            .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        check_response(response).await.map(|_| ())
+    }
+}
@@ -0,0 +1,126 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use super::DestinationKind;
+
+// 一直没有完成的上传（例如账户已断开）超过这个时间后放弃
+const MAX_AGE_DAYS: i64 = 7;
+
+lazy_static! {
+    // 读写 pending_uploads.json 时加锁，避免并发上传互相覆盖进度
+    static ref QUEUE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// 分块上传中已经完成的部分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedPart {
+    pub number: u32,
+    // S3 合并分块时需要的 ETag，WebDAV 分块为空
+    #[serde(default)]
+    pub etag: String,
+}
+
+/// 可续传上传的进度，由提供方在每个分块完成后更新
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadProgress {
+    // 第一次上传时确定的远程路径，续传时沿用，避免重名改名后上传到另一个文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_path: Option<String>,
+    // 服务端的分块上传 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<String>,
+    #[serde(default)]
+    pub parts: Vec<UploadedPart>,
+}
+
+/// 等待上传或上传中断的文件，保存在应用数据目录的 pending_uploads.json 中，应用重新启动后继续
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingUpload {
+    pub id: String,
+    pub provider: DestinationKind,
+    pub folder: String,
+    pub file: PathBuf,
+    pub category_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub progress: UploadProgress,
+}
+
+impl PendingUpload {
+    pub fn new(provider: DestinationKind, folder: &str, file: PathBuf, category_id: &str) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            provider,
+            folder: folder.to_string(),
+            file,
+            category_id: category_id.to_string(),
+            created_at: chrono::Utc::now(),
+            progress: UploadProgress::default(),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        chrono::Utc::now() - self.created_at > chrono::Duration::days(MAX_AGE_DAYS)
+    }
+
+    /// 保存当前进度，上传中断后从这里继续
+    pub fn save_progress(&self) {
+        let _guard = QUEUE_LOCK.lock().unwrap();
+        let mut uploads = read();
+        match uploads.iter_mut().find(|upload| upload.id == self.id) {
+            Some(upload) => *upload = self.clone(),
+            None => uploads.push(self.clone()),
+        }
+        write(&uploads);
+    }
+}
+
+pub fn list() -> Vec<PendingUpload> {
+    let _guard = QUEUE_LOCK.lock().unwrap();
+    read()
+}
+
+pub fn remove(id: &str) {
+    let _guard = QUEUE_LOCK.lock().unwrap();
+    let mut uploads = read();
+    uploads.retain(|upload| upload.id != id);
+    write(&uploads);
+}
+
+fn queue_path() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("pending_uploads.json"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_pending_uploads.json"))
+}
+
+fn read() -> Vec<PendingUpload> {
+    fs::read_to_string(queue_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write(uploads: &[PendingUpload]) {
+    let path = queue_path();
+    if uploads.is_empty() {
+        let _ = fs::remove_file(&path);
+        return;
+    }
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    match serde_json::to_string_pretty(uploads) {
+        Ok(content) => {
+            if let Err(e) = fs::write(&path, content) {
+                log::warn!("Failed to save pending uploads: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Failed to serialize pending uploads: {}", e),
+    }
+}
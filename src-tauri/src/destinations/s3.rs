@@ -0,0 +1,274 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::SeekFrom;
+use tauri::{AppHandle, Url};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::queue::{PendingUpload, UploadedPart};
+use super::{check_response, encode_path, numbered_name, parse_credentials, remote_path, DestinationKind, DestinationProvider};
+use crate::i18n::{t, t_format};
+
+// 超过这个大小的文件分块上传，每块完成后记录进度，中断后从未完成的块继续
+const PART_SIZE: u64 = 8 * 1024 * 1024;
+const MAX_NAME_ATTEMPTS: u32 = 100;
+
+/// S3 兼容服务的访问凭据，保存在系统钥匙串中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct S3Credentials {
+    // 服务地址，例如 "https://minio.example.com:9000"、"https://s3.eu-central-1.amazonaws.com"
+    pub endpoint: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub bucket: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+pub struct S3Provider;
+
+#[async_trait]
+impl DestinationProvider for S3Provider {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::S3
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    async fn connect(&self, _app: &AppHandle, credentials: Option<serde_json::Value>) -> Result<(), String> {
+        let mut credentials: S3Credentials = parse_credentials(credentials)?;
+        credentials.endpoint = credentials.endpoint.trim().trim_end_matches('/').to_string();
+        credentials.bucket = credentials.bucket.trim().to_string();
+        if credentials.region.trim().is_empty() {
+            credentials.region = default_region();
+        }
+
+        // 保存前确认存储桶可以访问
+        let client = S3Client::new(credentials.clone())?;
+        let response = client.send(Method::HEAD, None, &[], Vec::new()).await?;
+        check_response(response)
+            .await
+            .map_err(|e| t_format("destination_connect_failed", &[&e]))?;
+        super::save_credentials(self.kind(), &credentials)
+    }
+
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String> {
+        let credentials = super::load_credentials::<S3Credentials>(self.kind())?.ok_or_else(|| t("destination_not_connected"))?;
+        let client = S3Client::new(credentials)?;
+        let size = tokio::fs::metadata(&upload.file).await.map_err(|e| e.to_string())?.len();
+
+        let key = match upload.progress.remote_path.clone() {
+            Some(key) => key,
+            None => {
+                let key = client.available_key(upload).await?;
+                upload.progress.remote_path = Some(key.clone());
+                upload.save_progress();
+                key
+            }
+        };
+
+        if size <= PART_SIZE {
+            let bytes = tokio::fs::read(&upload.file).await.map_err(|e| e.to_string())?;
+            let response = client.send(Method::PUT, Some(&key), &[], bytes).await?;
+            return check_response(response).await.map(|_| ());
+        }
+        client.upload_parts(upload, &key, size).await
+    }
+}
+
+struct S3Client {
+    credentials: S3Credentials,
+    scheme: String,
+    // 带端口的主机名，参与签名
+    host: String,
+    // 使用路径风格地址（endpoint/bucket/key），MinIO 等自建服务通常只支持这种
+    bucket_path: String,
+}
+
+impl S3Client {
+    fn new(credentials: S3Credentials) -> Result<Self, String> {
+        let endpoint = Url::parse(&credentials.endpoint).map_err(|e| t_format("destination_invalid_credentials", &[&e.to_string()]))?;
+        let host = endpoint
+            .host_str()
+            .ok_or_else(|| t_format("destination_invalid_credentials", &[&credentials.endpoint]))?;
+        let host = match endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host.to_string(),
+        };
+        let bucket_path = format!("{}/{}", endpoint.path().trim_end_matches('/'), encode_path(&credentials.bucket));
+        Ok(Self {
+            scheme: endpoint.scheme().to_string(),
+            host,
+            bucket_path,
+            credentials,
+        })
+    }
+
+    // 选择远程没有同名对象的键，重名时加数字后缀
+    async fn available_key(&self, upload: &PendingUpload) -> Result<String, String> {
+        let name = upload
+            .file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for number in 0..MAX_NAME_ATTEMPTS {
+            let candidate = if number == 0 { name.clone() } else { numbered_name(&name, number) };
+            let key = remote_path(&upload.folder, &candidate).trim_start_matches('/').to_string();
+            let response = self.send(Method::HEAD, Some(&key), &[], Vec::new()).await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                return Ok(key);
+            }
+            check_response(response).await?;
+        }
+        Err(format!("Too many files named {}", name))
+    }
+
+    // 分块上传；已经完成的块记录在 upload.progress 中，续传时跳过
+    async fn upload_parts(&self, upload: &mut PendingUpload, key: &str, size: u64) -> Result<(), String> {
+        let upload_id = match upload.progress.upload_id.clone() {
+            Some(upload_id) => upload_id,
+            None => {
+                let response = self.send(Method::POST, Some(key), &[("uploads", "")], Vec::new()).await?;
+                let body = check_response(response).await?.text().await.map_err(|e| e.to_string())?;
+                let upload_id = xml_value(&body, "UploadId").ok_or_else(|| "Missing UploadId".to_string())?;
+                upload.progress.upload_id = Some(upload_id.clone());
+                upload.progress.parts.clear();
+                upload.save_progress();
+                upload_id
+            }
+        };
+
+        let mut file = tokio::fs::File::open(&upload.file).await.map_err(|e| e.to_string())?;
+        let part_count = size.div_ceil(PART_SIZE) as u32;
+        for number in 1..=part_count {
+            if upload.progress.parts.iter().any(|part| part.number == number) {
+                continue;
+            }
+            let offset = (number as u64 - 1) * PART_SIZE;
+            let mut buffer = vec![0u8; PART_SIZE.min(size - offset) as usize];
+            file.seek(SeekFrom::Start(offset)).await.map_err(|e| e.to_string())?;
+            file.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+
+            let part_number = number.to_string();
+            let response = self
+                .send(Method::PUT, Some(key), &[("partNumber", part_number.as_str()), ("uploadId", upload_id.as_str())], buffer)
+                .await?;
+            if response.status() == StatusCode::NOT_FOUND {
+                // 服务端已经清除了这次分块上传（例如过期），下次重试时从头开始
+                upload.progress.upload_id = None;
+                upload.progress.parts.clear();
+                upload.save_progress();
+            }
+            let response = check_response(response).await?;
+            let etag = response
+                .headers()
+                .get(reqwest::header::ETAG)
+                .and_then(|value| value.to_str().ok())
+                .ok_or_else(|| "Missing ETag".to_string())?
+                .to_string();
+            upload.progress.parts.push(UploadedPart { number, etag });
+            upload.save_progress();
+        }
+
+        let mut parts = upload.progress.parts.clone();
+        parts.sort_by_key(|part| part.number);
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+                .iter()
+                .map(|part| format!("<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>", part.number, part.etag))
+                .collect::<String>()
+        );
+        let response = self.send(Method::POST, Some(key), &[("uploadId", upload_id.as_str())], body.into_bytes()).await?;
+        let body = check_response(response).await?.text().await.map_err(|e| e.to_string())?;
+        // 合并失败时 S3 仍可能返回 200，错误在响应内容中
+        if body.contains("<Error>") {
+            return Err(xml_value(&body, "Message").unwrap_or(body));
+        }
+        Ok(())
+    }
+
+    // 发送带 AWS Signature Version 4 签名的请求；key 为空时访问存储桶本身
+    async fn send(&self, method: Method, key: Option<&str>, query: &[(&str, &str)], body: Vec<u8>) -> Result<reqwest::Response, String> {
+        let path = match key {
+            Some(key) => format!("{}/{}", self.bucket_path, encode_path(key)),
+            None => self.bucket_path.clone(),
+        };
+        let mut query = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", encode_path(name), encode_path(value)))
+            .collect::<Vec<_>>();
+        query.sort();
+        let query = query.join("&");
+
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex(&Sha256::digest(&body));
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, self.host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.credentials.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex(&Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = [self.credentials.region.as_str(), "s3", "aws4_request"]
+            .iter()
+            .fold(hmac(format!("AWS4{}", self.credentials.secret_access_key).as_bytes(), date.as_bytes()), |key, part| {
+                hmac(&key, part.as_bytes())
+            });
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.credentials.access_key_id,
+            scope,
+            signed_headers,
+            hex(&hmac(&signing_key, string_to_sign.as_bytes()))
+        );
+
+        let url = if query.is_empty() {
+            format!("{}://{}{}", self.scheme, self.host, path)
+        } else {
+            format!("{}://{}{}?{}", self.scheme, self.host, path, query)
+        };
+        crate::http::client()
+            .request(method, url)
+            .header(reqwest::header::AUTHORIZATION, authorization)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// 读取 XML 响应中第一个指定元素的文本
+fn xml_value(xml: &str, element: &str) -> Option<String> {
+    let start = xml.find(&format!("<{}>", element))? + element.len() + 2;
+    let end = start + xml[start..].find(&format!("</{}>", element))?;
+    Some(xml[start..end].to_string())
+}
@@ -0,0 +1,212 @@
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::io::SeekFrom;
+use tauri::AppHandle;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::queue::{PendingUpload, UploadedPart};
+use super::{check_response, encode_path, numbered_name, parse_credentials, remote_path, DestinationKind, DestinationProvider};
+use crate::i18n::{t, t_format};
+
+// Nextcloud 上超过这个大小的文件分块上传，每块完成后记录进度，中断后从未完成的块继续
+const CHUNK_SIZE: u64 = 10 * 1024 * 1024;
+const MAX_NAME_ATTEMPTS: u32 = 100;
+// Nextcloud 的文件地址，分块上传使用同一用户的 uploads 目录
+const NEXTCLOUD_FILES_PATH: &str = "/remote.php/dav/files/";
+
+/// WebDAV 服务器的访问凭据，保存在系统钥匙串中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebDavCredentials {
+    // 上传的根文件夹地址，例如 "https://cloud.example.com/remote.php/dav/files/alice"
+    pub url: String,
+    pub username: String,
+    pub password: String,
+}
+
+pub struct WebDavProvider;
+
+#[async_trait]
+impl DestinationProvider for WebDavProvider {
+    fn kind(&self) -> DestinationKind {
+        DestinationKind::WebDav
+    }
+
+    fn is_configured(&self) -> bool {
+        true
+    }
+
+    async fn connect(&self, _app: &AppHandle, credentials: Option<serde_json::Value>) -> Result<(), String> {
+        let mut credentials: WebDavCredentials = parse_credentials(credentials)?;
+        credentials.url = credentials.url.trim().trim_end_matches('/').to_string();
+        if !credentials.url.starts_with("https://") && !credentials.url.starts_with("http://") {
+            return Err(t_format("destination_invalid_credentials", &[&credentials.url]));
+        }
+
+        // 保存前确认根文件夹可以访问
+        let response = request(&credentials, "PROPFIND", &credentials.url)
+            .header("Depth", "0")
+            .send()
+            .await
+            .map_err(|e| t_format("destination_connect_failed", &[&e.to_string()]))?;
+        check_response(response)
+            .await
+            .map_err(|e| t_format("destination_connect_failed", &[&e]))?;
+        super::save_credentials(self.kind(), &credentials)
+    }
+
+    async fn upload(&self, upload: &mut PendingUpload) -> Result<(), String> {
+        let credentials =
+            super::load_credentials::<WebDavCredentials>(self.kind())?.ok_or_else(|| t("destination_not_connected"))?;
+        let size = tokio::fs::metadata(&upload.file).await.map_err(|e| e.to_string())?.len();
+
+        let path = match upload.progress.remote_path.clone() {
+            Some(path) => path,
+            None => {
+                create_folders(&credentials, &upload.folder).await?;
+                let path = available_path(&credentials, upload).await?;
+                upload.progress.remote_path = Some(path.clone());
+                upload.save_progress();
+                path
+            }
+        };
+        let target = format!("{}{}", credentials.url, encode_path(&path));
+
+        if size > CHUNK_SIZE {
+            if let Some(uploads_url) = nextcloud_uploads_url(&credentials.url) {
+                return upload_chunks(&credentials, upload, &uploads_url, &target, size).await;
+            }
+        }
+        // 其他服务器没有通用的续传方式，整个文件重新上传
+        let bytes = tokio::fs::read(&upload.file).await.map_err(|e| e.to_string())?;
+        let response = request(&credentials, "PUT", &target)
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        check_response(response).await.map(|_| ())
+    }
+}
+
+fn request(credentials: &WebDavCredentials, method: &str, url: &str) -> RequestBuilder {
+    let method = Method::from_bytes(method.as_bytes()).unwrap_or(Method::GET);
+    crate::http::client()
+        .request(method, url)
+        .basic_auth(&credentials.username, Some(&credentials.password))
+}
+
+// 逐级创建远程文件夹，已经存在的跳过
+async fn create_folders(credentials: &WebDavCredentials, folder: &str) -> Result<(), String> {
+    let mut path = String::new();
+    for name in folder.split('/').filter(|name| !name.is_empty()) {
+        path.push('/');
+        path.push_str(name);
+        let url = format!("{}{}", credentials.url, encode_path(&path));
+        let response = request(credentials, "MKCOL", &url).send().await.map_err(|e| e.to_string())?;
+        if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+            check_response(response).await?;
+        }
+    }
+    Ok(())
+}
+
+// 选择远程没有同名文件的路径，重名时加数字后缀
+async fn available_path(credentials: &WebDavCredentials, upload: &PendingUpload) -> Result<String, String> {
+    let name = upload
+        .file
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    for number in 0..MAX_NAME_ATTEMPTS {
+        let candidate = if number == 0 { name.clone() } else { numbered_name(&name, number) };
+        let path = remote_path(&upload.folder, &candidate);
+        let url = format!("{}{}", credentials.url, encode_path(&path));
+        let response = request(credentials, "HEAD", &url).send().await.map_err(|e| e.to_string())?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(path);
+        }
+        check_response(response).await?;
+    }
+    Err(format!("Too many files named {}", name))
+}
+
+// Nextcloud 的分块上传目录，例如 ".../remote.php/dav/uploads/alice"；不是 Nextcloud 地址时返回 None
+fn nextcloud_uploads_url(url: &str) -> Option<String> {
+    let index = url.find(NEXTCLOUD_FILES_PATH)?;
+    let user = url[index + NEXTCLOUD_FILES_PATH.len()..].split('/').next().filter(|user| !user.is_empty())?;
+    Some(format!("{}/remote.php/dav/uploads/{}", &url[..index], user))
+}
+
+// Nextcloud 分块上传：在 uploads 目录中逐块上传，完成后合并到目标路径；已经完成的块记录在 upload.progress 中
+async fn upload_chunks(
+    credentials: &WebDavCredentials,
+    upload: &mut PendingUpload,
+    uploads_url: &str,
+    target: &str,
+    size: u64,
+) -> Result<(), String> {
+    let upload_id = match upload.progress.upload_id.clone() {
+        Some(upload_id) => upload_id,
+        None => {
+            let upload_id = format!("filesortify-{}", uuid::Uuid::new_v4().simple());
+            upload.progress.upload_id = Some(upload_id.clone());
+            upload.progress.parts.clear();
+            upload.save_progress();
+            upload_id
+        }
+    };
+    let chunks_url = format!("{}/{}", uploads_url, upload_id);
+    let total_length = size.to_string();
+
+    let response = request(credentials, "MKCOL", &chunks_url)
+        .header("Destination", target)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    // 续传时目录已经存在
+    if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+        check_response(response).await?;
+    }
+
+    let mut file = tokio::fs::File::open(&upload.file).await.map_err(|e| e.to_string())?;
+    let chunk_count = size.div_ceil(CHUNK_SIZE) as u32;
+    for number in 1..=chunk_count {
+        if upload.progress.parts.iter().any(|part| part.number == number) {
+            continue;
+        }
+        let offset = (number as u64 - 1) * CHUNK_SIZE;
+        let mut buffer = vec![0u8; CHUNK_SIZE.min(size - offset) as usize];
+        file.seek(SeekFrom::Start(offset)).await.map_err(|e| e.to_string())?;
+        file.read_exact(&mut buffer).await.map_err(|e| e.to_string())?;
+
+        // 块名需要按顺序排列，Nextcloud 要求为 1 到 10000 的数字
+        let response = request(credentials, "PUT", &format!("{}/{:05}", chunks_url, number))
+            .header("Destination", target)
+            .header("OC-Total-Length", &total_length)
+            .body(buffer)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if response.status() == StatusCode::NOT_FOUND {
+            // 服务端已经清除了上传目录（例如过期），下次重试时从头开始
+            upload.progress.upload_id = None;
+            upload.progress.parts.clear();
+            upload.save_progress();
+        }
+        check_response(response).await?;
+        upload.progress.parts.push(UploadedPart {
+            number,
+            etag: String::new(),
+        });
+        upload.save_progress();
+    }
+
+    let response = request(credentials, "MOVE", &format!("{}/.file", chunks_url))
+        .header("Destination", target)
+        .header("OC-Total-Length", &total_length)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    check_response(response).await.map(|_| ())
+}
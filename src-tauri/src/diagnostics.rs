@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use crate::config::Config;
+use crate::settings::GeneralSettings;
+
+// 只打包最近几天的日志，避免诊断包过大
+const LOG_MAX_AGE: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+const LOG_MAX_FILES: usize = 10;
+
+/// 诊断包中的环境信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SystemInfo {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub language: String,
+    pub created_at: String,
+}
+
+/// 某个路径的监控状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonitoringStatus {
+    pub path: String,
+    pub monitoring: bool,
+    pub files_organized: u64,
+    pub last_organized: Option<String>,
+    pub monitoring_since: Option<String>,
+}
+
+pub fn default_diagnostics_dir() -> PathBuf {
+    crate::backup::app_data_dir().join("diagnostics")
+}
+
+/// 把日志、脱敏后的配置和设置、版本信息以及监控状态打包成一个 zip
+pub fn export(
+    destination: Option<PathBuf>,
+    app_version: &str,
+    monitored_folders: &[String],
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let destination = match destination {
+        Some(path) => path,
+        None => {
+            let file_name = format!(
+                "filesortify-diagnostics-{}.zip",
+                chrono::Local::now().format("%Y%m%d-%H%M%S")
+            );
+            default_diagnostics_dir().join(file_name)
+        }
+    };
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let system = SystemInfo {
+        app_version: app_version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        language: crate::i18n::get_language().code().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    // 配置或设置读取失败时记录错误信息，诊断包本身仍然生成
+    let config = Config::load();
+    let config_value = match &config {
+        Ok(config) => sanitize(serde_json::to_value(config)?),
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    };
+    let settings_value = match GeneralSettings::load() {
+        Ok(mut settings) => {
+            // 代理地址中可能带有用户名和密码
+            if settings.proxy.url.is_some() {
+                settings.proxy.url = Some("<redacted>".to_string());
+            }
+            sanitize(serde_json::to_value(&settings)?)
+        }
+        Err(e) => serde_json::json!({ "error": e.to_string() }),
+    };
+    let monitoring: Vec<MonitoringStatus> = config
+        .as_ref()
+        .map(|config| {
+            config
+                .list_paths()
+                .into_iter()
+                .map(|path| MonitoringStatus {
+                    monitoring: monitored_folders
+                        .iter()
+                        .any(|folder| std::path::Path::new(folder) == std::path::Path::new(&path.path)),
+                    path: redact(&path.path),
+                    files_organized: path.stats.files_organized,
+                    last_organized: path.stats.last_organized,
+                    monitoring_since: path.stats.monitoring_since,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut writer = zip::ZipWriter::new(File::create(&destination)?);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    writer.start_file("system.json", options)?;
+    writer.write_all(serde_json::to_string_pretty(&system)?.as_bytes())?;
+    writer.start_file("config.json", options)?;
+    writer.write_all(serde_json::to_string_pretty(&config_value)?.as_bytes())?;
+    writer.start_file("settings.json", options)?;
+    writer.write_all(serde_json::to_string_pretty(&settings_value)?.as_bytes())?;
+    writer.start_file("monitoring.json", options)?;
+    writer.write_all(serde_json::to_string_pretty(&monitoring)?.as_bytes())?;
+
+    for path in recent_logs() {
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else { continue };
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                writer.start_file(format!("logs/{}", name), options)?;
+                writer.write_all(redact(&content).as_bytes())?;
+            }
+            Err(e) => log::warn!("Failed to read log file {}: {}", path.display(), e),
+        }
+    }
+
+    writer.finish()?;
+    log::info!("Diagnostics exported to {}", destination.display());
+    Ok(destination)
+}
+
+// 最近修改过的日志文件，按时间从新到旧
+fn recent_logs() -> Vec<PathBuf> {
+    let Some(dir) = crate::logging::log_dir() else { return Vec::new() };
+    let Ok(entries) = fs::read_dir(&dir) else { return Vec::new() };
+    let now = SystemTime::now();
+
+    let mut files: Vec<(PathBuf, SystemTime)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("log"))
+        .filter_map(|entry| Some((entry.path(), entry.metadata().ok()?.modified().ok()?)))
+        .filter(|(_, modified)| now.duration_since(*modified).map(|age| age <= LOG_MAX_AGE).unwrap_or(true))
+        .collect();
+    files.sort_by(|a, b| b.1.cmp(&a.1));
+    files.into_iter().take(LOG_MAX_FILES).map(|(path, _)| path).collect()
+}
+
+// 把用户主目录替换成 ~，避免诊断包里出现用户名
+fn redact(text: &str) -> String {
+    match dirs::home_dir().map(|home| home.to_string_lossy().to_string()) {
+        Some(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+fn sanitize(value: Value) -> Value {
+    match value {
+        Value::String(s) => Value::String(redact(&s)),
+        Value::Array(items) => Value::Array(items.into_iter().map(sanitize).collect()),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (redact(&key), sanitize(value)))
+                .collect(),
+        ),
+        other => other,
+    }
+}
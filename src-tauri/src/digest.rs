@@ -0,0 +1,287 @@
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::{get_language, t, t_format};
+use crate::settings::GeneralSettings;
+use crate::AppState;
+
+// 每小时检查一次是否到了发送时间
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+// 等待启动完成，避免和启动时的其他通知同时出现
+const STARTUP_DELAY_SECS: u64 = 60;
+// 每周一上午 9 点（本地时间）之后发送上一周的汇总，错过时在本周内补发
+const SEND_HOUR: u32 = 9;
+const TOP_CATEGORY_COUNT: usize = 3;
+
+/// 每周整理汇总的设置，默认关闭
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestSettings {
+    pub enabled: bool,
+    // 显示系统通知
+    #[serde(default = "default_notification")]
+    pub notification: bool,
+    // 通过服务端发送汇总邮件的地址，为空时不发送邮件
+    #[serde(default)]
+    pub email: Option<String>,
+}
+
+fn default_notification() -> bool {
+    true
+}
+
+impl Default for DigestSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            notification: default_notification(),
+            email: None,
+        }
+    }
+}
+
+impl DigestSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(email) = self.email() {
+            let valid = email
+                .split_once('@')
+                .map(|(name, domain)| !name.is_empty() && domain.contains('.') && !domain.starts_with('.') && !domain.ends_with('.'))
+                .unwrap_or(false);
+            if !valid || email.contains(char::is_whitespace) {
+                return Err(format!("weekly_digest.email is not a valid email address: {}", email));
+            }
+        }
+        Ok(())
+    }
+
+    fn email(&self) -> Option<&str> {
+        self.email.as_deref().map(str::trim).filter(|email| !email.is_empty())
+    }
+}
+
+/// 分类及其整理的文件数
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryCount {
+    pub category_id: String,
+    pub category: String,
+    pub count: u64,
+}
+
+/// 一周的整理汇总，由整理记录生成
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeeklyDigest {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub files_organized: u64,
+    pub bytes_moved: u64,
+    // 文件数最多的几个分类
+    pub top_categories: Vec<CategoryCount>,
+    // 目标文件夹中已有内容相同的文件
+    pub duplicates_found: u64,
+}
+
+impl WeeklyDigest {
+    pub fn build(period_start: DateTime<Utc>, period_end: DateTime<Utc>) -> Self {
+        let entries: Vec<_> = crate::activity::since(period_start)
+            .into_iter()
+            .filter(|entry| entry.timestamp < period_end)
+            .collect();
+
+        let mut categories: HashMap<String, CategoryCount> = HashMap::new();
+        for entry in &entries {
+            categories
+                .entry(entry.category_id.clone())
+                .or_insert_with(|| CategoryCount {
+                    category_id: entry.category_id.clone(),
+                    category: entry.category.clone(),
+                    count: 0,
+                })
+                .count += 1;
+        }
+        let mut top_categories: Vec<CategoryCount> = categories.into_values().collect();
+        top_categories.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.category.cmp(&b.category)));
+        top_categories.truncate(TOP_CATEGORY_COUNT);
+
+        Self {
+            period_start,
+            period_end,
+            files_organized: entries.len() as u64,
+            bytes_moved: entries.iter().map(|entry| entry.size).sum(),
+            top_categories,
+            duplicates_found: entries.iter().filter(|entry| entry.duplicate).count() as u64,
+        }
+    }
+
+    /// 通知和邮件中的文字
+    pub fn text(&self) -> String {
+        if self.files_organized == 0 {
+            return t("digest_empty");
+        }
+        let mut text = t_format(
+            "digest_body",
+            &[&self.files_organized.to_string(), &format_size(self.bytes_moved), &self.duplicates_found.to_string()],
+        );
+        if !self.top_categories.is_empty() {
+            let categories = self
+                .top_categories
+                .iter()
+                .map(|category| format!("{} ({})", category.category, category.count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            text.push('\n');
+            text.push_str(&t_format("digest_top_categories", &[&categories]));
+        }
+        text
+    }
+}
+
+/// 启动后台任务：开启每周汇总后，每周一发送上一周的汇总
+pub fn start_background_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            check(&app).await;
+        }
+    });
+}
+
+async fn check(app: &AppHandle) {
+    let Ok(settings) = GeneralSettings::load() else {
+        return;
+    };
+    let settings = settings.weekly_digest;
+    if !settings.enabled {
+        return;
+    }
+
+    let period_end = current_period_end();
+    match last_sent() {
+        // 刚开启时从下一周开始发送
+        None => {
+            set_last_sent(period_end);
+            return;
+        }
+        Some(last_sent) if last_sent >= period_end => return,
+        Some(_) => {}
+    }
+
+    // 先记录已发送，发送失败时不在每次检查时重复通知
+    set_last_sent(period_end);
+    let digest = WeeklyDigest::build(period_end - Duration::days(7), period_end);
+    if let Err(e) = deliver(app, &settings, &digest).await {
+        log::error!("Failed to send weekly digest: {}", e);
+    }
+}
+
+// 最近一个已经过去的周一上午 9 点
+fn current_period_end() -> DateTime<Utc> {
+    let now = Local::now();
+    let monday = now.date_naive() - Duration::days(now.weekday().num_days_from_monday() as i64);
+    let send_time = monday
+        .and_hms_opt(SEND_HOUR, 0, 0)
+        .and_then(|time| Local.from_local_datetime(&time).earliest())
+        .unwrap_or(now);
+    let period_end = if send_time > now { send_time - Duration::days(7) } else { send_time };
+    period_end.with_timezone(&Utc)
+}
+
+async fn deliver(app: &AppHandle, settings: &DigestSettings, digest: &WeeklyDigest) -> Result<(), String> {
+    crate::telemetry::record_feature("weekly_digest");
+    if settings.notification {
+        let _ = tauri_plugin_notification::NotificationExt::notification(app)
+            .builder()
+            .title(&t("digest_title"))
+            .body(&digest.text())
+            .show();
+    }
+    if let Some(email) = settings.email() {
+        send_email(app, email, digest).await?;
+    }
+    Ok(())
+}
+
+// 由服务端发送汇总邮件
+async fn send_email(app: &AppHandle, email: &str, digest: &WeeklyDigest) -> Result<(), String> {
+    let (server_url, device_id) = {
+        let state = app.state::<AppState>();
+        let subscription = state.subscription.lock().await;
+        (subscription.webhook_server_url.clone(), subscription.device_id.clone())
+    };
+    let response = crate::http::client()
+        .post(&format!("{}/api/digests", server_url))
+        .json(&serde_json::json!({
+            "deviceId": device_id,
+            "email": email,
+            "language": get_language().code(),
+            "subject": t("digest_title"),
+            "text": digest.text(),
+            "digest": digest,
+        }))
+        .send()
+        .await
+        .map_err(|e| t_format("digest_email_failed", &[&e.to_string()]))?;
+    if !response.status().is_success() {
+        return Err(t_format("digest_email_failed", &[&format!("HTTP {}", response.status())]));
+    }
+    Ok(())
+}
+
+fn last_sent_path() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("digest_last_sent"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_digest_last_sent"))
+}
+
+fn last_sent() -> Option<DateTime<Utc>> {
+    let content = fs::read_to_string(last_sent_path()).ok()?;
+    DateTime::parse_from_rfc3339(content.trim()).ok().map(|time| time.with_timezone(&Utc))
+}
+
+fn set_last_sent(time: DateTime<Utc>) {
+    let path = last_sent_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = fs::write(&path, time.to_rfc3339()) {
+        log::warn!("Failed to save weekly digest state: {}", e);
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+// Tauri命令：获取最近 7 天的整理汇总
+#[tauri::command]
+pub async fn get_weekly_digest() -> Result<WeeklyDigest, String> {
+    let now = Utc::now();
+    Ok(WeeklyDigest::build(now - Duration::days(7), now))
+}
+
+// Tauri命令：按当前设置立即发送最近 7 天的汇总，用于检查通知和邮件设置
+#[tauri::command]
+pub async fn send_weekly_digest(app: AppHandle) -> Result<(), String> {
+    let settings = GeneralSettings::load()
+        .map(|settings| settings.weekly_digest)
+        .map_err(|e| e.to_string())?;
+    settings.validate()?;
+    deliver(&app, &settings, &get_weekly_digest().await?).await
+}
@@ -0,0 +1,69 @@
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::file_organizer::{FileSortify, OrganizePreview};
+use crate::i18n::{t, t_format};
+
+/// 请求用户确认整理拖放进来的文件夹（organize-drop-requested 事件），
+/// 用户确认后前端调用 organize_files 整理
+#[derive(Debug, Clone, Serialize)]
+pub struct DropRequest {
+    pub folder_path: String,
+    pub preview: OrganizePreview,
+    // 一次拖入多个项目时只处理第一个文件夹，其余忽略
+    pub ignored_count: usize,
+}
+
+/// 处理拖放到主窗口或 macOS Dock 图标上的路径：检查是否为文件夹，
+/// 生成整理预览后发送给前端确认，不会直接移动文件
+pub fn handle_drop(app_handle: &AppHandle, paths: Vec<PathBuf>) {
+    log::info!("Received {} dropped item(s)", paths.len());
+    show_main_window(app_handle);
+
+    let total = paths.len();
+    let Some(folder) = paths.into_iter().find(|path| path.is_dir()) else {
+        reject(app_handle, t("drop_not_a_folder"));
+        return;
+    };
+    let folder_path = folder.to_string_lossy().to_string();
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let path = folder_path.clone();
+        let preview = tauri::async_runtime::spawn_blocking(move || {
+            FileSortify::new(&path)
+                .and_then(|organizer| organizer.preview())
+                .map_err(|e| e.to_string())
+        })
+        .await;
+
+        match preview {
+            Ok(Ok(preview)) => {
+                crate::telemetry::record_feature("drop_organize");
+                let request = DropRequest {
+                    folder_path,
+                    preview,
+                    ignored_count: total - 1,
+                };
+                let _ = app_handle.emit("organize-drop-requested", &request);
+            }
+            Ok(Err(e)) => reject(&app_handle, t_format("drop_preview_failed", &[&e])),
+            Err(e) => reject(&app_handle, t_format("drop_preview_failed", &[&e.to_string()])),
+        }
+    });
+}
+
+// 拖入的不是可整理的文件夹时，由前端显示原因
+fn reject(app_handle: &AppHandle, message: String) {
+    log::warn!("Rejected dropped items: {}", message);
+    let _ = app_handle.emit("organize-drop-rejected", &serde_json::json!({ "message": message }));
+}
+
+fn show_main_window(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
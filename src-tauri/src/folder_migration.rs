@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// 某个分类在磁盘上对应的一个文件夹
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryFolder {
+    pub name: String,
+    pub entries: usize,
+}
+
+/// 分类文件夹的现状：以不同语言创建的同一分类文件夹会同时列出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryFolderStatus {
+    #[serde(rename = "categoryId")]
+    pub category_id: String,
+    // 整理时实际使用的文件夹名
+    pub target: String,
+    // 是否已固定文件夹名
+    pub pinned: bool,
+    // 除目标文件夹外，同一分类的其他已存在文件夹
+    pub duplicates: Vec<CategoryFolder>,
+}
+
+/// 合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FolderMigrationReport {
+    #[serde(rename = "categoryId")]
+    pub category_id: String,
+    pub target: String,
+    pub moved: usize,
+    #[serde(rename = "removedFolders")]
+    pub removed_folders: Vec<String>,
+}
+
+// 不区分大小写的文件系统上，仅大小写不同的名称指向同一个文件夹
+fn same_folder(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn count_entries(path: &Path) -> usize {
+    fs::read_dir(path).map(|entries| entries.count()).unwrap_or(0)
+}
+
+/// 列出 base 目录下各分类的文件夹情况，只返回需要处理的分类（未固定文件夹名或存在重复文件夹）
+pub fn scan(config: &Config, base: &Path) -> Vec<CategoryFolderStatus> {
+    let mut result: Vec<CategoryFolderStatus> = config
+        .categories_for(base)
+        .keys()
+        .filter_map(|category_id| {
+            let target = config.folder_name(category_id, base);
+            let duplicates: Vec<CategoryFolder> = config
+                .folder_name_candidates(category_id)
+                .into_iter()
+                .filter(|name| base.join(name).is_dir() && !same_folder(&base.join(name), &base.join(&target)))
+                .map(|name| CategoryFolder {
+                    entries: count_entries(&base.join(&name)),
+                    name,
+                })
+                .collect();
+            let pinned = config.folder_names.contains_key(category_id);
+
+            if pinned && duplicates.is_empty() {
+                return None;
+            }
+            Some(CategoryFolderStatus {
+                category_id: category_id.clone(),
+                target,
+                pinned,
+                duplicates,
+            })
+        })
+        .collect();
+
+    result.sort_by(|a, b| a.category_id.cmp(&b.category_id));
+    result
+}
+
+// 目标位置已存在同名文件时添加数字后缀
+fn unique_destination(folder: &Path, file_name: &std::ffi::OsStr) -> PathBuf {
+    let original = folder.join(file_name);
+    let mut destination = original.clone();
+    let mut counter = 1;
+    while destination.exists() {
+        let stem = original.file_stem().and_then(|s| s.to_str()).unwrap_or("file");
+        destination = match original.extension().and_then(|e| e.to_str()) {
+            Some(ext) => folder.join(format!("{}_{}.{}", stem, counter, ext)),
+            None => folder.join(format!("{}_{}", stem, counter)),
+        };
+        counter += 1;
+    }
+    destination
+}
+
+/// 把同一分类的其他文件夹合并到目标文件夹，并把目标文件夹名固定到配置中
+///
+/// target 为空时使用当前整理所用的文件夹名；调用方负责保存配置
+pub fn migrate(
+    config: &mut Config,
+    base: &Path,
+    category_id: &str,
+    target: Option<String>,
+) -> Result<FolderMigrationReport, Box<dyn std::error::Error>> {
+    let target = target.unwrap_or_else(|| config.folder_name(category_id, base));
+    let target_path = base.join(&target);
+    fs::create_dir_all(&target_path)?;
+
+    let mut moved = 0;
+    let mut removed_folders = Vec::new();
+
+    for name in config.folder_name_candidates(category_id) {
+        let source = base.join(&name);
+        if !source.is_dir() || same_folder(&source, &target_path) {
+            continue;
+        }
+
+        for entry in fs::read_dir(&source)? {
+            let entry = entry?;
+            let destination = unique_destination(&target_path, &entry.file_name());
+            fs::rename(entry.path(), &destination)?;
+            moved += 1;
+        }
+
+        // 只删除已经清空的文件夹
+        if fs::remove_dir(&source).is_ok() {
+            removed_folders.push(name);
+        }
+    }
+
+    config.folder_names.insert(category_id.to_string(), target.clone());
+    log::info!(
+        "Migrated folders for category {} into {}: {} entries moved",
+        category_id, target, moved
+    );
+
+    Ok(FolderMigrationReport {
+        category_id: category_id.to_string(),
+        target,
+        moved,
+        removed_folders,
+    })
+}
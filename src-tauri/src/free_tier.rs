@@ -0,0 +1,185 @@
+use chrono::{Local, NaiveDate};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::t_format;
+use crate::subscription::Subscription;
+
+/// 试用期结束后免费版每天最多整理的文件数
+pub const DAILY_FILE_LIMIT: u32 = 20;
+/// 免费版最多同时监控的文件夹数
+pub const MONITORED_FOLDER_LIMIT: usize = 1;
+
+lazy_static! {
+    static ref USAGE: Mutex<FreeTierUsage> = Mutex::new(FreeTierUsage::load());
+}
+
+// 按本地日期统计的整理数量，加密保存，被修改过时当天按额度已用完处理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FreeTierUsage {
+    date: NaiveDate,
+    files_organized: u32,
+}
+
+impl FreeTierUsage {
+    fn load() -> Self {
+        let path = Self::get_usage_path();
+        let Ok(data) = fs::read(&path) else {
+            return Self::empty();
+        };
+        match crate::secure_store::decrypt(&data).and_then(|content| serde_json::from_slice(&content).map_err(|e| e.to_string())) {
+            Ok(usage) => usage,
+            Err(e) => {
+                log::warn!("Free tier usage file is invalid, treating today's quota as used: {}", e);
+                Self {
+                    date: Local::now().date_naive(),
+                    files_organized: DAILY_FILE_LIMIT,
+                }
+            }
+        }
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::get_usage_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = crate::secure_store::encrypt(&serde_json::to_vec(self)?)?;
+        fs::write(&path, data)?;
+        Ok(())
+    }
+
+    fn empty() -> Self {
+        Self {
+            date: Local::now().date_naive(),
+            files_organized: 0,
+        }
+    }
+
+    // 跨天后重新计数
+    fn today(&mut self) -> &mut Self {
+        if self.date != Local::now().date_naive() {
+            *self = Self::empty();
+        }
+        self
+    }
+
+    fn get_usage_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("free_tier_usage.dat")
+        } else {
+            PathBuf::from("file_organizer_free_tier_usage.dat")
+        }
+    }
+}
+
+/// 免费版的限制和今天的使用情况
+#[derive(Debug, Clone, Serialize)]
+pub struct FreeTierStatus {
+    // 是否处于免费版（试用期结束且没有有效订阅）
+    pub limited: bool,
+    pub files_organized_today: u32,
+    pub daily_file_limit: u32,
+    pub monitored_folder_limit: usize,
+}
+
+/// 免费版的限制错误。序列化为带 kind 字段的对象，
+/// 前端收到 daily_file_limit_reached 或 monitored_folder_limit_reached 时可以引导用户升级
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FreeTierError {
+    DailyFileLimitReached {
+        files_organized: u32,
+        limit: u32,
+        message: String,
+    },
+    MonitoredFolderLimitReached {
+        monitored_folders: usize,
+        limit: usize,
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for FreeTierError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FreeTierError::DailyFileLimitReached { message, .. }
+            | FreeTierError::MonitoredFolderLimitReached { message, .. }
+            | FreeTierError::Other { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for FreeTierError {
+    fn from(message: String) -> Self {
+        FreeTierError::Other { message }
+    }
+}
+
+/// 试用期结束且没有有效订阅时按免费版限制使用
+pub fn is_limited(subscription: &Subscription) -> bool {
+    !subscription.can_use_app()
+}
+
+/// 在监控线程等非异步上下文中读取当前订阅判断是否受限，不能在异步命令中调用
+pub fn is_limited_blocking(app: &AppHandle) -> bool {
+    let state = app.state::<crate::AppState>();
+    let subscription = state.subscription.blocking_lock();
+    is_limited(&subscription)
+}
+
+pub fn status(subscription: &Subscription) -> FreeTierStatus {
+    let mut usage = USAGE.lock().unwrap();
+    FreeTierStatus {
+        limited: is_limited(subscription),
+        files_organized_today: usage.today().files_organized,
+        daily_file_limit: DAILY_FILE_LIMIT,
+        monitored_folder_limit: MONITORED_FOLDER_LIMIT,
+    }
+}
+
+/// 今天还能整理的文件数，额度已用完时返回错误
+pub fn remaining_today() -> Result<u32, FreeTierError> {
+    let mut usage = USAGE.lock().unwrap();
+    let files_organized = usage.today().files_organized;
+    if files_organized >= DAILY_FILE_LIMIT {
+        return Err(FreeTierError::DailyFileLimitReached {
+            files_organized,
+            limit: DAILY_FILE_LIMIT,
+            message: t_format("free_tier_daily_limit", &[&DAILY_FILE_LIMIT.to_string()]),
+        });
+    }
+    Ok(DAILY_FILE_LIMIT - files_organized)
+}
+
+/// 记录免费版整理的文件数
+pub fn record_files(count: u32) {
+    if count == 0 {
+        return;
+    }
+    let mut usage = USAGE.lock().unwrap();
+    let usage = usage.today();
+    usage.files_organized = usage.files_organized.saturating_add(count);
+    if let Err(e) = usage.save() {
+        log::warn!("Failed to save free tier usage: {}", e);
+    }
+}
+
+/// 检查是否还能再监控一个文件夹
+pub fn check_monitored_folders(monitored_folders: usize) -> Result<(), FreeTierError> {
+    if monitored_folders >= MONITORED_FOLDER_LIMIT {
+        return Err(FreeTierError::MonitoredFolderLimitReached {
+            monitored_folders,
+            limit: MONITORED_FOLDER_LIMIT,
+            message: t_format("free_tier_folder_limit", &[&MONITORED_FOLDER_LIMIT.to_string()]),
+        });
+    }
+    Ok(())
+}
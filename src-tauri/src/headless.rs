@@ -0,0 +1,205 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::core_host::HeadlessEvents;
+use crate::file_organizer::{FileSortify, UndoAction};
+use crate::i18n::{t, t_format, Language};
+use crate::settings::GeneralSettings;
+use crate::subscription::Subscription;
+
+/// 命令行版本的可执行文件名。与应用由同一份代码编译，按编译时的目标名称进入命令行模式
+pub const BIN_NAME: &str = "filesortify-cli";
+
+// 最近一次 organize 的移动记录，供 undo 撤销；应用内的撤销历史只保存在内存中
+const UNDO_JOURNAL_FILE: &str = "cli_undo.json";
+
+/// 当前可执行文件是否为命令行版本
+pub fn is_cli_binary() -> bool {
+    env!("CARGO_BIN_NAME") == BIN_NAME
+}
+
+/// 运行命令行版本，返回进程退出码。不启动 Tauri，整理逻辑来自 filesortify-core，
+/// 可以在服务器和脚本中无界面运行
+pub fn run(args: Vec<String>) -> i32 {
+    attach_console();
+
+    let settings = GeneralSettings::load().unwrap_or_default();
+    crate::payment_test_mode::init(settings.payment_test_mode);
+    let language = match &settings.language {
+        Some(code) => Language::from(code.as_str()),
+        None => crate::i18n::detect_system_language(),
+    };
+    crate::i18n::set_language(language);
+
+    let json = args.iter().any(|arg| arg == "--json");
+    let args: Vec<&str> = args.iter().map(String::as_str).filter(|arg| *arg != "--json").collect();
+
+    let result = match args.as_slice() {
+        ["organize", path] => organize(path, json),
+        ["watch", path] => watch(path),
+        ["preview"] => preview(None, json),
+        ["preview", path] => preview(Some(*path), json),
+        ["undo"] => undo(json),
+        ["help"] | ["--help"] | ["-h"] | [] => {
+            println!("{}", t("cli_usage"));
+            return 0;
+        }
+        _ => Err(t("cli_usage")),
+    };
+    crate::telemetry::flush();
+
+    match result {
+        Ok(()) => 0,
+        Err(message) => {
+            eprintln!("{}", message);
+            1
+        }
+    }
+}
+
+fn organize(path: &str, json: bool) -> Result<(), String> {
+    let folder = folder(path)?;
+    // 试用期结束后与应用一样按免费版的每日额度整理
+    let limit = if crate::free_tier::is_limited(&Subscription::load().unwrap_or_default()) {
+        Some(crate::free_tier::remaining_today().map_err(|e| e.to_string())? as usize)
+    } else {
+        None
+    };
+
+    let mut organizer = FileSortify::new(&folder)
+        .map_err(|e| t_format("init_failed", &[&e.to_string()]))?
+        .with_events(Arc::new(HeadlessEvents { limited: limit.is_some() }));
+    let count = organizer
+        .organize_existing_files_up_to(limit)
+        .map_err(|e| t_format("organize_failed", &[&e.to_string()]))?;
+    if limit.is_some() {
+        crate::free_tier::record_files(count as u32);
+    }
+    crate::telemetry::record_feature("cli_organize");
+
+    let actions = organizer.get_undo_history(organizer.get_undo_history_count());
+    if let Err(e) = save_undo_journal(&actions) {
+        log::warn!("Failed to save CLI undo journal: {}", e);
+    }
+
+    if json {
+        println!("{}", serde_json::json!({ "folderPath": folder, "moved": actions }));
+    } else {
+        println!("{}", t_format("files_organized", &[&count.to_string()]));
+    }
+    Ok(())
+}
+
+fn watch(path: &str) -> Result<(), String> {
+    let folder = folder(path)?;
+    let limited = crate::free_tier::is_limited(&Subscription::load().unwrap_or_default());
+    let mut organizer = FileSortify::new(&folder)
+        .map_err(|e| t_format("init_failed", &[&e.to_string()]))?
+        .with_events(Arc::new(HeadlessEvents { limited }));
+    organizer
+        .start_monitoring()
+        .map_err(|e| t_format("monitoring_start_failed", &[&e.to_string()]))?;
+    crate::telemetry::record_feature("cli_watch");
+    println!("{}", t_format("cli_watching", &[&folder]));
+
+    // 监控在后台线程中进行，等待 Ctrl+C 后停止
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime.block_on(tokio::signal::ctrl_c()).map_err(|e| e.to_string())?;
+    organizer.stop_monitoring();
+    Ok(())
+}
+
+fn preview(path: Option<&str>, json: bool) -> Result<(), String> {
+    let folder = match path {
+        Some(path) => folder(path)?,
+        None => dirs::download_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .ok_or_else(|| t("downloads_folder_not_found"))?,
+    };
+    let organizer = FileSortify::new(&folder).map_err(|e| t_format("init_failed", &[&e.to_string()]))?;
+    let preview = organizer.preview().map_err(|e| e.to_string())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&preview).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    for item in &preview.moves {
+        println!("{} -> {}", item.file_name, item.destination_folder);
+    }
+    println!(
+        "{}",
+        t_format("cli_preview_summary", &[&preview.moves.len().to_string(), &preview.unmatched_count.to_string()])
+    );
+    Ok(())
+}
+
+// 撤销最近一次 organize，已经被移走或原位置被占用的文件跳过
+fn undo(json: bool) -> Result<(), String> {
+    let actions = load_undo_journal();
+    let Some(first) = actions.first() else {
+        return Err(t("cli_nothing_to_undo"));
+    };
+
+    let mut organizer = FileSortify::new(&first.downloads_path.to_string_lossy())
+        .map_err(|e| t_format("init_failed", &[&e.to_string()]))?;
+    for action in actions.iter().cloned() {
+        organizer.undo_history.add_action(action);
+    }
+
+    let mut restored = Vec::new();
+    let mut failed = Vec::new();
+    for action in &actions {
+        match organizer.undo_action(&action.id) {
+            Ok(_) => restored.push(action.file_name.clone()),
+            Err(e) => {
+                eprintln!("{}", t_format("undo_failed", &[&e.to_string()]));
+                failed.push(action.file_name.clone());
+            }
+        }
+    }
+    let _ = fs::remove_file(undo_journal_path());
+    crate::telemetry::record_feature("cli_undo");
+
+    if json {
+        println!("{}", serde_json::json!({ "restored": restored, "failed": failed }));
+    } else {
+        println!("{}", t_format("cli_undo_done", &[&restored.len().to_string()]));
+    }
+    Ok(())
+}
+
+fn folder(path: &str) -> Result<String, String> {
+    crate::context_menu::validate_folder(path).map(|path| path.to_string_lossy().to_string())
+}
+
+fn undo_journal_path() -> PathBuf {
+    crate::backup::app_data_dir().join(UNDO_JOURNAL_FILE)
+}
+
+fn save_undo_journal(actions: &[UndoAction]) -> Result<(), Box<dyn std::error::Error>> {
+    let path = undo_journal_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(actions)?)?;
+    Ok(())
+}
+
+fn load_undo_journal() -> Vec<UndoAction> {
+    fs::read_to_string(undo_journal_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Windows 发布版没有控制台窗口，从终端运行时连接到父进程的控制台输出结果
+#[cfg(windows)]
+fn attach_console() {
+    unsafe {
+        winapi::um::wincon::AttachConsole(winapi::um::wincon::ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_console() {}
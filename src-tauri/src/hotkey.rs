@@ -0,0 +1,122 @@
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+use crate::config::Config;
+use crate::core_host::WithAppHandle;
+use crate::file_organizer::FileSortify;
+use crate::i18n::{t, t_format};
+use crate::toast::ActionableNotification;
+use crate::AppState;
+
+/// 默认的“立即整理”快捷键
+pub const DEFAULT_HOTKEY: &str = "CommandOrControl+Shift+O";
+
+/// 检查快捷键格式，例如 "CommandOrControl+Shift+O"
+pub fn validate(hotkey: &str) -> Result<(), String> {
+    hotkey
+        .trim()
+        .parse::<Shortcut>()
+        .map(|_| ())
+        .map_err(|e| t_format("invalid_hotkey", &[hotkey, &e.to_string()]))
+}
+
+/// 注册（或替换）“立即整理”快捷键；为空时取消注册
+pub fn apply(app_handle: &AppHandle, hotkey: Option<&str>) -> Result<(), String> {
+    let shortcuts = app_handle.global_shortcut();
+    shortcuts
+        .unregister_all()
+        .map_err(|e| t_format("register_hotkey_failed", &[&e.to_string()]))?;
+
+    let Some(hotkey) = hotkey.map(str::trim).filter(|h| !h.is_empty()) else {
+        return Ok(());
+    };
+    validate(hotkey)?;
+
+    shortcuts
+        .on_shortcut(hotkey, |app_handle, _shortcut, event| {
+            if event.state == ShortcutState::Pressed {
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    organize_and_notify(app_handle, default_path(), "hotkey").await;
+                });
+            }
+        })
+        .map_err(|e| t_format("register_hotkey_failed", &[&e.to_string()]))?;
+    log::info!("Registered organize hotkey: {}", hotkey);
+    Ok(())
+}
+
+// 默认路径：第一个已配置的路径，没有时使用系统下载文件夹
+fn default_path() -> Option<String> {
+    Config::load()
+        .ok()
+        .and_then(|config| config.list_paths().into_iter().next())
+        .map(|path| path.path)
+        .or_else(|| dirs::download_dir().map(|dir| dir.to_string_lossy().to_string()))
+}
+
+/// 不打开窗口直接整理一个文件夹，并用通知显示结果（快捷键和托盘菜单使用）。
+/// feature 是记录到使用统计中的入口名称
+pub async fn organize_and_notify(app_handle: AppHandle, folder_path: Option<String>, feature: &str) {
+    // 试用期结束后按免费版限制，只整理今天剩余额度内的文件
+    let limit = {
+        let state = app_handle.state::<AppState>();
+        let subscription = state.subscription.lock().await;
+        if crate::free_tier::is_limited(&subscription) {
+            Some(crate::free_tier::remaining_today())
+        } else {
+            None
+        }
+    };
+
+    let mut notify = true;
+    let mut notification = ActionableNotification {
+        title: t("hotkey_organize_title"),
+        ..Default::default()
+    };
+    let body = if let Some(Err(e)) = &limit {
+        e.to_string()
+    } else if let Some(folder_path) = folder_path {
+        let limit = limit.map(|remaining| remaining.unwrap_or_default() as usize);
+        crate::telemetry::record_feature(feature);
+        notify = Config::load()
+            .map(|config| config.notifications_enabled_for(std::path::Path::new(&folder_path)))
+            .unwrap_or(true);
+        notification.folder_path = Some(folder_path.clone());
+        let handle = app_handle.clone();
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            FileSortify::new(&folder_path)
+                .map_err(|e| t_format("init_failed", &[&e.to_string()]))
+                .and_then(|organizer| {
+                    let mut organizer = organizer.with_app_handle(handle);
+                    let count = organizer
+                        .organize_existing_files_up_to(limit)
+                        .map_err(|e| t_format("organize_failed", &[&e.to_string()]))?;
+                    // 这次整理的移动记录，通知上的“撤销”按钮使用
+                    Ok((count, organizer.get_undo_history(organizer.get_undo_history_count())))
+                })
+        })
+        .await;
+
+        match result {
+            Ok(Ok((count, undo))) => {
+                if limit.is_some() {
+                    crate::free_tier::record_files(count as u32);
+                }
+                notification.undo = undo;
+                t_format("files_organized", &[&count.to_string()])
+            }
+            Ok(Err(message)) => message,
+            Err(e) => t_format("organize_failed", &[&e.to_string()]),
+        }
+    } else {
+        t("downloads_folder_not_found")
+    };
+    notification.body = body;
+
+    if !notify {
+        log::info!("{}", notification.body);
+        return;
+    }
+    crate::toast::show(&app_handle, notification);
+}
@@ -0,0 +1,89 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// 代理模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    // 使用系统代理设置和 HTTP_PROXY / HTTPS_PROXY 等环境变量
+    System,
+    // 直连，忽略系统代理
+    None,
+    // 使用手动填写的代理地址
+    Manual,
+}
+
+impl Default for ProxyMode {
+    fn default() -> Self {
+        ProxyMode::System
+    }
+}
+
+/// 所有网络请求（订阅、支付、配置同步、GitHub、更新）共用的代理设置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub mode: ProxyMode,
+    // 手动代理地址，支持 http://、https://、socks5:// 和 socks5h://
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+impl ProxySettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mode != ProxyMode::Manual {
+            return Ok(());
+        }
+        let url = self.url.as_deref().map(str::trim).unwrap_or("");
+        let supported = ["http://", "https://", "socks5://", "socks5h://"];
+        if !supported.iter().any(|scheme| url.to_lowercase().starts_with(scheme)) {
+            return Err(format!("Unsupported proxy URL: {}", url));
+        }
+        reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL {}: {}", url, e))?;
+        Ok(())
+    }
+
+    /// 手动代理地址；非手动模式时为空
+    pub fn manual_url(&self) -> Option<&str> {
+        match self.mode {
+            ProxyMode::Manual => self.url.as_deref().map(str::trim).filter(|url| !url.is_empty()),
+            _ => None,
+        }
+    }
+}
+
+lazy_static! {
+    static ref PROXY: RwLock<ProxySettings> = RwLock::new(ProxySettings::default());
+}
+
+/// 更新当前使用的代理设置（启动时和设置修改后调用）
+pub fn set_proxy(settings: &ProxySettings) {
+    *PROXY.write().unwrap() = settings.clone();
+}
+
+pub fn proxy() -> ProxySettings {
+    PROXY.read().unwrap().clone()
+}
+
+/// 按当前代理设置创建 HTTP 客户端；所有网络请求都应通过这里创建客户端
+pub fn client() -> reqwest::Client {
+    let settings = proxy();
+    let mut builder = reqwest::Client::builder();
+
+    match settings.mode {
+        // reqwest 默认读取系统代理和代理环境变量
+        ProxyMode::System => {}
+        ProxyMode::None => builder = builder.no_proxy(),
+        ProxyMode::Manual => match settings.manual_url().map(reqwest::Proxy::all) {
+            Some(Ok(proxy)) => builder = builder.proxy(proxy),
+            Some(Err(e)) => log::error!("Invalid proxy URL, falling back to system proxy: {}", e),
+            None => log::warn!("Manual proxy selected without a URL, falling back to system proxy"),
+        },
+    }
+
+    builder.build().unwrap_or_else(|e| {
+        log::error!("Failed to build HTTP client: {}", e);
+        reqwest::Client::new()
+    })
+}
@@ -0,0 +1,409 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tokio::io::AsyncReadExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::core_host::WithAppHandle;
+use crate::file_organizer::{FileSortify, UndoAction};
+use crate::i18n::{t, t_format};
+use crate::loopback;
+use crate::AppState;
+
+pub const DEFAULT_PORT: u16 = 47823;
+// 请求头和请求体的上限，接口只接收很小的 JSON
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+const DEFAULT_HISTORY_LIMIT: usize = 50;
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
+// Prometheus 文本格式
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// 本机自动化接口的设置，默认关闭。开启后只监听 127.0.0.1，每个请求都需要带令牌
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    // Authorization: Bearer <token>，开启时自动生成
+    #[serde(default)]
+    pub token: String,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: DEFAULT_PORT,
+            token: String::new(),
+        }
+    }
+}
+
+impl LocalApiSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port < 1024 {
+            return Err("local_api.port must be between 1024 and 65535".to_string());
+        }
+        Ok(())
+    }
+
+    /// 开启时还没有令牌则生成一个
+    pub fn ensure_token(&mut self) {
+        if self.enabled && self.token.trim().is_empty() {
+            self.token = generate_token();
+        }
+    }
+}
+
+pub fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+lazy_static! {
+    // 正在运行的接口，设置变化时关闭后按新设置重新启动
+    static ref ACTIVE: Mutex<Option<oneshot::Sender<()>>> = Mutex::new(None);
+}
+
+/// 按设置启动或停止本机接口
+pub async fn apply(app: &AppHandle, settings: &LocalApiSettings) -> Result<(), String> {
+    if let Some(previous) = ACTIVE.lock().unwrap().take() {
+        let _ = previous.send(());
+    }
+    if !settings.enabled || settings.token.is_empty() {
+        return Ok(());
+    }
+
+    let listener = TcpListener::bind(("127.0.0.1", settings.port))
+        .await
+        .map_err(|e| t_format("local_api_start_failed", &[&settings.port.to_string(), &e.to_string()]))?;
+    log::info!("Local API listening on 127.0.0.1:{}", settings.port);
+
+    let (stop_sender, mut stop_receiver) = oneshot::channel();
+    *ACTIVE.lock().unwrap() = Some(stop_sender);
+
+    let app = app.clone();
+    let token = settings.token.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::select! {
+                _ = &mut stop_receiver => break,
+                accepted = listener.accept() => {
+                    let Ok((stream, _)) = accepted else {
+                        continue;
+                    };
+                    let app = app.clone();
+                    let token = token.clone();
+                    tauri::async_runtime::spawn(async move {
+                        handle_connection(stream, &app, &token).await;
+                    });
+                }
+            }
+        }
+        log::info!("Local API stopped");
+    });
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    url: Url,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn query(&self, name: &str) -> Option<String> {
+        self.url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.to_string())
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, app: &AppHandle, token: &str) {
+    let read = tokio::time::timeout(tokio::time::Duration::from_secs(5), read_request(&mut stream)).await;
+    let (status, content_type, body) = match read {
+        Ok(Some(request)) => route(app, token, request).await,
+        _ => (400, JSON_CONTENT_TYPE, error_body("bad request")),
+    };
+    loopback::respond(&mut stream, &format!("{} {}", status, reason(status)), content_type, &body).await;
+}
+
+async fn read_request(stream: &mut TcpStream) -> Option<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let header_end = loop {
+        let length = stream.read(&mut chunk).await.ok()?;
+        if length == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..length]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position;
+        }
+        if buffer.len() > MAX_REQUEST_BYTES {
+            return None;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let url = loopback::parse_target(request_line.next()?)?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    if content_length > MAX_REQUEST_BYTES {
+        return None;
+    }
+    let mut body = buffer[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let length = stream.read(&mut chunk).await.ok()?;
+        if length == 0 {
+            return None;
+        }
+        body.extend_from_slice(&chunk[..length]);
+    }
+    body.truncate(content_length);
+
+    Some(Request { method, url, headers, body })
+}
+
+async fn route(app: &AppHandle, token: &str, request: Request) -> (u16, &'static str, String) {
+    // 只接受直接发往本机地址的请求，防止网页通过 DNS 重绑定访问接口
+    let host = request.header("host").unwrap_or_default();
+    let host_name = host.rsplit_once(':').map(|(name, _)| name).unwrap_or(host);
+    if !matches!(host_name, "127.0.0.1" | "localhost") {
+        return (403, JSON_CONTENT_TYPE, error_body("forbidden"));
+    }
+    let authorized = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| tokens_match(value.trim(), token))
+        .unwrap_or(false);
+    if !authorized {
+        return (401, JSON_CONTENT_TYPE, error_body("unauthorized"));
+    }
+
+    if (request.method.as_str(), request.url.path()) == ("GET", "/metrics") {
+        return (200, METRICS_CONTENT_TYPE, metrics(app).await);
+    }
+
+    let result = match (request.method.as_str(), request.url.path()) {
+        ("GET", "/api/status") => status(app).await,
+        ("GET", "/api/preview") => preview(request.query("path")).await,
+        ("POST", "/api/organize") => organize(app, &request.body).await,
+        ("GET", "/api/history") => history(app, request.query("path"), request.query("limit")).await,
+        ("POST", "/api/downloads") => download_completed(app, &request.body).await,
+        _ => return (404, JSON_CONTENT_TYPE, error_body("not found")),
+    };
+    crate::telemetry::record_feature("local_api");
+    match result {
+        Ok(value) => (200, JSON_CONTENT_TYPE, value.to_string()),
+        Err(message) => (422, JSON_CONTENT_TYPE, error_body(&message)),
+    }
+}
+
+async fn status(app: &AppHandle) -> Result<serde_json::Value, String> {
+    let state = app.state::<AppState>();
+    let monitoring: Vec<String> = state.organizers.lock().await.keys().cloned().collect();
+    let (plan, can_use_app) = {
+        let subscription = state.subscription.lock().await;
+        (subscription.plan.clone(), subscription.can_use_app())
+    };
+    let organized_today = crate::config::Config::load().map(|config| config.files_organized_today()).unwrap_or(0);
+    Ok(serde_json::json!({
+        "version": app.package_info().version.to_string(),
+        "monitoring": monitoring,
+        "organizedToday": organized_today,
+        "plan": plan,
+        "canUseApp": can_use_app,
+    }))
+}
+
+// 供 Prometheus 抓取，不计入使用统计，避免每次抓取都记一次
+async fn metrics(app: &AppHandle) -> String {
+    let monitored_folders = app.state::<AppState>().organizers.lock().await.len();
+    crate::metrics::render(&crate::metrics::Gauges {
+        monitored_folders,
+        pending_moves: crate::pending::list().len(),
+    })
+}
+
+async fn preview(path: Option<String>) -> Result<serde_json::Value, String> {
+    let folder = folder_or_downloads(path)?;
+    let preview = tauri::async_runtime::spawn_blocking(move || {
+        FileSortify::new(&folder)
+            .and_then(|organizer| organizer.preview())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    serde_json::to_value(preview).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize)]
+struct OrganizeRequest {
+    path: Option<String>,
+}
+
+// 与“立即整理”相同：试用期结束后按免费版额度整理，结果同步显示在窗口中
+async fn organize(app: &AppHandle, body: &[u8]) -> Result<serde_json::Value, String> {
+    let request: OrganizeRequest = if body.is_empty() {
+        OrganizeRequest { path: None }
+    } else {
+        serde_json::from_slice(body).map_err(|e| e.to_string())?
+    };
+    let folder = folder_or_downloads(request.path)?;
+
+    let limit = {
+        let state = app.state::<AppState>();
+        let subscription = state.subscription.lock().await;
+        if crate::free_tier::is_limited(&subscription) {
+            Some(crate::free_tier::remaining_today().map_err(|e| e.to_string())? as usize)
+        } else {
+            None
+        }
+    };
+
+    let handle = app.clone();
+    let path = folder.clone();
+    let count = tauri::async_runtime::spawn_blocking(move || {
+        FileSortify::new(&path)
+            .map_err(|e| t_format("init_failed", &[&e.to_string()]))
+            .and_then(|organizer| {
+                organizer
+                    .with_app_handle(handle)
+                    .organize_existing_files_up_to(limit)
+                    .map_err(|e| t_format("organize_failed", &[&e.to_string()]))
+            })
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    if limit.is_some() {
+        crate::free_tier::record_files(count as u32);
+    }
+
+    Ok(serde_json::json!({
+        "folderPath": folder,
+        "moved": count,
+        "message": t_format("files_organized", &[&count.to_string()]),
+    }))
+}
+
+#[derive(Deserialize)]
+struct DownloadCompleted {
+    path: String,
+    url: String,
+    #[serde(default)]
+    referrer: Option<String>,
+}
+
+// 浏览器扩展报告下载完成：记录来源，文件在正在监控的文件夹中时立即整理，
+// 不需要等待文件系统事件和写入完成的判断
+async fn download_completed(app: &AppHandle, body: &[u8]) -> Result<serde_json::Value, String> {
+    let request: DownloadCompleted = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+    let path = std::path::PathBuf::from(&request.path);
+    if !path.is_file() {
+        return Err(t_format("download_not_found", &[&request.path]));
+    }
+    let source = crate::download_sources::record(&path, &request.url, request.referrer);
+
+    let folder = path.parent().map(|parent| parent.to_string_lossy().to_string()).unwrap_or_default();
+    let state = app.state::<AppState>();
+    // 移动文件可能要等待和重试，不能在持有锁时进行；在副本上整理，再把撤销记录写回
+    let organizer = state.organizers.lock().await.get(&folder).cloned();
+    let monitored = organizer.is_some();
+    let moved = match organizer {
+        Some(mut organizer) => {
+            let download = path.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                organizer
+                    .organize_completed_download(&download)
+                    .map_err(|e| t_format("organize_failed", &[&e.to_string()]))
+            })
+            .await
+            .map_err(|e| e.to_string())??
+        }
+        None => None,
+    };
+    if let Some(action) = &moved {
+        if let Some(organizer) = state.organizers.lock().await.get_mut(&folder) {
+            organizer.undo_history.add_action(action.clone());
+        }
+    }
+    crate::telemetry::record_feature("browser_extension");
+
+    Ok(serde_json::json!({
+        "monitored": monitored,
+        "organized": moved.is_some(),
+        "movedTo": moved.as_ref().map(|action| action.moved_to_path.clone()),
+        "category": moved.as_ref().map(|action| action.category.clone()),
+        "source": source,
+    }))
+}
+
+// 正在监控的路径的撤销历史，不指定路径时合并所有路径，按时间从新到旧
+async fn history(app: &AppHandle, path: Option<String>, limit: Option<String>) -> Result<serde_json::Value, String> {
+    let limit = limit.and_then(|limit| limit.parse::<usize>().ok()).unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let state = app.state::<AppState>();
+    let organizers = state.organizers.lock().await;
+
+    let mut actions: Vec<UndoAction> = match &path {
+        Some(path) => organizers
+            .get(path)
+            .ok_or_else(|| t("no_monitoring_for_path"))?
+            .get_undo_history(limit),
+        None => organizers.values().flat_map(|organizer| organizer.get_undo_history(limit)).collect(),
+    };
+    actions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    actions.truncate(limit);
+    serde_json::to_value(actions).map_err(|e| e.to_string())
+}
+
+fn folder_or_downloads(path: Option<String>) -> Result<String, String> {
+    match path {
+        Some(path) => crate::context_menu::validate_folder(&path).map(|path| path.to_string_lossy().to_string()),
+        None => dirs::download_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .ok_or_else(|| t("downloads_folder_not_found")),
+    }
+}
+
+// 按固定时间比较令牌，避免通过响应时间逐字节猜出令牌
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    provided.len() == expected.len()
+        && provided
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        _ => "Unprocessable Entity",
+    }
+}
@@ -0,0 +1,185 @@
+use chrono::NaiveDate;
+use lazy_static::lazy_static;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+// 单个日志文件的大小上限，超过后在同一天内切换到新文件
+const MAX_FILE_SIZE: u64 = 5 * 1024 * 1024;
+// 日志目录的总大小上限，超过时从最旧的文件开始删除
+const MAX_TOTAL_SIZE: u64 = 50 * 1024 * 1024;
+const FILE_PREFIX: &str = "filesortify-";
+pub const DEFAULT_LEVEL: &str = "info";
+
+// 当前写入的日志文件
+struct LogFile {
+    dir: Option<PathBuf>,
+    date: Option<NaiveDate>,
+    index: u32,
+    size: u64,
+    file: Option<File>,
+}
+
+struct FileLogger {
+    state: Mutex<LogFile>,
+}
+
+lazy_static! {
+    static ref LOGGER: FileLogger = FileLogger {
+        state: Mutex::new(LogFile {
+            dir: None,
+            date: None,
+            index: 0,
+            size: 0,
+            file: None,
+        }),
+    };
+}
+
+/// 日志目录：应用数据目录下的 logs
+pub fn log_dir() -> Option<PathBuf> {
+    crate::app_paths::app_data_dir().map(|dir| dir.join("logs"))
+}
+
+/// 解析日志级别名称（off / error / warn / info / debug / trace）
+pub fn parse_level(level: &str) -> Option<LevelFilter> {
+    level.trim().parse::<LevelFilter>().ok()
+}
+
+/// 安装文件日志；必须在其他模块写日志之前调用
+pub fn init(level: &str) {
+    // 先确定目录再安装日志器，避免解析目录时产生的日志重入
+    let dir = log_dir();
+    if let Ok(mut state) = LOGGER.state.lock() {
+        state.dir = dir;
+    }
+
+    if log::set_logger(&*LOGGER).is_err() {
+        return;
+    }
+    log::set_max_level(parse_level(level).unwrap_or(LevelFilter::Info));
+}
+
+/// 关闭并停止写日志文件，用于清除全部数据前释放日志文件
+pub fn disable_file() {
+    if let Ok(mut state) = LOGGER.state.lock() {
+        state.file = None;
+        state.dir = None;
+    }
+}
+
+/// 运行时调整日志级别
+pub fn set_level(level: &str) -> Result<(), String> {
+    let filter = parse_level(level).ok_or_else(|| format!("Invalid log level: {}", level))?;
+    log::set_max_level(filter);
+    Ok(())
+}
+
+impl LogFile {
+    fn path_for(&self, date: NaiveDate, index: u32) -> Option<PathBuf> {
+        let name = if index == 0 {
+            format!("{}{}.log", FILE_PREFIX, date.format("%Y-%m-%d"))
+        } else {
+            format!("{}{}.{}.log", FILE_PREFIX, date.format("%Y-%m-%d"), index)
+        };
+        self.dir.as_ref().map(|dir| dir.join(name))
+    }
+
+    // 按日期切换文件；当天的文件超过大小上限时使用下一个序号
+    fn rotate(&mut self, today: NaiveDate) {
+        self.file = None;
+        let Some(dir) = self.dir.clone() else { return };
+        if fs::create_dir_all(&dir).is_err() {
+            return;
+        }
+
+        let mut index = if self.date == Some(today) { self.index + 1 } else { 0 };
+        loop {
+            let Some(path) = self.path_for(today, index) else { return };
+            let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+            if size < MAX_FILE_SIZE {
+                self.file = OpenOptions::new().create(true).append(true).open(&path).ok();
+                self.size = size;
+                break;
+            }
+            index += 1;
+        }
+        self.date = Some(today);
+        self.index = index;
+
+        prune(&dir);
+    }
+
+    fn write(&mut self, line: &str) {
+        let today = chrono::Local::now().date_naive();
+        if self.file.is_none() || self.date != Some(today) || self.size >= MAX_FILE_SIZE {
+            self.rotate(today);
+        }
+        if let Some(file) = self.file.as_mut() {
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.size += line.len() as u64;
+            }
+        }
+    }
+}
+
+// 删除最旧的日志文件，直到总大小不超过上限
+fn prune(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let mut files: Vec<(PathBuf, u64, std::time::SystemTime)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(FILE_PREFIX))
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            Some((entry.path(), meta.len(), meta.modified().ok()?))
+        })
+        .collect();
+
+    let mut total: u64 = files.iter().map(|(_, size, _)| size).sum();
+    files.sort_by_key(|(_, _, modified)| *modified);
+    for (path, size, _) in files {
+        if total <= MAX_TOTAL_SIZE {
+            break;
+        }
+        if fs::remove_file(&path).is_ok() {
+            total -= size;
+        }
+    }
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format!(
+            "{} {:<5} [{}] {}\n",
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f%:z"),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+
+        // 开发时同时输出到终端
+        if cfg!(debug_assertions) {
+            eprint!("{}", line);
+        }
+        if let Ok(mut state) = self.state.lock() {
+            state.write(&line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            if let Some(file) = state.file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use tauri::Url;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+pub const HTML_CONTENT_TYPE: &str = "text/html; charset=utf-8";
+// 浏览器回调只需要请求行，一次读取就够
+const READ_TIMEOUT_SECS: u64 = 5;
+
+/// 读取浏览器发到本机回调地址的请求，返回请求的地址；读取超时或格式不对时返回 None
+pub async fn read_target(stream: &mut TcpStream) -> Option<Url> {
+    let mut buffer = [0u8; 4096];
+    let read = tokio::time::timeout(tokio::time::Duration::from_secs(READ_TIMEOUT_SECS), stream.read(&mut buffer)).await;
+    let Ok(Ok(length)) = read else {
+        return None;
+    };
+    let request = String::from_utf8_lossy(&buffer[..length]);
+    parse_target(request.lines().next()?.split_whitespace().nth(1)?)
+}
+
+/// 把请求行中的路径和查询参数解析为完整地址
+pub fn parse_target(target: &str) -> Option<Url> {
+    Url::parse(&format!("http://127.0.0.1{}", target)).ok()
+}
+
+/// 写出响应并关闭连接。status 包含状态码和原因，例如 "200 OK"
+pub async fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// 浏览器中显示的结果页面，标题和正文会转义
+pub fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>File Sortify</title></head>\
+         <body style=\"font-family: sans-serif; text-align: center; padding-top: 20vh\"><h2>{}</h2><p>{}</p></body></html>",
+        escape_html(title),
+        escape_html(body)
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
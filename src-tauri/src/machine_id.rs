@@ -0,0 +1,56 @@
+use sha2::{Digest, Sha256};
+
+// 不直接上传系统的原始标识，和应用名一起哈希后使用
+const SALT: &str = "fileSortify-device";
+
+/// 基于系统机器标识的设备 ID，重命名电脑或更换用户名后保持不变。
+/// 取不到机器标识时返回 None
+pub fn device_id() -> Option<String> {
+    let raw = raw_machine_id()?;
+    let digest = Sha256::digest(format!("{}:{}", SALT, raw.trim().to_lowercase()).as_bytes());
+    Some(format!("{:x}", digest)[..32].to_string())
+}
+
+// macOS：IOPlatformExpertDevice 的 IOPlatformUUID
+#[cfg(target_os = "macos")]
+fn raw_machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .find(|line| line.contains("\"IOPlatformUUID\""))
+        .and_then(|line| line.split('=').nth(1))
+        .map(|value| value.trim().trim_matches('"').to_string())
+        .filter(|value| !value.is_empty())
+}
+
+// Windows：安装系统时生成的 MachineGuid，32 位进程需要读取 64 位视图
+#[cfg(target_os = "windows")]
+fn raw_machine_id() -> Option<String> {
+    use winreg::enums::{HKEY_LOCAL_MACHINE, KEY_READ, KEY_WOW64_64KEY};
+    use winreg::RegKey;
+
+    let key = RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey_with_flags("SOFTWARE\\Microsoft\\Cryptography", KEY_READ | KEY_WOW64_64KEY)
+        .ok()?;
+    let guid: String = key.get_value("MachineGuid").ok()?;
+    Some(guid).filter(|guid| !guid.trim().is_empty())
+}
+
+// Linux：systemd 的 machine-id，老系统只有 dbus 的副本
+#[cfg(target_os = "linux")]
+fn raw_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .find(|id| !id.is_empty())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn raw_machine_id() -> Option<String> {
+    None
+}
@@ -4,20 +4,70 @@
 use tauri::{State, Manager, WindowEvent, RunEvent};
 use tokio::sync::Mutex;
 
-mod file_organizer;
-mod config;
+// 整理逻辑、配置和规则在 filesortify-core 中，按原来的模块路径引用
+use filesortify_core::{app_paths, config, config_migration, deferred, download_sources, events, file_organizer, i18n, pending, permissions, presets, unmatched};
+
+mod core_host;
+mod config_watcher;
+mod config_sync;
+mod config_import;
+mod rule_import;
+mod folder_migration;
+mod backup;
+mod reset;
+mod purge;
+mod managed_config;
 mod subscription;
+mod subscription_refresh;
+mod subscription_error;
+mod free_tier;
+mod referral;
+mod promo;
+mod machine_id;
 mod apple_subscription;
 mod updater;
 mod settings;
+mod settings_migration;
 mod autostart;
+mod cli;
+mod headless;
+mod context_menu;
+mod drop_target;
+mod rules_editor;
+mod local_api;
+mod loopback;
+mod metrics;
+mod webhooks;
+mod destinations;
+mod activity;
+mod digest;
+mod logging;
+mod diagnostics;
+mod http;
+mod telemetry;
+mod crash_report;
+mod hotkey;
+mod tray_status;
+mod toast;
+mod metered;
+mod secure_store;
+mod seats;
+mod payment;
+mod deep_link;
+mod payment_callback;
+mod payment_test_mode;
 
 #[cfg(target_os = "macos")]
 mod storekit_bridge;
+#[cfg(target_os = "macos")]
+mod scripting;
+mod msstore_bridge;
 
-use file_organizer::fileSortify;
+use file_organizer::FileSortify;
+use core_host::WithAppHandle;
 use config::Config;
 use subscription::{Subscription, SubscriptionPlan, PricingInfo, PackagesResponse};
+use subscription_error::{CommandError, SubscriptionError};
 use settings::GeneralSettings;
 use autostart::AutoStart;
 
@@ -25,14 +75,15 @@ use autostart::AutoStart;
 use std::collections::HashMap;
 
 struct AppState {
-    organizers: Mutex<HashMap<String, fileSortify>>,
+    organizers: Mutex<HashMap<String, FileSortify>>,
     subscription: Mutex<Subscription>,
     settings: Mutex<GeneralSettings>,
+    pending_reset: Mutex<Option<reset::ResetToken>>,
+    pending_purge: Mutex<Option<purge::PurgeToken>>,
 }
 
 // Tauri命令：开始整理文件
 // 在文件顶部添加
-mod i18n;
 use i18n::{t, t_format, set_language, Language};
 
 // 修改organize_files函数中的硬编码文本
@@ -41,25 +92,33 @@ async fn organize_files(
     folder_path: String,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    // 检查订阅状态
-    {
+) -> Result<String, free_tier::FreeTierError> {
+    // 试用期结束后按免费版限制，只能整理今天剩余额度内的文件
+    let limit = {
         let subscription = state.subscription.lock().await;
-        if !subscription.can_use_app() {
-            return Err(t("trial_ended"));
+        if free_tier::is_limited(&subscription) {
+            Some(free_tier::remaining_today()?)
+        } else {
+            None
         }
-    }
+    };
     
     // 只临时创建 organizer，不插入 organizers HashMap
-    match fileSortify::new(&folder_path) {
+    match FileSortify::new(&folder_path) {
         Ok(mut organizer) => {
             organizer = organizer.with_app_handle(app_handle.clone());
-            match organizer.organize_existing_files() {
-                Ok(count) => Ok(t_format("files_organized", &[&count.to_string()])),
-                Err(e) => Err(t_format("organize_failed", &[&e.to_string()]))
+            telemetry::record_feature("organize");
+            match organizer.organize_existing_files_up_to(limit.map(|limit| limit as usize)) {
+                Ok(count) => {
+                    if limit.is_some() {
+                        free_tier::record_files(count as u32);
+                    }
+                    Ok(t_format("files_organized", &[&count.to_string()]))
+                }
+                Err(e) => Err(t_format("organize_failed", &[&e.to_string()]).into())
             }
         }
-        Err(e) => Err(t_format("init_failed", &[&e.to_string()]))
+        Err(e) => Err(t_format("init_failed", &[&e.to_string()]).into())
     }
 }
 
@@ -69,50 +128,76 @@ async fn toggle_monitoring(
     folder_path: String,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<bool, String> {
-    // 检查订阅状态
-    {
+) -> Result<bool, free_tier::FreeTierError> {
+    // 试用期结束后按免费版限制监控的文件夹数，监控到的文件在监控线程中按每日额度处理
+    let limited = {
         let subscription = state.subscription.lock().await;
-        if !subscription.can_use_app() {
-            return Err(t("trial_ended"));
-        }
-    }
+        free_tier::is_limited(&subscription)
+    };
+    
+    // 重新切换监控后不再显示之前的错误
+    tray_status::clear_error();
     
     let mut organizers = state.organizers.lock().await;
     
     if let Some(organizer) = organizers.get_mut(&folder_path) {
         // 路径已经在监控，停止它
         organizer.stop_monitoring();
+        let notify = organizer.config.notifications_enabled_for(std::path::Path::new(&folder_path));
         organizers.remove(&folder_path);
         
+        if let Err(e) = Config::update_path_stats(std::path::Path::new(&folder_path), |stats| {
+            stats.monitoring_since = None;
+        }) {
+            log::error!("Failed to update path stats: {}", e);
+        }
+        
         // 发送通知
-        let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-            .builder()
-            .title(&t("monitoring_stopped_title"))
-            .body(&t("monitoring_stopped_body"))
-            .show();
+        if notify {
+            let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                .builder()
+                .title(&t("monitoring_stopped_title"))
+                .body(&t("monitoring_stopped_body"))
+                .show();
+        }
+        refresh_tray_menu(&app_handle);
             
         Ok(false)
     } else {
+        if limited {
+            free_tier::check_monitored_folders(organizers.len())?;
+        }
+        
         // 开始新的监控
-        match fileSortify::new(&folder_path) {
+        match FileSortify::new(&folder_path) {
             Ok(mut organizer) => {
                 organizer = organizer.with_app_handle(app_handle.clone());
                 if let Err(e) = organizer.start_monitoring() {
-                    return Err(t_format("monitoring_start_failed", &[&e.to_string()]));
+                    return Err(t_format("monitoring_start_failed", &[&e.to_string()]).into());
+                }
+                telemetry::record_feature("monitoring");
+                
+                if let Err(e) = Config::update_path_stats(std::path::Path::new(&folder_path), |stats| {
+                    stats.monitoring_since = Some(chrono::Utc::now().to_rfc3339());
+                }) {
+                    log::error!("Failed to update path stats: {}", e);
                 }
                 
                 // 发送通知
-                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-                    .builder()
-                    .title(&t("monitoring_started_title"))
-                    .body(&t_format("monitoring_started_body", &[&folder_path]))
-                    .show();
+                if organizer.config.notifications_enabled_for(std::path::Path::new(&folder_path)) {
+                    toast::show(&app_handle, toast::ActionableNotification {
+                        title: t("monitoring_started_title"),
+                        body: t_format("monitoring_started_body", &[&folder_path]),
+                        folder_path: Some(folder_path.clone()),
+                        undo: Vec::new(),
+                    });
+                }
                     
                 organizers.insert(folder_path.clone(), organizer);
+                refresh_tray_menu(&app_handle);
                 Ok(true)
             },
-            Err(e) => Err(t_format("init_failed", &[&e.to_string()]))
+            Err(e) => Err(t_format("init_failed", &[&e.to_string()]).into())
         }
     }
 }
@@ -130,12 +215,300 @@ async fn get_config() -> Result<Config, String> {
 // 修改save_config函数
 #[tauri::command]
 async fn save_config(config: Config) -> Result<String, String> {
+    if let Some(managed) = managed_config::ManagedConfig::load() {
+        managed.check_config_edit(&config)?;
+    }
+    
     match config.save() {
         Ok(_) => Ok(t("config_saved")),
         Err(e) => Err(t_format("save_config_failed", &[&e.to_string()]))
     }
 }
 
+// Tauri命令：获取托管配置状态（用于在界面上禁用被锁定的选项）
+#[tauri::command]
+async fn get_managed_config_status() -> Result<managed_config::ManagedConfigStatus, String> {
+    Ok(managed_config::ManagedConfig::status())
+}
+
+// 分类元数据命令
+
+// Tauri命令：获取所有分类的显示元数据
+#[tauri::command]
+async fn get_category_meta() -> Result<HashMap<String, config::CategoryMeta>, String> {
+    let config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    Ok(config.all_category_meta())
+}
+
+// Tauri命令：设置分类的显示元数据
+#[tauri::command]
+async fn set_category_meta(
+    category_id: String,
+    meta: config::CategoryMeta,
+) -> Result<config::CategoryMeta, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    config.set_category_meta(&category_id, meta)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(config.category_meta(&category_id))
+}
+
+// Tauri命令：打开系统设置中处理权限问题的页面（macOS 的“文件与文件夹”或“完全磁盘访问权限”）
+#[tauri::command]
+async fn open_permission_settings(kind: permissions::PermissionIssueKind, app_handle: tauri::AppHandle) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let url = permissions::settings_url(kind).ok_or_else(|| t("permission_settings_unavailable"))?;
+    app_handle.opener().open_url(url, None::<String>).map_err(|e| e.to_string())
+}
+
+// 分类预设命令
+
+// 未匹配扩展名命令
+
+// Tauri命令：获取整理时遇到的未匹配扩展名及出现次数
+#[tauri::command]
+async fn get_unmatched_extensions(folder: Option<String>) -> Result<Vec<unmatched::UnmatchedExtension>, String> {
+    Ok(unmatched::list(folder.as_deref()))
+}
+
+// Tauri命令：把扩展名归入某个分类
+#[tauri::command]
+async fn assign_extension_to_category(extension: String, category_id: String) -> Result<Config, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    config.assign_extension(&extension, &category_id)?;
+    
+    if let Some(managed) = managed_config::ManagedConfig::load() {
+        managed.check_config_edit(&config)?;
+    }
+    
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    let normalized = format!(".{}", extension.trim().trim_start_matches('.').to_lowercase());
+    if let Err(e) = unmatched::forget(&normalized) {
+        log::error!("Failed to update unmatched extensions: {}", e);
+    }
+    Ok(config)
+}
+
+// 配置导入命令
+
+// Tauri命令：预览导入配置与当前配置的差异（也支持 Hazel / DropIt 的规则文件）
+#[tauri::command]
+async fn preview_config_import(
+    path: String,
+    source: Option<rule_import::ImportSource>,
+) -> Result<config_import::ConfigImportDiff, String> {
+    let local = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let imported = rule_import::load(source.unwrap_or_default(), std::path::Path::new(&path))
+        .map_err(|e| t_format("import_config_failed", &[&e.to_string()]))?;
+    Ok(config_import::diff(&local, &imported))
+}
+
+// Tauri命令：按用户选择应用导入的配置
+#[tauri::command]
+async fn apply_config_import(
+    path: String,
+    source: Option<rule_import::ImportSource>,
+    selection: config_import::ImportSelection,
+) -> Result<Config, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let imported = rule_import::load(source.unwrap_or_default(), std::path::Path::new(&path))
+        .map_err(|e| t_format("import_config_failed", &[&e.to_string()]))?;
+    config_import::apply(&mut config, &imported, &selection)?;
+    
+    if let Some(managed) = managed_config::ManagedConfig::load() {
+        managed.check_config_edit(&config)?;
+    }
+    
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    telemetry::record_feature("config_import");
+    Ok(config)
+}
+
+// Tauri命令：列出内置的分类预设
+#[tauri::command]
+async fn list_presets() -> Result<Vec<presets::PresetInfo>, String> {
+    Ok(presets::list_presets())
+}
+
+// Tauri命令：应用分类预设（与现有配置合并）
+#[tauri::command]
+async fn apply_preset(name: String) -> Result<presets::PresetApplyReport, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let report = presets::apply_preset(&mut config, &name)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    telemetry::record_feature("preset");
+    Ok(report)
+}
+
+// 路径管理命令
+
+// Tauri命令：列出已配置的路径
+#[tauri::command]
+async fn list_paths() -> Result<Vec<config::PathConfig>, String> {
+    let config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    Ok(config.list_paths())
+}
+
+// Tauri命令：添加路径
+#[tauri::command]
+async fn add_path(path: String, name: Option<String>) -> Result<config::PathConfig, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let path_config = config.add_path(path, name)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(path_config)
+}
+
+// Tauri命令：删除路径（正在监控时先停止监控）
+#[tauri::command]
+async fn remove_path(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<config::PathConfig, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let removed = config.remove_path(&id)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    
+    let mut organizers = state.organizers.lock().await;
+    if let Some(mut organizer) = organizers.remove(&removed.path) {
+        organizer.stop_monitoring();
+    }
+    
+    Ok(removed)
+}
+
+// Tauri命令：更新路径配置
+#[tauri::command]
+async fn update_path(path_config: config::PathConfig) -> Result<config::PathConfig, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let updated = config.update_path(path_config)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(updated)
+}
+
+// Tauri命令：手动切换路径使用的规则集（传空值恢复按计划自动切换）
+#[tauri::command]
+async fn set_active_rule_set(
+    path_id: String,
+    rule_set_id: Option<String>,
+) -> Result<config::PathConfig, String> {
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    let path_config = config.set_active_rule_set(&path_id, rule_set_id)?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(path_config)
+}
+
+// Tauri命令：获取系统中开机启动的实际状态（而不是设置里最后保存的值）
+#[tauri::command]
+async fn get_autostart_status(state: State<'_, AppState>) -> Result<autostart::AutoStartReport, String> {
+    let scope = state.settings.lock().await.autostart_scope;
+    AutoStart::report(scope).map_err(|e| t_format("get_autostart_status_failed", &[&e]))
+}
+
+// Tauri命令：文件管理器右键菜单“用 FileSortify 整理”是否已启用
+#[tauri::command]
+async fn get_context_menu_enabled() -> Result<bool, String> {
+    context_menu::ContextMenu::is_enabled().map_err(|e| t_format("context_menu_update_failed", &[&e]))
+}
+
+// Tauri命令：启用或停用文件管理器右键菜单
+#[tauri::command]
+async fn set_context_menu_enabled(enabled: bool) -> Result<(), String> {
+    let result = if enabled {
+        context_menu::ContextMenu::enable()
+    } else {
+        context_menu::ContextMenu::disable()
+    };
+    result.map_err(|e| t_format("context_menu_update_failed", &[&e]))?;
+    telemetry::record_feature(if enabled { "context_menu_enabled" } else { "context_menu_disabled" });
+    Ok(())
+}
+
+// Tauri命令：重新生成本机接口的令牌，旧令牌立即失效
+#[tauri::command]
+async fn regenerate_local_api_token(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut settings = state.settings.lock().await;
+    settings.local_api.token = local_api::generate_token();
+    settings.save().map_err(|e| t_format("save_settings_failed", &[&e.to_string()]))?;
+    local_api::apply(&app_handle, &settings.local_api).await?;
+    Ok(settings.local_api.token.clone())
+}
+
+// Tauri命令：列出“询问我”模式下等待确认的文件
+#[tauri::command]
+async fn get_pending_moves() -> Result<Vec<pending::PendingMove>, String> {
+    Ok(pending::list())
+}
+
+// Tauri命令：列出被其他程序占用、等待稍后重试的文件
+#[tauri::command]
+async fn get_deferred_files() -> Result<Vec<deferred::DeferredFile>, String> {
+    Ok(deferred::list())
+}
+
+// Tauri命令：确认移动等待确认的文件
+#[tauri::command]
+async fn confirm_pending_move(id: String) -> Result<(), String> {
+    pending::resolve(&id, true)
+}
+
+// Tauri命令：拒绝移动，文件保留在原处
+#[tauri::command]
+async fn deny_pending_move(id: String) -> Result<(), String> {
+    pending::resolve(&id, false)
+}
+
+// 分类文件夹迁移命令
+
+// Tauri命令：检查路径下的分类文件夹，列出以不同语言重复创建的文件夹
+#[tauri::command]
+async fn get_category_folder_status(folder_path: String) -> Result<Vec<folder_migration::CategoryFolderStatus>, String> {
+    let config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    Ok(folder_migration::scan(&config, std::path::Path::new(&folder_path)))
+}
+
+// Tauri命令：把分类的重复文件夹合并到一个文件夹，并固定该文件夹名
+#[tauri::command]
+async fn migrate_category_folders(
+    folder_path: String,
+    category_id: String,
+    target: Option<String>,
+) -> Result<folder_migration::FolderMigrationReport, String> {
+    if let Some(name) = &target {
+        let name = name.trim();
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(t_format("invalid_folder_name", &[name]));
+        }
+    }
+    
+    let mut config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    if !config.categories_for(std::path::Path::new(&folder_path)).contains_key(&category_id) {
+        return Err(t_format("category_not_found", &[&category_id]));
+    }
+    
+    let report = folder_migration::migrate(
+        &mut config,
+        std::path::Path::new(&folder_path),
+        &category_id,
+        target.map(|name| name.trim().to_string()),
+    )
+    .map_err(|e| t_format("migrate_folders_failed", &[&e.to_string()]))?;
+    config.save().map_err(|e| t_format("save_config_failed", &[&e.to_string()]))?;
+    Ok(report)
+}
+
+// Tauri命令：获取路径统计信息
+#[tauri::command]
+async fn get_path_stats(folder_path: String) -> Result<config::PathStats, String> {
+    let config = Config::load().map_err(|e| t_format("load_config_failed", &[&e.to_string()]))?;
+    config
+        .find_path_by_folder(std::path::Path::new(&folder_path))
+        .map(|p| p.stats.clone())
+        .ok_or_else(|| t("path_config_not_found"))
+}
+
 // 修改select_folder函数
 #[tauri::command]
 async fn select_folder(app_handle: tauri::AppHandle) -> Result<Option<String>, String> {
@@ -180,6 +553,193 @@ async fn get_default_downloads_folder() -> Result<String, String> {
     Err(t("downloads_folder_not_found"))
 }
 
+// 配置同步命令
+
+// Tauri命令：获取同步设置
+#[tauri::command]
+async fn get_sync_settings() -> Result<config_sync::SyncSettings, String> {
+    config_sync::SyncSettings::load().map_err(|e| t_format("load_sync_settings_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：更新同步设置
+#[tauri::command]
+async fn update_sync_settings(settings: config_sync::SyncSettings) -> Result<String, String> {
+    match settings.save() {
+        Ok(_) => Ok(t("sync_settings_saved")),
+        Err(e) => Err(t_format("save_sync_settings_failed", &[&e.to_string()]))
+    }
+}
+
+// Tauri命令：立即同步配置
+#[tauri::command]
+async fn sync_config_now(
+    state: State<'_, AppState>,
+) -> Result<config_sync::SyncReport, String> {
+    let mut settings = config_sync::SyncSettings::load()
+        .map_err(|e| t_format("load_sync_settings_failed", &[&e.to_string()]))?;
+    
+    let (server_url, device_id) = {
+        let subscription = state.subscription.lock().await;
+        (subscription.webhook_server_url.clone(), subscription.device_id.clone())
+    };
+    
+    config_sync::sync(&mut settings, &server_url, &device_id).await
+}
+
+// 备份与恢复命令
+
+// Tauri命令：创建完整备份（历史记录由前端传入）
+#[tauri::command]
+async fn create_backup(
+    destination: Option<String>,
+    history: Option<serde_json::Value>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, String> {
+    let app_version = app_handle.package_info().version.to_string();
+    backup::create_backup(destination.map(std::path::PathBuf::from), history, &app_version)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| t_format("create_backup_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：从备份恢复，并重新加载内存中的订阅和设置
+#[tauri::command]
+async fn restore_backup(
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<backup::RestoreResult, String> {
+    let result = backup::restore_backup(std::path::Path::new(&path))
+        .map_err(|e| t_format("restore_backup_failed", &[&e.to_string()]))?;
+    
+    {
+        let mut subscription = state.subscription.lock().await;
+        *subscription = Subscription::load().unwrap_or_default();
+    }
+    {
+        let mut settings = state.settings.lock().await;
+        *settings = GeneralSettings::load().unwrap_or_default();
+        http::set_proxy(&settings.proxy);
+        webhooks::set(&settings.webhooks);
+        telemetry::set_enabled(settings.telemetry_enabled);
+        autostart::set_method(settings.autostart_method);
+    }
+    
+    Ok(result)
+}
+
+// 重置命令
+
+// Tauri命令：申请重置确认令牌
+#[tauri::command]
+async fn request_reset_token(
+    scope: reset::ResetScope,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let token = reset::ResetToken::new(scope);
+    let value = token.token.clone();
+    *state.pending_reset.lock().await = Some(token);
+    Ok(value)
+}
+
+// Tauri命令：备份后重置应用数据
+#[tauri::command]
+async fn reset_app_data(
+    scope: reset::ResetScope,
+    token: String,
+    history: Option<serde_json::Value>,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<reset::ResetResult, String> {
+    // 令牌只能使用一次
+    let pending = state.pending_reset.lock().await.take();
+    if !pending.map(|p| p.matches(&token, scope)).unwrap_or(false) {
+        return Err(t("reset_token_invalid"));
+    }
+    
+    let app_version = app_handle.package_info().version.to_string();
+    let result = reset::reset(scope, history, &app_version)
+        .map_err(|e| t_format("reset_failed", &[&e.to_string()]))?;
+    
+    let mut organizers = state.organizers.lock().await;
+    match scope {
+        reset::ResetScope::Config => {}
+        reset::ResetScope::History => {
+            for organizer in organizers.values_mut() {
+                organizer.clear_undo_history();
+            }
+        }
+        reset::ResetScope::Everything => {
+            for organizer in organizers.values_mut() {
+                organizer.stop_monitoring();
+            }
+            organizers.clear();
+            
+            let mut settings = state.settings.lock().await;
+            if settings.auto_start {
+                if let Err(e) = AutoStart::disable(settings.autostart_scope) {
+                    log::error!("Failed to disable auto start during reset: {}", e);
+                }
+            }
+            *settings = GeneralSettings::default();
+            autostart::set_method(settings.autostart_method);
+        }
+    }
+    drop(organizers);
+    refresh_tray_menu(&app_handle);
+    
+    Ok(result)
+}
+
+// Tauri命令：申请清除全部数据的确认令牌
+#[tauri::command]
+async fn request_purge_token(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let token = purge::PurgeToken::new();
+    let value = token.token.clone();
+    *state.pending_purge.lock().await = Some(token);
+    Ok(value)
+}
+
+// Tauri命令：清除本机和服务端的全部数据（包括订阅状态），不创建备份
+#[tauri::command]
+async fn purge_all_data(
+    token: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<purge::PurgeReport, String> {
+    // 令牌只能使用一次
+    let pending = state.pending_purge.lock().await.take();
+    if !pending.map(|p| p.matches(&token)).unwrap_or(false) {
+        return Err(t("purge_token_invalid"));
+    }
+    
+    {
+        let mut organizers = state.organizers.lock().await;
+        for organizer in organizers.values_mut() {
+            organizer.stop_monitoring();
+        }
+        organizers.clear();
+    }
+    {
+        let settings = state.settings.lock().await;
+        if settings.auto_start {
+            if let Err(e) = AutoStart::disable(settings.autostart_scope) {
+                log::error!("Failed to disable auto start during purge: {}", e);
+            }
+        }
+    }
+    
+    let subscription_clone = state.subscription.lock().await.clone();
+    let report = purge::purge_all_data(&subscription_clone).await;
+    
+    // 内存中的状态恢复为默认值，不写入磁盘
+    *state.settings.lock().await = GeneralSettings::default();
+    *state.subscription.lock().await = Subscription::new();
+    refresh_tray_menu(&app_handle);
+    log::info!("Purged {} files", report.removed_files.len());
+    Ok(report)
+}
+
 // 订阅相关命令
 
 // Tauri命令：获取订阅状态
@@ -204,12 +764,111 @@ async fn can_use_app(
 #[tauri::command]
 async fn can_use_app_secure(
     state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
 ) -> Result<bool, String> {
     let mut subscription = state.subscription.lock().await;
+    let previous_revocation = subscription.revocation;
     let can_use = subscription.can_use_app_secure().await;
+    subscription_refresh::notify_revocation(&app_handle, previous_revocation, &subscription);
     Ok(can_use)
 }
 
+// Tauri命令：获取免费版的限制和今天的使用情况
+#[tauri::command]
+async fn get_free_tier_status(
+    state: State<'_, AppState>,
+) -> Result<free_tier::FreeTierStatus, String> {
+    let subscription = state.subscription.lock().await;
+    Ok(free_tier::status(&subscription))
+}
+
+// 推荐码命令
+
+// Tauri命令：获取自己的推荐码，并同步服务端记录的推荐奖励
+#[tauri::command]
+async fn get_referral_info(
+    state: State<'_, AppState>,
+) -> Result<referral::ReferralInfo, String> {
+    let (server_url, device_id) = {
+        let subscription = state.subscription.lock().await;
+        (subscription.webhook_server_url.clone(), subscription.device_id.clone())
+    };
+    let response = referral::fetch(&server_url, &device_id).await?;
+    
+    let mut subscription = state.subscription.lock().await;
+    subscription.apply_referral_grants(response.grants)
+        .map_err(|e| t_format("save_subscription_failed", &[&e.to_string()]))?;
+    Ok(referral::ReferralInfo::new(response.code, &subscription))
+}
+
+// Tauri命令：兑换朋友的推荐码以延长试用期，每台设备只能兑换一次
+#[tauri::command]
+async fn redeem_referral_code(
+    code: String,
+    state: State<'_, AppState>,
+) -> Result<referral::ReferralInfo, String> {
+    let code = referral::normalize_code(&code).ok_or_else(|| t("referral_code_invalid"))?;
+    let (server_url, device_id) = {
+        let subscription = state.subscription.lock().await;
+        // 推荐奖励只延长试用期，已购买的用户不需要兑换
+        if !matches!(subscription.status, subscription::SubscriptionStatus::Trial) {
+            return Err(t("referral_trial_only"));
+        }
+        if subscription.redeemed_referral_code.is_some() {
+            return Err(t("referral_already_redeemed"));
+        }
+        (subscription.webhook_server_url.clone(), subscription.device_id.clone())
+    };
+    let response = referral::redeem(&server_url, &device_id, &code).await?;
+    
+    let mut subscription = state.subscription.lock().await;
+    subscription.redeemed_referral_code = Some(code);
+    subscription.apply_referral_grants(response.grants)
+        .map_err(|e| t_format("save_subscription_failed", &[&e.to_string()]))?;
+    subscription.save()
+        .map_err(|e| t_format("save_subscription_failed", &[&e.to_string()]))?;
+    Ok(referral::ReferralInfo::new(response.code, &subscription))
+}
+
+// Tauri命令：兑换优惠码。折扣码在下次 Creem 结账时使用，赠送码直接激活对应的计划
+#[tauri::command]
+async fn redeem_promo_code(
+    code: String,
+    state: State<'_, AppState>,
+) -> Result<promo::PromoRedemption, promo::PromoError> {
+    let code = promo::normalize_code(&code).ok_or_else(promo::PromoError::invalid)?;
+    let (server_url, device_id) = {
+        let subscription = state.subscription.lock().await;
+        // 已买断的用户无法再使用折扣或赠送
+        if matches!(subscription.plan, SubscriptionPlan::Lifetime) && subscription.is_subscription_active() {
+            return Err(promo::PromoError::AlreadySubscribed { message: t("promo_already_subscribed") });
+        }
+        (subscription.webhook_server_url.clone(), subscription.device_id.clone())
+    };
+    let response = promo::redeem(&server_url, &device_id, &code).await?;
+    
+    let mut subscription = state.subscription.lock().await;
+    match response {
+        promo::RedeemResponse::Discount { discount_percent } => {
+            subscription.promo_code = Some(code.clone());
+            subscription.save().map_err(promo::PromoError::request)?;
+            Ok(promo::PromoRedemption::Discount { code, discount_percent })
+        }
+        promo::RedeemResponse::Giveaway { grant_id, plan, expires_at } => {
+            let plan = SubscriptionPlan::from_name(&plan)
+                .filter(|plan| !matches!(plan, SubscriptionPlan::Free))
+                .ok_or_else(|| promo::PromoError::request(format!("unknown plan {}", plan)))?;
+            subscription.activate_promo_subscription(plan.clone(), grant_id, expires_at)
+                .map_err(promo::PromoError::request)?;
+            Ok(promo::PromoRedemption::Giveaway {
+                code,
+                plan,
+                expires_at: subscription.subscription_end_date,
+            })
+        }
+    }
+}
+
 // Tauri命令：获取套餐信息 (API: /api/packages)
 #[tauri::command]
 async fn get_packages() -> Result<PackagesResponse, String> {
@@ -220,7 +879,7 @@ async fn get_packages() -> Result<PackagesResponse, String> {
 #[tauri::command]
 async fn fetch_packages_from_server(
     state: State<'_, AppState>,
-) -> Result<PackagesResponse, String> {
+) -> Result<PackagesResponse, CommandError> {
     // 先克隆订阅数据，避免跨异步边界持有锁
     let mut subscription_clone = {
         let subscription = state.subscription.lock().await;
@@ -236,7 +895,7 @@ async fn fetch_packages_from_server(
             }
             Ok(packages)
         },
-        Err(e) => Err(t_format("fetch_packages_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::FetchPackagesFailed { reason }).into())
     }
 }
 
@@ -246,13 +905,10 @@ async fn activate_subscription(
     plan: String,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let mut subscription = state.subscription.lock().await;
     
-    let subscription_plan = match plan.as_str() {
-        "lifetime" => SubscriptionPlan::Lifetime,
-        _ => return Err(t("invalid_subscription_plan")),
-    };
+    let subscription_plan = SubscriptionPlan::from_name(&plan).ok_or(SubscriptionError::InvalidPlan { plan })?;
     
     match subscription.activate_subscription(subscription_plan) {
         Ok(_) => {
@@ -265,7 +921,7 @@ async fn activate_subscription(
                 
             Ok(t("purchase_activation_success"))
         }
-        Err(e) => Err(t_format("purchase_activation_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::ActivationFailed { reason }).into())
     }
 }
 
@@ -274,7 +930,7 @@ async fn activate_subscription(
 async fn cancel_subscription(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let mut subscription = state.subscription.lock().await;
     
     match subscription.cancel_subscription() {
@@ -288,14 +944,14 @@ async fn cancel_subscription(
                 
             Ok(t("subscription_cancelled"))
         }
-        Err(e) => Err(t_format("cancel_subscription_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::CancelFailed { reason }).into())
     }
 }
 
 // Tauri命令：打开支付页面 (已禁用，仅保留兼容性)
 #[tauri::command]
-async fn open_payment_page(_plan: String, _app_handle: tauri::AppHandle) -> Result<(), String> {
-    Err(t("payment_disabled"))
+async fn open_payment_page(_plan: String, _app_handle: tauri::AppHandle) -> Result<(), CommandError> {
+    Err(SubscriptionError::PaymentDisabled.into())
 }
 
 // 修改verify_apple_receipt函数
@@ -304,7 +960,7 @@ async fn verify_apple_receipt(
     receipt_data: String,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     // 先克隆订阅数据，避免跨异步边界持有锁
     let mut subscription_clone = {
         let subscription = state.subscription.lock().await;
@@ -328,7 +984,7 @@ async fn verify_apple_receipt(
                 
             Ok(t("apple_receipt_verify_success"))
         }
-        Err(e) => Err(t_format("apple_receipt_verify_failed_format", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::ReceiptVerificationFailed { reason }).into())
     }
 }
 
@@ -336,7 +992,7 @@ async fn verify_apple_receipt(
 #[tauri::command]
 async fn refresh_apple_subscription(
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     // 先克隆订阅数据，避免跨异步边界持有锁
     let mut subscription_clone = {
         let subscription = state.subscription.lock().await;
@@ -353,87 +1009,294 @@ async fn refresh_apple_subscription(
             
             Ok(t("subscription_status_refreshed"))
         }
-        Err(e) => Err(t_format("refresh_subscription_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::RefreshFailed { reason }).into())
+    }
+}
+
+// 处理 StoreKit 回调：转发为前端事件，购买或恢复完成后自动验证收据，验证成功才完成交易
+#[cfg(target_os = "macos")]
+async fn handle_storekit_events(
+    app_handle: tauri::AppHandle,
+    mut events: tokio::sync::mpsc::UnboundedReceiver<storekit_bridge::StoreKitEvent>,
+) {
+    use storekit_bridge::{StoreKitEvent, StoreKitManager};
+    use tauri::Emitter;
+
+    while let Some(event) = events.recv().await {
+        let transactions = match event {
+            StoreKitEvent::ProductsReceived(products) => {
+                let _ = app_handle.emit("apple-products-received", &products);
+                continue;
+            }
+            StoreKitEvent::PurchaseFailed(message) => {
+                let _ = app_handle.emit("apple-purchase-failed", &message);
+                continue;
+            }
+            StoreKitEvent::PurchaseCompleted(transaction) => {
+                let _ = app_handle.emit("apple-purchase-completed", &transaction);
+                vec![transaction]
+            }
+            StoreKitEvent::RestoreCompleted(transactions) => {
+                let _ = app_handle.emit("apple-restore-completed", &transactions);
+                transactions
+            }
+        };
+        if transactions.is_empty() {
+            continue;
+        }
+
+        let store_manager = StoreKitManager::new();
+        let receipt_data = match store_manager.get_receipt_data() {
+            Ok(receipt_data) => receipt_data,
+            Err(e) => {
+                log::error!("Failed to read App Store receipt: {}", e);
+                let _ = app_handle.emit("apple-receipt-verify-failed", &e);
+                continue;
+            }
+        };
+
+        let state = app_handle.state::<AppState>();
+        let mut subscription_clone = {
+            let subscription = state.subscription.lock().await;
+            subscription.clone()
+        };
+        match subscription_clone.verify_apple_receipt(receipt_data).await {
+            Ok(()) => {
+                {
+                    let mut subscription = state.subscription.lock().await;
+                    *subscription = subscription_clone.clone();
+                }
+                // 验证成功后才完成交易，失败的交易留在队列中，下次启动时 StoreKit 会重新发送
+                for transaction in &transactions {
+                    if let Err(e) = store_manager.finish_transaction(&transaction.transaction_identifier) {
+                        log::warn!("Failed to finish transaction {}: {}", transaction.transaction_identifier, e);
+                    }
+                }
+                let _ = app_handle.emit("apple-receipt-verified", &subscription_clone);
+                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                    .builder()
+                    .title(&t("apple_receipt_verify_success_title"))
+                    .body(&t("apple_receipt_verify_success"))
+                    .show();
+            }
+            Err(e) => {
+                log::error!("App Store receipt verification failed: {}", e);
+                let _ = app_handle.emit(
+                    "apple-receipt-verify-failed",
+                    &t_format("apple_receipt_verify_failed_format", &[&e.to_string()]),
+                );
+            }
+        }
     }
 }
 
 // 修改get_apple_products函数
 #[tauri::command]
-async fn get_apple_products() -> Result<serde_json::Value, String> {
-    Err(t("payment_disabled"))
+async fn get_apple_products() -> Result<serde_json::Value, CommandError> {
+    Err(SubscriptionError::PaymentDisabled.into())
 }
 
 // 修改start_apple_purchase函数
 #[tauri::command]
-async fn start_apple_purchase(product_id: String, _state: State<'_, AppState>) -> Result<String, String> {
+async fn start_apple_purchase(product_id: String, _state: State<'_, AppState>) -> Result<String, CommandError> {
     #[cfg(target_os = "macos")]
     {
         use crate::storekit_bridge::StoreKitManager;
         
         let mut store_manager = StoreKitManager::new();
-        store_manager.initialize().map_err(|e| t_format("apple_purchase_init_failed", &[&e.to_string()]))?;
+        store_manager.initialize().map_err(|e| SubscriptionError::StoreKitInitFailed { reason: e.to_string() })?;
         
-        store_manager.purchase_product(&product_id).map_err(|e| t_format("apple_purchase_start_failed", &[&e.to_string()]))?;
+        store_manager.purchase_product(&product_id).map_err(|e| SubscriptionError::AppStorePurchaseFailed { reason: e.to_string() })?;
         
         Ok(t("apple_purchase_started"))
     }
     
     #[cfg(not(target_os = "macos"))]
     {
-        Err(t("apple_purchase_macos_only_format"))
+        Err(SubscriptionError::AppStoreUnavailable.into())
     }
 }
 
 // Tauri命令：恢复购买
 #[tauri::command]
-async fn restore_apple_purchases(_state: State<'_, AppState>) -> Result<String, String> {
+async fn restore_apple_purchases(_state: State<'_, AppState>) -> Result<String, CommandError> {
     #[cfg(target_os = "macos")]
     {
         use crate::storekit_bridge::StoreKitManager;
         
         let mut store_manager = StoreKitManager::new();
-        store_manager.initialize().map_err(|e| t_format("storekit_init_failed", &[&e.to_string()]))?;
+        store_manager.initialize().map_err(|e| SubscriptionError::StoreKitInitFailed { reason: e.to_string() })?;
         
-        store_manager.restore_purchases().map_err(|e| t_format("restore_purchases_failed", &[&e.to_string()]))?;
+        store_manager.restore_purchases().map_err(|e| SubscriptionError::AppStoreRestoreFailed { reason: e.to_string() })?;
         
         Ok(t("purchase_restore_started"))
     }
     
     #[cfg(not(target_os = "macos"))]
     {
-        Err(t("apple_purchase_macos_only_format"))
+        Err(SubscriptionError::AppStoreUnavailable.into())
+    }
+}
+
+// Microsoft Store 相关命令
+
+// 主窗口句柄，商店对话框需要关联到它
+#[cfg(target_os = "windows")]
+fn main_window_hwnd(app_handle: &tauri::AppHandle) -> Result<isize, SubscriptionError> {
+    let window = app_handle.get_webview_window("main").ok_or(SubscriptionError::MainWindowNotFound)?;
+    let hwnd = window.hwnd().map_err(|_| SubscriptionError::MainWindowNotFound)?;
+    Ok(hwnd.0 as isize)
+}
+
+// 按 Microsoft Store 授权激活订阅
+#[cfg(target_os = "windows")]
+async fn apply_ms_store_license(
+    state: &State<'_, AppState>,
+    license: msstore_bridge::MsStoreLicense,
+) -> Result<(), SubscriptionError> {
+    let mut subscription = state.subscription.lock().await;
+    subscription
+        .activate_ms_store_subscription(license.plan(), license.sku_store_id.clone(), license.expiration_date)
+        .map_err(|e| SubscriptionError::wrap(e, |reason| SubscriptionError::MsStorePurchaseFailed { reason }))
+}
+
+// Tauri命令：获取 Microsoft Store 中的订阅加载项
+#[tauri::command]
+async fn get_ms_store_products(app_handle: tauri::AppHandle) -> Result<Vec<msstore_bridge::MsStoreProduct>, CommandError> {
+    #[cfg(target_os = "windows")]
+    {
+        let hwnd = main_window_hwnd(&app_handle)?;
+        let products = tokio::task::spawn_blocking(move || msstore_bridge::MsStoreManager::new(hwnd)?.get_products())
+            .await
+            .map_err(|e| SubscriptionError::MsStoreProductsFailed { reason: e.to_string() })?
+            .map_err(|reason| SubscriptionError::MsStoreProductsFailed { reason })?;
+        Ok(products)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = app_handle;
+        Err(SubscriptionError::MsStoreUnavailable.into())
+    }
+}
+
+// Tauri命令：在应用内通过 Microsoft Store 购买订阅
+#[tauri::command]
+async fn start_ms_store_purchase(
+    product_id: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, CommandError> {
+    #[cfg(target_os = "windows")]
+    {
+        use msstore_bridge::{MsStoreManager, MsStorePurchaseStatus};
+
+        let hwnd = main_window_hwnd(&app_handle)?;
+        let (status, license) = tokio::task::spawn_blocking(move || {
+            let manager = MsStoreManager::new(hwnd)?;
+            let status = manager.purchase(&product_id)?;
+            let license = match status {
+                MsStorePurchaseStatus::Succeeded | MsStorePurchaseStatus::AlreadyPurchased => manager.active_license()?,
+                _ => None,
+            };
+            Ok::<_, String>((status, license))
+        })
+        .await
+        .map_err(|e| SubscriptionError::MsStorePurchaseFailed { reason: e.to_string() })?
+        .map_err(|reason| SubscriptionError::MsStorePurchaseFailed { reason })?;
+
+        match (status, license) {
+            (MsStorePurchaseStatus::NotPurchased, _) => Err(SubscriptionError::PurchaseCancelled.into()),
+            (MsStorePurchaseStatus::NetworkError | MsStorePurchaseStatus::ServerError, _) => {
+                Err(SubscriptionError::MsStorePurchaseFailed { reason: format!("{:?}", status) }.into())
+            }
+            (_, None) => Err(SubscriptionError::NoStoreLicense.into()),
+            (_, Some(license)) => {
+                apply_ms_store_license(&state, license).await?;
+                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                    .builder()
+                    .title(&t("purchase_success_title"))
+                    .body(&t("purchase_success_body"))
+                    .show();
+                Ok(t("ms_store_purchase_success"))
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (product_id, state, app_handle);
+        Err(SubscriptionError::MsStoreUnavailable.into())
+    }
+}
+
+// Tauri命令：重新读取 Microsoft Store 授权（续费、恢复购买）
+#[tauri::command]
+async fn refresh_ms_store_license(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, CommandError> {
+    #[cfg(target_os = "windows")]
+    {
+        let hwnd = main_window_hwnd(&app_handle)?;
+        let license = tokio::task::spawn_blocking(move || msstore_bridge::MsStoreManager::new(hwnd)?.active_license())
+            .await
+            .map_err(|e| SubscriptionError::MsStorePurchaseFailed { reason: e.to_string() })?
+            .map_err(|reason| SubscriptionError::MsStorePurchaseFailed { reason })?
+            .ok_or(SubscriptionError::NoStoreLicense)?;
+        apply_ms_store_license(&state, license).await?;
+        Ok(t("subscription_status_refreshed"))
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (state, app_handle);
+        Err(SubscriptionError::MsStoreUnavailable.into())
     }
 }
 
 // Tauri命令：获取本地收据数据
 #[tauri::command]
-async fn get_local_receipt_data() -> Result<String, String> {
+async fn get_local_receipt_data() -> Result<String, CommandError> {
     #[cfg(target_os = "macos")]
     {
         use crate::storekit_bridge::StoreKitManager;
         
         let store_manager = StoreKitManager::new();
-        store_manager.get_receipt_data().map_err(|e| t_format("receipt_data_failed", &[&e.to_string()]))
+        let receipt_data = store_manager
+            .get_receipt_data()
+            .map_err(|e| SubscriptionError::ReceiptReadFailed { reason: e.to_string() })?;
+        Ok(receipt_data)
     }
     
     #[cfg(not(target_os = "macos"))]
     {
-        Err(t("receipt_macos_only"))
+        Err(SubscriptionError::ReceiptUnavailable.into())
     }
 }
 
 // Creem 订阅相关命令
 
+// 支付成功后跳转的地址：优先使用本机回调监听，无法监听时使用 filesortify:// 深度链接
+async fn checkout_success_url(app_handle: tauri::AppHandle) -> String {
+    match payment_callback::start(app_handle).await {
+        Ok(url) => url,
+        Err(e) => {
+            log::warn!("Failed to start payment callback listener: {}", e);
+            deep_link::PAYMENT_SUCCESS_URL.to_string()
+        }
+    }
+}
+
 // Tauri命令：创建 Creem 支付会话
 #[tauri::command]
 async fn create_creem_session(
     plan: String,
     state: State<'_, AppState>,
-) -> Result<subscription::CreemSessionResponse, String> {
-    let subscription_plan = match plan.as_str() {
-        "lifetime" => SubscriptionPlan::Lifetime,
-        _ => return Err(t("invalid_subscription_plan")),
-    };
+    app_handle: tauri::AppHandle,
+) -> Result<subscription::CreemSessionResponse, CommandError> {
+    let subscription_plan = SubscriptionPlan::from_name(&plan).ok_or(SubscriptionError::InvalidPlan { plan })?;
+    let success_url = checkout_success_url(app_handle).await;
 
     // 先克隆订阅数据，避免跨异步边界持有锁
     let mut subscription_clone = {
@@ -441,7 +1304,7 @@ async fn create_creem_session(
         subscription.clone()
     };
 
-    match subscription_clone.create_creem_session(subscription_plan).await {
+    match subscription_clone.create_creem_session(subscription_plan, &success_url).await {
         Ok(session_response) => {
             // 更新状态
             {
@@ -450,63 +1313,180 @@ async fn create_creem_session(
             }
             Ok(session_response)
         }
-        Err(e) => Err(t_format("create_payment_session_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::CreateSessionFailed { reason }).into())
     }
 }
 
 // Tauri命令：检查 Creem 支付状态
 #[tauri::command]
-async fn check_creem_payment_status(
+async fn check_creem_payment_status(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<subscription::CreemPaymentStatus, CommandError> {
+    // 先克隆订阅数据，避免跨异步边界持有锁
+    let mut subscription_clone = {
+        let subscription = state.subscription.lock().await;
+        subscription.clone()
+    };
+
+    match subscription_clone.check_creem_payment_status().await {
+        Ok(payment_status) => {
+            // 如果支付完成，发送通知（只要有userPackages返回就表示已经购买了）
+            if !payment_status.user_packages.is_empty() {
+                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                    .builder()
+                    .title(&t("purchase_success_title"))
+                    .body(&t("purchase_success_body"))
+                    .show();
+            }
+
+            // 多席位授权：在授权上登记当前设备，席位已满时通知前端
+            if !payment_status.user_packages.is_empty() {
+                seats::register_after_purchase(&app_handle, &subscription_clone).await;
+            }
+
+            // 更新状态
+            {
+                let mut subscription = state.subscription.lock().await;
+                *subscription = subscription_clone;
+            }
+
+            Ok(payment_status)
+        }
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::CheckStatusFailed { reason }).into())
+    }
+}
+
+// Tauri命令：通过 Creem 订单号或购买邮箱恢复购买（清除配置或更换电脑后使用）
+#[tauri::command]
+async fn restore_purchase(
+    identifier: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, CommandError> {
+    if identifier.trim().is_empty() {
+        return Err(SubscriptionError::RestoreIdentifierRequired.into());
+    }
+
+    // 先克隆订阅数据，避免跨异步边界持有锁
+    let mut subscription_clone = {
+        let subscription = state.subscription.lock().await;
+        subscription.clone()
+    };
+
+    let restored = subscription_clone
+        .restore_purchase(&identifier)
+        .await
+        .map_err(|e| SubscriptionError::wrap(e, |reason| SubscriptionError::RestoreFailed { reason }))?;
+    if !restored {
+        return Err(SubscriptionError::NoPurchaseFound.into());
+    }
+
+    // 恢复的授权同样占用一个席位
+    seats::register_after_purchase(&app_handle, &subscription_clone).await;
+
+    {
+        let mut subscription = state.subscription.lock().await;
+        *subscription = subscription_clone;
+    }
+
+    let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+        .builder()
+        .title(&t("purchase_restored_title"))
+        .body(&t("purchase_success_body"))
+        .show();
+    Ok(t("purchase_restored"))
+}
+
+// Tauri命令：打开 Creem 支付页面
+#[tauri::command]
+async fn open_creem_payment_page(
+    plan: String,
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<String, CommandError> {
+    // 按服务端配置的支付渠道创建结账会话
+    let session = create_checkout_session(plan, state, app_handle.clone()).await?;
+
+    // 打开支付页面
+    use tauri_plugin_opener::OpenerExt;
+    
+    if let Err(e) = app_handle.opener().open_url(&session.checkout_url, None::<String>) {
+        return Err(SubscriptionError::OpenPaymentPageFailed { reason: e.to_string() }.into());
+    }
+
+    Ok(session.session_id)
+}
+
+// Tauri命令：获取服务端配置的支付渠道
+#[tauri::command]
+async fn get_payment_provider(
+    state: State<'_, AppState>,
+) -> Result<payment::PaymentProviderKind, String> {
+    let server_url = state.subscription.lock().await.webhook_server_url.clone();
+    Ok(payment::provider_for(&server_url).await.kind())
+}
+
+// Tauri命令：通过服务端配置的支付渠道（Creem 或 Stripe）创建结账会话
+#[tauri::command]
+async fn create_checkout_session(
+    plan: String,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<subscription::CreemPaymentStatus, String> {
+) -> Result<payment::CheckoutSession, CommandError> {
+    let subscription_plan = SubscriptionPlan::from_name(&plan).ok_or(SubscriptionError::InvalidPlan { plan })?;
+    let success_url = checkout_success_url(app_handle).await;
+
     // 先克隆订阅数据，避免跨异步边界持有锁
     let mut subscription_clone = {
         let subscription = state.subscription.lock().await;
         subscription.clone()
     };
 
-    match subscription_clone.check_creem_payment_status().await {
-        Ok(payment_status) => {
-            // 如果支付完成，发送通知（只要有userPackages返回就表示已经购买了）
-            if !payment_status.user_packages.is_empty() {
-                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-                    .builder()
-                    .title(&t("purchase_success_title"))
-                    .body(&t("purchase_success_body"))
-                    .show();
-            }
-
-            // 更新状态
-            {
-                let mut subscription = state.subscription.lock().await;
-                *subscription = subscription_clone;
-            }
+    let provider = payment::provider_for(&subscription_clone.webhook_server_url).await;
+    let session = provider
+        .create_checkout(&mut subscription_clone, subscription_plan, &success_url)
+        .await
+        .map_err(|e| SubscriptionError::wrap(e, |reason| SubscriptionError::CreateSessionFailed { reason }))?;
 
-            Ok(payment_status)
-        }
-        Err(e) => Err(t_format("check_payment_status_failed", &[&e.to_string()]))
+    {
+        let mut subscription = state.subscription.lock().await;
+        *subscription = subscription_clone;
     }
+    Ok(session)
 }
 
-// Tauri命令：打开 Creem 支付页面
+// Tauri命令：通过服务端配置的支付渠道检查支付状态，支付完成时激活订阅
 #[tauri::command]
-async fn open_creem_payment_page(
-    plan: String,
+async fn check_payment_status(
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
-) -> Result<String, String> {
-    // 创建支付会话
-    let session_response = create_creem_session(plan, state).await?;
+) -> Result<bool, CommandError> {
+    // 先克隆订阅数据，避免跨异步边界持有锁
+    let mut subscription_clone = {
+        let subscription = state.subscription.lock().await;
+        subscription.clone()
+    };
 
-    // 打开支付页面
-    use tauri_plugin_opener::OpenerExt;
-    
-    if let Err(e) = app_handle.opener().open_url(&session_response.checkout_url, None::<String>) {
-        return Err(t_format("open_payment_page_failed", &[&e.to_string()]));
+    let provider = payment::provider_for(&subscription_clone.webhook_server_url).await;
+    let paid = provider
+        .check_status(&mut subscription_clone)
+        .await
+        .map_err(|e| SubscriptionError::wrap(e, |reason| SubscriptionError::CheckStatusFailed { reason }))?;
+
+    {
+        let mut subscription = state.subscription.lock().await;
+        *subscription = subscription_clone;
     }
 
-    Ok(session_response.user_package.id)
+    if paid {
+        let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+            .builder()
+            .title(&t("purchase_success_title"))
+            .body(&t("purchase_success_body"))
+            .show();
+    }
+    Ok(paid)
 }
 
 // Tauri命令：设置 webhook 服务器 URL
@@ -514,12 +1494,12 @@ async fn open_creem_payment_page(
 async fn set_webhook_server_url(
     url: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<String, CommandError> {
     let mut subscription = state.subscription.lock().await;
     
     match subscription.set_webhook_server_url(url) {
         Ok(_) => Ok(t("webhook_url_updated")),
-        Err(e) => Err(t_format("update_url_failed", &[&e.to_string()]))
+        Err(e) => Err(SubscriptionError::wrap(e, |reason| SubscriptionError::UpdateServerUrlFailed { reason }).into())
     }
 }
 
@@ -532,6 +1512,43 @@ async fn get_current_session_info(
     Ok(subscription.get_current_session_info())
 }
 
+// 多席位授权命令
+
+// 读取授权 ID 和服务器信息，没有购买时返回错误
+async fn seat_license(state: &State<'_, AppState>) -> Result<(String, String, String), seats::SeatError> {
+    let subscription = state.subscription.lock().await;
+    let license_id = subscription.license_id().ok_or_else(seats::SeatError::no_license)?;
+    Ok((subscription.webhook_server_url.clone(), license_id, subscription.device_id.clone()))
+}
+
+// Tauri命令：在授权上登记当前设备
+#[tauri::command]
+async fn register_device_seat(
+    state: State<'_, AppState>,
+) -> Result<seats::SeatUsage, seats::SeatError> {
+    let (server_url, license_id, device_id) = seat_license(&state).await?;
+    seats::register_device(&server_url, &license_id, &device_id).await
+}
+
+// Tauri命令：获取授权的席位使用情况（已用/总数和已登记的设备）
+#[tauri::command]
+async fn get_seat_usage(
+    state: State<'_, AppState>,
+) -> Result<seats::SeatUsage, seats::SeatError> {
+    let (server_url, license_id, device_id) = seat_license(&state).await?;
+    seats::get_usage(&server_url, &license_id, &device_id).await
+}
+
+// Tauri命令：释放某台设备占用的席位
+#[tauri::command]
+async fn release_device_seat(
+    target_device_id: String,
+    state: State<'_, AppState>,
+) -> Result<seats::SeatUsage, seats::SeatError> {
+    let (server_url, license_id, device_id) = seat_license(&state).await?;
+    seats::release_device(&server_url, &license_id, &target_device_id, &device_id).await
+}
+
 // Tauri命令：显示主窗口
 #[tauri::command]
 async fn show_main_window(app_handle: tauri::AppHandle) -> Result<(), String> {
@@ -561,6 +1578,55 @@ async fn get_app_version(app_handle: tauri::AppHandle) -> Result<String, String>
     Ok(app_handle.package_info().version.to_string())
 }
 
+// Tauri命令：导出诊断包（日志、脱敏后的配置和设置、版本信息、监控状态），返回文件路径
+#[tauri::command]
+async fn export_diagnostics(
+    destination: Option<String>,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let app_version = app_handle.package_info().version.to_string();
+    let monitored_folders: Vec<String> = state.organizers.lock().await.keys().cloned().collect();
+    diagnostics::export(destination.map(std::path::PathBuf::from), &app_version, &monitored_folders)
+        .map(|path| path.to_string_lossy().to_string())
+        .map_err(|e| t_format("export_diagnostics_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：预览下一次遥测上报的完整内容
+#[tauri::command]
+async fn get_telemetry_preview(app_handle: tauri::AppHandle) -> Result<telemetry::TelemetryReport, String> {
+    Ok(telemetry::preview(&app_handle.package_info().version.to_string()))
+}
+
+// 崩溃报告命令
+
+// Tauri命令：获取本地保存的崩溃报告（默认只返回还没发送过的），用于异常退出后询问用户是否发送
+#[tauri::command]
+async fn get_crash_reports(include_sent: Option<bool>) -> Result<Vec<crash_report::CrashReport>, String> {
+    Ok(crash_report::list(include_sent.unwrap_or(false)))
+}
+
+// Tauri命令：用户同意后发送崩溃报告
+#[tauri::command]
+async fn send_crash_report(id: String, state: State<'_, AppState>) -> Result<(), String> {
+    let server_url = state.subscription.lock().await.webhook_server_url.clone();
+    crash_report::upload(&id, &server_url)
+        .await
+        .map_err(|e| t_format("send_crash_report_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：删除崩溃报告
+#[tauri::command]
+async fn dismiss_crash_report(id: String) -> Result<(), String> {
+    crash_report::dismiss(&id).map_err(|e| t_format("dismiss_crash_report_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：获取日志文件所在的目录
+#[tauri::command]
+async fn get_log_directory() -> Result<Option<String>, String> {
+    Ok(logging::log_dir().map(|dir| dir.to_string_lossy().to_string()))
+}
+
 // Tauri命令：获取通用设置
 #[tauri::command]
 async fn get_general_settings(
@@ -574,26 +1640,75 @@ async fn get_general_settings(
 #[tauri::command]
 async fn update_general_settings(
     settings: GeneralSettings,
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
     let mut current_settings = state.settings.lock().await;
     let old_auto_start = current_settings.auto_start;
     
-    // 处理开机启动设置变化
-    if old_auto_start != settings.auto_start {
+    // 托管配置锁定的设置不允许修改
+    let mut settings = settings;
+    if let Some(managed) = managed_config::ManagedConfig::load() {
+        let mut enforced = settings.clone();
+        managed.apply_to_settings(&mut enforced);
+        if serde_json::to_value(&enforced).ok() != serde_json::to_value(&settings).ok() {
+            return Err(t("managed_setting_locked"));
+        }
+        settings = enforced;
+    }
+    
+    // 处理开机启动设置变化；切换登记范围或方式时先移除旧的登记
+    if old_auto_start != settings.auto_start
+        || current_settings.autostart_scope != settings.autostart_scope
+        || current_settings.autostart_method != settings.autostart_method
+    {
+        if old_auto_start {
+            if let Err(e) = AutoStart::disable(current_settings.autostart_scope) {
+                return Err(t_format("disable_autostart_failed", &[&e.to_string()]));
+            }
+        }
+        autostart::set_method(settings.autostart_method);
         if settings.auto_start {
-            if let Err(e) = AutoStart::enable() {
+            if let Err(e) = AutoStart::enable(settings.autostart_scope) {
                 return Err(t_format("enable_autostart_failed", &[&e.to_string()]));
             }
-        } else {
-            if let Err(e) = AutoStart::disable() {
-                return Err(t_format("disable_autostart_failed", &[&e.to_string()]));
-            }
         }
     }
     
+    logging::set_level(&settings.log_level)?;
+    settings.proxy.validate()?;
+    http::set_proxy(&settings.proxy);
+    webhooks::validate_all(&settings.webhooks)?;
+    webhooks::set(&settings.webhooks);
+    settings.weekly_digest.validate()?;
+    telemetry::set_enabled(settings.telemetry_enabled);
+    
+    if current_settings.organize_hotkey != settings.organize_hotkey {
+        hotkey::apply(&app_handle, settings.organize_hotkey.as_deref())?;
+    }
+    
+    settings.local_api.validate()?;
+    settings.local_api.ensure_token();
+    if current_settings.local_api != settings.local_api {
+        local_api::apply(&app_handle, &settings.local_api).await?;
+    }
+    
+    // 偏好格式变化时立即转换现有配置文件
+    if current_settings.config_format != settings.config_format {
+        if let Err(e) = Config::convert_to(settings.config_format) {
+            return Err(t_format("convert_config_failed", &[&e.to_string()]));
+        }
+    }
+    
+    let language_changed = current_settings.language != settings.language;
     *current_settings = settings.clone();
     
+    if language_changed {
+        if let Some(code) = &settings.language {
+            apply_language(&app_handle, Language::from(code.as_str()));
+        }
+    }
+    
     match settings.save() {
         Ok(_) => Ok(t("settings_saved")),
         Err(e) => Err(t_format("save_settings_failed", &[&e.to_string()]))
@@ -605,12 +1720,57 @@ async fn update_general_settings(
 async fn update_setting(
     key: String,
     value: serde_json::Value,
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<String, String> {
+    if let Some(managed) = managed_config::ManagedConfig::load() {
+        if managed.is_setting_locked(&key) {
+            return Err(t("managed_setting_locked"));
+        }
+    }
+    
     let mut settings = state.settings.lock().await;
+    let old_format = settings.config_format;
     
     match settings.update_setting(&key, value) {
         Ok(_) => {
+            if settings.config_format != old_format {
+                if let Err(e) = Config::convert_to(settings.config_format) {
+                    settings.config_format = old_format;
+                    return Err(t_format("convert_config_failed", &[&e.to_string()]));
+                }
+            }
+
+            if key == "language" {
+                if let Some(code) = &settings.language {
+                    apply_language(&app_handle, Language::from(code.as_str()));
+                }
+            }
+            
+            if key == "log_level" {
+                logging::set_level(&settings.log_level)?;
+            }
+            
+            if key == "proxy" {
+                http::set_proxy(&settings.proxy);
+            }
+            
+            if key == "telemetry_enabled" {
+                telemetry::set_enabled(settings.telemetry_enabled);
+            }
+            
+            if key == "organize_hotkey" {
+                hotkey::apply(&app_handle, settings.organize_hotkey.as_deref())?;
+            }
+            
+            if key == "local_api" {
+                local_api::apply(&app_handle, &settings.local_api).await?;
+            }
+            
+            if key == "webhooks" {
+                webhooks::set(&settings.webhooks);
+            }
+
             match settings.save() {
                 Ok(_) => Ok(t_format("setting_updated", &[&key])),
                 Err(e) => Err(t_format("save_settings_failed", &[&e.to_string()]))
@@ -620,20 +1780,213 @@ async fn update_setting(
     }
 }
 
-// 修改setup_system_tray函数中的菜单项文本
-fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
-    use tauri::{
-        menu::{Menu, MenuItem, PredefinedMenuItem},
-        tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+// 把订阅状态变化转发给前端，并在托盘提示中显示
+async fn handle_subscription_changes(
+    app_handle: tauri::AppHandle,
+    mut changes: tokio::sync::mpsc::UnboundedReceiver<subscription::SubscriptionChange>,
+) {
+    use subscription::SubscriptionChange;
+    use tauri::Emitter;
+
+    while let Some(change) = changes.recv().await {
+        let _ = app_handle.emit("subscription-changed", &change);
+        tray_status::refresh(&app_handle);
+
+        let note = match &change {
+            SubscriptionChange::Activated { .. } => None,
+            SubscriptionChange::Expired { .. } => Some(t("tray_subscription_expired")),
+            SubscriptionChange::TrialEnding { days_remaining } => {
+                Some(t_format("tray_trial_ending", &[&days_remaining.to_string()]))
+            }
+            SubscriptionChange::VerificationFailed { .. } => continue,
+        };
+        tray_status::set_subscription_note(&app_handle, note);
+    }
+}
+
+// 托盘“立即整理”子菜单中配置路径的菜单项 ID 前缀，后面是路径 ID
+const TRAY_ORGANIZE_PATH_PREFIX: &str = "organize-path:";
+// 托盘中切换路径监控的勾选项 ID 前缀，后面是路径 ID
+const TRAY_MONITOR_PATH_PREFIX: &str = "monitor-path:";
+
+// 按当前语言创建托盘菜单，monitored 是正在监控的文件夹路径
+fn build_tray_menu<M: Manager<tauri::Wry>>(
+    manager: &M,
+    monitored: &std::collections::HashSet<String>,
+) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{CheckMenuItem, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+    type R = tauri::Wry;
+    
+    // 顶部不可点击的标题，显示今天整理的文件数，整理文件后更新
+    let organized_today_item = MenuItem::with_id(manager, "organized-today", tray_status::organized_today_label(), false, None::<&str>)?;
+    tray_status::set_organized_today_item(organized_today_item.clone());
+    let header_separator = PredefinedMenuItem::separator(manager)?;
+    
+    let show_item = MenuItem::with_id(manager, "show", &t("show_window"), true, None::<&str>)?;
+    let hide_item = MenuItem::with_id(manager, "hide", &t("hide_window"), true, None::<&str>)?;
+    let separator = PredefinedMenuItem::separator(manager)?;
+    let quit_item = MenuItem::with_id(manager, "quit", &t("quit"), true, None::<&str>)?;
+    
+    let paths = Config::load().map(|config| config.list_paths()).unwrap_or_default();
+    let path_label = |path: &config::PathConfig| {
+        if path.name.trim().is_empty() { path.path.clone() } else { path.name.clone() }
+    };
+    
+    // 每个已配置的路径一个勾选项，勾选状态表示是否正在监控
+    let monitor_items = paths
+        .iter()
+        .map(|path| {
+            let id = format!("{}{}", TRAY_MONITOR_PATH_PREFIX, path.id);
+            CheckMenuItem::with_id(manager, id, path_label(path), true, monitored.contains(&path.path), None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let monitor_separator = PredefinedMenuItem::separator(manager)?;
+    
+    // 立即整理：系统下载文件夹和每个已配置的路径
+    let downloads_item = MenuItem::with_id(manager, "organize-downloads", &t("tray_organize_downloads"), true, None::<&str>)?;
+    let path_items = paths
+        .iter()
+        .map(|path| {
+            let id = format!("{}{}", TRAY_ORGANIZE_PATH_PREFIX, path.id);
+            MenuItem::with_id(manager, id, path_label(path), true, None::<&str>)
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+    let organize_separator = PredefinedMenuItem::separator(manager)?;
+    let mut organize_items: Vec<&dyn IsMenuItem<R>> = vec![&downloads_item];
+    if !path_items.is_empty() {
+        organize_items.push(&organize_separator);
+        organize_items.extend(path_items.iter().map(|item| item as &dyn IsMenuItem<R>));
+    }
+    let organize_menu = Submenu::with_items(manager, &t("hotkey_organize_title"), true, &organize_items)?;
+    let organize_separator_end = PredefinedMenuItem::separator(manager)?;
+    
+    let mut items: Vec<&dyn IsMenuItem<R>> = vec![&organized_today_item, &header_separator, &show_item, &hide_item, &separator];
+    if !monitor_items.is_empty() {
+        items.extend(monitor_items.iter().map(|item| item as &dyn IsMenuItem<R>));
+        items.push(&monitor_separator);
+    }
+    items.extend([&organize_menu as &dyn IsMenuItem<R>, &organize_separator_end, &quit_item]);
+    Menu::with_items(manager, &items)
+}
+
+// 重建托盘菜单（切换语言、路径配置或监控状态变化后）。
+// 需要等待整理器的锁，在后台任务中进行，调用方可以持有锁
+fn refresh_tray_menu(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let monitored = {
+            let state = app_handle.state::<AppState>();
+            let organizers = state.organizers.lock().await;
+            organizers.keys().cloned().collect()
+        };
+        if let Some(tray) = app_handle.tray_by_id("main-tray") {
+            match build_tray_menu(&app_handle, &monitored) {
+                Ok(menu) => {
+                    if let Err(e) = tray.set_menu(Some(menu)) {
+                        log::error!("Failed to update tray menu: {}", e);
+                    }
+                }
+                Err(e) => log::error!("Failed to build tray menu: {}", e),
+            }
+        }
+        tray_status::refresh(&app_handle);
+    });
+}
+
+// 托盘菜单中的监控勾选项：切换该路径的监控，并通知前端更新状态
+fn toggle_monitoring_from_tray(app_handle: &tauri::AppHandle, path_id: &str) {
+    let Some(path) = Config::load().ok().and_then(|config| config.find_path(path_id).cloned()) else {
+        log::warn!("Tray menu refers to an unknown path: {}", path_id);
+        refresh_tray_menu(app_handle);
+        return;
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        use tauri::Emitter;
+        
+        let state = app_handle.state::<AppState>();
+        match toggle_monitoring(path.path.clone(), state, app_handle.clone()).await {
+            Ok(monitoring) => {
+                let payload = serde_json::json!({ "folderPath": path.path, "isMonitoring": monitoring });
+                let _ = app_handle.emit("monitoring-changed", &payload);
+            }
+            Err(e) => {
+                // 失败时（例如免费版的文件夹数限制）恢复勾选状态并提示原因
+                refresh_tray_menu(&app_handle);
+                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                    .builder()
+                    .title(&t("monitoring_toggle_failed_title"))
+                    .body(&e.to_string())
+                    .show();
+            }
+        }
+    });
+}
+
+// 托盘菜单的“立即整理”：不打开窗口直接整理，结果用通知显示
+fn organize_from_tray(app_handle: &tauri::AppHandle, menu_id: &str) {
+    let folder_path = if menu_id == "organize-downloads" {
+        dirs::download_dir().map(|dir| dir.to_string_lossy().to_string())
+    } else if let Some(path_id) = menu_id.strip_prefix(TRAY_MONITOR_PATH_PREFIX) {
+        toggle_monitoring_from_tray(app_handle, path_id);
+        return;
+    } else if let Some(path_id) = menu_id.strip_prefix(TRAY_ORGANIZE_PATH_PREFIX) {
+        // 菜单可能还没有随配置更新，找不到时忽略
+        let Some(path) = Config::load().ok().and_then(|config| config.find_path(path_id).cloned()) else {
+            log::warn!("Tray menu refers to an unknown path: {}", path_id);
+            return;
+        };
+        Some(path.path)
+    } else {
+        return;
     };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        hotkey::organize_and_notify(app_handle, folder_path, "tray_organize").await;
+    });
+}
+
+// 切换后端语言：重建托盘菜单，并通知前端刷新缓存的后端文案（分类名称、错误消息等）
+fn apply_language(app_handle: &tauri::AppHandle, language: Language) {
+    use tauri::Emitter;
     
-    // 创建托盘菜单
-    let show_item = MenuItem::with_id(app, "show", &t("show_window"), true, None::<&str>)?;
-    let hide_item = MenuItem::with_id(app, "hide", &t("hide_window"), true, None::<&str>)?;
-    let separator = PredefinedMenuItem::separator(app)?;
-    let quit_item = MenuItem::with_id(app, "quit", &t("quit"), true, None::<&str>)?;
+    set_language(language);
+    refresh_tray_menu(app_handle);
+    
+    let payload = serde_json::json!({ "language": language.code() });
+    if let Err(e) = app_handle.emit("language-changed", &payload) {
+        eprintln!("Failed to emit language changed event: {}", e);
+    }
+}
+
+// 文件管理器右键菜单“用 FileSortify 整理”的入口：Windows 通过 --organize 启动参数
+// （应用已运行时由单实例插件转发），macOS Finder Sync 扩展通过 filesortify://organize 链接。
+// 与托盘的“立即整理”一样不打开窗口，结果用通知显示
+fn organize_from_context_menu(app_handle: &tauri::AppHandle, folder_path: &str) {
+    log::info!("Organize requested from context menu: {}", folder_path);
+    let folder_path = match context_menu::validate_folder(folder_path) {
+        Ok(path) => path.to_string_lossy().to_string(),
+        Err(message) => {
+            let _ = tauri_plugin_notification::NotificationExt::notification(app_handle)
+                .builder()
+                .title(&t("context_menu_sort"))
+                .body(&message)
+                .show();
+            return;
+        }
+    };
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        hotkey::organize_and_notify(app_handle, Some(folder_path), "context_menu_organize").await;
+    });
+}
+
+// 修改setup_system_tray函数中的菜单项文本
+fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
     
-    let menu = Menu::with_items(app, &[&show_item, &hide_item, &separator, &quit_item])?;
+    // 创建托盘菜单，启动时还没有正在监控的路径
+    let menu = build_tray_menu(app, &std::collections::HashSet::new())?;
     
     // 创建系统托盘图标
     let _tray = TrayIconBuilder::with_id("main-tray")
@@ -656,6 +2009,8 @@ fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Err
                         } else {
                             let _ = window.show();
                             let _ = window.set_focus();
+                            tray_status::clear_error();
+                            tray_status::refresh(app_handle);
                         }
                     }
                 }
@@ -669,6 +2024,8 @@ fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Err
                         let _ = window.show();
                         let _ = window.set_focus();
                     }
+                    tray_status::clear_error();
+                    tray_status::refresh(app_handle);
                 }
                 "hide" => {
                     if let Some(window) = app_handle.get_webview_window("main") {
@@ -678,7 +2035,7 @@ fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Err
                 "quit" => {
                     app_handle.exit(0);
                 }
-                _ => {}
+                id => organize_from_tray(app_handle, id),
             }
         })
         .build(app)?;
@@ -688,17 +2045,45 @@ fn setup_system_tray(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Err
 
 // 添加同步语言的命令
 #[tauri::command]
-async fn sync_language(language: String) -> Result<(), String> {
-    let lang = match language.as_str() {
-        "zh" => Language::Chinese,
-        "en" => Language::English,
-        _ => Language::English,
-    };
+async fn sync_language(
+    language: String,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let lang = Language::from(language.as_str());
+    apply_language(&app_handle, lang);
     
-    set_language(lang);
+    // 保存语言选择，下次启动时沿用
+    let mut settings = state.settings.lock().await;
+    if settings.language.as_deref() != Some(lang.code()) {
+        settings.language = Some(lang.code().to_string());
+        settings.save().map_err(|e| t_format("save_settings_failed", &[&e.to_string()]))?;
+    }
     Ok(())
 }
 
+// Tauri命令：获取支持的语言列表
+#[tauri::command]
+async fn get_supported_languages() -> Result<Vec<i18n::LanguageInfo>, String> {
+    Ok(i18n::supported_languages())
+}
+
+// 内部检查用：列出某个语言（默认当前语言）相对英文缺少的翻译键
+#[tauri::command]
+async fn get_missing_translations(language: Option<String>) -> Result<i18n::MissingTranslations, String> {
+    let lang = language
+        .map(|code| Language::from(code.as_str()))
+        .unwrap_or_else(i18n::get_language);
+    Ok(i18n::missing_translations(lang))
+}
+
+// 重新加载语言文件，返回自定义语言文件所在的目录
+#[tauri::command]
+async fn reload_locales() -> Result<Option<String>, String> {
+    i18n::reload_translations();
+    Ok(i18n::locale_override_dir().map(|dir| dir.to_string_lossy().to_string()))
+}
+
 // 撤销相关命令
 #[tauri::command]
 async fn get_undo_history(
@@ -728,12 +2113,15 @@ async fn undo_file_action(
     if let Some(organizer) = organizers.get_mut(&folder_path) {
         match organizer.undo_action(&action_id) {
             Ok(message) => {
+                telemetry::record_feature("undo");
                 // 发送通知
-                let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
-                    .builder()
-                    .title(&t("undo_success_title"))
-                    .body(&message)
-                    .show();
+                if organizer.config.notifications_enabled_for(std::path::Path::new(&folder_path)) {
+                    let _ = tauri_plugin_notification::NotificationExt::notification(&app_handle)
+                        .builder()
+                        .title(&t("undo_success_title"))
+                        .body(&message)
+                        .show();
+                }
                 Ok(message)
             }
             Err(e) => Err(t_format("undo_failed", &[&e.to_string()]))
@@ -826,33 +2214,158 @@ async fn move_file_direct(
 }
 
 // 在main函数中注册这个命令
+
 fn main() {
+    // 尽早安装文件日志，读取设置后再应用其中的日志级别
+    logging::init(logging::DEFAULT_LEVEL);
+    crash_report::install(env!("CARGO_PKG_VERSION"));
+    core_host::install();
+    
+    // 命令行版本（filesortify-cli）不启动界面，执行子命令后退出
+    if headless::is_cli_binary() {
+        std::process::exit(headless::run(std::env::args().skip(1).collect()));
+    }
+    
+    let cli_args = cli::CliArgs::from_env();
+    // 提权后的辅助进程：只修改所有用户的开机启动，然后退出
+    if let Some(enable) = cli_args.all_users_autostart {
+        match AutoStart::apply_all_users(enable) {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                log::error!("Failed to update all-users autostart: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    
     // 初始化订阅状态和设置
+    let mut settings = GeneralSettings::load().unwrap_or_default();
+    // 测试模式决定订阅文件和服务器，需要在加载订阅之前确定
+    payment_test_mode::init(settings.payment_test_mode);
     let subscription = Subscription::load().unwrap_or_default();
-    let settings = GeneralSettings::load().unwrap_or_default();
+    if let Err(e) = logging::set_level(&settings.log_level) {
+        log::warn!("{}", e);
+    }
+    http::set_proxy(&settings.proxy);
+    webhooks::set(&settings.webhooks);
+    telemetry::set_enabled(settings.telemetry_enabled);
+    autostart::set_method(settings.autostart_method);
+    
+    if cli_args.autostart {
+        log::info!("Launched by autostart");
+        telemetry::record_feature("autostart_launch");
+    }
+    
+    // 首次启动时按系统语言选择界面语言，并保存下来
+    let language = match &settings.language {
+        Some(code) => Language::from(code.as_str()),
+        None => {
+            let detected = i18n::detect_system_language();
+            settings.language = Some(detected.code().to_string());
+            if let Err(e) = settings.save() {
+                log::error!("Failed to save detected language: {}", e);
+            }
+            detected
+        }
+    };
+    set_language(language);
+    
+    // 主窗口默认不可见，由 setup 决定是否显示，避免隐藏启动时窗口闪现
+    // 从右键菜单启动时只在后台整理，不显示窗口
+    let start_hidden = settings.start_hidden || cli_args.hidden || cli_args.organize.is_some();
+    let launch_organize = cli_args.organize.clone();
+    let organize_hotkey = settings.organize_hotkey.clone();
+    let local_api_settings = settings.local_api.clone();
     
     tauri::Builder::default()
+        // 必须最先注册：Windows 和 Linux 上打开 filesortify:// 链接会启动新进程，由它把链接转给已运行的实例
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            // 右键菜单再次启动应用时，由已运行的实例整理文件夹
+            if let Some(folder_path) = cli::CliArgs::parse(argv.into_iter().skip(1)).organize {
+                organize_from_context_menu(app, &folder_path);
+                return;
+            }
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .manage(AppState {
             organizers: Mutex::new(HashMap::new()),
             subscription: Mutex::new(subscription),
             settings: Mutex::new(settings),
+            pending_reset: Mutex::new(None),
+            pending_purge: Mutex::new(None),
         })
+        .manage(rules_editor::RulesEditorState::default())
         .invoke_handler(tauri::generate_handler![
             organize_files,
             toggle_monitoring,
             get_config,
             save_config,
+            get_managed_config_status,
+            get_category_meta,
+            set_category_meta,
+            open_permission_settings,
+            list_presets,
+            apply_preset,
+            set_active_rule_set,
+            get_autostart_status,
+            rules_editor::open_rules_editor,
+            rules_editor::get_rules_editor_session,
+            rules_editor::set_rules_editor_unsaved,
+            rules_editor::relay_window_event,
+            regenerate_local_api_token,
+            webhooks::test_webhook,
+            destinations::connect_destination,
+            destinations::disconnect_destination,
+            destinations::get_destination_status,
+            destinations::set_category_destination,
+            digest::get_weekly_digest,
+            digest::send_weekly_digest,
+            get_context_menu_enabled,
+            set_context_menu_enabled,
+            get_pending_moves,
+            get_deferred_files,
+            confirm_pending_move,
+            deny_pending_move,
+            get_category_folder_status,
+            migrate_category_folders,
+            get_unmatched_extensions,
+            assign_extension_to_category,
+            preview_config_import,
+            apply_config_import,
+            list_paths,
+            add_path,
+            remove_path,
+            update_path,
+            get_path_stats,
+            get_sync_settings,
+            update_sync_settings,
+            sync_config_now,
+            create_backup,
+            restore_backup,
+            request_reset_token,
+            reset_app_data,
+            request_purge_token,
+            purge_all_data,
             select_folder,
             get_default_downloads_folder,
             get_subscription_status,
             can_use_app,
             can_use_app_secure,
+            get_free_tier_status,
+            get_referral_info,
+            redeem_referral_code,
+            redeem_promo_code,
             get_packages,
             fetch_packages_from_server,
             activate_subscription,
@@ -864,18 +2377,37 @@ fn main() {
             // start_apple_purchase,
             // restore_apple_purchases,
             // get_local_receipt_data,
+            get_ms_store_products,
+            start_ms_store_purchase,
+            refresh_ms_store_license,
             create_creem_session,
             check_creem_payment_status,
+            restore_purchase,
             open_creem_payment_page,
+            get_payment_provider,
+            create_checkout_session,
+            check_payment_status,
             set_webhook_server_url,
             get_current_session_info,
+            register_device_seat,
+            get_seat_usage,
+            release_device_seat,
             show_main_window,
             hide_main_window,
             get_app_version,
+            get_log_directory,
+            export_diagnostics,
+            get_telemetry_preview,
+            get_crash_reports,
+            send_crash_report,
+            dismiss_crash_report,
             get_general_settings,
             update_general_settings,
             update_setting,
             sync_language,
+            get_supported_languages,
+            get_missing_translations,
+            reload_locales,
             // 撤销相关命令
             get_undo_history,
             undo_file_action,
@@ -884,21 +2416,81 @@ fn main() {
             move_file_direct,
             updater::check_update,
             updater::install_update,
+            updater::rollback::rollback_update,
+            updater::staged::get_staged_update,
+            updater::staged::apply_staged_update,
+            updater::download::pause_update_download,
+            updater::download::resume_update_download,
+            updater::download::cancel_update_download,
             updater::scheduler::get_scheduler_config,
             updater::scheduler::update_scheduler_config,
+            updater::scheduler::skip_update_version,
+            updater::scheduler::snooze_update,
+            updater::scheduler::reset_update_reminders,
             updater::github::get_github_releases,
-            updater::github::get_latest_github_release
+            updater::github::get_latest_github_release,
+            updater::github::install_github_release
         ])
-        .setup(|app| {
-            // 设置默认语言
-            set_language(Language::English);
+        .setup(move |app| {
             // 设置系统托盘
             setup_system_tray(app)?;
+            tray_status::refresh(app.handle());
+            tray_status::update_organized_today(app.handle());
+            
+            // 注册“立即整理”快捷键；被其他程序占用时只记录错误
+            if let Err(e) = hotkey::apply(app.handle(), organize_hotkey.as_deref()) {
+                log::error!("Failed to register organize hotkey: {}", e);
+            }
+            
+            // 开启了本机自动化接口时启动；端口被占用时只记录错误
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = local_api::apply(&handle, &local_api_settings).await {
+                    log::error!("Failed to start local API: {}", e);
+                }
+            });
+            
+            // 继续上次退出时没有完成的远程目标上传
+            destinations::resume_pending(app.handle());
+            
+            // 开启每周汇总后每周一发送上一周的整理汇总
+            digest::start_background_task(app.handle().clone());
+            
+            // 支付完成后结账页通过 filesortify:// 链接跳转回应用
+            deep_link::setup(app.handle());
+            
+            // 通过右键菜单启动
+            if let Some(folder_path) = &launch_organize {
+                organize_from_context_menu(app.handle(), folder_path);
+            }
+            
+            // AppleScript 和快捷指令的命令
+            #[cfg(target_os = "macos")]
+            scripting::install(app.handle());
+            
+            // StoreKit 回调通过通道转发到这里处理
+            #[cfg(target_os = "macos")]
+            tauri::async_runtime::spawn(handle_storekit_events(app.handle().clone(), storekit_bridge::subscribe()));
+            
+            // 订阅状态变化转发给前端和托盘
+            tauri::async_runtime::spawn(handle_subscription_changes(app.handle().clone(), subscription::subscribe()));
+            
+            // 监听配置文件的外部修改
+            if let Err(e) = config_watcher::start(app.handle().clone()) {
+                log::error!("Failed to start config watcher: {}", e);
+            }
             
             // 设置窗口事件处理
             let window = app.get_webview_window("main").unwrap();
             let app_handle = app.handle().clone();
             
+            if start_hidden {
+                log::info!("Starting hidden in system tray");
+            } else {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            
             window.on_window_event(move |event| {
                 match event {
                     WindowEvent::CloseRequested { api, .. } => {
@@ -916,6 +2508,10 @@ fn main() {
                             .body(&t("app_minimized_body"))
                             .show();
                     }
+                    // 拖放到窗口上的文件夹，预览后由用户确认整理
+                    WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                        drop_target::handle_drop(&app_handle, paths.clone());
+                    }
                     _ => {}
                 }
             });
@@ -941,6 +2537,68 @@ fn main() {
                             }
                         }
                     });
+                    
+                    // 应用被移动或更新后，开机启动可能仍指向旧路径，检查并自动修复
+                    let app_handle_clone = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        // 等待界面开始监听事件
+                        tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
+                        
+                        let (auto_start, scope) = {
+                            let state = app_handle_clone.state::<AppState>();
+                            let settings = state.settings.lock().await;
+                            (settings.auto_start, settings.autostart_scope)
+                        };
+                        if !auto_start {
+                            return;
+                        }
+                        match AutoStart::repair_if_drifted(scope) {
+                            Ok(true) => log::info!("Repaired autostart entry"),
+                            Ok(false) => {}
+                            Err(e) => {
+                                log::error!("Failed to repair autostart entry: {}", e);
+                                use tauri::Emitter;
+                                let _ = app_handle_clone.emit("autostart-repair-failed", &serde_json::json!({
+                                    "error": e,
+                                    "scope": scope,
+                                }));
+                            }
+                        }
+                    });
+                    
+                    // 定期向服务端重新确认已激活的订阅，续费失败进入宽限期时提醒用户
+                    subscription_refresh::start_background_task(app_handle.clone());
+                    
+                    // 遥测开启时每小时检查一次是否需要上报（实际上报间隔为一天）
+                    let app_handle_clone = app_handle.clone();
+                    tauri::async_runtime::spawn(async move {
+                        let app_version = app_handle_clone.package_info().version.to_string();
+                        loop {
+                            tokio::time::sleep(tokio::time::Duration::from_secs(60 * 60)).await;
+                            if !telemetry::is_enabled() {
+                                continue;
+                            }
+                            let server_url = {
+                                let state = app_handle_clone.state::<AppState>();
+                                let subscription = state.subscription.lock().await;
+                                subscription.webhook_server_url.clone()
+                            };
+                            if let Err(e) = telemetry::send_if_due(&server_url, &app_version).await {
+                                log::warn!("Failed to send telemetry: {}", e);
+                            }
+                        }
+                    });
+                }
+                RunEvent::Exit => {
+                    telemetry::flush();
+                    // 选择了退出时安装的更新在这里安装
+                    updater::staged::apply_on_exit(app_handle);
+                }
+                // 拖放到 Dock 图标上的文件夹（Info.plist 中声明了接受文件夹）
+                #[cfg(target_os = "macos")]
+                RunEvent::Opened { urls } => {
+                    let paths = urls.iter().filter_map(|url| url.to_file_path().ok()).collect();
+                    drop_target::handle_drop(app_handle, paths);
                 }
                 RunEvent::Reopen { has_visible_windows, .. } => {
                     // 当点击 Dock 图标时触发（macOS 特有）
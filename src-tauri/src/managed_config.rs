@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::settings::GeneralSettings;
+
+/// 由管理员部署的只读系统级配置，优先级高于用户配置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ManagedConfig {
+    // 强制下发的分类（同ID的用户分类会被覆盖）
+    #[serde(default)]
+    pub categories: HashMap<String, Vec<String>>,
+    #[serde(rename = "categoryNames", default)]
+    pub category_names: HashMap<String, String>,
+    // 为 true 时只使用下发的分类，用户不能修改
+    #[serde(rename = "lockCategories", default)]
+    pub lock_categories: bool,
+    // 强制的通用设置，键与 GeneralSettings::update_setting 一致
+    #[serde(default)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManagedConfigStatus {
+    pub active: bool,
+    pub source: Option<String>,
+    pub locked_categories: bool,
+    pub managed_categories: Vec<String>,
+    pub locked_settings: Vec<String>,
+}
+
+impl ManagedConfig {
+    /// 读取系统级托管配置；未部署时返回 None
+    pub fn load() -> Option<Self> {
+        let (content, source) = Self::read_source()?;
+        match serde_json::from_str::<ManagedConfig>(&content) {
+            Ok(managed) => Some(managed),
+            Err(e) => {
+                log::error!("Invalid managed config at {}: {}", source, e);
+                None
+            }
+        }
+    }
+
+    pub fn status() -> ManagedConfigStatus {
+        let source = Self::read_source().map(|(_, source)| source);
+        match Self::load() {
+            Some(managed) => ManagedConfigStatus {
+                active: true,
+                source,
+                locked_categories: managed.lock_categories,
+                managed_categories: managed.categories.keys().cloned().collect(),
+                locked_settings: managed.settings.keys().cloned().collect(),
+            },
+            None => ManagedConfigStatus {
+                active: false,
+                source: None,
+                locked_categories: false,
+                managed_categories: Vec::new(),
+                locked_settings: Vec::new(),
+            },
+        }
+    }
+
+    /// 把托管分类叠加到用户配置上
+    pub fn apply_to_config(&self, config: &mut Config) {
+        if self.categories.is_empty() {
+            return;
+        }
+        if self.lock_categories {
+            config.categories.clear();
+        }
+        for (id, extensions) in &self.categories {
+            config.categories.insert(id.clone(), extensions.clone());
+        }
+        for (id, name) in &self.category_names {
+            config.category_names.insert(id.clone(), name.clone());
+        }
+    }
+
+    /// 保存前去掉叠加的托管分类，换回用户配置文件中原来的值；托管配置撤销后用户的分类仍然保留
+    pub fn strip_from_config(&self, config: &mut Config, user: &Config) {
+        if self.categories.is_empty() {
+            return;
+        }
+        if self.lock_categories {
+            config.categories = user.categories.clone();
+        }
+        for id in self.categories.keys() {
+            match user.categories.get(id) {
+                Some(extensions) => config.categories.insert(id.clone(), extensions.clone()),
+                None => config.categories.remove(id),
+            };
+        }
+        for id in self.category_names.keys() {
+            match user.category_names.get(id) {
+                Some(name) => config.category_names.insert(id.clone(), name.clone()),
+                None => config.category_names.remove(id),
+            };
+        }
+    }
+
+    /// 把强制设置写入通用设置
+    pub fn apply_to_settings(&self, settings: &mut GeneralSettings) {
+        for (key, value) in &self.settings {
+            if let Err(e) = settings.update_setting(key, value.clone()) {
+                log::error!("Invalid managed setting {}: {}", key, e);
+            }
+        }
+    }
+
+    /// 保存前把强制设置换回用户设置文件中原来的值（没有时去掉，读取时使用默认值）
+    pub fn strip_from_settings(&self, settings: &mut serde_json::Value, user: &serde_json::Value) {
+        let Some(settings) = settings.as_object_mut() else {
+            return;
+        };
+        for key in self.settings.keys() {
+            match user.get(key) {
+                Some(value) => settings.insert(key.clone(), value.clone()),
+                None => settings.remove(key),
+            };
+        }
+    }
+
+    pub fn is_setting_locked(&self, key: &str) -> bool {
+        self.settings.contains_key(key)
+    }
+
+    /// 检查用户对配置的修改是否触碰了托管分类
+    pub fn check_config_edit(&self, new_config: &Config) -> Result<(), String> {
+        if self.lock_categories {
+            let same = new_config.categories.len() == self.categories.len()
+                && self.categories.iter().all(|(id, exts)| new_config.categories.get(id) == Some(exts));
+            if !same {
+                return Err(crate::i18n::t("managed_categories_locked"));
+            }
+            return Ok(());
+        }
+        for (id, extensions) in &self.categories {
+            if new_config.categories.get(id) != Some(extensions) {
+                return Err(crate::i18n::t_format("managed_category_locked", &[id]));
+            }
+        }
+        Ok(())
+    }
+
+    // 返回 (内容, 来源描述)
+    fn read_source() -> Option<(String, String)> {
+        #[cfg(target_os = "windows")]
+        {
+            if let Some(content) = Self::read_windows_policy() {
+                return Some((content, "HKLM\\SOFTWARE\\Policies\\FileSortify".to_string()));
+            }
+        }
+
+        let path = Self::get_managed_path()?;
+        let content = fs::read_to_string(&path).ok()?;
+        Some((content, path.to_string_lossy().to_string()))
+    }
+
+    fn get_managed_path() -> Option<PathBuf> {
+        #[cfg(target_os = "macos")]
+        {
+            Some(PathBuf::from("/Library/Application Support/FileSortify/managed.json"))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let program_data = std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+            Some(PathBuf::from(program_data).join("FileSortify").join("managed.json"))
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            Some(PathBuf::from("/etc/filesortify/managed.json"))
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        {
+            None
+        }
+    }
+
+    // 组策略下发的 JSON 字符串：HKLM\SOFTWARE\Policies\FileSortify\ManagedConfig (REG_SZ)
+    #[cfg(target_os = "windows")]
+    fn read_windows_policy() -> Option<String> {
+        let output = std::process::Command::new("reg")
+            .args(&[
+                "query",
+                "HKLM\\SOFTWARE\\Policies\\FileSortify",
+                "/v",
+                "ManagedConfig",
+            ])
+            .output()
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .find_map(|line| line.split_once("REG_SZ").map(|(_, value)| value.trim().to_string()))
+            .filter(|value| !value.is_empty())
+    }
+}
@@ -0,0 +1,70 @@
+/// 当前网络是否按流量计费。无法判断时视为不计费，避免永远不下载更新。
+/// 会启动系统查询，可能阻塞一两秒，异步代码中应放到 spawn_blocking 里调用。
+pub fn is_metered() -> bool {
+    match platform::is_metered() {
+        Ok(metered) => metered,
+        Err(e) => {
+            log::debug!("Failed to detect metered connection: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use std::os::windows::process::CommandExt;
+
+    // 不弹出 PowerShell 窗口
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    // Windows.Networking.Connectivity 的连接费用：NetworkCostType 为 Fixed / Variable，
+    // 或处于漫游、接近/超过流量上限时视为按流量计费
+    const SCRIPT: &str = "$p = [Windows.Networking.Connectivity.NetworkInformation,Windows.Networking.Connectivity,ContentType=WindowsRuntime]::GetInternetConnectionProfile(); \
+        if ($p -eq $null) { 'none' } else { $c = $p.GetConnectionCost(); \
+        \"$($c.NetworkCostType) $($c.Roaming) $($c.ApproachingDataLimit) $($c.OverDataLimit)\" }";
+
+    pub fn is_metered() -> Result<bool, String> {
+        let output = std::process::Command::new("powershell.exe")
+            .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let fields: Vec<&str> = stdout.split_whitespace().collect();
+        match fields.as_slice() {
+            ["none"] => Ok(false),
+            [cost_type, flags @ ..] => {
+                let limited = !cost_type.eq_ignore_ascii_case("Unrestricted") && !cost_type.eq_ignore_ascii_case("Unknown");
+                Ok(limited || flags.iter().any(|flag| flag.eq_ignore_ascii_case("True")))
+            }
+            [] => Err("Empty connection cost".to_string()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    extern "C" {
+        fn network_is_expensive() -> i32;
+    }
+
+    // 见 network.h：-1 无法判断，0 否，1 是
+    pub fn is_metered() -> Result<bool, String> {
+        match unsafe { network_is_expensive() } {
+            1 => Ok(true),
+            0 => Ok(false),
+            _ => Err("Network path status unavailable".to_string()),
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn is_metered() -> Result<bool, String> {
+        Err("Metered connection detection is not supported on this platform".to_string())
+    }
+}
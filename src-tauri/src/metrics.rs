@@ -0,0 +1,70 @@
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+// 应用启动以来的计数，只保存在内存中；重启后从 0 开始，Prometheus 会按计数器重置处理
+static FILES_ORGANIZED: AtomicU64 = AtomicU64::new(0);
+static ERRORS: AtomicU64 = AtomicU64::new(0);
+static WATCHER_RESTARTS: AtomicU64 = AtomicU64::new(0);
+
+pub fn record_file_organized() {
+    FILES_ORGANIZED.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_error() {
+    ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 配置变化后正在监控的路径重新启动监控
+pub fn record_watcher_restart() {
+    WATCHER_RESTARTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// 当前状态的数值，由调用方在生成时读取
+pub struct Gauges {
+    pub monitored_folders: usize,
+    // “询问我”模式下等待确认的文件数
+    pub pending_moves: usize,
+}
+
+/// 生成 Prometheus 文本格式（text/plain; version=0.0.4）的指标，由本机接口的 /metrics 提供
+pub fn render(gauges: &Gauges) -> String {
+    let mut output = String::new();
+    let metrics = [
+        (
+            "filesortify_files_organized_total",
+            "counter",
+            "Files moved into category folders since the app started.",
+            FILES_ORGANIZED.load(Ordering::Relaxed),
+        ),
+        (
+            "filesortify_errors_total",
+            "counter",
+            "Errors reported while organizing or monitoring since the app started.",
+            ERRORS.load(Ordering::Relaxed),
+        ),
+        (
+            "filesortify_watcher_restarts_total",
+            "counter",
+            "Folder watchers restarted after a configuration change since the app started.",
+            WATCHER_RESTARTS.load(Ordering::Relaxed),
+        ),
+        (
+            "filesortify_monitored_folders",
+            "gauge",
+            "Folders currently being monitored.",
+            gauges.monitored_folders as u64,
+        ),
+        (
+            "filesortify_queue_depth",
+            "gauge",
+            "Files waiting for confirmation before being moved.",
+            gauges.pending_moves as u64,
+        ),
+    ];
+    for (name, kind, help, value) in metrics {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} {}", name, kind);
+        let _ = writeln!(output, "{} {}", name, value);
+    }
+    output
+}
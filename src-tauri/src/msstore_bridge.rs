@@ -0,0 +1,175 @@
+// 商店接口只在 Windows 上可用，其他平台只用到这里的类型
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::subscription::SubscriptionPlan;
+
+// 加载项的应用内产品 ID（InAppOfferToken），与 App Store 的产品 ID 保持一致
+pub const MONTHLY_PRODUCT_ID: &str = "com.fileSortify.monthly";
+pub const YEARLY_PRODUCT_ID: &str = "com.fileSortify.yearly";
+
+/// Microsoft Store 中的订阅加载项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MsStoreProduct {
+    pub product_id: String,
+    pub store_id: String,
+    pub title: String,
+    pub description: String,
+    pub formatted_price: String,
+}
+
+/// 当前用户拥有的有效订阅授权
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MsStoreLicense {
+    pub product_id: String,
+    pub sku_store_id: String,
+    pub expiration_date: Option<DateTime<Utc>>,
+}
+
+impl MsStoreLicense {
+    pub fn plan(&self) -> SubscriptionPlan {
+        if self.product_id == YEARLY_PRODUCT_ID {
+            SubscriptionPlan::Yearly
+        } else {
+            SubscriptionPlan::Monthly
+        }
+    }
+}
+
+/// 购买结果，对应 StorePurchaseStatus
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MsStorePurchaseStatus {
+    Succeeded,
+    AlreadyPurchased,
+    NotPurchased,
+    NetworkError,
+    ServerError,
+}
+
+/// Windows.Services.Store 的封装。接口都是同步等待 WinRT 异步操作，应放到 spawn_blocking 中调用
+#[cfg(target_os = "windows")]
+pub struct MsStoreManager {
+    context: windows::Services::Store::StoreContext,
+}
+
+#[cfg(target_os = "windows")]
+impl MsStoreManager {
+    /// 桌面应用需要把商店对话框关联到主窗口
+    pub fn new(hwnd: isize) -> Result<Self, String> {
+        use windows::core::Interface;
+        use windows::Services::Store::StoreContext;
+        use windows::Win32::Foundation::HWND;
+        use windows::Win32::UI::Shell::IInitializeWithWindow;
+
+        let context = StoreContext::GetDefault().map_err(|e| e.to_string())?;
+        let initialize: IInitializeWithWindow = context.cast().map_err(|e| e.to_string())?;
+        unsafe { initialize.Initialize(HWND(hwnd as *mut std::ffi::c_void)) }.map_err(|e| e.to_string())?;
+        Ok(Self { context })
+    }
+
+    /// 获取应用关联的订阅加载项
+    pub fn get_products(&self) -> Result<Vec<MsStoreProduct>, String> {
+        use windows::core::HSTRING;
+        use windows::Foundation::Collections::IIterable;
+
+        // 订阅加载项的类型为 Durable
+        let kinds = IIterable::<HSTRING>::try_from(vec![HSTRING::from("Durable")]).map_err(|e| e.to_string())?;
+        let result = self
+            .context
+            .GetAssociatedStoreProductsAsync(&kinds)
+            .and_then(|operation| operation.get())
+            .map_err(|e| e.to_string())?;
+        if let Ok(error) = result.ExtendedError() {
+            if error.is_err() {
+                return Err(format!("Failed to get store products: {}", error.message()));
+            }
+        }
+
+        let mut products = Vec::new();
+        for pair in result.Products().map_err(|e| e.to_string())? {
+            let product = pair.Value().map_err(|e| e.to_string())?;
+            let product_id = product.InAppOfferToken().map_err(|e| e.to_string())?.to_string();
+            if product_id != MONTHLY_PRODUCT_ID && product_id != YEARLY_PRODUCT_ID {
+                continue;
+            }
+            products.push(MsStoreProduct {
+                product_id,
+                store_id: product.StoreId().map_err(|e| e.to_string())?.to_string(),
+                title: product.Title().map_err(|e| e.to_string())?.to_string(),
+                description: product.Description().map_err(|e| e.to_string())?.to_string(),
+                formatted_price: product
+                    .Price()
+                    .and_then(|price| price.FormattedPrice())
+                    .map_err(|e| e.to_string())?
+                    .to_string(),
+            });
+        }
+        Ok(products)
+    }
+
+    /// 弹出商店的购买对话框，product_id 为应用内产品 ID
+    pub fn purchase(&self, product_id: &str) -> Result<MsStorePurchaseStatus, String> {
+        use windows::core::HSTRING;
+        use windows::Services::Store::StorePurchaseStatus;
+
+        let store_id = self
+            .get_products()?
+            .into_iter()
+            .find(|product| product.product_id == product_id)
+            .map(|product| product.store_id)
+            .ok_or_else(|| format!("Unknown store product: {}", product_id))?;
+
+        let result = self
+            .context
+            .RequestPurchaseAsync(&HSTRING::from(store_id))
+            .and_then(|operation| operation.get())
+            .map_err(|e| e.to_string())?;
+
+        let status = match result.Status().map_err(|e| e.to_string())? {
+            StorePurchaseStatus::Succeeded => MsStorePurchaseStatus::Succeeded,
+            StorePurchaseStatus::AlreadyPurchased => MsStorePurchaseStatus::AlreadyPurchased,
+            StorePurchaseStatus::NetworkError => MsStorePurchaseStatus::NetworkError,
+            StorePurchaseStatus::ServerError => MsStorePurchaseStatus::ServerError,
+            _ => MsStorePurchaseStatus::NotPurchased,
+        };
+        Ok(status)
+    }
+
+    /// 当前用户有效的订阅授权，有多个时取到期最晚的
+    pub fn active_license(&self) -> Result<Option<MsStoreLicense>, String> {
+        let license = self
+            .context
+            .GetAppLicenseAsync()
+            .and_then(|operation| operation.get())
+            .map_err(|e| e.to_string())?;
+
+        let mut active = Vec::new();
+        for pair in license.AddOnLicenses().map_err(|e| e.to_string())? {
+            let addon = pair.Value().map_err(|e| e.to_string())?;
+            if !addon.IsActive().map_err(|e| e.to_string())? {
+                continue;
+            }
+            let product_id = addon.InAppOfferToken().map_err(|e| e.to_string())?.to_string();
+            if product_id != MONTHLY_PRODUCT_ID && product_id != YEARLY_PRODUCT_ID {
+                continue;
+            }
+            active.push(MsStoreLicense {
+                product_id,
+                sku_store_id: addon.SkuStoreId().map_err(|e| e.to_string())?.to_string(),
+                expiration_date: addon.ExpirationDate().ok().and_then(|date| from_universal_time(date.UniversalTime)),
+            });
+        }
+        Ok(active.into_iter().max_by_key(|license| license.expiration_date))
+    }
+}
+
+// WinRT DateTime 为自 1601-01-01 起的 100 纳秒数
+#[cfg(target_os = "windows")]
+fn from_universal_time(ticks: i64) -> Option<DateTime<Utc>> {
+    const UNIX_EPOCH_TICKS: i64 = 116_444_736_000_000_000;
+    let since_epoch = ticks.checked_sub(UNIX_EPOCH_TICKS)?;
+    DateTime::from_timestamp(since_epoch / 10_000_000, ((since_epoch % 10_000_000) * 100) as u32)
+}
@@ -0,0 +1,18 @@
+#ifndef NETWORK_H
+#define NETWORK_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// 当前网络路径是否为按流量计费（蜂窝网络、个人热点）或开启了低数据模式
+// 返回值：-1 无法判断，0 否，1 是
+int network_is_expensive(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // NETWORK_H
@@ -0,0 +1,27 @@
+#import <Foundation/Foundation.h>
+#import <Network/Network.h>
+#import "network.h"
+
+int network_is_expensive(void) {
+    if (@available(macOS 10.15, *)) {
+        __block int result = -1;
+        dispatch_semaphore_t done = dispatch_semaphore_create(0);
+        dispatch_queue_t queue = dispatch_queue_create("com.filesortify.app.network", DISPATCH_QUEUE_SERIAL);
+        nw_path_monitor_t monitor = nw_path_monitor_create();
+
+        // 启动后会立即回调一次当前路径
+        nw_path_monitor_set_update_handler(monitor, ^(nw_path_t path) {
+            if (result == -1) {
+                result = (nw_path_is_expensive(path) || nw_path_is_constrained(path)) ? 1 : 0;
+                dispatch_semaphore_signal(done);
+            }
+        });
+        nw_path_monitor_set_queue(monitor, queue);
+        nw_path_monitor_start(monitor);
+
+        dispatch_semaphore_wait(done, dispatch_time(DISPATCH_TIME_NOW, 2 * NSEC_PER_SEC));
+        nw_path_monitor_cancel(monitor);
+        return result;
+    }
+    return -1;
+}
@@ -0,0 +1,299 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::i18n::t;
+use crate::subscription::{RevocationReason, Subscription, SubscriptionPlan};
+use crate::subscription_error::SubscriptionError;
+use crate::AppState;
+
+// 支付完成的回调可能比 webhook 先到，最多等待 CONFIRM_ATTEMPTS * CONFIRM_INTERVAL_SECS 秒
+const CONFIRM_ATTEMPTS: u32 = 10;
+const CONFIRM_INTERVAL_SECS: u64 = 2;
+
+type PaymentResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// 支付渠道，由服务端配置决定
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaymentProviderKind {
+    #[default]
+    Creem,
+    Stripe,
+}
+
+// GET /api/payment-config 的响应
+#[derive(Debug, Deserialize)]
+struct PaymentConfig {
+    provider: PaymentProviderKind,
+}
+
+/// 结账会话，前端打开 checkout_url 完成支付
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckoutSession {
+    pub provider: PaymentProviderKind,
+    pub session_id: String,
+    pub checkout_url: String,
+}
+
+/// 支付渠道的结账和支付状态查询
+#[async_trait]
+pub trait PaymentProvider: Send + Sync {
+    fn kind(&self) -> PaymentProviderKind;
+
+    /// 为指定计划创建结账会话，支付成功后跳转到 success_url
+    async fn create_checkout(
+        &self,
+        subscription: &mut Subscription,
+        plan: SubscriptionPlan,
+        success_url: &str,
+    ) -> PaymentResult<CheckoutSession>;
+
+    /// 查询支付状态并更新本地订阅，返回是否有有效的购买
+    async fn check_status(&self, subscription: &mut Subscription) -> PaymentResult<bool>;
+}
+
+/// 按服务端配置选择支付渠道，服务端不可达或未配置时使用 Creem
+pub async fn provider_for(server_url: &str) -> Box<dyn PaymentProvider> {
+    let kind = match fetch_config(server_url).await {
+        Ok(config) => config.provider,
+        Err(e) => {
+            log::warn!("Failed to fetch payment config, using Creem: {}", e);
+            PaymentProviderKind::Creem
+        }
+    };
+    provider(kind)
+}
+
+pub fn provider(kind: PaymentProviderKind) -> Box<dyn PaymentProvider> {
+    match kind {
+        PaymentProviderKind::Creem => Box::new(CreemProvider),
+        PaymentProviderKind::Stripe => Box::new(StripeProvider),
+    }
+}
+
+/// 收到支付完成的回调（深度链接或本机回调监听）后立即检查支付状态，直到订阅激活或超时
+pub async fn confirm(app: AppHandle) {
+    let _ = app.emit("payment-confirming", ());
+
+    for attempt in 0..CONFIRM_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(tokio::time::Duration::from_secs(CONFIRM_INTERVAL_SECS)).await;
+        }
+
+        let state = app.state::<AppState>();
+        // 先克隆订阅数据，避免跨异步边界持有锁
+        let mut subscription_clone = {
+            let subscription = state.subscription.lock().await;
+            subscription.clone()
+        };
+
+        let provider = provider_for(&subscription_clone.webhook_server_url).await;
+        match provider.check_status(&mut subscription_clone).await {
+            Ok(true) => {
+                crate::seats::register_after_purchase(&app, &subscription_clone).await;
+                {
+                    let mut subscription = state.subscription.lock().await;
+                    *subscription = subscription_clone.clone();
+                }
+
+                let _ = app.emit("payment-confirmed", &subscription_clone);
+                let _ = tauri_plugin_notification::NotificationExt::notification(&app)
+                    .builder()
+                    .title(&t("purchase_success_title"))
+                    .body(&t("purchase_success_body"))
+                    .show();
+                return;
+            }
+            Ok(false) => {}
+            Err(e) => log::warn!("Payment status check failed: {}", e),
+        }
+    }
+
+    // 仍未确认时交给前端原有的轮询
+    log::warn!("Payment not confirmed after checkout callback");
+    let _ = app.emit("payment-pending", ());
+}
+
+async fn fetch_config(server_url: &str) -> PaymentResult<PaymentConfig> {
+    let response = crate::http::client()
+        .get(&format!("{}/api/payment-config", server_url))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("Failed to fetch payment config: {}", response.status()).into());
+    }
+    Ok(response.json().await?)
+}
+
+/// Creem：沿用 Subscription 中已有的会话创建和状态检查
+pub struct CreemProvider;
+
+#[async_trait]
+impl PaymentProvider for CreemProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Creem
+    }
+
+    async fn create_checkout(
+        &self,
+        subscription: &mut Subscription,
+        plan: SubscriptionPlan,
+        success_url: &str,
+    ) -> PaymentResult<CheckoutSession> {
+        let response = subscription.create_creem_session(plan, success_url).await?;
+        Ok(CheckoutSession {
+            provider: self.kind(),
+            session_id: response.user_package.id,
+            checkout_url: response.checkout_url,
+        })
+    }
+
+    async fn check_status(&self, subscription: &mut Subscription) -> PaymentResult<bool> {
+        let payment_status = subscription.check_creem_payment_status().await?;
+        Ok(!payment_status.user_packages.is_empty() && subscription.is_subscription_active())
+    }
+}
+
+/// Stripe：密钥只保存在 webhook 服务器上，客户端通过服务器创建 Checkout Session 并查询订阅
+pub struct StripeProvider;
+
+#[derive(Debug, Serialize)]
+struct StripeCheckoutRequest {
+    #[serde(rename = "userId")]
+    user_id: String,
+    plan: String,
+    #[serde(rename = "successUrl")]
+    success_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeCheckoutResponse {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripePurchasesResponse {
+    purchases: Vec<StripePurchase>,
+}
+
+/// Stripe 中的一次购买：买断为 Payment，按周期订阅为 Subscription
+#[derive(Debug, Deserialize)]
+struct StripePurchase {
+    id: String,
+    plan: String,
+    // active / trialing / past_due / canceled / paid（买断）/ refunded / disputed
+    status: String,
+    #[serde(rename = "currentPeriodEnd", default)]
+    current_period_end: Option<DateTime<Utc>>,
+}
+
+#[async_trait]
+impl PaymentProvider for StripeProvider {
+    fn kind(&self) -> PaymentProviderKind {
+        PaymentProviderKind::Stripe
+    }
+
+    async fn create_checkout(
+        &self,
+        subscription: &mut Subscription,
+        plan: SubscriptionPlan,
+        success_url: &str,
+    ) -> PaymentResult<CheckoutSession> {
+        let plan = match plan {
+            SubscriptionPlan::Lifetime => "lifetime",
+            SubscriptionPlan::Monthly => "monthly",
+            SubscriptionPlan::Yearly => "yearly",
+            SubscriptionPlan::Free => return Err(SubscriptionError::FreePlanNotPurchasable.into()),
+        };
+        let request = StripeCheckoutRequest {
+            user_id: subscription.device_id.clone(),
+            plan: plan.to_string(),
+            success_url: success_url.to_string(),
+        };
+
+        let response = crate::http::client()
+            .post(&format!("{}/api/stripe/checkout", subscription.webhook_server_url))
+            .json(&request)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to create session: {}", response.status()).into());
+        }
+
+        let session: StripeCheckoutResponse = response.json().await?;
+        Ok(CheckoutSession {
+            provider: self.kind(),
+            session_id: session.session_id,
+            checkout_url: session.url,
+        })
+    }
+
+    async fn check_status(&self, subscription: &mut Subscription) -> PaymentResult<bool> {
+        let response = crate::http::client()
+            .get(&format!("{}/api/stripe/purchases", subscription.webhook_server_url))
+            .query(&[("userId", subscription.device_id.as_str())])
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to check status: {}", response.status()).into());
+        }
+        let purchases: StripePurchasesResponse = response.json().await?;
+
+
+        // 优先买断，其次是到期时间最晚的有效订阅
+        let now = Utc::now();
+        let valid = purchases
+            .purchases
+            .iter()
+            .filter_map(|purchase| SubscriptionPlan::from_name(&purchase.plan).map(|plan| (purchase, plan)))
+            .filter(|(purchase, plan)| match plan {
+                SubscriptionPlan::Lifetime => purchase.status == "paid",
+                _ => {
+                    matches!(purchase.status.as_str(), "active" | "trialing")
+                        && purchase.current_period_end.map(|end| end > now).unwrap_or(false)
+                }
+            })
+            .max_by_key(|(purchase, plan)| (!plan.is_recurring(), purchase.current_period_end));
+
+        if let Some((purchase, plan)) = valid {
+            subscription.activate_stripe_subscription(plan, purchase.id.clone(), purchase.current_period_end)?;
+            return Ok(true);
+        }
+
+        // 当前的购买被退款或拒付时撤销授权
+        let current = purchases
+            .purchases
+            .iter()
+            .find(|purchase| subscription.stripe_subscription_id.as_deref() == Some(purchase.id.as_str()));
+        let revocation = match current.map(|purchase| purchase.status.as_str()) {
+            Some("refunded") => Some(RevocationReason::Refunded),
+            Some("disputed") => Some(RevocationReason::ChargedBack),
+            _ => None,
+        };
+        if let Some(reason) = revocation {
+            if subscription.revocation.is_none() {
+                subscription.revoke(reason)?;
+            }
+            return Ok(false);
+        }
+
+        // 续费扣款失败的订阅进入宽限期
+        let past_due = purchases.purchases.iter().find(|purchase| {
+            purchase.status == "past_due" && subscription.stripe_subscription_id.as_deref() == Some(purchase.id.as_str())
+        });
+        let mut changed = false;
+        if let Some(purchase) = past_due {
+            let since = purchase.current_period_end.map(|end| end.min(now)).unwrap_or(now);
+            changed |= subscription.enter_grace_period(since);
+        }
+        changed |= subscription.refresh_expiry();
+        if changed {
+            subscription.save()?;
+        }
+        Ok(false)
+    }
+}
@@ -0,0 +1,83 @@
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Url};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+use crate::i18n::t;
+use crate::loopback;
+
+// 结账页打开后最多等待 30 分钟
+const LISTEN_TIMEOUT_SECS: u64 = 30 * 60;
+const CALLBACK_PATH: &str = "/payment/success";
+
+lazy_static! {
+    // 正在运行的监听，开始新的结账时关闭旧的
+    static ref ACTIVE: Mutex<Option<oneshot::Sender<()>>> = Mutex::new(None);
+}
+
+/// 在本机随机端口上启动一次性的回调监听，返回结账成功后跳转的地址。
+/// 支付成功页请求该地址后立即确认支付并激活订阅，然后关闭监听
+pub async fn start(app: AppHandle) -> std::io::Result<String> {
+    let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+    let port = listener.local_addr()?.port();
+    // 只接受带本次随机令牌的请求，避免本机其他程序伪造回调
+    let token = uuid::Uuid::new_v4().simple().to_string();
+
+    let (stop_sender, mut stop_receiver) = oneshot::channel();
+    if let Some(previous) = ACTIVE.lock().unwrap().replace(stop_sender) {
+        let _ = previous.send(());
+    }
+
+    let callback_token = token.clone();
+    tauri::async_runtime::spawn(async move {
+        let timeout = tokio::time::sleep(tokio::time::Duration::from_secs(LISTEN_TIMEOUT_SECS));
+        tokio::pin!(timeout);
+
+        loop {
+            tokio::select! {
+                _ = &mut timeout => {
+                    log::info!("Payment callback listener timed out");
+                    break;
+                }
+                _ = &mut stop_receiver => break,
+                accepted = listener.accept() => {
+                    let Ok((stream, _)) = accepted else {
+                        continue;
+                    };
+                    if handle_connection(stream, &callback_token).await {
+                        log::info!("Received payment callback on port {}", port);
+                        if let Some(window) = app.get_webview_window("main") {
+                            let _ = window.show();
+                            let _ = window.set_focus();
+                        }
+                        crate::payment::confirm(app).await;
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(format!("http://127.0.0.1:{}{}?token={}", port, CALLBACK_PATH, token))
+}
+
+// 读取请求行并返回简单的页面，请求是带正确令牌的回调时返回 true
+async fn handle_connection(mut stream: TcpStream, token: &str) -> bool {
+    let Some(url) = loopback::read_target(&mut stream).await else {
+        return false;
+    };
+    let matched = is_callback(&url, token);
+    if matched {
+        let page = loopback::html_page(&t("payment_callback_title"), &t("payment_callback_body"));
+        loopback::respond(&mut stream, "200 OK", loopback::HTML_CONTENT_TYPE, &page).await;
+    } else {
+        loopback::respond(&mut stream, "404 Not Found", loopback::HTML_CONTENT_TYPE, "").await;
+    }
+    matched
+}
+
+// 结账服务会在地址后追加自己的参数，只检查路径和令牌
+fn is_callback(url: &Url, token: &str) -> bool {
+    url.path() == CALLBACK_PATH && url.query_pairs().any(|(key, value)| key == "token" && value == token)
+}
@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// 开发者用的支付测试模式：订阅和支付请求发往测试服务器，不产生真实扣款
+pub const TEST_MODE_ENV: &str = "FILESORTIFY_PAYMENT_TEST_MODE";
+// 指定其他测试服务器，例如本地运行的服务端
+pub const STAGING_URL_ENV: &str = "FILESORTIFY_STAGING_SERVER_URL";
+
+pub const PRODUCTION_SERVER_URL: &str = "https://filesortify.picasso-designs.com";
+const STAGING_SERVER_URL: &str = "https://staging.filesortify.picasso-designs.com";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 根据隐藏设置和环境变量决定是否开启测试模式。
+/// 只在启动时加载订阅之前调用，运行期间切换需要重启
+pub fn init(setting: bool) {
+    let enabled = setting || enabled_by_env();
+    ENABLED.store(enabled, Ordering::Relaxed);
+    if enabled {
+        log::warn!("Payment test mode is enabled, using {}", server_url());
+    }
+}
+
+/// 环境变量是否开启了测试模式
+pub fn enabled_by_env() -> bool {
+    std::env::var(TEST_MODE_ENV)
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 新订阅使用的服务器地址
+pub fn server_url() -> String {
+    if !is_enabled() {
+        return PRODUCTION_SERVER_URL.to_string();
+    }
+    std::env::var(STAGING_URL_ENV)
+        .ok()
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| STAGING_SERVER_URL.to_string())
+}
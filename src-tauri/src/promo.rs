@@ -0,0 +1,135 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{t, t_format};
+use crate::subscription::SubscriptionPlan;
+
+/// 优惠码的兑换结果：折扣码在下次 Creem 结账时使用，赠送码直接激活对应的计划
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PromoRedemption {
+    Discount {
+        code: String,
+        discount_percent: u32,
+    },
+    Giveaway {
+        code: String,
+        plan: SubscriptionPlan,
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+/// 优惠码相关的错误。序列化为带 kind 字段的对象，前端按 kind 显示对应的提示
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PromoError {
+    Invalid { message: String },
+    Expired { message: String },
+    AlreadyRedeemed { message: String },
+    AlreadySubscribed { message: String },
+    Request { message: String },
+}
+
+impl std::fmt::Display for PromoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromoError::Invalid { message }
+            | PromoError::Expired { message }
+            | PromoError::AlreadyRedeemed { message }
+            | PromoError::AlreadySubscribed { message }
+            | PromoError::Request { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl PromoError {
+    pub fn invalid() -> Self {
+        PromoError::Invalid { message: t("promo_code_invalid") }
+    }
+
+    pub fn request(e: impl std::fmt::Display) -> Self {
+        PromoError::Request { message: t_format("promo_request_failed", &[&e.to_string()]) }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RedeemRequest {
+    #[serde(rename = "deviceId")]
+    device_id: String,
+    code: String,
+}
+
+/// POST /api/promo-codes/redeem 的响应
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RedeemResponse {
+    Discount {
+        #[serde(rename = "discountPercent")]
+        discount_percent: u32,
+    },
+    Giveaway {
+        #[serde(rename = "grantId")]
+        grant_id: String,
+        plan: String,
+        #[serde(rename = "expiresAt", default)]
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct GrantStatus {
+    valid: bool,
+}
+
+/// 向服务端验证并兑换优惠码
+pub async fn redeem(server_url: &str, device_id: &str, code: &str) -> Result<RedeemResponse, PromoError> {
+    let request = RedeemRequest {
+        device_id: device_id.to_string(),
+        code: code.to_string(),
+    };
+    let response = crate::http::client()
+        .post(&format!("{}/api/promo-codes/redeem", server_url))
+        .json(&request)
+        .send()
+        .await
+        .map_err(PromoError::request)?;
+
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => Err(PromoError::invalid()),
+        reqwest::StatusCode::GONE => Err(PromoError::Expired { message: t("promo_code_expired") }),
+        // 此设备已经兑换过，或优惠码的使用次数已满
+        reqwest::StatusCode::CONFLICT => Err(PromoError::AlreadyRedeemed { message: t("promo_code_already_redeemed") }),
+        status if !status.is_success() => Err(PromoError::request(status)),
+        _ => response.json().await.map_err(PromoError::request),
+    }
+}
+
+/// 确认赠送码激活的授权仍然有效，服务端撤销赠送时返回 false
+pub async fn verify_grant(
+    server_url: &str,
+    device_id: &str,
+    grant_id: &str,
+) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    let response = crate::http::client()
+        .get(&format!("{}/api/promo-codes/grants/{}", server_url, grant_id))
+        .query(&[("deviceId", device_id)])
+        .send()
+        .await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(false);
+    }
+    if !response.status().is_success() {
+        return Err(format!("Failed to verify promo grant: {}", response.status()).into());
+    }
+    let status: GrantStatus = response.json().await?;
+    Ok(status.valid)
+}
+
+/// 优惠码只包含字母、数字和连字符，不区分大小写
+pub fn normalize_code(code: &str) -> Option<String> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() || code.len() > 64 || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    Some(code)
+}
@@ -0,0 +1,135 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::subscription::Subscription;
+
+// 确认令牌的有效期
+const TOKEN_TTL_SECONDS: i64 = 120;
+
+/// 清除全部数据前由前端申请的一次性确认令牌
+#[derive(Debug, Clone)]
+pub struct PurgeToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl PurgeToken {
+    pub fn new() -> Self {
+        Self {
+            token: uuid::Uuid::new_v4().to_string(),
+            expires_at: Utc::now() + Duration::seconds(TOKEN_TTL_SECONDS),
+        }
+    }
+
+    pub fn matches(&self, token: &str) -> bool {
+        self.token == token && Utc::now() < self.expires_at
+    }
+}
+
+/// 清除结果，列出删除了什么以及没能删除的内容
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeReport {
+    // 相对于应用数据目录的路径
+    pub removed_files: Vec<String>,
+    pub failed_files: Vec<String>,
+    // 服务端删除的设备登记（包括迁移前的旧设备 ID）
+    pub server_devices_removed: Vec<String>,
+    pub server_error: Option<String>,
+    pub seat_released: bool,
+    pub keychain_key_removed: bool,
+    // 历史记录保存在前端，前端需要清空本地历史
+    pub clear_history: bool,
+}
+
+/// 清除本机和服务端的全部数据：服务端的设备登记和席位、订阅状态、设置和配置、
+/// 日志、崩溃报告和备份，以及钥匙串中的加密密钥。不会先创建备份。
+/// 服务端不可达时仍然清除本地数据，并在报告中给出错误
+pub async fn purge_all_data(subscription: &Subscription) -> PurgeReport {
+    let mut report = PurgeReport {
+        clear_history: true,
+        ..Default::default()
+    };
+
+    purge_server(subscription, &mut report).await;
+
+    // 关闭日志文件后才能删除，之后不再写日志文件
+    crate::logging::disable_file();
+    crate::telemetry::set_enabled(false);
+
+    let data_dir = crate::backup::app_data_dir();
+    if data_dir.exists() {
+        remove_dir_contents(&data_dir, &data_dir, &mut report);
+    }
+
+    match crate::secure_store::delete_key() {
+        Ok(removed) => report.keychain_key_removed = removed,
+        Err(e) => report.failed_files.push(format!("keychain: {}", e)),
+    }
+
+    report
+}
+
+async fn purge_server(subscription: &Subscription, report: &mut PurgeReport) {
+    let server_url = &subscription.webhook_server_url;
+
+    if let Some(license_id) = subscription.license_id() {
+        match crate::seats::release_device(server_url, &license_id, &subscription.device_id, &subscription.device_id).await {
+            Ok(_) => report.seat_released = true,
+            Err(e) => log::warn!("Failed to release seat during purge: {}", e),
+        }
+    }
+
+    let device_ids = std::iter::once(&subscription.device_id).chain(subscription.previous_device_ids.iter());
+    for device_id in device_ids {
+        match delete_device(server_url, device_id).await {
+            Ok(()) => report.server_devices_removed.push(device_id.clone()),
+            Err(e) => {
+                log::warn!("Failed to delete device data on the server: {}", e);
+                report.server_error = Some(e.to_string());
+            }
+        }
+    }
+}
+
+// 删除服务端记录的设备数据（购买关联、推荐奖励、优惠码兑换等），没有记录时同样视为成功
+async fn delete_device(server_url: &str, device_id: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let response = crate::http::client()
+        .delete(&format!("{}/api/devices/{}", server_url, device_id))
+        .send()
+        .await?;
+    if response.status().is_success() || response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(());
+    }
+    Err(format!("Failed to delete device data: {}", response.status()).into())
+}
+
+// 删除目录中的全部文件和子目录，保留数据目录本身
+fn remove_dir_contents(dir: &Path, root: &Path, report: &mut PurgeReport) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            report.failed_files.push(format!("{}: {}", relative(dir, root), e));
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            remove_dir_contents(&path, root, report);
+            if let Err(e) = fs::remove_dir(&path) {
+                report.failed_files.push(format!("{}: {}", relative(&path, root), e));
+            }
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => report.removed_files.push(relative(&path, root)),
+            Err(e) => report.failed_files.push(format!("{}: {}", relative(&path, root), e)),
+        }
+    }
+}
+
+fn relative(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().to_string()
+}
@@ -0,0 +1,132 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{t, t_format};
+use crate::subscription::Subscription;
+
+/// 推荐奖励最多累计的试用天数
+pub const MAX_BONUS_DAYS: i64 = 30;
+
+/// 一次推荐奖励：兑换朋友的推荐码，或自己的推荐码被朋友兑换。
+/// 由服务端记录和下发，本地只保存副本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReferralGrant {
+    pub id: String,
+    #[serde(rename = "bonusDays")]
+    pub bonus_days: i64,
+    #[serde(rename = "grantedAt")]
+    pub granted_at: DateTime<Utc>,
+}
+
+/// 推荐码和已获得的奖励
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferralInfo {
+    pub code: String,
+    pub bonus_days: i64,
+    pub max_bonus_days: i64,
+    pub redeemed_code: Option<String>,
+    pub trial_days_remaining: i64,
+}
+
+impl ReferralInfo {
+    pub fn new(code: String, subscription: &Subscription) -> Self {
+        Self {
+            code,
+            bonus_days: subscription.referral_bonus_days(),
+            max_bonus_days: MAX_BONUS_DAYS,
+            redeemed_code: subscription.redeemed_referral_code.clone(),
+            trial_days_remaining: subscription.get_trial_days_remaining(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ReferralRequest {
+    #[serde(rename = "deviceId")]
+    device_id: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RedeemRequest {
+    #[serde(rename = "deviceId")]
+    device_id: String,
+    code: String,
+}
+
+/// POST /api/referrals 的响应：当前设备的推荐码（没有时由服务端生成）和全部奖励
+#[derive(Debug, Deserialize)]
+pub struct ReferralResponse {
+    pub code: String,
+    #[serde(default)]
+    pub grants: Vec<ReferralGrant>,
+}
+
+/// 获取当前设备的推荐码和服务端记录的奖励
+pub async fn fetch(server_url: &str, device_id: &str) -> Result<ReferralResponse, String> {
+    let request = ReferralRequest {
+        device_id: device_id.to_string(),
+    };
+    let response = crate::http::client()
+        .post(&format!("{}/api/referrals", server_url))
+        .json(&request)
+        .send()
+        .await
+        .map_err(request_failed)?;
+    if !response.status().is_success() {
+        return Err(request_failed(response.status()));
+    }
+    response.json().await.map_err(request_failed)
+}
+
+/// 兑换朋友的推荐码，成功后服务端返回包含新奖励的全部奖励
+pub async fn redeem(server_url: &str, device_id: &str, code: &str) -> Result<ReferralResponse, String> {
+    let request = RedeemRequest {
+        device_id: device_id.to_string(),
+        code: code.to_string(),
+    };
+    let response = crate::http::client()
+        .post(&format!("{}/api/referrals/redeem", server_url))
+        .json(&request)
+        .send()
+        .await
+        .map_err(request_failed)?;
+
+    match response.status() {
+        reqwest::StatusCode::NOT_FOUND => Err(t("referral_code_invalid")),
+        // 已兑换过推荐码，或兑换的是自己的推荐码
+        reqwest::StatusCode::CONFLICT => Err(t("referral_already_redeemed")),
+        reqwest::StatusCode::UNPROCESSABLE_ENTITY => Err(t("referral_own_code")),
+        status if !status.is_success() => Err(request_failed(status)),
+        _ => response.json().await.map_err(request_failed),
+    }
+}
+
+/// 推荐码只包含字母和数字，不区分大小写
+pub fn normalize_code(code: &str) -> Option<String> {
+    let code = code.trim().to_uppercase();
+    if code.is_empty() || code.len() > 32 || !code.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(code)
+}
+
+/// 检查奖励是否合理：编号不重复、时间不在未来且不早于试用开始、总天数不超过上限
+pub fn grants_are_valid(grants: &[ReferralGrant], trial_start: Option<DateTime<Utc>>) -> bool {
+    let now = Utc::now();
+    let mut ids = std::collections::HashSet::new();
+    let mut total = 0;
+    for grant in grants {
+        if !ids.insert(grant.id.as_str()) || grant.bonus_days <= 0 || grant.granted_at > now {
+            return false;
+        }
+        if trial_start.map(|start| grant.granted_at < start).unwrap_or(true) {
+            return false;
+        }
+        total += grant.bonus_days;
+    }
+    total <= MAX_BONUS_DAYS
+}
+
+fn request_failed(e: impl std::fmt::Display) -> String {
+    t_format("referral_request_failed", &[&e.to_string()])
+}
@@ -0,0 +1,96 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backup;
+use crate::config::Config;
+use crate::settings::GeneralSettings;
+
+// 确认令牌的有效期
+const TOKEN_TTL_SECONDS: i64 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetScope {
+    Config,     // 仅分类和路径配置
+    History,    // 仅整理历史和撤销记录
+    Everything, // 全部应用数据（订阅状态除外）
+}
+
+/// 重置前由前端申请的一次性确认令牌
+#[derive(Debug, Clone)]
+pub struct ResetToken {
+    pub token: String,
+    pub scope: ResetScope,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ResetToken {
+    pub fn new(scope: ResetScope) -> Self {
+        Self {
+            token: uuid::Uuid::new_v4().to_string(),
+            scope,
+            expires_at: Utc::now() + Duration::seconds(TOKEN_TTL_SECONDS),
+        }
+    }
+
+    pub fn matches(&self, token: &str, scope: ResetScope) -> bool {
+        self.token == token && self.scope == scope && Utc::now() < self.expires_at
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResetResult {
+    pub scope: ResetScope,
+    pub backup_path: String,
+    pub removed_files: Vec<String>,
+    // 历史记录保存在前端，为 true 时前端需要清空本地历史
+    pub clear_history: bool,
+}
+
+/// 备份后按范围重置数据
+pub fn reset(
+    scope: ResetScope,
+    history: Option<serde_json::Value>,
+    app_version: &str,
+) -> Result<ResetResult, Box<dyn std::error::Error>> {
+    let backup_path = backup::create_backup(None, history, app_version)?;
+    let mut removed: Vec<PathBuf> = Vec::new();
+
+    match scope {
+        ResetScope::Config => {
+            for path in Config::candidate_paths() {
+                if path.exists() {
+                    fs::remove_file(&path)?;
+                    removed.push(path);
+                }
+            }
+            Config::default().save()?;
+        }
+        ResetScope::History => crate::activity::clear(),
+        ResetScope::Everything => {
+            crate::activity::clear();
+            for path in backup::data_files(&backup::app_data_dir())? {
+                // 订阅状态不随重置清除，否则重置就能重新获得试用期
+                if path.file_name().and_then(|n| n.to_str()) == Some("subscription.json") {
+                    continue;
+                }
+                fs::remove_file(&path)?;
+                removed.push(path);
+            }
+            GeneralSettings::default().save()?;
+            Config::default().save()?;
+        }
+    }
+
+    Ok(ResetResult {
+        scope,
+        backup_path: backup_path.to_string_lossy().to_string(),
+        removed_files: removed
+            .iter()
+            .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+            .collect(),
+        clear_history: matches!(scope, ResetScope::History | ResetScope::Everything),
+    })
+}
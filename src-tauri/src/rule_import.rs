@@ -0,0 +1,276 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::Config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    #[default]
+    FileSortify, // FileSortify 自己导出的配置文件
+    Hazel,       // Hazel 导出的 .hazelrules（macOS）
+    DropIt,      // DropIt 的配置档案 .ini（Windows）
+}
+
+/// 按来源读取要导入的规则，统一转换成 FileSortify 配置
+pub fn load(source: ImportSource, path: &Path) -> Result<Config, Box<dyn std::error::Error>> {
+    let rules = match source {
+        ImportSource::FileSortify => return Config::read_from(path),
+        ImportSource::Hazel => parse_hazel(path)?,
+        ImportSource::DropIt => parse_dropit(&fs::read_to_string(path)?),
+    };
+    if rules.is_empty() {
+        return Err("No extension-based rules found".into());
+    }
+    Ok(to_config(rules))
+}
+
+// (规则名称, 扩展名)
+type ImportedRules = Vec<(String, Vec<String>)>;
+
+// 把外部规则转换成分类：名称作为显示名，生成稳定的ID
+fn to_config(rules: ImportedRules) -> Config {
+    let mut config = Config {
+        categories: HashMap::new(),
+        category_names: HashMap::new(),
+        category_meta: HashMap::new(),
+        paths: None,
+        rules: None,
+        hooks: Default::default(),
+        category_destinations: HashMap::new(),
+        ..Config::default()
+    };
+
+    for (name, extensions) in rules {
+        let base_id = category_id_for(&name);
+        let mut id = base_id.clone();
+        let mut suffix = 2;
+        while config.categories.contains_key(&id) {
+            id = format!("{}_{}", base_id, suffix);
+            suffix += 1;
+        }
+        config.categories.insert(id.clone(), extensions);
+        config.category_names.insert(id, name);
+    }
+    config
+}
+
+fn category_id_for(name: &str) -> String {
+    let slug: String = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let slug = slug.split('_').filter(|s| !s.is_empty()).collect::<Vec<_>>().join("_");
+    if slug.is_empty() {
+        "imported".to_string()
+    } else {
+        slug
+    }
+}
+
+fn normalize_extension(raw: &str) -> Option<String> {
+    let ext = raw.trim().trim_start_matches('*').trim_start_matches('.');
+    if ext.is_empty() || ext.len() > 10 || !ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(format!(".{}", ext.to_lowercase()))
+}
+
+// DropIt 档案是 INI 文件，每个关联一个节：
+// [Pictures]
+// Rules=*.jpg;*.png
+// Action=$1
+// 只导入纯扩展名匹配（*.ext）的规则，其他模式无法对应到分类
+fn parse_dropit(content: &str) -> ImportedRules {
+    let mut rules = Vec::new();
+    let mut section: Option<String> = None;
+    let mut enabled = true;
+    let mut extensions: Vec<String> = Vec::new();
+
+    let mut flush = |section: &Option<String>, enabled: bool, extensions: &mut Vec<String>| {
+        if let Some(name) = section {
+            if enabled && !extensions.is_empty() {
+                rules.push((name.clone(), std::mem::take(extensions)));
+            }
+        }
+        extensions.clear();
+    };
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            flush(&section, enabled, &mut extensions);
+            let name = line[1..line.len() - 1].trim().to_string();
+            section = if name.eq_ignore_ascii_case("General") || name.eq_ignore_ascii_case("Target") {
+                None
+            } else {
+                Some(name)
+            };
+            enabled = true;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        match key.trim().to_lowercase().as_str() {
+            "rules" => {
+                for pattern in value.split(|c| c == ';' || c == ',') {
+                    let pattern = pattern.trim();
+                    // 只接受 *.ext 形式，带其他通配或名称前缀的规则跳过
+                    if let Some(ext) = pattern.strip_prefix("*.") {
+                        if let Some(ext) = normalize_extension(ext) {
+                            if !extensions.contains(&ext) {
+                                extensions.push(ext);
+                            }
+                        }
+                    }
+                }
+            }
+            "state" => enabled = !value.trim().eq_ignore_ascii_case("disabled"),
+            _ => {}
+        }
+    }
+    flush(&section, enabled, &mut extensions);
+
+    rules
+}
+
+// Hazel 的规则导出是 NSKeyedArchiver 格式的 plist。先展开成普通的树，
+// 再找出带名称和条件的规则，收集其中“扩展名”条件的值
+fn parse_hazel(path: &Path) -> Result<ImportedRules, Box<dyn std::error::Error>> {
+    let root = plist::Value::from_file(path)?;
+    let tree = match root.as_dictionary().and_then(|d| d.get("$objects")).and_then(|o| o.as_array()) {
+        Some(objects) => {
+            let top = root
+                .as_dictionary()
+                .and_then(|d| d.get("$top"))
+                .cloned()
+                .unwrap_or_else(|| plist::Value::Array(objects.to_vec()));
+            unarchive(&top, objects, 0)
+        }
+        None => unarchive(&root, &[], 0),
+    };
+
+    let mut rules = Vec::new();
+    collect_hazel_rules(&tree, &mut rules);
+    Ok(rules)
+}
+
+// 展开归档对象的引用关系；限制深度以防循环引用
+fn unarchive(value: &plist::Value, objects: &[plist::Value], depth: usize) -> serde_json::Value {
+    use serde_json::Value as Json;
+
+    if depth > 32 {
+        return Json::Null;
+    }
+    match value {
+        plist::Value::Uid(uid) => match objects.get(uid.get() as usize) {
+            Some(object) => unarchive(object, objects, depth + 1),
+            None => Json::Null,
+        },
+        plist::Value::String(s) if s == "$null" => Json::Null,
+        plist::Value::String(s) => Json::String(s.clone()),
+        plist::Value::Boolean(b) => Json::Bool(*b),
+        plist::Value::Integer(i) => i.as_signed().map(Json::from).unwrap_or(Json::Null),
+        plist::Value::Array(items) => Json::Array(items.iter().map(|v| unarchive(v, objects, depth + 1)).collect()),
+        plist::Value::Dictionary(dict) => {
+            // NSDictionary / NSArray 归档后是 NS.keys + NS.objects
+            if let Some(values) = dict.get("NS.objects").and_then(|v| v.as_array()) {
+                let values: Vec<Json> = values.iter().map(|v| unarchive(v, objects, depth + 1)).collect();
+                return match dict.get("NS.keys").and_then(|k| k.as_array()) {
+                    Some(keys) => Json::Object(
+                        keys.iter()
+                            .map(|k| unarchive(k, objects, depth + 1))
+                            .zip(values)
+                            .filter_map(|(k, v)| k.as_str().map(|k| (k.to_string(), v)))
+                            .collect(),
+                    ),
+                    None => Json::Array(values),
+                };
+            }
+            if let Some(s) = dict.get("NS.string") {
+                return unarchive(s, objects, depth + 1);
+            }
+            Json::Object(
+                dict.iter()
+                    .filter(|(k, _)| !k.starts_with('$'))
+                    .map(|(k, v)| (k.clone(), unarchive(v, objects, depth + 1)))
+                    .collect(),
+            )
+        }
+        _ => Json::Null,
+    }
+}
+
+fn collect_hazel_rules(node: &serde_json::Value, rules: &mut ImportedRules) {
+    match node {
+        serde_json::Value::Object(map) => {
+            let name = map.get("name").or_else(|| map.get("displayName")).and_then(|v| v.as_str());
+            let has_conditions = map.keys().any(|k| k.to_lowercase().contains("condition"));
+            if let (Some(name), true) = (name, has_conditions) {
+                let mut extensions = Vec::new();
+                collect_extension_conditions(node, &mut extensions);
+                if !extensions.is_empty() {
+                    rules.push((name.to_string(), extensions));
+                }
+                return;
+            }
+            for child in map.values() {
+                collect_hazel_rules(child, rules);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for child in items {
+                collect_hazel_rules(child, rules);
+            }
+        }
+        _ => {}
+    }
+}
+
+// 条件对象的属性是 extension 时，它的值（value/values 字段）就是要匹配的扩展名；
+// 运算符等其他字段不是扩展名，不读取
+fn collect_extension_conditions(node: &serde_json::Value, out: &mut Vec<String>) {
+    match node {
+        serde_json::Value::Object(map) => {
+            let mentions_extension = map.iter().any(|(k, v)| {
+                k.to_lowercase().contains("attribute")
+                    && v.as_str().map(|s| s.to_lowercase().contains("extension")).unwrap_or(false)
+            });
+            if mentions_extension {
+                for (key, value) in map {
+                    let key = key.to_lowercase();
+                    if key.contains("attribute") || !key.contains("value") {
+                        continue;
+                    }
+                    let values: Vec<&str> = match value {
+                        serde_json::Value::String(s) => vec![s.as_str()],
+                        serde_json::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+                        _ => Vec::new(),
+                    };
+                    for value in values {
+                        if let Some(ext) = normalize_extension(value) {
+                            if !out.contains(&ext) {
+                                out.push(ext);
+                            }
+                        }
+                    }
+                }
+            }
+            for child in map.values() {
+                collect_extension_conditions(child, out);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for child in items {
+                collect_extension_conditions(child, out);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,113 @@
+use serde::Serialize;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, WindowEvent};
+
+use crate::i18n::{t, t_format};
+
+/// 规则编辑器窗口的标签，权限见 capabilities/rules-editor.json
+pub const WINDOW_LABEL: &str = "rules-editor";
+const MAIN_WINDOW_LABEL: &str = "main";
+
+/// 规则编辑器窗口自己的状态，与主窗口的 AppState 分开管理，窗口关闭后清空
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RulesEditorSession {
+    // 正在编辑的路径，为空时编辑全局规则
+    pub path_id: Option<String>,
+    // 有未保存的修改时，关闭窗口前先让编辑器确认
+    pub unsaved_changes: bool,
+}
+
+#[derive(Default)]
+pub struct RulesEditorState(Mutex<RulesEditorSession>);
+
+/// 在窗口之间转发的消息（window-relay 事件），from 是发送方窗口的标签
+#[derive(Debug, Clone, Serialize)]
+pub struct RelayMessage {
+    pub from: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+}
+
+/// 打开规则编辑器窗口并切换到指定路径；窗口已经打开时只切换路径并聚焦
+pub fn open(app_handle: &AppHandle, path_id: Option<String>) -> Result<(), String> {
+    let state = app_handle.state::<RulesEditorState>();
+    {
+        let mut session = state.0.lock().unwrap();
+        session.path_id = path_id.clone();
+    }
+
+    if let Some(window) = app_handle.get_webview_window(WINDOW_LABEL) {
+        let _ = window.emit_to(WINDOW_LABEL, "rules-editor-open-path", &serde_json::json!({ "pathId": path_id }));
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    // 前端按 window 参数渲染规则编辑器，路径通过 get_rules_editor_session 获取
+    let window = WebviewWindowBuilder::new(app_handle, WINDOW_LABEL, WebviewUrl::App("index.html?window=rules-editor".into()))
+        .title(t("rules_editor_title"))
+        .inner_size(1100.0, 760.0)
+        .min_inner_size(800.0, 560.0)
+        .center()
+        .build()
+        .map_err(|e| t_format("rules_editor_open_failed", &[&e.to_string()]))?;
+
+    let handle = app_handle.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::CloseRequested { api, .. } => {
+            // 有未保存的修改时交给编辑器确认，编辑器保存或放弃后再关闭窗口
+            let unsaved = handle.state::<RulesEditorState>().0.lock().unwrap().unsaved_changes;
+            if unsaved {
+                api.prevent_close();
+                let _ = handle.emit_to(WINDOW_LABEL, "rules-editor-close-requested", ());
+            }
+        }
+        WindowEvent::Destroyed => {
+            *handle.state::<RulesEditorState>().0.lock().unwrap() = RulesEditorSession::default();
+            let _ = handle.emit_to(MAIN_WINDOW_LABEL, "rules-editor-closed", ());
+        }
+        _ => {}
+    });
+
+    crate::telemetry::record_feature("rules_editor");
+    Ok(())
+}
+
+// Tauri命令：打开规则编辑器窗口
+#[tauri::command]
+pub fn open_rules_editor(path_id: Option<String>, app_handle: AppHandle) -> Result<(), String> {
+    open(&app_handle, path_id)
+}
+
+// Tauri命令：规则编辑器窗口启动后获取要编辑的路径
+#[tauri::command]
+pub fn get_rules_editor_session(state: tauri::State<'_, RulesEditorState>) -> RulesEditorSession {
+    state.0.lock().unwrap().clone()
+}
+
+// Tauri命令：编辑器记录是否有未保存的修改
+#[tauri::command]
+pub fn set_rules_editor_unsaved(unsaved: bool, state: tauri::State<'_, RulesEditorState>) {
+    state.0.lock().unwrap().unsaved_changes = unsaved;
+}
+
+// Tauri命令：把消息转发给另一个窗口（主窗口和规则编辑器之间），例如编辑器保存规则后通知主窗口刷新
+#[tauri::command]
+pub fn relay_window_event(
+    event: String,
+    payload: serde_json::Value,
+    window: tauri::WebviewWindow,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    let target = if window.label() == WINDOW_LABEL { MAIN_WINDOW_LABEL } else { WINDOW_LABEL };
+    // 对方窗口没有打开时消息直接丢弃
+    if app_handle.get_webview_window(target).is_none() {
+        return Ok(());
+    }
+    let message = RelayMessage {
+        from: window.label().to_string(),
+        event,
+        payload,
+    };
+    app_handle.emit_to(target, "window-relay", &message).map_err(|e| e.to_string())
+}
@@ -0,0 +1,21 @@
+#ifndef SCRIPTING_H
+#define SCRIPTING_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// AppleScript 命令完成后调用，token 为 scripting_handle_command 收到的值，只能调用一次
+void scripting_command_finished(void* token, bool success, const char* message);
+
+// 回调函数声明（由Rust实现）
+// command：organize / start_monitoring / undo_last；folder_path 可以为空
+void scripting_handle_command(const char* command, const char* folder_path, void* token);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // SCRIPTING_H
@@ -0,0 +1,74 @@
+#import <Foundation/Foundation.h>
+#import "scripting.h"
+
+// 对应 macos/FileSortify.sdef 中的命令。命令挂起后交给 Rust 异步执行，
+// 完成时通过 scripting_command_finished 恢复，脚本和快捷指令得到结果或错误
+@interface FSScriptCommand : NSScriptCommand
+- (const char *)commandName;
+@end
+
+@implementation FSScriptCommand
+
+- (const char *)commandName {
+    return "";
+}
+
+- (id)performDefaultImplementation {
+    // 文件夹可以写成 alias、POSIX file 或路径文本
+    id target = [self directParameter];
+    NSString *folderPath = nil;
+    if ([target isKindOfClass:[NSURL class]]) {
+        folderPath = [(NSURL *)target path];
+    } else if ([target isKindOfClass:[NSString class]]) {
+        folderPath = (NSString *)target;
+    }
+
+    [self suspendExecution];
+    scripting_handle_command([self commandName], folderPath != nil ? [folderPath UTF8String] : NULL, (__bridge_retained void *)self);
+    return nil;
+}
+
+@end
+
+@interface FSOrganizeFolderCommand : FSScriptCommand
+@end
+
+@implementation FSOrganizeFolderCommand
+- (const char *)commandName {
+    return "organize";
+}
+@end
+
+@interface FSStartMonitoringCommand : FSScriptCommand
+@end
+
+@implementation FSStartMonitoringCommand
+- (const char *)commandName {
+    return "start_monitoring";
+}
+@end
+
+@interface FSUndoLastCommand : FSScriptCommand
+@end
+
+@implementation FSUndoLastCommand
+- (const char *)commandName {
+    return "undo_last";
+}
+@end
+
+void scripting_command_finished(void *token, bool success, const char *message) {
+    NSScriptCommand *command = (__bridge_transfer NSScriptCommand *)token;
+    NSString *text = message != NULL ? [NSString stringWithUTF8String:message] : @"";
+    // 挂起的命令必须在主线程恢复
+    dispatch_async(dispatch_get_main_queue(), ^{
+        if (success) {
+            [command resumeExecutionWithResult:text];
+        } else {
+            // errOSAGeneralError，脚本中显示为普通错误
+            [command setScriptErrorNumber:-2700];
+            [command setScriptErrorString:text];
+            [command resumeExecutionWithResult:nil];
+        }
+    });
+}
@@ -0,0 +1,120 @@
+use lazy_static::lazy_static;
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::i18n::{t, t_format};
+use crate::AppState;
+
+extern "C" {
+    fn scripting_command_finished(token: *mut c_void, success: bool, message: *const c_char);
+}
+
+lazy_static! {
+    // 应用启动完成前收到的命令直接返回错误
+    static ref APP_HANDLE: Mutex<Option<AppHandle>> = Mutex::new(None);
+}
+
+/// 开始处理 AppleScript / 快捷指令命令（organize folder、start monitoring、undo last），
+/// 命令定义见 macos/FileSortify.sdef
+pub fn install(app_handle: &AppHandle) {
+    *APP_HANDLE.lock().unwrap() = Some(app_handle.clone());
+}
+
+// 挂起的 NSScriptCommand，完成时必须且只能交回一次
+struct PendingCommand(usize);
+
+impl PendingCommand {
+    fn finish(self, result: Result<String, String>) {
+        let (success, message) = match result {
+            Ok(message) => (true, message),
+            Err(message) => (false, message),
+        };
+        let message = CString::new(message).unwrap_or_default();
+        unsafe {
+            scripting_command_finished(self.0 as *mut c_void, success, message.as_ptr());
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn scripting_handle_command(command: *const c_char, folder_path: *const c_char, token: *mut c_void) {
+    let pending = PendingCommand(token as usize);
+    let command = unsafe { CStr::from_ptr(command) }.to_string_lossy().to_string();
+    let folder_path = if folder_path.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(folder_path) }.to_string_lossy().to_string())
+    };
+
+    let Some(app_handle) = APP_HANDLE.lock().unwrap().clone() else {
+        pending.finish(Err(t("script_not_ready")));
+        return;
+    };
+    log::info!("AppleScript command: {} {:?}", command, folder_path);
+
+    tauri::async_runtime::spawn(async move {
+        let result = match command.as_str() {
+            "organize" => organize(&app_handle, folder_path).await,
+            "start_monitoring" => start_monitoring(&app_handle, folder_path).await,
+            "undo_last" => undo_last(&app_handle, folder_path).await,
+            _ => Err(t_format("script_unknown_command", &[&command])),
+        };
+        if result.is_ok() {
+            crate::telemetry::record_feature("applescript");
+        }
+        pending.finish(result);
+    });
+}
+
+async fn organize(app_handle: &AppHandle, folder_path: Option<String>) -> Result<String, String> {
+    let folder = folder_or_downloads(folder_path)?;
+    crate::organize_files(folder, app_handle.state::<AppState>(), app_handle.clone())
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// 已经在监控时直接返回成功，重复运行快捷指令不会把监控关掉
+async fn start_monitoring(app_handle: &AppHandle, folder_path: Option<String>) -> Result<String, String> {
+    let folder = folder_or_downloads(folder_path)?;
+    let already_monitoring = app_handle.state::<AppState>().organizers.lock().await.contains_key(&folder);
+    if !already_monitoring {
+        crate::toggle_monitoring(folder.clone(), app_handle.state::<AppState>(), app_handle.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+        let payload = serde_json::json!({ "folderPath": folder, "isMonitoring": true });
+        let _ = app_handle.emit("monitoring-changed", &payload);
+    }
+    Ok(t_format("monitoring_started_body", &[&folder]))
+}
+
+// 撤销历史只保存在正在监控的路径中，不指定路径时撤销所有路径中最近的一次移动
+async fn undo_last(app_handle: &AppHandle, folder_path: Option<String>) -> Result<String, String> {
+    let folder = match folder_path {
+        Some(path) => Some(crate::context_menu::validate_folder(&path)?.to_string_lossy().to_string()),
+        None => None,
+    };
+    let latest = {
+        let state = app_handle.state::<AppState>();
+        let organizers = state.organizers.lock().await;
+        organizers
+            .iter()
+            .filter(|(path, _)| folder.as_ref().map_or(true, |folder| folder == *path))
+            .filter_map(|(path, organizer)| organizer.get_undo_history(1).into_iter().next().map(|action| (path.clone(), action)))
+            .max_by(|(_, a), (_, b)| a.timestamp.cmp(&b.timestamp))
+    };
+    let Some((path, action)) = latest else {
+        return Err(t("cli_nothing_to_undo"));
+    };
+    crate::undo_file_action(path, action.id, app_handle.state::<AppState>(), app_handle.clone()).await
+}
+
+fn folder_or_downloads(path: Option<String>) -> Result<String, String> {
+    match path {
+        Some(path) => crate::context_menu::validate_folder(&path).map(|path| path.to_string_lossy().to_string()),
+        None => dirs::download_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .ok_or_else(|| t("downloads_folder_not_found")),
+    }
+}
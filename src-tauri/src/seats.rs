@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::{t, t_format};
+use crate::subscription::Subscription;
+
+/// 多席位授权的使用情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatUsage {
+    #[serde(rename = "seatsUsed")]
+    pub seats_used: u32,
+    #[serde(rename = "seatsTotal")]
+    pub seats_total: u32,
+    #[serde(default)]
+    pub devices: Vec<SeatDevice>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatDevice {
+    #[serde(rename = "deviceId")]
+    pub device_id: String,
+    #[serde(rename = "deviceName", default)]
+    pub device_name: String,
+    #[serde(rename = "registeredAt", default)]
+    pub registered_at: Option<String>,
+    // 是否为当前设备，由客户端填写
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// 席位相关的错误。序列化为带 kind 字段的对象，
+/// 前端收到 seat_limit_reached 时可以引导用户释放其他设备或购买更多席位
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SeatError {
+    SeatLimitReached {
+        seats_used: u32,
+        seats_total: u32,
+        message: String,
+    },
+    NoLicense {
+        message: String,
+    },
+    Request {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for SeatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeatError::SeatLimitReached { message, .. }
+            | SeatError::NoLicense { message }
+            | SeatError::Request { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl SeatError {
+    pub fn no_license() -> Self {
+        SeatError::NoLicense { message: t("no_license_for_seats") }
+    }
+
+    fn request(e: impl std::fmt::Display) -> Self {
+        SeatError::Request { message: t_format("seat_request_failed", &[&e.to_string()]) }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct RegisterDeviceRequest {
+    #[serde(rename = "deviceId")]
+    device_id: String,
+    #[serde(rename = "deviceName")]
+    device_name: String,
+}
+
+/// 服务端席位已满时返回 409 和当前的使用情况
+#[derive(Debug, Deserialize)]
+struct SeatLimitResponse {
+    #[serde(rename = "seatsUsed")]
+    seats_used: u32,
+    #[serde(rename = "seatsTotal")]
+    seats_total: u32,
+}
+
+/// 在授权上登记当前设备，已登记过的设备不会重复占用席位
+pub async fn register_device(server_url: &str, license_id: &str, device_id: &str) -> Result<SeatUsage, SeatError> {
+    let request = RegisterDeviceRequest {
+        device_id: device_id.to_string(),
+        device_name: device_name(),
+    };
+    let response = crate::http::client()
+        .post(&devices_url(server_url, license_id))
+        .json(&request)
+        .send()
+        .await
+        .map_err(SeatError::request)?;
+
+    if response.status() == reqwest::StatusCode::CONFLICT {
+        let limit: SeatLimitResponse = response.json().await.map_err(SeatError::request)?;
+        return Err(SeatError::SeatLimitReached {
+            seats_used: limit.seats_used,
+            seats_total: limit.seats_total,
+            message: t_format("seat_limit_reached", &[&limit.seats_total.to_string()]),
+        });
+    }
+    parse_usage(response, device_id).await
+}
+
+/// 购买或恢复购买后在授权上登记当前设备，席位已满时发送 seat-limit-reached 事件
+pub async fn register_after_purchase(app: &tauri::AppHandle, subscription: &Subscription) {
+    use tauri::Emitter;
+
+    let Some(license_id) = subscription.license_id() else {
+        return;
+    };
+    match register_device(&subscription.webhook_server_url, &license_id, &subscription.device_id).await {
+        Ok(_) => {}
+        Err(e @ SeatError::SeatLimitReached { .. }) => {
+            let _ = app.emit("seat-limit-reached", &e);
+        }
+        Err(e) => log::warn!("Failed to register device seat: {}", e),
+    }
+}
+
+/// 查询授权的席位使用情况
+pub async fn get_usage(server_url: &str, license_id: &str, device_id: &str) -> Result<SeatUsage, SeatError> {
+    let response = crate::http::client()
+        .get(&devices_url(server_url, license_id))
+        .send()
+        .await
+        .map_err(SeatError::request)?;
+    parse_usage(response, device_id).await
+}
+
+/// 释放某台设备占用的席位，用于在席位已满时移除不再使用的设备
+pub async fn release_device(
+    server_url: &str,
+    license_id: &str,
+    target_device_id: &str,
+    device_id: &str,
+) -> Result<SeatUsage, SeatError> {
+    let response = crate::http::client()
+        .delete(&format!("{}/{}", devices_url(server_url, license_id), target_device_id))
+        .send()
+        .await
+        .map_err(SeatError::request)?;
+    parse_usage(response, device_id).await
+}
+
+fn devices_url(server_url: &str, license_id: &str) -> String {
+    format!("{}/api/licenses/{}/devices", server_url, license_id)
+}
+
+async fn parse_usage(response: reqwest::Response, device_id: &str) -> Result<SeatUsage, SeatError> {
+    if !response.status().is_success() {
+        return Err(SeatError::request(response.status()));
+    }
+    let mut usage: SeatUsage = response.json().await.map_err(SeatError::request)?;
+    for device in &mut usage.devices {
+        device.current = device.device_id == device_id;
+    }
+    Ok(usage)
+}
+
+// 在设备列表中显示的名称
+fn device_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .or_else(|_| std::env::var("HOST"))
+        .unwrap_or_else(|_| std::env::consts::OS.to_string())
+}
@@ -0,0 +1,276 @@
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use base64::Engine;
+use std::path::PathBuf;
+
+// 系统钥匙串中的服务名和条目名
+const KEYRING_SERVICE: &str = "com.fileSortify.tool";
+const KEYRING_ENTRY: &str = "subscription-key";
+// 旧格式订阅文件已经迁移过的标记
+const LEGACY_MIGRATED_ENTRY: &str = "legacy-migrated";
+
+// 加密文件格式：魔数 + 12 字节随机数 + AES-256-GCM 密文（含认证标签）
+const MAGIC: &[u8] = b"FSE1";
+const NONCE_LEN: usize = 12;
+
+/// 数据是否为新的加密格式（旧版本使用 XOR 混淆，没有魔数）
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC) && data.len() > MAGIC.len() + NONCE_LEN
+}
+
+/// 用保存在系统钥匙串中的密钥加密
+pub fn encrypt(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    encrypt_with_key(&get_or_create_key()?, plaintext)
+}
+
+/// 解密并校验数据，被修改过或密钥不匹配时返回错误
+pub fn decrypt(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) {
+        return Err("Data is not in the encrypted format".to_string());
+    }
+    decrypt_with_key(&get_or_create_key()?, data)
+}
+
+fn encrypt_with_key(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| e.to_string())?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, plaintext).map_err(|e| e.to_string())?;
+
+    let mut data = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+fn decrypt_with_key(key: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_encrypted(data) {
+        return Err("Data is not in the encrypted format".to_string());
+    }
+    let (nonce, ciphertext) = data[MAGIC.len()..].split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new_from_slice(key).map_err(|e| e.to_string())?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Decryption failed: data was modified or the key does not match".to_string())
+}
+
+/// 是否还允许读取旧的 XOR 格式。旧格式的密钥由设备信息推算，任何人都能伪造，
+/// 所以只在第一次迁移时读取：已经迁移过，或者已经有加密密钥（用过新格式）时都不再接受
+pub fn legacy_migration_allowed() -> bool {
+    let migrated = keyring::Entry::new(KEYRING_SERVICE, LEGACY_MIGRATED_ENTRY)
+        .and_then(|entry| entry.get_password())
+        .is_ok();
+    !migrated && !legacy_marker_path().exists() && !has_key()
+}
+
+/// 记录旧格式已经迁移，之后不再接受旧格式。钥匙串不可用时写入配置目录中的标记文件
+pub fn record_legacy_migration() {
+    let stored = keyring::Entry::new(KEYRING_SERVICE, LEGACY_MIGRATED_ENTRY).and_then(|entry| entry.set_password("1"));
+    if let Err(e) = stored {
+        log::warn!("Failed to store legacy migration marker in the system keychain, using marker file: {}", e);
+    }
+    // 同时写入标记文件，钥匙串条目被删除时仍然有效
+    let path = legacy_marker_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(e) = std::fs::write(&path, "1") {
+        log::warn!("Failed to write legacy migration marker: {}", e);
+    }
+}
+
+fn legacy_marker_path() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("subscription.migrated"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_subscription.migrated"))
+}
+
+// 钥匙串或密钥文件中是否已经有加密密钥，不会生成新的密钥
+fn has_key() -> bool {
+    let in_keychain = keyring::Entry::new(KEYRING_SERVICE, KEYRING_ENTRY)
+        .and_then(|entry| entry.get_password())
+        .map(|encoded| decode_key(&encoded).is_some())
+        .unwrap_or(false);
+    in_keychain
+        || std::fs::read_to_string(fallback_key_path())
+            .ok()
+            .and_then(|encoded| decode_key(&encoded))
+            .is_some()
+}
+
+// 钥匙串不可用时（例如 Linux 上没有运行 Secret Service）退回到配置目录中的密钥文件
+fn fallback_key_path() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("subscription.key"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_subscription.key"))
+}
+
+fn decode_key(encoded: &str) -> Option<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .filter(|key| key.len() == 32)
+}
+
+/// 读取加密密钥，第一次使用时生成随机密钥并保存到 macOS 钥匙串 / Windows 凭据管理器 / Secret Service
+fn get_or_create_key() -> Result<Vec<u8>, String> {
+    match keyring::Entry::new(KEYRING_SERVICE, KEYRING_ENTRY) {
+        Ok(entry) => match entry.get_password() {
+            Ok(encoded) => {
+                if let Some(key) = decode_key(&encoded) {
+                    return Ok(key);
+                }
+                set_aside_invalid_key(&encoded, "the system keychain")?;
+            }
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) => {
+                log::warn!("System keychain unavailable, using key file: {}", e);
+                return file_key();
+            }
+        },
+        Err(e) => {
+            log::warn!("System keychain unavailable, using key file: {}", e);
+            return file_key();
+        }
+    }
+
+    // 之前退回过密钥文件时迁移到钥匙串，保证已加密的数据仍能解密
+    let path = fallback_key_path();
+    let key = match read_key_file(&path)? {
+        Some(key) => key,
+        None => Aes256Gcm::generate_key(&mut OsRng).to_vec(),
+    };
+    let encoded = base64::engine::general_purpose::STANDARD.encode(&key);
+
+    let stored = keyring::Entry::new(KEYRING_SERVICE, KEYRING_ENTRY).and_then(|entry| entry.set_password(&encoded));
+    match stored {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&path);
+            Ok(key)
+        }
+        Err(e) => {
+            log::warn!("Failed to store subscription key in the system keychain, using key file: {}", e);
+            write_key_file(&path, &encoded)?;
+            Ok(key)
+        }
+    }
+}
+
+/// 删除钥匙串中的加密密钥，返回是否有密钥被删除。密钥文件随数据目录一起删除
+pub fn delete_key() -> Result<bool, String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_ENTRY).map_err(|e| e.to_string())?;
+    match entry.delete_password() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn file_key() -> Result<Vec<u8>, String> {
+    let path = fallback_key_path();
+    if let Some(key) = read_key_file(&path)? {
+        return Ok(key);
+    }
+    let key = Aes256Gcm::generate_key(&mut OsRng).to_vec();
+    write_key_file(&path, &base64::engine::general_purpose::STANDARD.encode(&key))?;
+    Ok(key)
+}
+
+// 读取密钥文件；文件不存在时返回 None，内容无效时先另存原内容再返回 None
+fn read_key_file(path: &PathBuf) -> Result<Option<Vec<u8>>, String> {
+    let encoded = match std::fs::read_to_string(path) {
+        Ok(encoded) => encoded,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read subscription key file: {}", e)),
+    };
+    if let Some(key) = decode_key(&encoded) {
+        return Ok(Some(key));
+    }
+    set_aside_invalid_key(&encoded, &path.display().to_string())?;
+    Ok(None)
+}
+
+// 保存的密钥无法解析时不直接覆盖：先把原内容另存到数据目录，便于排查或手动恢复；
+// 另存失败时返回错误，不生成新密钥
+fn set_aside_invalid_key(encoded: &str, source: &str) -> Result<(), String> {
+    let key_path = fallback_key_path();
+    let file_name = key_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup_path = key_path.with_file_name(format!(
+        "{}.invalid-{}",
+        file_name,
+        chrono::Local::now().format("%Y%m%d%H%M%S")
+    ));
+    write_key_file(&backup_path, encoded)
+        .map_err(|e| format!("Invalid subscription key in {} could not be backed up: {}", source, e))?;
+    log::error!(
+        "Invalid subscription key in {}, saved the old value to {} and generating a new key",
+        source,
+        backup_path.display()
+    );
+    Ok(())
+}
+
+// 密钥文件只允许当前用户读写
+fn write_key_file(path: &PathBuf, encoded: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, encoded).map_err(|e| e.to_string())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600)).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_key() -> Vec<u8> {
+        Aes256Gcm::generate_key(&mut OsRng).to_vec()
+    }
+
+    #[test]
+    fn round_trips_with_the_same_key() {
+        let key = random_key();
+        let data = encrypt_with_key(&key, b"{\"plan\":\"pro\"}").unwrap();
+        assert!(is_encrypted(&data));
+        assert_eq!(decrypt_with_key(&key, &data).unwrap(), b"{\"plan\":\"pro\"}");
+    }
+
+    #[test]
+    fn uses_a_fresh_nonce_for_each_encryption() {
+        let key = random_key();
+        assert_ne!(encrypt_with_key(&key, b"same").unwrap(), encrypt_with_key(&key, b"same").unwrap());
+    }
+
+    #[test]
+    fn rejects_modified_data_and_other_keys() {
+        let key = random_key();
+        let mut data = encrypt_with_key(&key, b"subscription").unwrap();
+        assert!(decrypt_with_key(&random_key(), &data).is_err());
+
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        assert!(decrypt_with_key(&key, &data).is_err());
+    }
+
+    #[test]
+    fn rejects_legacy_data() {
+        assert!(!is_encrypted(b"legacy xor data"));
+        assert!(decrypt_with_key(&random_key(), b"legacy xor data").is_err());
+    }
+
+    #[test]
+    fn accepts_only_32_byte_keys() {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(random_key());
+        assert_eq!(decode_key(&format!("{}\n", encoded)).map(|k| k.len()), Some(32));
+        assert!(decode_key(&base64::engine::general_purpose::STANDARD.encode([0u8; 16])).is_none());
+        assert!(decode_key("not base64!").is_none());
+    }
+}
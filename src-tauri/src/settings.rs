@@ -1,26 +1,102 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use crate::config::ConfigFormat;
+use crate::settings_migration::{self, CURRENT_SETTINGS_VERSION};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralSettings {
+    // 设置文件的 schema 版本，用于升级旧版本的设置
+    #[serde(default = "default_version")]
+    pub version: String,
     pub auto_start: bool,
+    // 开机启动登记给当前用户还是本机所有用户（仅 Windows）
+    #[serde(default)]
+    pub autostart_scope: crate::autostart::AutoStartScope,
+    // Linux 上的开机启动方式：desktop（XDG 自启动）或 systemd（用户服务）
+    #[serde(default)]
+    pub autostart_method: crate::autostart::AutoStartMethod,
     pub theme: String,
+    // 配置文件的偏好保存格式
+    #[serde(default)]
+    pub config_format: ConfigFormat,
+    // 界面语言代码；为空表示还没有选择过，启动时按系统语言自动设置
+    #[serde(default)]
+    pub language: Option<String>,
+    // 启动时不显示主窗口，直接进入托盘（适合开机自启）
+    #[serde(default)]
+    pub start_hidden: bool,
+    // 写入日志文件的最低级别：off / error / warn / info / debug / trace
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    // 网络请求使用的代理
+    #[serde(default)]
+    pub proxy: crate::http::ProxySettings,
+    // 匿名使用统计，默认关闭，需要用户主动开启
+    #[serde(default)]
+    pub telemetry_enabled: bool,
+    // “立即整理”全局快捷键，为空表示不注册
+    #[serde(default = "default_organize_hotkey")]
+    pub organize_hotkey: Option<String>,
+    // 开发者用的支付测试模式，界面不显示，修改后重启生效
+    #[serde(default)]
+    pub payment_test_mode: bool,
+    // 本机自动化接口（Alfred、Raycast、脚本等），默认关闭
+    #[serde(default)]
+    pub local_api: crate::local_api::LocalApiSettings,
+    // 接收整理事件的 Webhook 地址（Slack、Discord 或自建服务）
+    #[serde(default)]
+    pub webhooks: Vec<crate::webhooks::WebhookConfig>,
+    // 每周整理汇总（通知和/或邮件），默认关闭
+    #[serde(default)]
+    pub weekly_digest: crate::digest::DigestSettings,
+    // 当前版本不认识的字段（例如由更新版本写入），保存时原样写回
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+fn default_version() -> String {
+    CURRENT_SETTINGS_VERSION.to_string()
+}
+
+fn default_organize_hotkey() -> Option<String> {
+    Some(crate::hotkey::DEFAULT_HOTKEY.to_string())
+}
+
+fn default_log_level() -> String {
+    crate::logging::DEFAULT_LEVEL.to_string()
 }
 
 impl GeneralSettings {
     pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
         let settings_path = Self::get_settings_path();
         
-        if settings_path.exists() {
+        let mut settings = if settings_path.exists() {
             let content = fs::read_to_string(&settings_path)?;
-            let settings: GeneralSettings = serde_json::from_str(&content)?;
-            Ok(settings)
+            let raw = serde_json::from_str::<serde_json::Value>(&content)?;
+            let outcome = settings_migration::migrate(raw)?;
+            let settings = serde_json::from_value::<GeneralSettings>(outcome.value)?;
+            
+            // 升级了旧版本设置时，先备份原文件再写回
+            if outcome.migrated {
+                let backup_path = crate::config_migration::backup_original(&settings_path, &outcome.from_version)?;
+                log::info!("Backed up settings before migration: {}", backup_path.display());
+                settings.save()?;
+            }
+            
+            settings
         } else {
             let settings = Self::default();
             settings.save()?;
-            Ok(settings)
+            settings
+        };
+        
+        // 托管配置中的强制设置优先
+        if let Some(managed) = crate::managed_config::ManagedConfig::load() {
+            managed.apply_to_settings(&mut settings);
         }
+        
+        Ok(settings)
     }
     
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
@@ -30,15 +106,24 @@ impl GeneralSettings {
             fs::create_dir_all(parent)?;
         }
         
-        let content = serde_json::to_string_pretty(self)?;
+        let mut value = serde_json::to_value(self)?;
+        // 托管配置的强制设置只在读取时叠加，保存时写回用户自己的值
+        if let Some(managed) = crate::managed_config::ManagedConfig::load() {
+            let user = fs::read_to_string(&settings_path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                .unwrap_or_default();
+            managed.strip_from_settings(&mut value, &user);
+        }
+        let content = serde_json::to_string_pretty(&value)?;
         fs::write(&settings_path, content)?;
         
         Ok(())
     }
     
     fn get_settings_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("fileSortify").join("settings.json")
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("settings.json")
         } else {
             PathBuf::from("file_organizer_settings.json")
         }
@@ -53,6 +138,18 @@ impl GeneralSettings {
                     return Err("auto_start must be a boolean".to_string());
                 }
             }
+            "autostart_scope" => {
+                match serde_json::from_value::<crate::autostart::AutoStartScope>(value) {
+                    Ok(scope) => self.autostart_scope = scope,
+                    Err(_) => return Err("autostart_scope must be one of current_user, all_users".to_string()),
+                }
+            }
+            "autostart_method" => {
+                match serde_json::from_value::<crate::autostart::AutoStartMethod>(value) {
+                    Ok(method) => self.autostart_method = method,
+                    Err(_) => return Err("autostart_method must be one of desktop, systemd".to_string()),
+                }
+            }
             "theme" => {
                 if let Some(val) = value.as_str() {
                     self.theme = val.to_string();
@@ -60,6 +157,82 @@ impl GeneralSettings {
                     return Err("theme must be a string".to_string());
                 }
             }
+            "config_format" => {
+                match serde_json::from_value::<ConfigFormat>(value) {
+                    Ok(format) => self.config_format = format,
+                    Err(_) => return Err("config_format must be one of json, toml, yaml".to_string()),
+                }
+            }
+            "language" => {
+                if let Some(val) = value.as_str() {
+                    self.language = Some(crate::i18n::Language::from(val).code().to_string());
+                } else {
+                    return Err("language must be a string".to_string());
+                }
+            }
+            "start_hidden" => {
+                if let Some(val) = value.as_bool() {
+                    self.start_hidden = val;
+                } else {
+                    return Err("start_hidden must be a boolean".to_string());
+                }
+            }
+            "log_level" => {
+                match value.as_str().filter(|val| crate::logging::parse_level(val).is_some()) {
+                    Some(val) => self.log_level = val.trim().to_lowercase(),
+                    None => return Err("log_level must be one of off, error, warn, info, debug, trace".to_string()),
+                }
+            }
+            "proxy" => {
+                let proxy = serde_json::from_value::<crate::http::ProxySettings>(value)
+                    .map_err(|e| format!("proxy must be an object with mode and url: {}", e))?;
+                proxy.validate()?;
+                self.proxy = proxy;
+            }
+            "telemetry_enabled" => {
+                if let Some(val) = value.as_bool() {
+                    self.telemetry_enabled = val;
+                } else {
+                    return Err("telemetry_enabled must be a boolean".to_string());
+                }
+            }
+            "payment_test_mode" => {
+                if let Some(val) = value.as_bool() {
+                    self.payment_test_mode = val;
+                } else {
+                    return Err("payment_test_mode must be a boolean".to_string());
+                }
+            }
+            "local_api" => {
+                let mut local_api = serde_json::from_value::<crate::local_api::LocalApiSettings>(value)
+                    .map_err(|e| format!("local_api must be an object with enabled and port: {}", e))?;
+                local_api.validate()?;
+                local_api.ensure_token();
+                self.local_api = local_api;
+            }
+            "webhooks" => {
+                let webhooks = serde_json::from_value::<Vec<crate::webhooks::WebhookConfig>>(value)
+                    .map_err(|e| format!("webhooks must be a list of objects with url, events and enabled: {}", e))?;
+                crate::webhooks::validate_all(&webhooks)?;
+                self.webhooks = webhooks;
+            }
+            "weekly_digest" => {
+                let weekly_digest = serde_json::from_value::<crate::digest::DigestSettings>(value)
+                    .map_err(|e| format!("weekly_digest must be an object with enabled, notification and email: {}", e))?;
+                weekly_digest.validate()?;
+                self.weekly_digest = weekly_digest;
+            }
+            "organize_hotkey" => {
+                match value {
+                    serde_json::Value::Null => self.organize_hotkey = None,
+                    serde_json::Value::String(val) if val.trim().is_empty() => self.organize_hotkey = None,
+                    serde_json::Value::String(val) => {
+                        crate::hotkey::validate(&val)?;
+                        self.organize_hotkey = Some(val.trim().to_string());
+                    }
+                    _ => return Err("organize_hotkey must be a string or null".to_string()),
+                }
+            }
             _ => return Err(format!("Unknown setting key: {}", key)),
         }
         Ok(())
@@ -69,8 +242,23 @@ impl GeneralSettings {
 impl Default for GeneralSettings {
     fn default() -> Self {
         GeneralSettings {
+            version: default_version(),
             auto_start: false,
+            autostart_scope: crate::autostart::AutoStartScope::default(),
+            autostart_method: crate::autostart::AutoStartMethod::default(),
             theme: "system".to_string(),
+            config_format: ConfigFormat::default(),
+            language: None,
+            start_hidden: false,
+            log_level: default_log_level(),
+            proxy: crate::http::ProxySettings::default(),
+            telemetry_enabled: false,
+            organize_hotkey: default_organize_hotkey(),
+            payment_test_mode: false,
+            local_api: crate::local_api::LocalApiSettings::default(),
+            webhooks: Vec::new(),
+            weekly_digest: crate::digest::DigestSettings::default(),
+            extra: serde_json::Map::new(),
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+use serde_json::{json, Value};
+
+use crate::config_migration::{compare_versions, MigrationOutcome};
+
+/// 当前通用设置的 schema 版本
+pub const CURRENT_SETTINGS_VERSION: &str = "1.0";
+
+// 单步迁移：把 from 版本的设置升级到 to 版本
+type MigrationFn = fn(&mut Value) -> Result<(), String>;
+
+struct Migration {
+    from: &'static str,
+    to: &'static str,
+    apply: MigrationFn,
+}
+
+// 迁移步骤必须按版本顺序排列，每一步只负责相邻两个版本之间的升级
+const MIGRATIONS: &[Migration] = &[
+    Migration { from: "0", to: "1.0", apply: migrate_0_to_1_0 },
+];
+
+/// 将原始设置逐步升级到当前版本
+pub fn migrate(mut value: Value) -> Result<MigrationOutcome, String> {
+    if !value.is_object() {
+        return Err("Settings root must be a JSON object".to_string());
+    }
+
+    let from_version = read_version(&value);
+    let mut version = from_version.clone();
+
+    if compare_versions(&version, CURRENT_SETTINGS_VERSION) == std::cmp::Ordering::Greater {
+        // 来自更新版本的设置，原样加载；不认识的字段会被保留并在保存时写回
+        log::warn!(
+            "Settings version {} is newer than supported version {}",
+            version, CURRENT_SETTINGS_VERSION
+        );
+        return Ok(MigrationOutcome { value, from_version, migrated: false });
+    }
+
+    while compare_versions(&version, CURRENT_SETTINGS_VERSION) == std::cmp::Ordering::Less {
+        let step = MIGRATIONS
+            .iter()
+            .find(|m| compare_versions(m.from, &version) != std::cmp::Ordering::Greater
+                && compare_versions(m.to, &version) == std::cmp::Ordering::Greater)
+            .ok_or_else(|| format!("No migration path from settings version {}", version))?;
+
+        (step.apply)(&mut value)?;
+        value["version"] = json!(step.to);
+        log::info!("Migrated settings from version {} to {}", version, step.to);
+        version = step.to.to_string();
+    }
+
+    let migrated = version != from_version;
+    Ok(MigrationOutcome { value, from_version, migrated })
+}
+
+fn read_version(value: &Value) -> String {
+    match value.get("version") {
+        Some(Value::String(v)) if !v.trim().is_empty() => v.trim().to_string(),
+        Some(Value::Number(n)) => n.to_string(),
+        // 没有版本号的设置来自加入版本号之前
+        _ => "0".to_string(),
+    }
+}
+
+// 0 -> 1.0：补齐早期设置文件缺少的必填字段，并把语言统一为语言代码
+fn migrate_0_to_1_0(value: &mut Value) -> Result<(), String> {
+    let obj = value.as_object_mut().ok_or("Settings root must be a JSON object")?;
+
+    if !obj.get("auto_start").map(|v| v.is_boolean()).unwrap_or(false) {
+        obj.insert("auto_start".to_string(), json!(false));
+    }
+    if !obj.get("theme").map(|v| v.is_string()).unwrap_or(false) {
+        obj.insert("theme".to_string(), json!("system"));
+    }
+
+    // 早期版本可能保存 "zh-CN"、"English" 这样的值
+    match obj.get("language") {
+        Some(Value::String(language)) => {
+            let code = crate::i18n::Language::from(language.as_str()).code();
+            obj.insert("language".to_string(), json!(code));
+        }
+        Some(Value::Null) | None => {}
+        Some(_) => {
+            obj.remove("language");
+        }
+    }
+
+    Ok(())
+}
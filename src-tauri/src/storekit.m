@@ -172,11 +172,11 @@
         on_purchase_completed([jsonString UTF8String]);
     }
     
-    [[SKPaymentQueue defaultQueue] finishTransaction:transaction];
+    // 交易由 Rust 端在收据验证成功后调用 finish_transaction 完成
 }
 
 - (void)restoreTransaction:(SKPaymentTransaction *)transaction {
-    [self completeTransaction:transaction];
+    // 恢复的交易在 paymentQueueRestoreCompletedTransactionsFinished 中统一通过 on_restore_completed 传出
 }
 
 - (void)failedTransaction:(SKPaymentTransaction *)transaction {
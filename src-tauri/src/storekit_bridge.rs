@@ -1,5 +1,9 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 
 // 定义StoreKit相关的外部函数接口
 #[cfg(target_os = "macos")]
@@ -12,6 +16,54 @@ extern "C" {
     fn finish_transaction(transaction_id: *const c_char) -> bool;
 }
 
+/// storekit.m 回调中传出的交易信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoreKitTransaction {
+    pub transaction_identifier: String,
+    pub product_identifier: String,
+    pub transaction_date: f64,
+    #[serde(default)]
+    pub original_transaction_identifier: String,
+}
+
+/// StoreKit 回调事件。回调在 StoreKit 的线程上执行，通过通道交给应用的异步运行时处理
+#[derive(Debug, Clone)]
+pub enum StoreKitEvent {
+    ProductsReceived(serde_json::Value),
+    PurchaseCompleted(StoreKitTransaction),
+    PurchaseFailed(String),
+    RestoreCompleted(Vec<StoreKitTransaction>),
+}
+
+lazy_static! {
+    static ref EVENTS: Mutex<Option<UnboundedSender<StoreKitEvent>>> = Mutex::new(None);
+}
+
+/// 开始接收 StoreKit 回调事件，再次调用会替换之前的接收端
+pub fn subscribe() -> UnboundedReceiver<StoreKitEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    *EVENTS.lock().unwrap() = Some(sender);
+    receiver
+}
+
+fn send_event(event: StoreKitEvent) {
+    match EVENTS.lock().unwrap().as_ref() {
+        Some(sender) => {
+            let _ = sender.send(event);
+        }
+        None => log::warn!("StoreKit event dropped, no listener: {:?}", event),
+    }
+}
+
+// 回调传入的 C 字符串，空指针或非 UTF-8 时返回 None
+unsafe fn callback_str<'a>(ptr: *const c_char) -> Option<&'a str> {
+    if ptr.is_null() {
+        return None;
+    }
+    CStr::from_ptr(ptr).to_str().ok()
+}
+
 pub struct StoreKitManager {
     initialized: bool,
 }
@@ -159,56 +211,46 @@ impl Default for StoreKitManager {
 // StoreKit回调处理
 #[no_mangle]
 pub extern "C" fn on_products_received(products_json: *const c_char) {
-    if products_json.is_null() {
+    let Some(json) = (unsafe { callback_str(products_json) }) else {
         return;
-    }
-
-    unsafe {
-        if let Ok(c_str) = CStr::from_ptr(products_json).to_str() {
-            log::info!("Products received: {}", c_str);
-            // 这里可以发送事件到前端
-        }
+    };
+    log::info!("Products received: {}", json);
+    match serde_json::from_str(json) {
+        Ok(products) => send_event(StoreKitEvent::ProductsReceived(products)),
+        Err(e) => log::error!("Failed to parse StoreKit products: {}", e),
     }
 }
 
 #[no_mangle]
 pub extern "C" fn on_purchase_completed(transaction_json: *const c_char) {
-    if transaction_json.is_null() {
+    let Some(json) = (unsafe { callback_str(transaction_json) }) else {
         return;
-    }
-
-    unsafe {
-        if let Ok(c_str) = CStr::from_ptr(transaction_json).to_str() {
-            log::info!("Purchase completed: {}", c_str);
-            // 这里可以发送事件到前端，触发收据验证
-        }
+    };
+    log::info!("Purchase completed: {}", json);
+    // 交易在收据验证成功后才会完成（finish_transaction），否则 StoreKit 会在下次启动时重新发送
+    match serde_json::from_str(json) {
+        Ok(transaction) => send_event(StoreKitEvent::PurchaseCompleted(transaction)),
+        Err(e) => log::error!("Failed to parse StoreKit transaction: {}", e),
     }
 }
 
 #[no_mangle]
 pub extern "C" fn on_purchase_failed(error_message: *const c_char) {
-    if error_message.is_null() {
+    let Some(message) = (unsafe { callback_str(error_message) }) else {
         return;
-    }
-
-    unsafe {
-        if let Ok(c_str) = CStr::from_ptr(error_message).to_str() {
-            log::error!("Purchase failed: {}", c_str);
-            // 这里可以发送错误事件到前端
-        }
-    }
+    };
+    log::error!("Purchase failed: {}", message);
+    send_event(StoreKitEvent::PurchaseFailed(message.to_string()));
 }
 
 #[no_mangle]
 pub extern "C" fn on_restore_completed(transactions_json: *const c_char) {
-    if transactions_json.is_null() {
+    let Some(json) = (unsafe { callback_str(transactions_json) }) else {
         return;
+    };
+    log::info!("Restore completed: {}", json);
+    match serde_json::from_str(json) {
+        Ok(transactions) => send_event(StoreKitEvent::RestoreCompleted(transactions)),
+        Err(e) => log::error!("Failed to parse restored StoreKit transactions: {}", e),
     }
-
-    unsafe {
-        if let Ok(c_str) = CStr::from_ptr(transactions_json).to_str() {
-            log::info!("Restore completed: {}", c_str);
-            // 这里可以发送事件到前端，触发收据验证
-        }
-    }
-}
\ No newline at end of file
+}
@@ -1,23 +1,126 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, Utc, Duration, Months};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use crate::i18n::t;
+use crate::referral::ReferralGrant;
+use crate::subscription_error::SubscriptionError;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// 试用期剩余天数不超过该值时每天提醒一次
+const TRIAL_ENDING_NOTICE_DAYS: i64 = 1;
+// 系统时间回拨超过该分钟数时视为篡改时钟，小幅的网络校时不算
+const CLOCK_ROLLBACK_TOLERANCE_MINUTES: i64 = 60;
+
+lazy_static! {
+    // 进程启动时的单调时钟和系统时间，运行期间系统时间回拨时两者的差值会变化
+    static ref PROCESS_CLOCK: (std::time::Instant, DateTime<Utc>) = (std::time::Instant::now(), Utc::now());
+    static ref CHANGES: Mutex<Option<UnboundedSender<SubscriptionChange>>> = Mutex::new(None);
+    // 上次保存或检查时的状态，用于判断状态变化
+    static ref LIFECYCLE: Mutex<Option<Lifecycle>> = Mutex::new(None);
+}
+
+/// 订阅状态的变化，由 main 转发为前端的 subscription-changed 事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SubscriptionChange {
+    Activated { plan: SubscriptionPlan },
+    // 试用期结束时 plan 为 Free
+    Expired { plan: SubscriptionPlan },
+    TrialEnding { days_remaining: i64 },
+    VerificationFailed { message: String },
+}
+
+#[derive(Debug, Clone)]
+struct Lifecycle {
+    plan: SubscriptionPlan,
+    paid_active: bool,
+    trial_active: bool,
+    // 已经提醒过的试用剩余天数
+    trial_days_notified: Option<i64>,
+}
+
+/// 订阅状态变化的接收端，只保留最后一个订阅者
+pub fn subscribe() -> UnboundedReceiver<SubscriptionChange> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    *CHANGES.lock().unwrap() = Some(sender);
+    receiver
+}
+
+pub fn publish(change: SubscriptionChange) {
+    log::info!("Subscription changed: {:?}", change);
+    if let Some(sender) = CHANGES.lock().unwrap().as_ref() {
+        let _ = sender.send(change);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SubscriptionPlan {
     Free,
     Lifetime,  // 买断版本
+    Monthly,   // 按月自动续费
+    Yearly,    // 按年自动续费
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl SubscriptionPlan {
+    /// 前端传入的计划名称
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "lifetime" => Some(SubscriptionPlan::Lifetime),
+            "monthly" => Some(SubscriptionPlan::Monthly),
+            "yearly" => Some(SubscriptionPlan::Yearly),
+            _ => None,
+        }
+    }
+
+    /// 服务端套餐的计费周期（month / year，买断为空）
+    pub fn from_interval(interval: Option<&str>) -> Self {
+        match interval {
+            Some("month") => SubscriptionPlan::Monthly,
+            Some("year") => SubscriptionPlan::Yearly,
+            _ => SubscriptionPlan::Lifetime,
+        }
+    }
+
+    pub fn is_recurring(&self) -> bool {
+        matches!(self, SubscriptionPlan::Monthly | SubscriptionPlan::Yearly)
+    }
+
+    /// 从 start 开始的一个计费周期的结束时间，买断版本没有结束时间
+    pub fn period_end(&self, start: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            SubscriptionPlan::Monthly => start.checked_add_months(Months::new(1)),
+            SubscriptionPlan::Yearly => start.checked_add_months(Months::new(12)),
+            _ => None,
+        }
+    }
+
+    /// 服务端对应的套餐名称
+    fn package_name(&self) -> &'static str {
+        match self {
+            SubscriptionPlan::Monthly => "File Sortify Monthly",
+            SubscriptionPlan::Yearly => "File Sortify Yearly",
+            _ => "File Sortify",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SubscriptionStatus {
     Trial,      // 试用期
     Active,     // 活跃订阅
     Expired,    // 已过期
     Cancelled,  // 已取消
+    PastDue,    // 续费失败，处于宽限期
+}
+
+// 服务端没有配置时的续费宽限期天数
+fn default_grace_period_days() -> i64 {
+    7
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,12 +134,82 @@ pub struct Subscription {
     pub device_id: String,
     pub apple_receipt_data: Option<String>,
     pub apple_transaction_id: Option<String>,
+    #[serde(default)]
+    pub ms_store_license_id: Option<String>,
+    #[serde(default)]
+    pub stripe_subscription_id: Option<String>,
     pub auto_renew_enabled: bool,
     // Creem 相关字段
     pub creem_session_id: Option<String>,
     pub creem_transaction_id: Option<String>,
     pub webhook_server_url: String,
-    pub package_id: String
+    pub package_id: String,
+    // 续费失败后的宽限期
+    #[serde(default = "default_grace_period_days")]
+    pub grace_period_days: i64,
+    #[serde(default)]
+    pub grace_period_end: Option<DateTime<Utc>>,
+    // 推荐奖励的试用天数，以服务端记录为准
+    #[serde(default)]
+    pub referral_grants: Vec<ReferralGrant>,
+    #[serde(default)]
+    pub redeemed_referral_code: Option<String>,
+    // 已兑换、等待下次 Creem 结账使用的折扣码
+    #[serde(default)]
+    pub promo_code: Option<String>,
+    // 赠送码激活授权时服务端的赠送记录 ID
+    #[serde(default)]
+    pub promo_grant_id: Option<String>,
+    // 购买被退款或拒付时撤销授权的原因
+    #[serde(default)]
+    pub revocation: Option<RevocationReason>,
+    // 迁移到机器标识之前使用过的设备 ID
+    #[serde(default)]
+    pub previous_device_ids: Vec<String>,
+    // 见过的最晚的系统时间，用于发现时钟回拨
+    #[serde(default)]
+    pub last_seen_at: Option<DateTime<Utc>>,
+    // 发现时钟回拨后需要向服务端确认时间，确认前不计试用期和离线使用
+    #[serde(default)]
+    pub clock_verification_required: bool,
+    // 支付测试模式下创建的订阅，授权来自测试服务器，不是真实购买
+    #[serde(default)]
+    pub test_mode: bool,
+}
+
+/// 授权的购买渠道，决定向哪个服务端确认订阅
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PurchaseChannel {
+    Apple,
+    Stripe,
+    MicrosoftStore,
+    // 赠送码激活，没有任何渠道的购买记录
+    Promo,
+    Creem,
+}
+
+/// 续费失败、处于宽限期时提醒用户更新支付方式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalNotice {
+    pub plan: SubscriptionPlan,
+    pub grace_period_end: DateTime<Utc>,
+    pub days_remaining: i64,
+}
+
+/// 购买被撤销的原因
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationReason {
+    Refunded,
+    ChargedBack,
+}
+
+/// 授权因退款或拒付被撤销时告知用户原因
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationNotice {
+    pub reason: RevocationReason,
+    pub plan: SubscriptionPlan,
+    pub message: String,
 }
 
 impl Subscription {
@@ -53,11 +226,24 @@ impl Subscription {
             device_id,
             apple_receipt_data: None,
             apple_transaction_id: None,
+            ms_store_license_id: None,
+            stripe_subscription_id: None,
             auto_renew_enabled: false,
             creem_session_id: None,
             creem_transaction_id: None,
-            webhook_server_url: "https://filesortify.picasso-designs.com".to_string(),
+            webhook_server_url: crate::payment_test_mode::server_url(),
             package_id: "cme9f2aum0000uph23ghk00sd".to_string(),
+            grace_period_days: default_grace_period_days(),
+            grace_period_end: None,
+            referral_grants: Vec::new(),
+            redeemed_referral_code: None,
+            promo_code: None,
+            promo_grant_id: None,
+            revocation: None,
+            previous_device_ids: Vec::new(),
+            last_seen_at: Some(Utc::now()),
+            clock_verification_required: false,
+            test_mode: crate::payment_test_mode::is_enabled(),
         }
     }
     
@@ -65,14 +251,31 @@ impl Subscription {
         let config_path = Self::get_subscription_path();
         
         if config_path.exists() {
-            let encrypted_content = fs::read(&config_path)?;
-            let content = Self::decrypt_data(&encrypted_content)?;
-            let mut subscription: Subscription = serde_json::from_str(&content)?;
+            let mut subscription = match Self::read_file(&config_path) {
+                Ok(subscription) => subscription,
+                Err(e) => {
+                    // 文件无法解密或解析时不重新开始试用，否则破坏文件就能获得新的试用期
+                    log::warn!("Failed to read subscription file: {}", e);
+                    let mut subscription = Self::new();
+                    subscription.trial_start_date = Some(Utc::now() - Duration::days(3));
+                    subscription.clock_verification_required = true;
+                    return Ok(subscription);
+                }
+            };
+            subscription.record_clock();
+            subscription.refresh_expiry();
             
             // 验证数据完整性
             if !subscription.verify_data_integrity() {
-                // 数据可能被篡改，重置为试用状态
+                // 数据可能被篡改，或系统时间被调到试用开始之前。不重置试用期（否则回拨时钟就能重新试用），
+                // 保留原来的试用开始时间，向服务端确认时间之前不能使用试用期
+                log::warn!("Subscription data failed the integrity check, requiring server verification");
+                let trial_start_date = subscription.trial_start_date;
+                let last_seen_at = subscription.last_seen_at;
                 subscription = Self::new();
+                subscription.trial_start_date = trial_start_date;
+                subscription.last_seen_at = last_seen_at.max(subscription.last_seen_at);
+                subscription.clock_verification_required = true;
                 subscription.save()?;
             } else {
                 // 更新检查时间
@@ -88,6 +291,12 @@ impl Subscription {
         }
     }
     
+    fn read_file(path: &std::path::Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let encrypted_content = fs::read(path)?;
+        let content = Self::decrypt_data(&encrypted_content)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+    
     pub fn save(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let config_path = Self::get_subscription_path();
         
@@ -99,29 +308,197 @@ impl Subscription {
         let encrypted_content = Self::encrypt_data(&content)?;
         fs::write(&config_path, encrypted_content)?;
         
+        self.check_lifecycle();
         Ok(())
     }
+
+    /// 与上次保存或检查时的状态比较，发布激活、过期和试用即将结束的变化。
+    /// 保存时自动调用，试用期等按时间变化的状态需要定期调用
+    pub fn check_lifecycle(&self) {
+        let mut last = LIFECYCLE.lock().unwrap();
+        let paid_active = self.is_subscription_active();
+        let trial_active = self.is_trial_active();
+        let trial_days_remaining = self.get_trial_days_remaining();
+        let mut current = Lifecycle {
+            plan: self.plan.clone(),
+            paid_active,
+            trial_active,
+            trial_days_notified: last.as_ref().and_then(|last| last.trial_days_notified),
+        };
+
+        if let Some(previous) = last.as_ref() {
+            if paid_active && !previous.paid_active {
+                publish(SubscriptionChange::Activated { plan: self.plan.clone() });
+            } else if previous.paid_active && !paid_active {
+                publish(SubscriptionChange::Expired { plan: previous.plan.clone() });
+            } else if previous.trial_active && !trial_active && !paid_active {
+                publish(SubscriptionChange::Expired { plan: SubscriptionPlan::Free });
+            }
+        }
+
+        if trial_active
+            && trial_days_remaining <= TRIAL_ENDING_NOTICE_DAYS
+            && current.trial_days_notified != Some(trial_days_remaining)
+        {
+            publish(SubscriptionChange::TrialEnding { days_remaining: trial_days_remaining });
+            current.trial_days_notified = Some(trial_days_remaining);
+        }
+        *last = Some(current);
+    }
     
     pub fn is_trial_active(&self) -> bool {
-        if let Some(trial_start) = self.trial_start_date {
-            let trial_end = trial_start + Duration::days(3);
+        // 时钟被回拨过时，确认时间之前不能继续使用试用期
+        if self.clock_verification_required {
+            return false;
+        }
+        if let Some(trial_end) = self.trial_end() {
             Utc::now() < trial_end && matches!(self.status, SubscriptionStatus::Trial)
         } else {
             false
         }
     }
+
+    /// 试用结束时间：3 天试用加上推荐奖励。每次奖励从发放时间和当时的试用结束时间中较晚的一个起算，
+    /// 试用结束后才兑换的奖励也能重新获得试用
+    pub fn trial_end(&self) -> Option<DateTime<Utc>> {
+        let mut trial_end = self.trial_start_date? + Duration::days(3);
+        for grant in &self.referral_grants {
+            trial_end = trial_end.max(grant.granted_at) + Duration::days(grant.bonus_days);
+        }
+        Some(trial_end)
+    }
+
+    pub fn referral_bonus_days(&self) -> i64 {
+        self.referral_grants.iter().map(|grant| grant.bonus_days).sum()
+    }
+
+    /// 用服务端下发的推荐奖励替换本地记录。按发放时间排序，
+    /// 丢弃不合理的记录，总天数超过上限的部分不计入
+    pub fn apply_referral_grants(&mut self, mut grants: Vec<ReferralGrant>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        grants.sort_by_key(|grant| grant.granted_at);
+
+        let mut accepted: Vec<ReferralGrant> = Vec::new();
+        let mut total = 0;
+        for mut grant in grants {
+            if total >= crate::referral::MAX_BONUS_DAYS {
+                break;
+            }
+            grant.bonus_days = grant.bonus_days.min(crate::referral::MAX_BONUS_DAYS - total);
+            let mut candidate = accepted.clone();
+            candidate.push(grant.clone());
+            if !crate::referral::grants_are_valid(&candidate, self.trial_start_date) {
+                log::warn!("Ignoring invalid referral grant {}", grant.id);
+                continue;
+            }
+            total += grant.bonus_days;
+            accepted = candidate;
+        }
+
+        if accepted != self.referral_grants {
+            self.referral_grants = accepted;
+            self.save()?;
+        }
+        Ok(())
+    }
     
     pub fn is_subscription_active(&self) -> bool {
-        match self.status {
-            SubscriptionStatus::Active => {
-                // 买断版本没有过期时间，一旦激活就永久有效
-                match self.plan {
-                    SubscriptionPlan::Lifetime => true,
-                    _ => false,
+        match self.plan {
+            // 买断版本没有过期时间，一旦激活就永久有效
+            SubscriptionPlan::Lifetime => matches!(self.status, SubscriptionStatus::Active),
+            // 按周期订阅在当前周期结束前有效，取消自动续费后仍可用到周期结束
+            // 续费失败时在宽限期结束前仍然有效
+            SubscriptionPlan::Monthly | SubscriptionPlan::Yearly => match self.status {
+                SubscriptionStatus::Active | SubscriptionStatus::Cancelled => {
+                    self.subscription_end_date.map(|end| Utc::now() < end).unwrap_or(false)
+                }
+                SubscriptionStatus::PastDue => {
+                    self.grace_period_end.map(|end| Utc::now() < end).unwrap_or(false)
                 }
+                _ => false,
+            },
+            SubscriptionPlan::Free => false,
+        }
+    }
+
+    /// 按周期订阅过了结束时间时更新状态，返回状态是否改变：
+    /// 自动续费的订阅先进入宽限期等待续费，已取消或宽限期结束的订阅转为已过期
+    pub fn refresh_expiry(&mut self) -> bool {
+        if !self.plan.is_recurring() {
+            return false;
+        }
+        let now = Utc::now();
+        let ended = |end: Option<DateTime<Utc>>| end.map(|end| now >= end).unwrap_or(false);
+
+        match self.status {
+            SubscriptionStatus::Active if ended(self.subscription_end_date) => {
+                let since = self.subscription_end_date.unwrap_or(now);
+                self.enter_grace_period(since);
+                // 离线太久时宽限期可能也已经结束
+                self.refresh_expiry();
+                true
+            }
+            SubscriptionStatus::Cancelled if ended(self.subscription_end_date) => {
+                self.expire();
+                true
+            }
+            SubscriptionStatus::PastDue if ended(self.grace_period_end) => {
+                self.expire();
+                true
             }
-            _ => false
+            _ => false,
+        }
+    }
+
+    /// 续费失败（服务端标记为 past_due 或周期结束后没有续费）时进入宽限期，宽限期从 since 开始计算
+    pub fn enter_grace_period(&mut self, since: DateTime<Utc>) -> bool {
+        if !self.plan.is_recurring() || !matches!(self.status, SubscriptionStatus::Active) {
+            return false;
+        }
+        self.status = SubscriptionStatus::PastDue;
+        self.grace_period_end = Some(since + Duration::days(self.grace_period_days));
+        true
+    }
+
+    fn expire(&mut self) {
+        self.status = SubscriptionStatus::Expired;
+        self.auto_renew_enabled = false;
+        self.grace_period_end = None;
+    }
+
+    /// 购买被退款或拒付时撤销授权，订阅转为已过期
+    pub fn revoke(&mut self, reason: RevocationReason) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        log::warn!("Subscription revoked: {:?}", reason);
+        self.expire();
+        self.revocation = Some(reason);
+        self.save()?;
+        Ok(())
+    }
+
+    /// 授权被撤销时返回原因说明
+    pub fn revocation_notice(&self) -> Option<RevocationNotice> {
+        let reason = self.revocation?;
+        let message = match reason {
+            RevocationReason::Refunded => t("license_revoked_refunded"),
+            RevocationReason::ChargedBack => t("license_revoked_chargeback"),
+        };
+        Some(RevocationNotice {
+            reason,
+            plan: self.plan.clone(),
+            message,
+        })
+    }
+
+    /// 处于宽限期时返回续费提醒
+    pub fn renewal_notice(&self) -> Option<RenewalNotice> {
+        if !matches!(self.status, SubscriptionStatus::PastDue) {
+            return None;
         }
+        let grace_period_end = self.grace_period_end?;
+        Some(RenewalNotice {
+            plan: self.plan.clone(),
+            grace_period_end,
+            days_remaining: (grace_period_end - Utc::now()).num_days().max(0),
+        })
     }
     
     pub fn can_use_app(&self) -> bool {
@@ -140,14 +517,24 @@ impl Subscription {
             return false;
         }
         
+        // 时钟被回拨过时先向服务端确认时间
+        if self.record_clock() {
+            let _ = self.save();
+        }
+        if self.clock_verification_required {
+            if let Err(e) = self.verify_clock_with_server().await {
+                log::warn!("Failed to verify clock with server: {}", e);
+            }
+        }
+        
         // 如果是激活状态，需要服务端验证
-        if matches!(self.status, SubscriptionStatus::Active) {
+        if matches!(self.status, SubscriptionStatus::Active | SubscriptionStatus::PastDue) {
             match self.verify_with_server().await {
                 Ok(is_valid) => is_valid,
                 Err(_) => {
                     // 网络错误时，允许短期离线使用
                     let hours_since_check = (Utc::now() - self.last_check_date).num_hours();
-                    hours_since_check < 72 // 允许72小时离线使用
+                    hours_since_check < 72 && !self.clock_verification_required // 允许72小时离线使用
                 }
             }
         } else {
@@ -156,8 +543,7 @@ impl Subscription {
     }
     
     pub fn get_trial_days_remaining(&self) -> i64 {
-        if let Some(trial_start) = self.trial_start_date {
-            let trial_end = trial_start + Duration::days(3);
+        if let Some(trial_end) = self.trial_end() {
             let remaining = trial_end - Utc::now();
             remaining.num_days().max(0)
         } else {
@@ -183,16 +569,28 @@ impl Subscription {
                 self.status = SubscriptionStatus::Active;
                 self.subscription_start_date = Some(now);
                 self.subscription_end_date = None; // 买断版本没有过期时间
+                self.auto_renew_enabled = false;
+                self.grace_period_end = None;
             }
-            SubscriptionPlan::Free => return Err("Cannot activate free plan".into()),
+            SubscriptionPlan::Monthly | SubscriptionPlan::Yearly => {
+                self.subscription_end_date = plan.period_end(now);
+                self.grace_period_end = None;
+                self.plan = plan;
+                self.status = SubscriptionStatus::Active;
+                self.subscription_start_date = Some(now);
+                self.auto_renew_enabled = true;
+            }
+            SubscriptionPlan::Free => return Err(SubscriptionError::FreePlanNotPurchasable.into()),
         }
         
         self.save()?;
         Ok(())
     }
     
+    /// 取消订阅。按周期订阅只是停止自动续费，当前周期结束前仍可使用
     pub fn cancel_subscription(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         self.status = SubscriptionStatus::Cancelled;
+        self.auto_renew_enabled = false;
         self.save()?;
         Ok(())
     }
@@ -200,6 +598,8 @@ impl Subscription {
     pub fn get_pricing_info() -> PricingInfo {
         PricingInfo {
             lifetime_price: 20.0,
+            monthly_price: 1.99,
+            yearly_price: 19.99,
             trial_days: 3,
             currency: "USD".to_string(),
         }
@@ -218,13 +618,15 @@ impl Subscription {
                 product_id: "prod_1FjuD56FEgYYC8VKIwEACW".to_string(),
                 created_at: "2025-08-13T03:34:20.014Z".to_string(),
                 updated_at: "2025-08-13T03:34:20.014Z".to_string(),
+                interval: None,
+                grace_period_days: None,
             }
         }
     }
 
     /// 从服务端获取套餐信息
     pub async fn fetch_packages_from_server(&mut self) -> Result<PackagesResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let client = reqwest::Client::new();
+        let client = crate::http::client();
         let response = client
             .get(&format!("{}/api/packages?name=File%20Sortify", self.webhook_server_url))
             .send()
@@ -241,16 +643,97 @@ impl Subscription {
         Ok(packages_response)
     }
 
-    /// 验证Apple订阅收据 (已禁用，仅保留兼容性)
-    pub async fn verify_apple_receipt(&mut self, _receipt_data: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Apple Store 功能已禁用，直接返回错误
-        Err(t("payment_disabled").into())
+    /// 验证Apple订阅收据（旧版收据或 StoreKit 2 签名交易），有效时激活对应的按周期订阅
+    pub async fn verify_apple_receipt(&mut self, receipt_data: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let config = crate::apple_subscription::AppleSubscriptionConfig::default();
+        let status = config
+            .validator()
+            .validate_subscription(&receipt_data)
+            .await
+            .map_err(|e| e.to_string())?;
+        // Apple 退款后交易带有撤销时间
+        if status.is_cancelled && self.apple_transaction_id.as_deref() == Some(status.original_transaction_id.as_str()) {
+            self.revoke(RevocationReason::Refunded)?;
+        }
+        if !status.is_active {
+            return Err(SubscriptionError::AppleSubscriptionInactive.into());
+        }
+
+        let plan = if status.product_id == config.yearly_product_id {
+            SubscriptionPlan::Yearly
+        } else {
+            SubscriptionPlan::Monthly
+        };
+        // 同一订阅的续费保留最初的开始时间
+        let renewal = self.apple_transaction_id.as_deref() == Some(status.original_transaction_id.as_str());
+        if !renewal || self.subscription_start_date.is_none() {
+            self.subscription_start_date = Some(Utc::now());
+        }
+        self.subscription_end_date = status.expires_date.or_else(|| plan.period_end(Utc::now()));
+        self.plan = plan;
+        self.status = SubscriptionStatus::Active;
+        self.auto_renew_enabled = status.auto_renew_status;
+        self.grace_period_end = None;
+        self.apple_receipt_data = Some(receipt_data);
+        self.apple_transaction_id = Some(status.original_transaction_id);
+        self.last_check_date = Utc::now();
+        self.save()?;
+        Ok(())
     }
 
-    /// 刷新Apple订阅状态 (已禁用，仅保留兼容性)
+    /// 用保存的收据重新验证，刷新Apple订阅状态
     pub async fn refresh_apple_subscription(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Apple Store 功能已禁用，直接返回错误
-        Err(t("payment_disabled").into())
+        let receipt_data = self.apple_receipt_data.clone().ok_or_else(|| t("no_apple_receipt"))?;
+        self.verify_apple_receipt(receipt_data).await
+    }
+
+    /// 记录当前系统时间，发现大幅回拨（早于见过的最晚时间，或运行期间系统时间比单调时钟慢）时
+    /// 标记为需要向服务端确认时间。返回是否新发现了回拨
+    pub fn record_clock(&mut self) -> bool {
+        let now = Utc::now();
+        let tolerance = Duration::minutes(CLOCK_ROLLBACK_TOLERANCE_MINUTES);
+
+        let (started, started_at) = *PROCESS_CLOCK;
+        let elapsed = Duration::from_std(started.elapsed()).unwrap_or_else(|_| Duration::zero());
+        let rolled_back_since_start = now + tolerance < started_at + elapsed;
+        let rolled_back_since_last_seen = self.last_seen_at.map(|last| now + tolerance < last).unwrap_or(false);
+
+        let detected = (rolled_back_since_start || rolled_back_since_last_seen) && !self.clock_verification_required;
+        if detected {
+            log::warn!("System clock moved backwards (last seen {:?}, now {})", self.last_seen_at, now);
+            self.clock_verification_required = true;
+        }
+        self.last_seen_at = Some(self.last_seen_at.map(|last| last.max(now)).unwrap_or(now));
+        detected
+    }
+
+    /// 用服务端响应的 Date 头确认系统时间，时间正确时解除时钟回拨的标记
+    pub async fn verify_clock_with_server(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let response = crate::http::client()
+            .get(&format!("{}/api/packages", self.webhook_server_url))
+            .send()
+            .await?;
+        let server_time = response
+            .headers()
+            .get(reqwest::header::DATE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+            .map(|time| time.with_timezone(&Utc))
+            .ok_or("Server response has no Date header")?;
+
+        let now = Utc::now();
+        if (server_time - now).num_minutes().abs() > CLOCK_ROLLBACK_TOLERANCE_MINUTES {
+            return Ok(false);
+        }
+        // 时间已经正确，之前见过的更晚的时间来自被调快的时钟
+        self.clock_verification_required = false;
+        self.last_seen_at = Some(now);
+        // 时间正确时试用开始时间仍在未来，说明被修改过，按试用已结束处理
+        if self.trial_start_date.map(|start| start > now).unwrap_or(false) {
+            self.trial_start_date = Some(now - Duration::days(3));
+        }
+        self.save()?;
+        Ok(true)
     }
 
     /// 检查是否需要刷新订阅状态
@@ -275,7 +758,13 @@ impl Subscription {
         false
     }
     
+    // 优先使用系统的机器标识，取不到时沿用旧的主机名和用户名哈希
     fn generate_device_id() -> String {
+        crate::machine_id::device_id().unwrap_or_else(Self::legacy_device_id)
+    }
+
+    /// 旧版本的设备 ID：主机名和用户名的哈希，重命名电脑后会变化，批量部署的机器上可能重复
+    fn legacy_device_id() -> String {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
         
@@ -297,10 +786,16 @@ impl Subscription {
     }
     
     fn get_subscription_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("fileSortify").join("subscription.json")
+        // 测试模式的订阅单独保存，不会覆盖真实的订阅状态
+        let file_name = if crate::payment_test_mode::is_enabled() {
+            "subscription_test.json"
         } else {
-            PathBuf::from("subscription.json")
+            "subscription.json"
+        };
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join(file_name)
+        } else {
+            PathBuf::from(file_name)
         }
     }
 }
@@ -308,6 +803,8 @@ impl Subscription {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricingInfo {
     pub lifetime_price: f64,
+    pub monthly_price: f64,
+    pub yearly_price: f64,
     pub trial_days: i32,
     pub currency: String,
 }
@@ -325,6 +822,12 @@ pub struct PackageInfo {
     pub created_at: String,
     #[serde(rename = "updatedAt")]
     pub updated_at: String,
+    // 计费周期：month / year，买断套餐为空
+    #[serde(default)]
+    pub interval: Option<String>,
+    // 续费失败后的宽限期天数，为空时使用默认值
+    #[serde(default, rename = "gracePeriodDays")]
+    pub grace_period_days: Option<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -345,6 +848,20 @@ pub struct CreemSessionRequest {
     pub user_id: String,
     #[serde(rename = "packageId")]
     pub package_id: String,
+    // 支付完成后跳转回应用的地址
+    #[serde(rename = "successUrl")]
+    pub success_url: String,
+    // 兑换过的折扣码，由服务端在创建结账时应用
+    #[serde(rename = "discountCode", skip_serializing_if = "Option::is_none")]
+    pub discount_code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DeviceMigrationRequest {
+    #[serde(rename = "fromDeviceId")]
+    from_device_id: String,
+    #[serde(rename = "toDeviceId")]
+    to_device_id: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -389,6 +906,11 @@ pub struct CreemPaymentStatus {
 impl Subscription {
     /// 验证订阅状态的完整性
     pub fn verify_subscription_integrity(&self) -> bool {
+        // 推荐奖励会延长试用期，必须是合理的服务端记录
+        if !crate::referral::grants_are_valid(&self.referral_grants, self.trial_start_date) {
+            return false;
+        }
+        
         // 检查关键字段的一致性
         match self.status {
             SubscriptionStatus::Active => {
@@ -397,13 +919,25 @@ impl Subscription {
                     return false;
                 }
                 
-                // 买断版本不应该有结束时间
+                // 买断版本不应该有结束时间，按周期订阅必须有
                 if matches!(self.plan, SubscriptionPlan::Lifetime) && self.subscription_end_date.is_some() {
                     return false;
                 }
+                if self.plan.is_recurring() && self.subscription_end_date.is_none() {
+                    return false;
+                }
                 
                 // 必须有交易ID
-                if self.creem_transaction_id.is_none() && self.apple_transaction_id.is_none() {
+                if self.license_id().is_none() {
+                    return false;
+                }
+            }
+            SubscriptionStatus::PastDue => {
+                // 只有按周期订阅会续费失败，且必须有宽限期结束时间
+                if !self.plan.is_recurring() || self.grace_period_end.is_none() {
+                    return false;
+                }
+                if self.license_id().is_none() {
                     return false;
                 }
             }
@@ -469,22 +1003,25 @@ impl Subscription {
         true
     }
 
-    /// 简单的XOR加密（用于混淆，不是强加密）
+    /// AES-256-GCM 加密，密钥保存在系统钥匙串中
     fn encrypt_data(data: &str) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
-        let key = Self::get_encryption_key();
-        let mut encrypted = Vec::new();
-        
-        for (i, byte) in data.bytes().enumerate() {
-            let key_byte = key[i % key.len()];
-            encrypted.push(byte ^ key_byte);
-        }
-        
-        Ok(encrypted)
+        crate::secure_store::encrypt(data.as_bytes()).map_err(|e| e.into())
     }
 
-    /// 解密数据
+    /// 解密数据。旧版本的 XOR 格式只在第一次迁移时读取一次（load 之后会以新格式重新保存），
+    /// 之后没有认证的旧格式数据一律拒绝
     fn decrypt_data(encrypted_data: &[u8]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        let key = Self::get_encryption_key();
+        if crate::secure_store::is_encrypted(encrypted_data) {
+            let decrypted = crate::secure_store::decrypt(encrypted_data)?;
+            return String::from_utf8(decrypted).map_err(|e| e.into());
+        }
+        if !crate::secure_store::legacy_migration_allowed() {
+            return Err("Subscription file is not in the encrypted format".into());
+        }
+
+        log::info!("Migrating subscription file from the legacy format");
+        crate::secure_store::record_legacy_migration();
+        let key = Self::get_legacy_encryption_key();
         let mut decrypted = Vec::new();
         
         for (i, &byte) in encrypted_data.iter().enumerate() {
@@ -495,8 +1032,8 @@ impl Subscription {
         String::from_utf8(decrypted).map_err(|e| e.into())
     }
 
-    /// 生成基于设备的加密密钥
-    fn get_encryption_key() -> Vec<u8> {
+    /// 旧版本基于设备信息生成的 XOR 密钥，仅用于读取旧格式的订阅文件
+    fn get_legacy_encryption_key() -> Vec<u8> {
         let mut hasher = DefaultHasher::new();
         
         // 使用设备特征生成密钥
@@ -525,53 +1062,118 @@ impl Subscription {
         key
     }
 
-    /// 验证服务端订阅状态（复用 check_creem_payment_status 逻辑）
-    pub async fn verify_with_server(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-        // 如果有 Creem 会话ID，直接使用现有的检查逻辑
-        match self.check_creem_payment_status().await {
-            Ok(payment_status) => {
-                // 检查支付状态是否与本地状态一致
-                let server_is_paid = !payment_status.user_packages.is_empty();
-                let local_is_active = matches!(self.status, SubscriptionStatus::Active);
-                
-                if local_is_active && !server_is_paid {
-                    // 本地显示激活但服务端显示未支付 - 可能被篡改
-                    self.status = SubscriptionStatus::Expired;
-                    self.save()?;
+    /// 按购买渠道向对应的服务端确认订阅，返回服务端是否确认了有效购买。
+    /// Microsoft Store 的授权需要关联窗口才能查询，返回 None，由调用方按到期时间判断。
+    /// 与 verify_with_server 不同，网络错误直接返回
+    pub async fn revalidate(&mut self) -> Result<Option<bool>, Box<dyn std::error::Error + Send + Sync>> {
+        match self.purchase_channel() {
+            PurchaseChannel::Apple => {
+                self.refresh_apple_subscription().await?;
+                Ok(Some(self.is_subscription_active()))
+            }
+            PurchaseChannel::Stripe => {
+                // 宽限期内 Stripe 没有有效购买，但授权仍然有效
+                crate::payment::provider(crate::payment::PaymentProviderKind::Stripe).check_status(self).await?;
+                Ok(Some(self.is_subscription_active()))
+            }
+            PurchaseChannel::MicrosoftStore => Ok(None),
+            PurchaseChannel::Promo | PurchaseChannel::Creem => self.revalidate_with_server().await.map(Some),
+        }
+    }
+
+    // 向服务端确认赠送码或 Creem 购买仍然有效，服务端没有有效购买时本地不再保持激活状态
+    async fn revalidate_with_server(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // 赠送码激活的授权向服务端确认赠送记录没有被撤销
+        if self.is_promo_license() {
+            let grant_id = self.promo_grant_id.clone().unwrap_or_default();
+            let valid = crate::promo::verify_grant(&self.webhook_server_url, &self.device_id, &grant_id).await?;
+            if !valid && self.is_subscription_active() {
+                self.expire();
+                self.save()?;
+            }
+            return Ok(valid && self.is_subscription_active());
+        }
+
+        let payment_status = self.check_creem_payment_status().await?;
+
+        // 当前的购买不在已支付列表中时，检查是否已被退款或拒付
+        if let Some(transaction_id) = self.creem_transaction_id.clone() {
+            let still_paid = payment_status.user_packages.iter().any(|p| Self::creem_transaction_id_of(p) == transaction_id);
+            if !still_paid && self.revocation.is_none() {
+                if let Some(reason) = self.find_creem_revocation(&transaction_id).await? {
+                    self.revoke(reason)?;
                     return Ok(false);
                 }
-                
-                return Ok(server_is_paid);
             }
+        }
+
+        let was_active = matches!(self.status, SubscriptionStatus::Active);
+        let server_is_paid = self.reconcile_creem_payment(!payment_status.user_packages.is_empty());
+        if was_active && !matches!(self.status, SubscriptionStatus::Active) {
+            self.save()?;
+        }
+        Ok(server_is_paid)
+    }
+
+    // 检查 Creem 的支付状态是否与本地状态一致（已过期的按周期订阅不算，宽限期内的算），返回服务端是否确认了有效购买。
+    // 本地显示激活但服务端显示未支付时可能被篡改，转为已过期；其他渠道的授权不按 Creem 的记录过期
+    fn reconcile_creem_payment(&mut self, has_paid_packages: bool) -> bool {
+        let server_is_paid = self.is_subscription_active()
+            && (has_paid_packages || matches!(self.status, SubscriptionStatus::PastDue));
+        let local_is_active = matches!(self.status, SubscriptionStatus::Active);
+
+        if local_is_active && !server_is_paid && self.purchase_channel() == PurchaseChannel::Creem {
+            self.status = SubscriptionStatus::Expired;
+        }
+        server_is_paid
+    }
+
+    /// 验证服务端订阅状态（复用 check_creem_payment_status 逻辑）
+    pub async fn verify_with_server(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        // 按购买渠道向对应的服务端确认
+        match self.revalidate().await {
+            Ok(Some(server_is_paid)) => return Ok(server_is_paid),
+            // Microsoft Store 的授权按本地到期时间判断
+            Ok(None) => return Ok(self.is_subscription_active()),
             Err(e) => {
                 // 网络错误或其他问题，记录但不立即失效
                 eprintln!("Server verification failed: {}", e);
+                publish(SubscriptionChange::VerificationFailed { message: e.to_string() });
             }
         }
         
+        // 宽限期有明确的结束时间，离线时直接按本地状态判断
+        if matches!(self.status, SubscriptionStatus::PastDue) {
+            return Ok(self.is_subscription_active());
+        }
+
         // 如果无法验证且是激活状态，降级处理
         if matches!(self.status, SubscriptionStatus::Active) {
-            // 允许短期离线使用
+            // 允许短期离线使用，时钟被回拨过时不允许
             let hours_since_check = (Utc::now() - self.last_check_date).num_hours();
-            return Ok(hours_since_check < 72);
+            return Ok(hours_since_check < 72 && !self.clock_verification_required);
         }
         
         Ok(self.is_trial_active())
     }
 
     /// 创建 Creem 支付会话
-    pub async fn create_creem_session(&mut self, plan: SubscriptionPlan) -> Result<CreemSessionResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let _plan_str = match plan {
-            SubscriptionPlan::Lifetime => "lifetime",
-            SubscriptionPlan::Free => return Err("Cannot create session for free plan".into()),
+    pub async fn create_creem_session(&mut self, plan: SubscriptionPlan, success_url: &str) -> Result<CreemSessionResponse, Box<dyn std::error::Error + Send + Sync>> {
+        // 按周期订阅对应 Creem 中的周期性产品，需要先查到对应的套餐
+        let package_id = match plan {
+            SubscriptionPlan::Lifetime => self.package_id.clone(),
+            SubscriptionPlan::Monthly | SubscriptionPlan::Yearly => self.fetch_package_id(&plan).await?,
+            SubscriptionPlan::Free => return Err(SubscriptionError::FreePlanNotPurchasable.into()),
         };
 
         let request = CreemSessionRequest {
             user_id: self.device_id.clone(),
-            package_id: self.package_id.clone(),
+            package_id,
+            success_url: success_url.to_string(),
+            discount_code: self.promo_code.clone(),
         };
 
-        let client = reqwest::Client::new();
+        let client = crate::http::client();
         let response = client
             .post(&format!("{}/api/checkout", self.webhook_server_url))
             .json(&request)
@@ -591,42 +1193,255 @@ impl Subscription {
         Ok(session_response)
     }
 
+    /// 查询某个计划在服务端的套餐 ID
+    async fn fetch_package_id(&self, plan: &SubscriptionPlan) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let client = crate::http::client();
+        let response = client
+            .get(&format!("{}/api/packages", self.webhook_server_url))
+            .query(&[("name", plan.package_name())])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to fetch packages: {}", response.status()).into());
+        }
+
+        let packages_response: PackagesResponse = response.json().await?;
+        Ok(packages_response.packages.id)
+    }
+
+    /// 查询指定支付状态（PAID、PAST_DUE）的用户套餐
+    async fn fetch_user_packages(&self, status: &str) -> Result<CreemPaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
+        let client = crate::http::client();
+        let response = client
+            .get(&format!("{}/api/user-packages?userId={}&status={}", self.webhook_server_url, self.device_id.clone(), status))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(format!("Failed to check status: {}", response.status()).into());
+        }
+
+        Ok(response.json().await?)
+    }
+
     /// 检查 Creem 支付状态
     pub async fn check_creem_payment_status(&mut self) -> Result<CreemPaymentStatus, Box<dyn std::error::Error + Send + Sync>> {
-        let client = reqwest::Client::new();
+        let payment_status = self.fetch_user_packages("PAID").await?;
+        let now = Utc::now();
+
+        if !self.activate_paid_packages(&payment_status.user_packages)? && self.plan.is_recurring() {
+            // 没有有效的已支付订阅：续费扣款失败时服务端会把订阅标记为 past_due
+            let past_due = match self.fetch_user_packages("PAST_DUE").await {
+                Ok(status) => status.user_packages.into_iter().find(|p| p.package.interval.is_some()),
+                Err(e) => {
+                    log::warn!("Failed to check past due subscriptions: {}", e);
+                    None
+                }
+            };
+
+            let mut changed = false;
+            if let Some(user_package) = past_due {
+                if let Some(days) = user_package.package.grace_period_days {
+                    self.grace_period_days = days;
+                }
+                let since = user_package
+                    .expires_at
+                    .as_deref()
+                    .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+                    .map(|e| e.with_timezone(&Utc).min(now))
+                    .unwrap_or(now);
+                changed |= self.enter_grace_period(since);
+            }
+            // 本地周期已经结束时进入宽限期，宽限期结束后转为已过期
+            changed |= self.refresh_expiry();
+            if changed {
+                self.save()?;
+            }
+        }
+
+        Ok(payment_status)
+    }
+
+    /// 通过 Creem 订单号或购买时使用的邮箱找回购买记录并在本机重新激活，没有找到有效购买时返回 false
+    pub async fn restore_purchase(&mut self, identifier: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let identifier = identifier.trim();
+        let key = if identifier.contains('@') { "email" } else { "orderId" };
+
+        // 服务端把找到的购买关联到当前设备，之后的状态检查按设备 ID 即可查到
+        let client = crate::http::client();
         let response = client
-            .get(&format!("{}/api/user-packages?userId={}&status=PAID", self.webhook_server_url, self.device_id.clone()))
+            .get(&format!("{}/api/user-packages/restore", self.webhook_server_url))
+            .query(&[(key, identifier), ("userId", self.device_id.as_str()), ("status", "PAID")])
             .send()
             .await?;
 
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
         if !response.status().is_success() {
-            return Err(format!("Failed to check status: {}", response.status()).into());
+            return Err(format!("Failed to restore purchase: {}", response.status()).into());
         }
 
         let payment_status: CreemPaymentStatus = response.json().await?;
+        self.activate_paid_packages(&payment_status.user_packages)
+    }
 
-        // 如果有任何已支付的用户套餐，表示已经购买了
-        if !payment_status.user_packages.is_empty() {
-            // 取第一个已支付的套餐
-            let user_package = &payment_status.user_packages[0];
-            
-            // 对于买断版本，直接激活为 Lifetime 计划
-            let plan = SubscriptionPlan::Lifetime;
-            
-            // 使用 checkout_id 作为 transaction_id
-            let transaction_id = user_package.checkout_id
-                .clone()
-                .unwrap_or_else(|| user_package.id.clone());
+    /// 从已支付的套餐中激活订阅，没有有效的套餐时返回 false。
+    /// 优先买断，其次是到期时间最晚的按周期订阅；续费后服务端会延后 expiresAt
+    // 使用 checkout_id 作为 transaction_id
+    fn creem_transaction_id_of(user_package: &UserPackage) -> String {
+        user_package.checkout_id.clone().unwrap_or_else(|| user_package.id.clone())
+    }
 
-            self.activate_creem_subscription(plan, transaction_id)?;
+    // 服务端收到 Creem 的退款或拒付 webhook 后把购买标记为 REFUNDED 或 CHARGEBACK
+    async fn find_creem_revocation(
+        &self,
+        transaction_id: &str,
+    ) -> Result<Option<RevocationReason>, Box<dyn std::error::Error + Send + Sync>> {
+        for (status, reason) in [("REFUNDED", RevocationReason::Refunded), ("CHARGEBACK", RevocationReason::ChargedBack)] {
+            let revoked = self.fetch_user_packages(status).await?;
+            if revoked.user_packages.iter().any(|p| Self::creem_transaction_id_of(p) == transaction_id) {
+                return Ok(Some(reason));
+            }
         }
+        Ok(None)
+    }
 
-        Ok(payment_status)
+    fn activate_paid_packages(&mut self, user_packages: &[UserPackage]) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let now = Utc::now();
+        let best = user_packages
+            .iter()
+            .map(|user_package| {
+                let plan = SubscriptionPlan::from_interval(user_package.package.interval.as_deref());
+                let expires_at = user_package
+                    .expires_at
+                    .as_deref()
+                    .and_then(|e| DateTime::parse_from_rfc3339(e).ok())
+                    .map(|e| e.with_timezone(&Utc));
+                (user_package, plan, expires_at)
+            })
+            .filter(|(_, plan, expires_at)| !plan.is_recurring() || expires_at.map(|e| e > now).unwrap_or(false))
+            .max_by_key(|(_, plan, expires_at)| (!plan.is_recurring(), *expires_at));
+
+        if let Some((user_package, plan, expires_at)) = best {
+            let transaction_id = Self::creem_transaction_id_of(user_package);
+
+            if let Some(days) = user_package.package.grace_period_days {
+                self.grace_period_days = days;
+            }
+            self.activate_creem_subscription(plan, transaction_id, expires_at)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// 激活 Creem 订阅，按周期订阅使用服务端返回的到期时间
+    pub fn activate_creem_subscription(
+        &mut self,
+        plan: SubscriptionPlan,
+        transaction_id: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let renewal = self.is_renewal_of(self.creem_transaction_id.as_deref(), &transaction_id);
+        self.apply_purchase(plan, expires_at, renewal)?;
+        self.creem_transaction_id = Some(transaction_id);
+        // 折扣码只用于一次购买
+        if !renewal {
+            self.promo_code = None;
+        }
+        self.save()?;
+        Ok(())
+    }
+
+    /// 激活 Microsoft Store 购买的订阅，license_id 为加载项的 SKU Store ID
+    pub fn activate_ms_store_subscription(
+        &mut self,
+        plan: SubscriptionPlan,
+        license_id: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let renewal = self.is_renewal_of(self.ms_store_license_id.as_deref(), &license_id);
+        self.apply_purchase(plan, expires_at, renewal)?;
+        self.ms_store_license_id = Some(license_id);
+        self.save()?;
+        Ok(())
+    }
+
+    /// 激活 Stripe 购买的订阅，subscription_id 为 Stripe 的订阅或支付 ID
+    pub fn activate_stripe_subscription(
+        &mut self,
+        plan: SubscriptionPlan,
+        subscription_id: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let renewal = self.is_renewal_of(self.stripe_subscription_id.as_deref(), &subscription_id);
+        self.apply_purchase(plan, expires_at, renewal)?;
+        self.stripe_subscription_id = Some(subscription_id);
+        self.save()?;
+        Ok(())
     }
 
-    /// 激活 Creem 订阅
-    pub fn activate_creem_subscription(&mut self, plan: SubscriptionPlan, transaction_id: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    /// 赠送码直接激活计划。赠送的按周期订阅不会自动续费，到期后直接转为已过期
+    pub fn activate_promo_subscription(
+        &mut self,
+        plan: SubscriptionPlan,
+        grant_id: String,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.apply_purchase(plan, expires_at, false)?;
+        if self.plan.is_recurring() {
+            self.status = SubscriptionStatus::Cancelled;
+            self.auto_renew_enabled = false;
+        }
+        self.promo_grant_id = Some(grant_id);
+        self.save()?;
+        Ok(())
+    }
+
+    /// 授权的购买渠道。同时有多个渠道的记录时，App Store、Stripe 和 Microsoft Store 优先于 Creem
+    pub fn purchase_channel(&self) -> PurchaseChannel {
+        if self.apple_transaction_id.is_some() {
+            PurchaseChannel::Apple
+        } else if self.stripe_subscription_id.is_some() {
+            PurchaseChannel::Stripe
+        } else if self.ms_store_license_id.is_some() {
+            PurchaseChannel::MicrosoftStore
+        } else if self.is_promo_license() {
+            PurchaseChannel::Promo
+        } else {
+            PurchaseChannel::Creem
+        }
+    }
+
+    // 当前授权只来自赠送码，没有任何渠道的购买记录
+    fn is_promo_license(&self) -> bool {
+        self.promo_grant_id.is_some()
+            && self.creem_transaction_id.is_none()
+            && self.apple_transaction_id.is_none()
+            && self.ms_store_license_id.is_none()
+            && self.stripe_subscription_id.is_none()
+    }
+
+    // 同一笔订阅的续费（包括宽限期内补缴成功）保留最初的开始时间
+    fn is_renewal_of(&self, current_id: Option<&str>, id: &str) -> bool {
+        current_id == Some(id)
+            && matches!(
+                self.status,
+                SubscriptionStatus::Active | SubscriptionStatus::Cancelled | SubscriptionStatus::PastDue
+            )
+    }
+
+    // 按购买的计划更新状态，各支付渠道共用；调用方负责记录交易 ID 并保存
+    fn apply_purchase(
+        &mut self,
+        plan: SubscriptionPlan,
+        expires_at: Option<DateTime<Utc>>,
+        renewal: bool,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let now = Utc::now();
+        self.grace_period_end = None;
+        self.revocation = None;
 
         match plan {
             SubscriptionPlan::Lifetime => {
@@ -634,16 +1449,57 @@ impl Subscription {
                 self.status = SubscriptionStatus::Active;
                 self.subscription_start_date = Some(now);
                 self.subscription_end_date = None; // 买断版本没有过期时间
-                self.creem_transaction_id = Some(transaction_id);
-                self.last_check_date = Utc::now();
+                self.auto_renew_enabled = false;
             }
-            SubscriptionPlan::Free => return Err("Cannot activate free plan".into()),
+            SubscriptionPlan::Monthly | SubscriptionPlan::Yearly => {
+                self.subscription_end_date = expires_at.or_else(|| plan.period_end(now));
+                self.plan = plan;
+                // 本地已取消自动续费时保持取消状态，到期后转为过期
+                if !(renewal && matches!(self.status, SubscriptionStatus::Cancelled)) {
+                    self.status = SubscriptionStatus::Active;
+                    self.auto_renew_enabled = true;
+                }
+                if !renewal || self.subscription_start_date.is_none() {
+                    self.subscription_start_date = Some(now);
+                }
+            }
+            SubscriptionPlan::Free => return Err(SubscriptionError::FreePlanNotPurchasable.into()),
         }
 
-        self.save()?;
+        self.last_check_date = now;
         Ok(())
     }
 
+    /// 旧版本生成的设备 ID 与机器标识不同时迁移到机器标识。先让服务端把旧 ID 下的购买、
+    /// 席位和奖励记录转到新 ID，成功后才切换本地 ID，服务端不可达时保持旧 ID 下次重试
+    pub async fn migrate_device_id(&mut self) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let Some(machine_id) = crate::machine_id::device_id() else {
+            return Ok(false);
+        };
+        if machine_id == self.device_id {
+            return Ok(false);
+        }
+
+        let request = DeviceMigrationRequest {
+            from_device_id: self.device_id.clone(),
+            to_device_id: machine_id.clone(),
+        };
+        let response = crate::http::client()
+            .post(&format!("{}/api/devices/migrate", self.webhook_server_url))
+            .json(&request)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(format!("Failed to migrate device id: {}", response.status()).into());
+        }
+
+        log::info!("Migrated device id to the machine identifier");
+        let previous = std::mem::replace(&mut self.device_id, machine_id);
+        self.previous_device_ids.push(previous);
+        self.save()?;
+        Ok(true)
+    }
+
     /// 设置 webhook 服务器 URL
     pub fn set_webhook_server_url(&mut self, url: String) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         self.webhook_server_url = url;
@@ -651,8 +1507,109 @@ impl Subscription {
         Ok(())
     }
 
+    /// 多席位授权使用购买时的交易 ID 作为授权 ID
+    pub fn license_id(&self) -> Option<String> {
+        self.creem_transaction_id
+            .clone()
+            .or_else(|| self.apple_transaction_id.clone())
+            .or_else(|| self.ms_store_license_id.clone())
+            .or_else(|| self.stripe_subscription_id.clone())
+            .or_else(|| self.promo_grant_id.clone())
+    }
+
     /// 获取当前的支付会话信息
     pub fn get_current_session_info(&self) -> Option<String> {
         self.creem_session_id.clone()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn active_monthly(channel: PurchaseChannel) -> Subscription {
+        let mut subscription = Subscription::new();
+        subscription.plan = SubscriptionPlan::Monthly;
+        subscription.status = SubscriptionStatus::Active;
+        subscription.subscription_start_date = Some(Utc::now() - Duration::days(20));
+        subscription.subscription_end_date = Some(Utc::now() + Duration::days(10));
+        match channel {
+            PurchaseChannel::Apple => subscription.apple_transaction_id = Some("apple-1".to_string()),
+            PurchaseChannel::Stripe => subscription.stripe_subscription_id = Some("sub_1".to_string()),
+            PurchaseChannel::MicrosoftStore => subscription.ms_store_license_id = Some("ms-1".to_string()),
+            PurchaseChannel::Promo => subscription.promo_grant_id = Some("grant-1".to_string()),
+            PurchaseChannel::Creem => subscription.creem_transaction_id = Some("creem-1".to_string()),
+        }
+        subscription
+    }
+
+    #[test]
+    fn purchase_channel_follows_purchase_records() {
+        for channel in [
+            PurchaseChannel::Apple,
+            PurchaseChannel::Stripe,
+            PurchaseChannel::MicrosoftStore,
+            PurchaseChannel::Promo,
+            PurchaseChannel::Creem,
+        ] {
+            assert_eq!(active_monthly(channel).purchase_channel(), channel);
+        }
+
+        // 赠送码之后又购买时按购买渠道确认
+        let mut subscription = active_monthly(PurchaseChannel::Promo);
+        subscription.stripe_subscription_id = Some("sub_1".to_string());
+        assert_eq!(subscription.purchase_channel(), PurchaseChannel::Stripe);
+    }
+
+    #[test]
+    fn creem_lookup_expires_creem_purchases() {
+        let mut subscription = active_monthly(PurchaseChannel::Creem);
+        assert!(!subscription.reconcile_creem_payment(false));
+        assert_eq!(subscription.status, SubscriptionStatus::Expired);
+
+        let mut subscription = active_monthly(PurchaseChannel::Creem);
+        assert!(subscription.reconcile_creem_payment(true));
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+    }
+
+    #[test]
+    fn creem_lookup_keeps_apple_subscription() {
+        let mut subscription = active_monthly(PurchaseChannel::Apple);
+        subscription.reconcile_creem_payment(false);
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+        assert!(subscription.is_subscription_active());
+    }
+
+    #[test]
+    fn creem_lookup_keeps_stripe_subscription() {
+        let mut subscription = active_monthly(PurchaseChannel::Stripe);
+        subscription.reconcile_creem_payment(false);
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+        assert!(subscription.is_subscription_active());
+    }
+
+    #[test]
+    fn creem_lookup_keeps_ms_store_subscription() {
+        let mut subscription = active_monthly(PurchaseChannel::MicrosoftStore);
+        subscription.reconcile_creem_payment(false);
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+        assert!(subscription.is_subscription_active());
+    }
+
+    #[tokio::test]
+    async fn ms_store_revalidation_uses_local_expiry() {
+        let mut subscription = active_monthly(PurchaseChannel::MicrosoftStore);
+        assert_eq!(subscription.revalidate().await.unwrap(), None);
+        assert!(subscription.verify_with_server().await.unwrap());
+
+        subscription.subscription_end_date = Some(Utc::now() - Duration::days(1));
+        assert!(!subscription.verify_with_server().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn apple_revalidation_does_not_fall_back_to_creem() {
+        // 没有收据时直接报错，不会按 Creem 的记录判断
+        let mut subscription = active_monthly(PurchaseChannel::Apple);
+        assert!(subscription.revalidate().await.is_err());
+        assert_eq!(subscription.status, SubscriptionStatus::Active);
+    }
+}
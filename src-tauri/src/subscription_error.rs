@@ -0,0 +1,114 @@
+use serde::Serialize;
+
+use crate::i18n::{t, t_format};
+
+/// 订阅和支付的错误。序列化为 { code, params }：code 是稳定的错误代码，
+/// 前端可以按它区分具体的失败并用 params 自行翻译
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", content = "params", rename_all = "snake_case")]
+pub enum SubscriptionError {
+    InvalidPlan { plan: String },
+    FreePlanNotPurchasable,
+    PaymentDisabled,
+    // 当前平台没有对应的应用商店
+    AppStoreUnavailable,
+    ReceiptUnavailable,
+    MsStoreUnavailable,
+    MainWindowNotFound,
+    RestoreIdentifierRequired,
+    NoPurchaseFound,
+    PurchaseCancelled,
+    NoStoreLicense,
+    AppleSubscriptionInactive,
+    FetchPackagesFailed { reason: String },
+    ActivationFailed { reason: String },
+    CancelFailed { reason: String },
+    ReceiptVerificationFailed { reason: String },
+    RefreshFailed { reason: String },
+    StoreKitInitFailed { reason: String },
+    AppStorePurchaseFailed { reason: String },
+    AppStoreRestoreFailed { reason: String },
+    ReceiptReadFailed { reason: String },
+    MsStoreProductsFailed { reason: String },
+    MsStorePurchaseFailed { reason: String },
+    CreateSessionFailed { reason: String },
+    CheckStatusFailed { reason: String },
+    RestoreFailed { reason: String },
+    OpenPaymentPageFailed { reason: String },
+    UpdateServerUrlFailed { reason: String },
+}
+
+impl SubscriptionError {
+    /// 按当前语言翻译的错误信息
+    pub fn message(&self) -> String {
+        match self {
+            SubscriptionError::InvalidPlan { .. } => t("invalid_subscription_plan"),
+            SubscriptionError::FreePlanNotPurchasable => t("free_plan_not_purchasable"),
+            SubscriptionError::PaymentDisabled => t("payment_disabled"),
+            SubscriptionError::AppStoreUnavailable => t("apple_purchase_macos_only_format"),
+            SubscriptionError::ReceiptUnavailable => t("receipt_macos_only"),
+            SubscriptionError::MsStoreUnavailable => t("ms_store_windows_only"),
+            SubscriptionError::MainWindowNotFound => t("main_window_not_found"),
+            SubscriptionError::RestoreIdentifierRequired => t("restore_identifier_required"),
+            SubscriptionError::NoPurchaseFound => t("no_purchase_found"),
+            SubscriptionError::PurchaseCancelled => t("ms_store_purchase_cancelled"),
+            SubscriptionError::NoStoreLicense => t("ms_store_no_license"),
+            SubscriptionError::AppleSubscriptionInactive => t("apple_subscription_inactive"),
+            SubscriptionError::FetchPackagesFailed { reason } => t_format("fetch_packages_failed", &[reason]),
+            SubscriptionError::ActivationFailed { reason } => t_format("purchase_activation_failed", &[reason]),
+            SubscriptionError::CancelFailed { reason } => t_format("cancel_subscription_failed", &[reason]),
+            SubscriptionError::ReceiptVerificationFailed { reason } => t_format("apple_receipt_verify_failed_format", &[reason]),
+            SubscriptionError::RefreshFailed { reason } => t_format("refresh_subscription_failed", &[reason]),
+            SubscriptionError::StoreKitInitFailed { reason } => t_format("storekit_init_failed", &[reason]),
+            SubscriptionError::AppStorePurchaseFailed { reason } => t_format("apple_purchase_start_failed", &[reason]),
+            SubscriptionError::AppStoreRestoreFailed { reason } => t_format("restore_purchases_failed", &[reason]),
+            SubscriptionError::ReceiptReadFailed { reason } => t_format("receipt_data_failed", &[reason]),
+            SubscriptionError::MsStoreProductsFailed { reason } => t_format("ms_store_products_failed", &[reason]),
+            SubscriptionError::MsStorePurchaseFailed { reason } => t_format("ms_store_purchase_failed", &[reason]),
+            SubscriptionError::CreateSessionFailed { reason } => t_format("create_payment_session_failed", &[reason]),
+            SubscriptionError::CheckStatusFailed { reason } => t_format("check_payment_status_failed", &[reason]),
+            SubscriptionError::RestoreFailed { reason } => t_format("restore_purchase_failed", &[reason]),
+            SubscriptionError::OpenPaymentPageFailed { reason } => t_format("open_payment_page_failed", &[reason]),
+            SubscriptionError::UpdateServerUrlFailed { reason } => t_format("update_url_failed", &[reason]),
+        }
+    }
+
+    /// 订阅方法返回的错误本身是 SubscriptionError 时原样保留，
+    /// 其他错误（网络、服务端状态码等）按所在的操作包装
+    pub fn wrap(e: Box<dyn std::error::Error + Send + Sync>, wrap: impl FnOnce(String) -> Self) -> Self {
+        match e.downcast::<SubscriptionError>() {
+            Ok(error) => *error,
+            Err(e) => wrap(e.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for SubscriptionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for SubscriptionError {}
+
+/// 订阅和支付命令返回给前端的错误：在错误代码和参数之外附带翻译好的 message，
+/// 不需要区分错误的地方可以直接显示
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandError {
+    #[serde(flatten)]
+    pub error: SubscriptionError,
+    pub message: String,
+}
+
+impl From<SubscriptionError> for CommandError {
+    fn from(error: SubscriptionError) -> Self {
+        let message = error.message();
+        CommandError { error, message }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
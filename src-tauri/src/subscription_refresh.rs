@@ -0,0 +1,143 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::i18n::{t, t_format};
+use crate::subscription::{self, RevocationReason, Subscription, SubscriptionChange, SubscriptionPlan, SubscriptionStatus};
+use crate::AppState;
+
+// 每小时检查一次，是否需要请求服务端由 should_refresh_subscription 决定
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+// 等待界面开始监听事件
+const STARTUP_DELAY_SECS: u64 = 10;
+
+/// 影响应用使用权限的订阅状态
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entitlement {
+    pub plan: SubscriptionPlan,
+    pub status: SubscriptionStatus,
+    pub can_use_app: bool,
+}
+
+impl Entitlement {
+    fn of(subscription: &Subscription) -> Self {
+        Self {
+            plan: subscription.plan.clone(),
+            status: subscription.status.clone(),
+            can_use_app: subscription.can_use_app(),
+        }
+    }
+}
+
+/// 使用权限变化时发送 subscription-entitlement-changed 事件的内容
+#[derive(Debug, Clone, Serialize)]
+pub struct EntitlementChange {
+    pub previous: Entitlement,
+    pub current: Entitlement,
+}
+
+/// 启动后台订阅刷新任务：定期向服务端重新确认已激活的订阅，
+/// 续费失败进入宽限期时提醒用户，使用权限变化时通知前端
+pub fn start_background_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(tokio::time::Duration::from_secs(STARTUP_DELAY_SECS)).await;
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            refresh(&app).await;
+        }
+    });
+}
+
+async fn refresh(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    // 先克隆订阅数据，避免跨异步边界持有锁
+    let mut subscription_clone = {
+        let subscription = state.subscription.lock().await;
+        subscription.clone()
+    };
+    if let Err(e) = subscription_clone.migrate_device_id().await {
+        log::warn!("Failed to migrate device id: {}", e);
+    }
+    if subscription_clone.record_clock() {
+        subscription::publish(SubscriptionChange::VerificationFailed { message: t("clock_rollback_detected") });
+    }
+    if subscription_clone.clock_verification_required {
+        match subscription_clone.verify_clock_with_server().await {
+            Ok(true) => log::info!("System clock verified with server"),
+            Ok(false) => log::warn!("System clock still differs from server time"),
+            Err(e) => log::warn!("Failed to verify clock with server: {}", e),
+        }
+    }
+    let previous = Entitlement::of(&subscription_clone);
+    let previous_revocation = subscription_clone.revocation;
+    let was_past_due = subscription_clone.renewal_notice().is_some();
+
+    let is_paid = matches!(subscription_clone.status, SubscriptionStatus::Active | SubscriptionStatus::PastDue);
+    if is_paid && (subscription_clone.should_refresh_subscription() || was_past_due) {
+        match revalidate(&mut subscription_clone).await {
+            Ok(true) => {
+                subscription_clone.last_check_date = chrono::Utc::now();
+                if let Err(e) = subscription_clone.save() {
+                    log::warn!("Failed to save subscription: {}", e);
+                }
+            }
+            Ok(false) => {}
+            Err(e) => {
+                log::warn!("Failed to refresh subscription: {}", e);
+                subscription::publish(SubscriptionChange::VerificationFailed { message: e.to_string() });
+            }
+        }
+    }
+    // 离线时也按本地时间推进宽限期和到期
+    if subscription_clone.refresh_expiry() {
+        let _ = subscription_clone.save();
+    }
+    // 试用期按时间结束，不会经过保存
+    subscription_clone.check_lifecycle();
+
+    if let Some(notice) = subscription_clone.renewal_notice() {
+        let _ = app.emit("subscription-renewal-needed", &notice);
+        // 系统通知只在刚进入宽限期时发送一次
+        if !was_past_due {
+            let _ = tauri_plugin_notification::NotificationExt::notification(app)
+                .builder()
+                .title(&t("renewal_needed_title"))
+                .body(&t_format("renewal_needed_body", &[&notice.days_remaining.to_string()]))
+                .show();
+        }
+    }
+
+    notify_revocation(app, previous_revocation, &subscription_clone);
+
+    let current = Entitlement::of(&subscription_clone);
+    {
+        let mut subscription = state.subscription.lock().await;
+        *subscription = subscription_clone;
+    }
+    if current != previous {
+        log::info!("Subscription entitlement changed: {:?} -> {:?}", previous, current);
+        let _ = app.emit("subscription-entitlement-changed", &EntitlementChange { previous, current });
+    }
+}
+
+/// 服务端验证发现购买被退款或拒付时，发送 subscription-revoked 事件并用系统通知说明原因
+pub fn notify_revocation(app: &AppHandle, previous: Option<RevocationReason>, subscription: &Subscription) {
+    if previous.is_some() {
+        return;
+    }
+    let Some(notice) = subscription.revocation_notice() else {
+        return;
+    };
+    let _ = app.emit("subscription-revoked", &notice);
+    let _ = tauri_plugin_notification::NotificationExt::notification(app)
+        .builder()
+        .title(&t("license_revoked_title"))
+        .body(&notice.message)
+        .show();
+}
+
+// 按购买渠道向对应的服务端确认订阅，返回是否完成了确认。
+// Microsoft Store 的授权需要关联窗口才能查询，后台只按到期时间判断
+async fn revalidate(subscription: &mut Subscription) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(subscription.revalidate().await?.is_some())
+}
@@ -0,0 +1,211 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+// 管理员或用户可以通过环境变量彻底关闭遥测，优先级高于设置
+pub const DISABLE_ENV: &str = "FILESORTIFY_DISABLE_TELEMETRY";
+// 两次上报之间的最短间隔
+const REPORT_INTERVAL_HOURS: i64 = 24;
+// 计数先累积在内存中，最多隔这么久写入一次文件
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // 监控线程和命令会同时累加计数
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+    // 还没有写入文件的计数和上次写入的时间
+    static ref PENDING: Mutex<(BTreeMap<String, u64>, Option<Instant>)> = Mutex::new((BTreeMap::new(), None));
+}
+
+// 本地累积的计数，只包含数量，不包含文件名、路径或设备标识
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TelemetryStore {
+    counters: BTreeMap<String, u64>,
+    period_start: Option<String>,
+    last_sent: Option<String>,
+}
+
+/// 上报的完整内容，预览和实际发送使用同一份数据
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryReport {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub language: String,
+    pub period_start: Option<String>,
+    pub period_end: String,
+    pub counters: BTreeMap<String, u64>,
+}
+
+impl TelemetryStore {
+    fn load() -> Self {
+        fs::read_to_string(Self::get_store_path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let path = Self::get_store_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    fn get_store_path() -> PathBuf {
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("telemetry.json")
+        } else {
+            PathBuf::from("file_organizer_telemetry.json")
+        }
+    }
+}
+
+/// 环境变量是否强制关闭了遥测
+pub fn disabled_by_env() -> bool {
+    std::env::var(DISABLE_ENV)
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// 根据设置开启或关闭遥测；关闭时清除本地累积的数据
+pub fn set_enabled(enabled: bool) {
+    let enabled = enabled && !disabled_by_env();
+    let was_enabled = ENABLED.swap(enabled, Ordering::Relaxed);
+    if was_enabled && !enabled {
+        let _guard = STORE_LOCK.lock().unwrap();
+        PENDING.lock().unwrap().0.clear();
+        let path = TelemetryStore::get_store_path();
+        if path.exists() {
+            if let Err(e) = fs::remove_file(&path) {
+                log::error!("Failed to remove telemetry data: {}", e);
+            }
+        }
+    }
+}
+
+/// 累加一个计数；遥测未开启时什么都不做。
+/// 计数先保存在内存中，距上次写入超过 FLUSH_INTERVAL 时才写入文件
+pub fn record(counter: &str, amount: u64) {
+    if !is_enabled() || amount == 0 {
+        return;
+    }
+    let due = {
+        let mut pending = PENDING.lock().unwrap();
+        *pending.0.entry(counter.to_string()).or_insert(0) += amount;
+        pending.1.map(|flushed| flushed.elapsed() >= FLUSH_INTERVAL).unwrap_or(true)
+    };
+    if due {
+        flush();
+    }
+}
+
+/// 把内存中的计数写入文件，应用退出前调用
+pub fn flush() {
+    let _guard = STORE_LOCK.lock().unwrap();
+    flush_locked();
+}
+
+// 调用方需持有 STORE_LOCK
+fn flush_locked() {
+    let counters = {
+        let mut pending = PENDING.lock().unwrap();
+        pending.1 = Some(Instant::now());
+        std::mem::take(&mut pending.0)
+    };
+    if counters.is_empty() || !is_enabled() {
+        return;
+    }
+    let mut store = TelemetryStore::load();
+    if store.period_start.is_none() {
+        store.period_start = Some(chrono::Utc::now().to_rfc3339());
+    }
+    for (counter, amount) in counters {
+        *store.counters.entry(counter).or_insert(0) += amount;
+    }
+    if let Err(e) = store.save() {
+        log::error!("Failed to save telemetry data: {}", e);
+    }
+}
+
+/// 记录一次功能使用
+pub fn record_feature(feature: &str) {
+    record(&format!("feature.{}", feature), 1);
+}
+
+/// 下一次上报的内容
+pub fn preview(app_version: &str) -> TelemetryReport {
+    let _guard = STORE_LOCK.lock().unwrap();
+    flush_locked();
+    build_report(&TelemetryStore::load(), app_version)
+}
+
+fn build_report(store: &TelemetryStore, app_version: &str) -> TelemetryReport {
+    TelemetryReport {
+        app_version: app_version.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        language: crate::i18n::get_language().code().to_string(),
+        period_start: store.period_start.clone(),
+        period_end: chrono::Utc::now().to_rfc3339(),
+        counters: store.counters.clone(),
+    }
+}
+
+/// 距上次上报超过间隔时把累积的计数发送到服务端，成功后清零
+pub async fn send_if_due(server_url: &str, app_version: &str) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    if !is_enabled() {
+        return Ok(false);
+    }
+
+    let report = {
+        let _guard = STORE_LOCK.lock().unwrap();
+        flush_locked();
+        let store = TelemetryStore::load();
+        let due = store
+            .last_sent
+            .as_deref()
+            .and_then(|sent| chrono::DateTime::parse_from_rfc3339(sent).ok())
+            .map(|sent| chrono::Utc::now().signed_duration_since(sent) >= chrono::Duration::hours(REPORT_INTERVAL_HOURS))
+            .unwrap_or(true);
+        if !due || store.counters.is_empty() {
+            return Ok(false);
+        }
+        build_report(&store, app_version)
+    };
+
+    let response = crate::http::client()
+        .post(&format!("{}/api/telemetry", server_url))
+        .json(&report)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("Telemetry upload failed: {}", response.status()).into());
+    }
+
+    // 只扣除已发送的数量，发送期间新增的计数保留到下一次
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store = TelemetryStore::load();
+    for (counter, sent) in &report.counters {
+        if let Some(value) = store.counters.get_mut(counter) {
+            *value = value.saturating_sub(*sent);
+        }
+    }
+    store.counters.retain(|_, value| *value > 0);
+    store.period_start = if store.counters.is_empty() { None } else { Some(report.period_end.clone()) };
+    store.last_sent = Some(chrono::Utc::now().to_rfc3339());
+    store.save().map_err(|e| e.to_string())?;
+    Ok(true)
+}
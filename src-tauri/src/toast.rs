@@ -0,0 +1,159 @@
+use tauri::AppHandle;
+
+use crate::file_organizer::UndoAction;
+
+/// 整理结果等通知的内容。Windows 上显示为带“撤销”“打开文件夹”“在应用中查看”按钮的通知，
+/// 其他平台显示为普通通知
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub struct ActionableNotification {
+    pub title: String,
+    pub body: String,
+    // 有文件夹时显示“打开文件夹”
+    pub folder_path: Option<String>,
+    // 这次整理的移动记录，不为空时显示“撤销”
+    pub undo: Vec<UndoAction>,
+}
+
+pub fn show(app: &AppHandle, notification: ActionableNotification) {
+    if let Err(e) = platform::show(app, &notification) {
+        // 不支持操作按钮时退回普通通知
+        log::warn!("Failed to show actionable notification: {}", e);
+        show_plain(app, &notification);
+    }
+}
+
+fn show_plain(app: &AppHandle, notification: &ActionableNotification) {
+    let _ = tauri_plugin_notification::NotificationExt::notification(app)
+        .builder()
+        .title(&notification.title)
+        .body(&notification.body)
+        .show();
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use tauri::{Emitter, Manager};
+    use tauri_winrt_notification::Toast;
+
+    use crate::core_host::WithAppHandle;
+    use crate::file_organizer::FileSortify;
+    use crate::i18n::{t, t_format};
+
+    // 通知按钮对应的操作
+    const ACTION_UNDO: &str = "undo";
+    const ACTION_OPEN_FOLDER: &str = "open-folder";
+    const ACTION_SHOW_IN_APP: &str = "show-in-app";
+
+    pub fn show(app: &AppHandle, notification: &ActionableNotification) -> Result<(), String> {
+        // 开发模式下应用没有注册 AppUserModelID，借用 PowerShell 的
+        let app_id = if cfg!(debug_assertions) {
+            Toast::POWERSHELL_APP_ID.to_string()
+        } else {
+            app.config().identifier.clone()
+        };
+
+        let mut toast = Toast::new(&app_id).title(&notification.title).text1(&notification.body);
+        if !notification.undo.is_empty() {
+            toast = toast.add_button(&t("toast_undo"), ACTION_UNDO);
+        }
+        if notification.folder_path.is_some() {
+            toast = toast.add_button(&t("toast_open_folder"), ACTION_OPEN_FOLDER);
+        }
+        toast = toast.add_button(&t("toast_show_in_app"), ACTION_SHOW_IN_APP);
+
+        let handler_app = app.clone();
+        let notification = notification.clone();
+        toast
+            .on_activated(move |action| {
+                // 点击通知本身时没有 action
+                handle_action(&handler_app, &notification, action.as_deref().unwrap_or(ACTION_SHOW_IN_APP));
+                Ok(())
+            })
+            .show()
+            .map_err(|e| e.to_string())
+    }
+
+    /// 处理通知上的按钮，与应用内的撤销、打开文件夹操作相同
+    fn handle_action(app: &AppHandle, notification: &ActionableNotification, action: &str) {
+        crate::telemetry::record_feature("notification_action");
+        match action {
+            ACTION_UNDO => {
+                let app = app.clone();
+                let actions = notification.undo.clone();
+                tauri::async_runtime::spawn(async move {
+                    let result = tauri::async_runtime::spawn_blocking({
+                        let app = app.clone();
+                        move || undo_all(&app, actions)
+                    })
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|result| result);
+                    let body = match result {
+                        Ok(count) => t_format("toast_undo_done", &[&count.to_string()]),
+                        Err(message) => message,
+                    };
+                    let _ = tauri_plugin_notification::NotificationExt::notification(&app)
+                        .builder()
+                        .title(&t("undo_success_title"))
+                        .body(&body)
+                        .show();
+                });
+            }
+            ACTION_OPEN_FOLDER => {
+                use tauri_plugin_opener::OpenerExt;
+
+                if let Some(folder_path) = &notification.folder_path {
+                    if let Err(e) = app.opener().open_path(folder_path, None::<String>) {
+                        log::error!("Failed to open folder {}: {}", folder_path, e);
+                    }
+                }
+            }
+            _ => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.unminimize();
+                    let _ = window.set_focus();
+                }
+                let _ = app.emit(
+                    "notification-action",
+                    &serde_json::json!({ "action": ACTION_SHOW_IN_APP, "folderPath": notification.folder_path }),
+                );
+            }
+        }
+    }
+
+    // 按从新到旧的顺序撤销这次整理的所有移动，已经被移走的文件跳过
+    fn undo_all(app: &AppHandle, actions: Vec<UndoAction>) -> Result<usize, String> {
+        let Some(first) = actions.first() else {
+            return Ok(0);
+        };
+        let mut organizer = FileSortify::new(&first.downloads_path.to_string_lossy())
+            .map_err(|e| t_format("init_failed", &[&e.to_string()]))?
+            .with_app_handle(app.clone());
+        for action in actions.iter().cloned() {
+            organizer.undo_history.add_action(action);
+        }
+
+        let mut restored = 0;
+        for action in &actions {
+            match organizer.undo_action(&action.id) {
+                Ok(_) => restored += 1,
+                Err(e) => log::warn!("{}", t_format("undo_failed", &[&e.to_string()])),
+            }
+        }
+        Ok(restored)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+mod platform {
+    use super::*;
+
+    // 其他平台的通知不支持这些按钮，显示为普通通知
+    pub fn show(app: &AppHandle, notification: &ActionableNotification) -> Result<(), String> {
+        show_plain(app, notification);
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::image::Image;
+use tauri::menu::MenuItem;
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::config::Config;
+use crate::i18n::t_format;
+use crate::AppState;
+
+// 整理或监控出错后保持错误状态，直到用户打开窗口或重新切换监控
+static HAS_ERROR: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    // 托盘图标当前显示的状态，没有变化时不重新设置图标
+    static ref CURRENT: Mutex<Option<TrayStatus>> = Mutex::new(None);
+    // 托盘菜单顶部显示今天整理数量的菜单项，重建菜单时替换
+    static ref ORGANIZED_TODAY_ITEM: Mutex<Option<MenuItem<Wry>>> = Mutex::new(None);
+    // 订阅状态的提示（试用期即将结束、订阅已过期），显示在托盘提示的第二行
+    static ref SUBSCRIPTION_NOTE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// 托盘图标显示的状态，按优先级从高到低：出错、试用期结束、正在监控、已暂停
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayStatus {
+    Error,
+    TrialExpired,
+    Monitoring,
+    Paused,
+}
+
+impl TrayStatus {
+    // 右下角圆点的颜色，正在监控和已暂停不显示圆点
+    fn badge_color(self) -> Option<[u8; 3]> {
+        match self {
+            TrayStatus::Error => Some([0xe5, 0x39, 0x35]),
+            TrayStatus::TrialExpired => Some([0xfb, 0x8c, 0x00]),
+            TrayStatus::Monitoring | TrayStatus::Paused => None,
+        }
+    }
+}
+
+/// 记录整理或监控中的错误，托盘图标显示错误状态
+pub fn report_error(app_handle: &AppHandle) {
+    if !HAS_ERROR.swap(true, Ordering::Relaxed) {
+        refresh(app_handle);
+    }
+}
+
+/// 用户已经看到错误（打开了窗口或重新切换了监控）
+pub fn clear_error() {
+    HAS_ERROR.store(false, Ordering::Relaxed);
+}
+
+/// 按当前的监控、错误和订阅状态更新托盘图标。需要等待状态锁，在后台任务中进行
+pub fn refresh(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let status = current(&app_handle).await;
+        {
+            let mut shown = CURRENT.lock().unwrap();
+            if *shown == Some(status) {
+                return;
+            }
+            *shown = Some(status);
+        }
+
+        let (Some(tray), Some(base)) = (app_handle.tray_by_id("main-tray"), app_handle.default_window_icon()) else {
+            return;
+        };
+        log::debug!("Tray status changed: {:?}", status);
+        if let Err(e) = tray.set_icon(Some(render(base, status))) {
+            log::error!("Failed to update tray icon: {}", e);
+        }
+    });
+}
+
+/// 今天整理数量的文案，例如“今天整理了 37 个文件”
+pub fn organized_today_label() -> String {
+    let count = Config::load().map(|config| config.files_organized_today()).unwrap_or(0);
+    t_format("tray_organized_today", &[&count.to_string()])
+}
+
+/// 记录托盘菜单中显示今天整理数量的菜单项
+pub fn set_organized_today_item(item: MenuItem<Wry>) {
+    *ORGANIZED_TODAY_ITEM.lock().unwrap() = Some(item);
+}
+
+/// 整理文件后更新托盘提示和菜单中的今天整理数量
+pub fn update_organized_today(app_handle: &AppHandle) {
+    let label = organized_today_label();
+    if let Some(item) = ORGANIZED_TODAY_ITEM.lock().unwrap().as_ref() {
+        let _ = item.set_text(&label);
+    }
+    set_tooltip(app_handle, &label);
+}
+
+/// 设置或清除托盘提示中的订阅状态
+pub fn set_subscription_note(app_handle: &AppHandle, note: Option<String>) {
+    *SUBSCRIPTION_NOTE.lock().unwrap() = note;
+    set_tooltip(app_handle, &organized_today_label());
+}
+
+// 托盘提示：“File Sortify — 今天整理的数量”，有订阅提示时显示在第二行
+fn set_tooltip(app_handle: &AppHandle, organized_today: &str) {
+    let mut tooltip = format!("File Sortify — {}", organized_today);
+    if let Some(note) = SUBSCRIPTION_NOTE.lock().unwrap().as_ref() {
+        tooltip.push('\n');
+        tooltip.push_str(note);
+    }
+    if let Some(tray) = app_handle.tray_by_id("main-tray") {
+        let _ = tray.set_tooltip(Some(&tooltip));
+    }
+}
+
+async fn current(app_handle: &AppHandle) -> TrayStatus {
+    if HAS_ERROR.load(Ordering::Relaxed) {
+        return TrayStatus::Error;
+    }
+    let state = app_handle.state::<AppState>();
+    if crate::free_tier::is_limited(&*state.subscription.lock().await) {
+        return TrayStatus::TrialExpired;
+    }
+    if state.organizers.lock().await.is_empty() {
+        TrayStatus::Paused
+    } else {
+        TrayStatus::Monitoring
+    }
+}
+
+// 已暂停时显示灰色图标，出错和试用期结束时在右下角加彩色圆点
+fn render(base: &Image<'_>, status: TrayStatus) -> Image<'static> {
+    let (width, height) = (base.width(), base.height());
+    let mut rgba = base.rgba().to_vec();
+
+    if status == TrayStatus::Paused {
+        for pixel in rgba.chunks_exact_mut(4) {
+            let gray = (pixel[0] as u32 * 30 + pixel[1] as u32 * 59 + pixel[2] as u32 * 11) / 100;
+            pixel[..3].fill(gray as u8);
+        }
+    }
+
+    if let Some(color) = status.badge_color() {
+        let radius = width.min(height) as f32 * 0.22;
+        let border = (radius * 0.2).max(1.0);
+        let (cx, cy) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+        for y in 0..height {
+            for x in 0..width {
+                let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+                if distance > radius + border {
+                    continue;
+                }
+                // 圆点外有一圈白边，在深色和浅色任务栏上都能看清
+                let fill = if distance > radius { [0xff; 3] } else { color };
+                let offset = ((y * width + x) * 4) as usize;
+                rgba[offset..offset + 3].copy_from_slice(&fill);
+                rgba[offset + 3] = 0xff;
+            }
+        }
+    }
+
+    Image::new_owned(rgba, width, height)
+}
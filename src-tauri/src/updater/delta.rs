@@ -0,0 +1,163 @@
+use serde::Deserialize;
+use std::io::Cursor;
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::Update;
+
+/// updates.json 中发布的增量包，按目标平台分组：
+/// "deltas": { "windows-x86_64": [{ "from": "1.0.0", "url": "https://.../1.0.0-1.1.0.patch" }] }
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeltaArtifact {
+    pub from: String,
+    pub url: String,
+}
+
+// 与 updates.json 中 platforms 的键一致
+fn current_target() -> String {
+    let os = match std::env::consts::OS {
+        "macos" => "darwin",
+        os => os,
+    };
+    format!("{}-{}", os, std::env::consts::ARCH)
+}
+
+pub(super) fn cache_dir() -> PathBuf {
+    crate::app_paths::app_data_dir()
+        .map(|dir| dir.join("updates"))
+        .unwrap_or_else(|| PathBuf::from("file_organizer_updates"))
+}
+
+pub(super) fn package_path(version: &str) -> PathBuf {
+    cache_dir().join(format!("package-{}.bin", version))
+}
+
+// 安装包的签名与安装包放在一起，回滚时重新校验
+pub(super) fn signature_path(version: &str) -> PathBuf {
+    cache_dir().join(format!("package-{}.sig", version))
+}
+
+/// 保存已校验通过的安装包签名
+pub(super) fn store_signature(version: &str, signature: &str) {
+    if let Err(e) = std::fs::create_dir_all(cache_dir()) {
+        log::warn!("Failed to create update cache directory: {}", e);
+        return;
+    }
+    if let Err(e) = std::fs::write(signature_path(version), signature) {
+        log::warn!("Failed to cache update signature for {}: {}", version, e);
+    }
+}
+
+/// 缓存目录中保存了安装包的版本
+pub(super) fn cached_versions() -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(cache_dir()) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("package-")
+                .and_then(|rest| rest.strip_suffix(".bin"))
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// 保存已校验的旧版本安装包和签名，用于回滚，不清理缓存中的其他版本
+pub(super) fn store_rollback_package(version: &str, bytes: &[u8], signature: &str) {
+    if let Err(e) = std::fs::create_dir_all(cache_dir()) {
+        log::warn!("Failed to create update cache directory: {}", e);
+        return;
+    }
+    if let Err(e) = std::fs::write(package_path(version), bytes) {
+        log::warn!("Failed to cache package {} for rollback: {}", version, e);
+        return;
+    }
+    store_signature(version, signature);
+}
+
+/// 保存刚下载的完整安装包，下次更新时作为增量补丁的基准。
+/// 同时保留当前运行版本的安装包，用于回滚。
+pub fn store_package(version: &str, current_version: &str, bytes: &[u8]) {
+    let dir = cache_dir();
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        log::warn!("Failed to create update cache directory: {}", e);
+        return;
+    }
+
+    let path = package_path(version);
+    if let Err(e) = std::fs::write(&path, bytes) {
+        log::warn!("Failed to cache update package {}: {}", path.display(), e);
+        return;
+    }
+
+    // 只保留新版本和当前版本的安装包
+    for cached in cached_versions() {
+        if cached != version && cached != current_version {
+            let _ = std::fs::remove_file(package_path(&cached));
+            let _ = std::fs::remove_file(signature_path(&cached));
+        }
+    }
+}
+
+fn find_artifact(update: &Update) -> Option<DeltaArtifact> {
+    let deltas = update.raw_json.get("deltas")?.get(current_target())?;
+    let artifacts: Vec<DeltaArtifact> = serde_json::from_value(deltas.clone()).ok()?;
+    artifacts.into_iter().find(|artifact| artifact.from == update.current_version)
+}
+
+async fn download_patch(app: &AppHandle, url: &str) -> Result<Vec<u8>, String> {
+    let url = super::mirror::select(url).await;
+    let mut response = crate::http::client()
+        .get(&url)
+        .header("User-Agent", "FileSortify-Updater")
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("Delta download failed: {}", response.status()));
+    }
+
+    let total = response.content_length();
+    let mut patch = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        patch.extend_from_slice(&chunk);
+        if let Some(total) = total {
+            let _ = app.emit("update-progress", (patch.len() as f64 / total as f64) * 100.0);
+        }
+    }
+    Ok(patch)
+}
+
+/// 尝试通过增量补丁得到新版本的安装包。
+/// 没有发布对应补丁、本地没有当前版本的安装包，或者合成结果校验失败时返回 None，
+/// 调用方改为下载完整安装包。
+pub async fn try_download(app: &AppHandle, update: &Update) -> Option<Vec<u8>> {
+    let artifact = find_artifact(update)?;
+    let base = std::fs::read(package_path(&update.current_version)).ok()?;
+
+    log::info!("Downloading delta update {} -> {}", artifact.from, update.version);
+    let patch = match download_patch(app, &artifact.url).await {
+        Ok(patch) => patch,
+        Err(e) => {
+            log::warn!("Delta update download failed, falling back to full package: {}", e);
+            return None;
+        }
+    };
+
+    let mut target = Vec::new();
+    let applied = qbsdiff::Bspatch::new(&patch).and_then(|patcher| patcher.apply(&base, Cursor::new(&mut target)));
+    if let Err(e) = applied {
+        log::warn!("Failed to apply delta update, falling back to full package: {}", e);
+        return None;
+    }
+
+    if let Err(e) = super::verify_signature(app, &target, &update.signature) {
+        log::warn!("Delta update signature mismatch, falling back to full package: {}", e);
+        return None;
+    }
+
+    log::info!("Delta update applied ({} bytes patch, {} bytes package)", patch.len(), target.len());
+    Some(target)
+}
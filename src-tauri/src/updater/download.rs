@@ -0,0 +1,254 @@
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+use crate::i18n::t;
+
+// 下载控制状态
+const RUNNING: u8 = 0;
+const PAUSED: u8 = 1;
+const CANCELLED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(RUNNING);
+// 同一时间只允许一个更新下载
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// 发送给前端的下载进度（update-download-progress 事件）
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub percent: f64,
+    pub bytes_per_sec: u64,
+    pub eta_secs: Option<u64>,
+    pub paused: bool,
+}
+
+// 未完成的下载保存在更新缓存目录中，暂停、断网或重启应用后从断点继续
+fn partial_path(version: &str) -> PathBuf {
+    super::delta::cache_dir().join(format!("download-{}.part", version))
+}
+
+struct ActiveGuard;
+
+impl Drop for ActiveGuard {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::SeqCst);
+    }
+}
+
+// 按最近一段时间的字节数计算速度，避免开始阶段的波动
+struct SpeedMeter {
+    window_start: Instant,
+    window_bytes: u64,
+    bytes_per_sec: u64,
+    last_emit: Option<Instant>,
+}
+
+impl SpeedMeter {
+    fn new() -> Self {
+        Self {
+            window_start: Instant::now(),
+            window_bytes: 0,
+            bytes_per_sec: 0,
+            last_emit: None,
+        }
+    }
+
+    fn record(&mut self, bytes: u64) {
+        self.window_bytes += bytes;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.bytes_per_sec = (self.window_bytes as f64 / elapsed.as_secs_f64()) as u64;
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    // 最多每 250 毫秒发送一次进度
+    fn should_emit(&mut self) -> bool {
+        let due = self.last_emit.map_or(true, |last| last.elapsed() >= Duration::from_millis(250));
+        if due {
+            self.last_emit = Some(Instant::now());
+        }
+        due
+    }
+}
+
+fn emit_progress(app: &AppHandle, downloaded: u64, total: Option<u64>, bytes_per_sec: u64, paused: bool) {
+    let percent = total
+        .filter(|total| *total > 0)
+        .map_or(0.0, |total| (downloaded as f64 / total as f64) * 100.0);
+    let eta_secs = match total {
+        Some(total) if bytes_per_sec > 0 && !paused => Some(total.saturating_sub(downloaded) / bytes_per_sec),
+        _ => None,
+    };
+    let progress = DownloadProgress {
+        downloaded,
+        total,
+        percent,
+        bytes_per_sec,
+        eta_secs,
+        paused,
+    };
+    let _ = app.emit("update-download-progress", &progress);
+    // 兼容只监听百分比的旧前端
+    let _ = app.emit("update-progress", percent);
+}
+
+// 暂停期间等待继续或取消，返回 false 表示已取消
+async fn wait_while_paused() -> bool {
+    loop {
+        match STATE.load(Ordering::SeqCst) {
+            PAUSED => tokio::time::sleep(Duration::from_millis(200)).await,
+            CANCELLED => return false,
+            _ => return true,
+        }
+    }
+}
+
+/// 下载更新安装包，支持暂停、继续（HTTP Range 请求）和取消。
+/// 连接中断时同样从断点重试，下载完成后返回完整内容并删除临时文件。
+pub async fn fetch(app: &AppHandle, url: &str, version: &str) -> Result<Vec<u8>, String> {
+    if ACTIVE.swap(true, Ordering::SeqCst) {
+        return Err(t("update_download_in_progress"));
+    }
+    let _guard = ActiveGuard;
+    STATE.store(RUNNING, Ordering::SeqCst);
+
+    std::fs::create_dir_all(super::delta::cache_dir()).map_err(|e| e.to_string())?;
+    let path = partial_path(version);
+    let url = super::mirror::select(url).await;
+    let mut meter = SpeedMeter::new();
+    let mut total = None;
+    let mut failures = 0;
+
+    loop {
+        if !wait_while_paused().await {
+            let _ = std::fs::remove_file(&path);
+            return Err(t("update_download_cancelled"));
+        }
+
+        let offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let mut request = crate::http::client()
+            .get(&url)
+            .header("User-Agent", "FileSortify-Updater");
+        if offset > 0 {
+            request = request.header("Range", format!("bytes={}-", offset));
+        }
+
+        let result = download_range(app, request, &path, offset, &mut total, &mut meter).await;
+        match result {
+            Ok(true) => break,
+            // 暂停或取消，回到循环开头处理
+            Ok(false) => {
+                let downloaded = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                emit_progress(app, downloaded, total, 0, STATE.load(Ordering::SeqCst) == PAUSED);
+                meter.reset();
+            }
+            Err(e) => {
+                failures += 1;
+                if failures >= 3 {
+                    return Err(e);
+                }
+                log::warn!("Update download interrupted, retrying from offset: {}", e);
+                tokio::time::sleep(Duration::from_secs(2)).await;
+            }
+        }
+    }
+
+    let bytes = std::fs::read(&path).map_err(|e| e.to_string())?;
+    let _ = std::fs::remove_file(&path);
+    Ok(bytes)
+}
+
+// 下载一段数据并追加到临时文件，返回 true 表示下载完成，false 表示被暂停或取消
+async fn download_range(
+    app: &AppHandle,
+    request: reqwest::RequestBuilder,
+    path: &PathBuf,
+    offset: u64,
+    total: &mut Option<u64>,
+    meter: &mut SpeedMeter,
+) -> Result<bool, String> {
+    let mut response = request.send().await.map_err(|e| e.to_string())?;
+    let status = response.status();
+    if !status.is_success() {
+        // 服务器认为断点无效（例如文件已更换），从头开始
+        if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            let _ = std::fs::remove_file(path);
+        }
+        return Err(format!("Update download failed: {}", status));
+    }
+
+    // 服务器不支持 Range 时返回 200 和完整内容，需要从头写入
+    let resumed = status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut downloaded = if resumed { offset } else { 0 };
+    *total = response.content_length().map(|length| length + downloaded);
+    if resumed {
+        log::info!("Resuming update download at {} bytes", offset);
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).map_err(|e| e.to_string())?;
+        downloaded += chunk.len() as u64;
+        meter.record(chunk.len() as u64);
+        if meter.should_emit() {
+            emit_progress(app, downloaded, *total, meter.bytes_per_sec, false);
+        }
+
+        if STATE.load(Ordering::SeqCst) != RUNNING {
+            file.flush().map_err(|e| e.to_string())?;
+            return Ok(false);
+        }
+    }
+
+    file.flush().map_err(|e| e.to_string())?;
+    emit_progress(app, downloaded, total.or(Some(downloaded)), meter.bytes_per_sec, false);
+    Ok(true)
+}
+
+// Tauri命令：暂停正在进行的更新下载
+#[tauri::command]
+pub fn pause_update_download() -> Result<(), String> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Err(t("no_update_download"));
+    }
+    let _ = STATE.compare_exchange(RUNNING, PAUSED, Ordering::SeqCst, Ordering::SeqCst);
+    Ok(())
+}
+
+// Tauri命令：继续已暂停的更新下载
+#[tauri::command]
+pub fn resume_update_download() -> Result<(), String> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Err(t("no_update_download"));
+    }
+    let _ = STATE.compare_exchange(PAUSED, RUNNING, Ordering::SeqCst, Ordering::SeqCst);
+    Ok(())
+}
+
+// Tauri命令：取消更新下载并删除已下载的部分
+#[tauri::command]
+pub fn cancel_update_download() -> Result<(), String> {
+    if !ACTIVE.load(Ordering::SeqCst) {
+        return Err(t("no_update_download"));
+    }
+    STATE.store(CANCELLED, Ordering::SeqCst);
+    Ok(())
+}
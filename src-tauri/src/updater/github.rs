@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use reqwest;
+use sha2::{Digest, Sha256};
+use tauri::AppHandle;
+
+use super::source::UpdateSource;
+use crate::i18n::t_format;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GitHubRelease {
@@ -8,6 +13,8 @@ pub struct GitHubRelease {
     pub body: String,
     pub published_at: String,
     pub prerelease: bool,
+    #[serde(default)]
+    pub draft: bool,
     pub assets: Vec<GitHubAsset>,
 }
 
@@ -23,6 +30,8 @@ pub struct GitHubClient {
     token: Option<String>,
     repo_owner: String,
     repo_name: String,
+    // 是否包含预发布版本，默认只看正式发布
+    include_prereleases: bool,
 }
 
 impl GitHubClient {
@@ -31,11 +40,38 @@ impl GitHubClient {
             token,
             repo_owner,
             repo_name,
+            include_prereleases: false,
         }
     }
 
+    pub fn with_prereleases(mut self, include_prereleases: bool) -> Self {
+        self.include_prereleases = include_prereleases;
+        self
+    }
+
+    /// 应用自己的发布仓库，取自 Cargo.toml 中的 repository
+    pub fn for_app() -> Self {
+        let mut parts = env!("CARGO_PKG_REPOSITORY")
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .rsplit('/');
+        let repo_name = parts.next().unwrap_or("FileSortify").to_string();
+        let repo_owner = parts.next().unwrap_or_default().to_string();
+        Self::new(repo_owner, repo_name, None).with_prereleases(prereleases_enabled())
+    }
+
+    /// 最新的发布。/releases/latest 会忽略预发布版本，开启预发布时改为在发布列表中查找
     pub async fn get_latest_release(&self) -> Result<GitHubRelease, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        if self.include_prereleases {
+            return self
+                .get_releases(20)
+                .await?
+                .into_iter()
+                .max_by(|a, b| a.published_at.cmp(&b.published_at))
+                .ok_or_else(|| "No releases found".into());
+        }
+
+        let client = crate::http::client();
         let url = format!(
             "https://api.github.com/repos/{}/{}/releases/latest",
             self.repo_owner, self.repo_name
@@ -60,8 +96,32 @@ impl GitHubClient {
         Ok(release)
     }
 
+    /// 指定标签的发布，草稿和预发布也会返回
+    pub async fn get_release_by_tag(&self, tag: &str) -> Result<GitHubRelease, Box<dyn std::error::Error>> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/releases/tags/{}",
+            self.repo_owner, self.repo_name, tag
+        );
+
+        let mut request = crate::http::client().get(&url);
+
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("token {}", token));
+        }
+
+        request = request.header("User-Agent", "FileSortify-Updater");
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(format!("GitHub API request failed: {}", response.status()).into());
+        }
+
+        Ok(response.json().await?)
+    }
+
     pub async fn get_releases(&self, per_page: u32) -> Result<Vec<GitHubRelease>, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::new();
+        let client = crate::http::client();
         let url = format!(
             "https://api.github.com/repos/{}/{}/releases?per_page={}",
             self.repo_owner, self.repo_name, per_page
@@ -82,7 +142,11 @@ impl GitHubClient {
         }
 
         let releases: Vec<GitHubRelease> = response.json().await?;
-        Ok(releases)
+        // 草稿总是排除，预发布版本需要显式开启
+        Ok(releases
+            .into_iter()
+            .filter(|release| !release.draft && (self.include_prereleases || !release.prerelease))
+            .collect())
     }
 
     pub fn get_platform_asset<'a>(&self, release: &'a GitHubRelease) -> Option<&'a GitHubAsset> {
@@ -95,12 +159,127 @@ impl GitHubClient {
             let platform_match = match platform.as_str() {
                 "windows" => name.contains("windows") || name.ends_with(".msi") || name.ends_with(".exe"),
                 "macos" => name.contains("darwin") || name.contains("macos") || name.ends_with(".dmg") || name.ends_with(".app.tar.gz"),
-                "linux" => name.contains("linux") || name.ends_with(".deb") || name.ends_with(".rpm") || name.ends_with(".AppImage"),
+                "linux" => name.contains("linux") || name.ends_with(".deb") || name.ends_with(".rpm") || name.ends_with(".appimage"),
                 _ => false,
             };
-            platform_match && (arch == "universal" || name.contains(&arch))
+            platform_match && matches_arch(&name, &arch)
         })
     }
+
+    /// 可以直接安装的资源：与应用内更新使用相同格式的安装包
+    pub fn get_installable_asset<'a>(&self, release: &'a GitHubRelease) -> Option<&'a GitHubAsset> {
+        installable_asset(&release.assets, &get_current_platform(), &get_current_arch())
+    }
+
+    async fn download(&self, url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+        let mut request = crate::http::client().get(url);
+
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", format!("token {}", token));
+        }
+
+        request = request.header("User-Agent", "FileSortify-Updater");
+
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            return Err(format!("Asset download failed: {}", response.status()).into());
+        }
+
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    // 发布中记录的 SHA-256：优先 <资源名>.sha256，其次 SHA256SUMS / checksums.txt
+    async fn expected_sha256(&self, release: &GitHubRelease, asset: &GitHubAsset) -> Result<String, Box<dyn std::error::Error>> {
+        let single = format!("{}.sha256", asset.name);
+        if let Some(checksum) = release.assets.iter().find(|a| a.name == single) {
+            let content = String::from_utf8(self.download(&checksum.browser_download_url).await?)?;
+            if let Some(hash) = content.split_whitespace().next() {
+                return Ok(hash.to_lowercase());
+            }
+        }
+
+        let lists = ["SHA256SUMS", "SHA256SUMS.txt", "checksums.txt"];
+        if let Some(list) = release.assets.iter().find(|a| lists.contains(&a.name.as_str())) {
+            let content = String::from_utf8(self.download(&list.browser_download_url).await?)?;
+            for line in content.lines() {
+                let mut parts = line.split_whitespace();
+                if let (Some(hash), Some(name)) = (parts.next(), parts.next()) {
+                    if name.trim_start_matches('*') == asset.name {
+                        return Ok(hash.to_lowercase());
+                    }
+                }
+            }
+        }
+
+        Err(format!("No SHA-256 checksum published for {}", asset.name).into())
+    }
+
+    /// 下载资源并在返回前校验 SHA-256 和 <资源名>.sig 签名，缺少任一项或不匹配都视为失败。
+    /// 返回安装包和它的签名
+    pub async fn download_verified_asset(
+        &self,
+        app: &AppHandle,
+        release: &GitHubRelease,
+        asset: &GitHubAsset,
+    ) -> Result<(Vec<u8>, String), String> {
+        self.download_verified(app, release, asset, true).await
+    }
+
+    /// 与 download_verified_asset 相同，但不显示下载进度，也不占用更新下载，用于在后台缓存回滚用的安装包
+    pub async fn download_verified_asset_in_background(
+        &self,
+        app: &AppHandle,
+        release: &GitHubRelease,
+        asset: &GitHubAsset,
+    ) -> Result<(Vec<u8>, String), String> {
+        self.download_verified(app, release, asset, false).await
+    }
+
+    async fn download_verified(
+        &self,
+        app: &AppHandle,
+        release: &GitHubRelease,
+        asset: &GitHubAsset,
+        show_progress: bool,
+    ) -> Result<(Vec<u8>, String), String> {
+        let signature_name = format!("{}.sig", asset.name);
+        let signature_asset = release
+            .assets
+            .iter()
+            .find(|a| a.name == signature_name)
+            .ok_or_else(|| t_format("asset_verification_failed", &[&asset.name, "signature not published"]))?;
+
+        let expected = self
+            .expected_sha256(release, asset)
+            .await
+            .map_err(|e| t_format("asset_verification_failed", &[&asset.name, &e.to_string()]))?;
+        let version = release.tag_name.trim_start_matches('v');
+        let bytes = if show_progress {
+            super::download::fetch(app, &asset.browser_download_url, version).await
+        } else {
+            self.download(&asset.browser_download_url).await.map_err(|e| e.to_string())
+        }
+        .map_err(|e| t_format("asset_download_failed", &[&asset.name, &e]))?;
+
+        let actual = format!("{:x}", Sha256::digest(&bytes));
+        if actual != expected {
+            log::error!("Checksum mismatch for {}: expected {}, got {}", asset.name, expected, actual);
+            return Err(t_format("asset_verification_failed", &[&asset.name, "SHA-256 mismatch"]));
+        }
+
+        let signature = self
+            .download(&signature_asset.browser_download_url)
+            .await
+            .map_err(|e| t_format("asset_download_failed", &[&signature_name, &e.to_string()]))?;
+        let signature = String::from_utf8_lossy(&signature).trim().to_string();
+        super::verify_signature(app, &bytes, &signature).map_err(|e| {
+            log::error!("Signature verification failed for {}: {}", asset.name, e);
+            t_format("asset_verification_failed", &[&asset.name, &e])
+        })?;
+
+        Ok((bytes, signature))
+    }
 }
 
 fn get_current_platform() -> String {
@@ -131,13 +310,49 @@ fn get_current_arch() -> String {
     return "unknown".to_string();
 }
 
+fn installable_asset<'a>(assets: &'a [GitHubAsset], platform: &str, arch: &str) -> Option<&'a GitHubAsset> {
+    let suffixes: &[&str] = match platform {
+        "windows" => &[".msi", "-setup.exe"],
+        "macos" => &[".app.tar.gz"],
+        "linux" => &[".appimage"],
+        _ => &[],
+    };
+    assets.iter().find(|asset| {
+        let name = asset.name.to_lowercase();
+        suffixes.iter().any(|suffix| name.ends_with(suffix)) && (matches_arch(&name, arch) || name.contains("universal"))
+    })
+}
+
+// 资源名中的架构：Tauri 打包的 macOS 和 Linux 资源使用 aarch64、amd64、x86_64，Windows 使用 x64，
+// 统一为 get_current_arch 的 x64 / arm64 后再比较
+fn matches_arch(name: &str, arch: &str) -> bool {
+    if arch == "universal" {
+        return true;
+    }
+    let name = name
+        .to_lowercase()
+        .replace("x86_64", "x64")
+        .replace("amd64", "x64")
+        .replace("aarch64", "arm64");
+    name.contains(arch)
+}
+
+// 更新设置中的预发布开关
+fn prereleases_enabled() -> bool {
+    super::scheduler::UpdateSchedulerConfig::load()
+        .map(|config| config.include_prereleases)
+        .unwrap_or(false)
+}
+
 #[tauri::command]
 pub async fn get_github_releases(
     repo_owner: String,
     repo_name: String,
-    token: Option<String>
+    token: Option<String>,
+    include_prereleases: Option<bool>
 ) -> Result<Vec<GitHubRelease>, String> {
-    let client = GitHubClient::new(repo_owner, repo_name, token);
+    let client = GitHubClient::new(repo_owner, repo_name, token)
+        .with_prereleases(include_prereleases.unwrap_or_else(prereleases_enabled));
     client.get_releases(10).await.map_err(|e| e.to_string())
 }
 
@@ -145,8 +360,90 @@ pub async fn get_github_releases(
 pub async fn get_latest_github_release(
     repo_owner: String,
     repo_name: String,
-    token: Option<String>
+    token: Option<String>,
+    include_prereleases: Option<bool>
 ) -> Result<GitHubRelease, String> {
-    let client = GitHubClient::new(repo_owner, repo_name, token);
+    let client = GitHubClient::new(repo_owner, repo_name, token)
+        .with_prereleases(include_prereleases.unwrap_or_else(prereleases_enabled));
     client.get_latest_release().await.map_err(|e| e.to_string())
-}
\ No newline at end of file
+}
+
+// Tauri命令：从 GitHub 发布安装最新版本，安装包校验通过后才会执行
+#[tauri::command]
+pub async fn install_github_release(
+    app: AppHandle,
+    repo_owner: String,
+    repo_name: String,
+    token: Option<String>
+) -> Result<String, String> {
+    let client = GitHubClient::new(repo_owner, repo_name, token).with_prereleases(prereleases_enabled());
+    let source = UpdateSource::find_on_github(&app, client)
+        .await?
+        .ok_or_else(|| "No update available".to_string())?;
+
+    let bytes = source.download(&app).await?;
+    source.install(&app, bytes, true)?;
+    Ok(source.version())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // v1.4.0 发布中的资源名
+    fn release_assets() -> Vec<GitHubAsset> {
+        [
+            "FileSortify_1.4.0_aarch64.dmg",
+            "FileSortify_1.4.0_x64.dmg",
+            "FileSortify_aarch64.app.tar.gz",
+            "FileSortify_aarch64.app.tar.gz.sig",
+            "FileSortify_x64.app.tar.gz",
+            "FileSortify_x64.app.tar.gz.sig",
+            "FileSortify_1.4.0_x64_en-US.msi",
+            "FileSortify_1.4.0_x64_en-US.msi.sig",
+            "FileSortify_1.4.0_x64-setup.exe",
+            "FileSortify_1.4.0_arm64-setup.exe",
+            "file-sortify_1.4.0_amd64.AppImage",
+            "file-sortify_1.4.0_amd64.AppImage.sig",
+            "file-sortify_1.4.0_aarch64.AppImage",
+            "file-sortify_1.4.0_amd64.deb",
+            "file-sortify-1.4.0-1.x86_64.rpm",
+            "SHA256SUMS",
+        ]
+        .iter()
+        .map(|name| GitHubAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://github.com/Pulset/FileSortify/releases/download/v1.4.0/{}", name),
+            size: 0,
+            content_type: "application/octet-stream".to_string(),
+        })
+        .collect()
+    }
+
+    fn installable(platform: &str, arch: &str) -> Option<String> {
+        installable_asset(&release_assets(), platform, arch).map(|asset| asset.name.clone())
+    }
+
+    #[test]
+    fn arch_aliases_match() {
+        for name in ["app_aarch64.dmg", "app_arm64-setup.exe"] {
+            assert!(matches_arch(name, "arm64"), "{}", name);
+            assert!(!matches_arch(name, "x64"), "{}", name);
+        }
+        for name in ["app_x64.dmg", "app_amd64.AppImage", "app-1.x86_64.rpm"] {
+            assert!(matches_arch(name, "x64"), "{}", name);
+            assert!(!matches_arch(name, "arm64"), "{}", name);
+            assert!(!matches_arch(name, "x86"), "{}", name);
+        }
+    }
+
+    #[test]
+    fn finds_installable_asset_for_each_platform() {
+        assert_eq!(installable("macos", "arm64").as_deref(), Some("FileSortify_aarch64.app.tar.gz"));
+        assert_eq!(installable("macos", "x64").as_deref(), Some("FileSortify_x64.app.tar.gz"));
+        assert_eq!(installable("windows", "x64").as_deref(), Some("FileSortify_1.4.0_x64_en-US.msi"));
+        assert_eq!(installable("windows", "arm64").as_deref(), Some("FileSortify_1.4.0_arm64-setup.exe"));
+        assert_eq!(installable("linux", "x64").as_deref(), Some("file-sortify_1.4.0_amd64.AppImage"));
+        assert_eq!(installable("linux", "arm64").as_deref(), Some("file-sortify_1.4.0_aarch64.AppImage"));
+    }
+}
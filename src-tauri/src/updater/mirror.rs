@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+// 单个下载地址的探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 把原始下载地址换成镜像地址。
+/// 镜像中含有 {url} 时替换为完整的原始地址（如 https://ghproxy.example.com/{url}），
+/// 否则镜像作为新的站点根地址，保留原始地址的路径和查询参数。
+pub fn rewrite(url: &str, mirror: &str) -> Option<String> {
+    let mirror = mirror.trim();
+    if mirror.is_empty() {
+        return None;
+    }
+    if mirror.contains("{url}") {
+        return Some(mirror.replace("{url}", url));
+    }
+
+    let original = tauri::Url::parse(url).ok()?;
+    let mut path = original.path().to_string();
+    if let Some(query) = original.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+    Some(format!("{}{}", mirror.trim_end_matches('/'), path))
+}
+
+/// 校验镜像配置，只允许 http(s) 地址
+pub fn validate(mirrors: &[String]) -> Result<(), String> {
+    for mirror in mirrors {
+        let probe = mirror.replace("{url}", "https://example.com/");
+        match tauri::Url::parse(&probe) {
+            Ok(url) if url.scheme() == "https" || url.scheme() == "http" => {}
+            _ => return Err(mirror.clone()),
+        }
+    }
+    Ok(())
+}
+
+// 只请求第一个字节，测量从发出请求到收到响应头的时间
+async fn probe(url: String) -> Option<(String, Duration)> {
+    let started = Instant::now();
+    let request = crate::http::client()
+        .get(&url)
+        .header("User-Agent", "FileSortify-Updater")
+        .header("Range", "bytes=0-0")
+        .send();
+    let response = tokio::time::timeout(PROBE_TIMEOUT, request).await.ok()?.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    Some((url, started.elapsed()))
+}
+
+/// 在原始地址和配置的镜像中选出延迟最低的可用地址，全部探测失败时使用原始地址
+pub async fn select(url: &str) -> String {
+    let mirrors = super::scheduler::UpdateSchedulerConfig::load()
+        .map(|config| config.mirrors)
+        .unwrap_or_default();
+    if mirrors.is_empty() {
+        return url.to_string();
+    }
+
+    let mut probes = tokio::task::JoinSet::new();
+    probes.spawn(probe(url.to_string()));
+    for candidate in mirrors.iter().filter_map(|mirror| rewrite(url, mirror)) {
+        probes.spawn(probe(candidate));
+    }
+
+    let mut best: Option<(String, Duration)> = None;
+    while let Some(result) = probes.join_next().await {
+        if let Ok(Some((candidate, latency))) = result {
+            log::debug!("Update mirror {} responded in {:?}", candidate, latency);
+            if best.as_ref().map_or(true, |(_, fastest)| latency < *fastest) {
+                best = Some((candidate, latency));
+            }
+        }
+    }
+
+    match best {
+        Some((candidate, latency)) => {
+            if candidate != url {
+                log::info!("Downloading update from mirror {} ({:?})", candidate, latency);
+            }
+            candidate
+        }
+        None => url.to_string(),
+    }
+}
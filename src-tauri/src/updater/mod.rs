@@ -1,10 +1,20 @@
+pub mod delta;
+pub mod download;
 pub mod github;
+pub mod mirror;
+pub mod notify;
+pub mod rollback;
+pub mod source;
+pub mod staged;
 pub mod scheduler;
 
+use base64::Engine;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::AppHandle;
 use tauri_plugin_updater::UpdaterExt;
 
+use source::UpdateSource;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateInfo {
     pub version: String,
@@ -21,101 +31,120 @@ pub struct UpdateStatus {
     pub latest_version: Option<String>,
     pub download_url: Option<String>,
     pub body: Option<String>,
+    // 最新版本被用户跳过
+    #[serde(default)]
+    pub skipped: bool,
+    // 处于“稍后提醒”期间
+    #[serde(default)]
+    pub snoozed: bool,
+}
+
+// 设置了手动代理时，检查和下载更新也走代理
+fn build_updater(app: &AppHandle) -> tauri_plugin_updater::Result<tauri_plugin_updater::Updater> {
+    let mut builder = app.updater_builder();
+    if let Some(url) = crate::http::proxy().manual_url().and_then(|url| tauri::Url::parse(url).ok()) {
+        builder = builder.proxy(url);
+    }
+    builder.build()
+}
+
+/// 用 tauri.conf.json 中的更新公钥校验安装包的 minisign 签名（base64 编码，与 .sig 文件内容相同）
+pub fn verify_signature(app: &AppHandle, bytes: &[u8], signature: &str) -> Result<(), String> {
+    let pubkey = app
+        .config()
+        .plugins
+        .0
+        .get("updater")
+        .and_then(|updater| updater.get("pubkey"))
+        .and_then(|pubkey| pubkey.as_str())
+        .ok_or("Updater public key is not configured")?;
+
+    let decode = |value: &str| -> Result<String, String> {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(bytes).map_err(|e| e.to_string())
+    };
+
+    let public_key = minisign_verify::PublicKey::decode(&decode(pubkey)?).map_err(|e| e.to_string())?;
+    let signature = minisign_verify::Signature::decode(&decode(signature)?).map_err(|e| e.to_string())?;
+    public_key.verify(bytes, &signature, true).map_err(|e| e.to_string())
 }
 
 pub async fn check_for_updates(app: AppHandle) -> Result<UpdateStatus, String> {
     let current_version = app.package_info().version.to_string();
     
-    match app.updater() {
-        Ok(updater) => {
-            match updater.check().await {
-                Ok(Some(update)) => {
-                    Ok(UpdateStatus {
-                        available: true,
-                        current_version,
-                        latest_version: Some(update.version.clone()),
-                        download_url: Some(update.download_url.to_string()),
-                        body: Some(update.body.unwrap_or_default()),
-                    })
-                },
-                Ok(None) => {
-                    Ok(UpdateStatus {
-                        available: false,
-                        current_version,
-                        latest_version: None,
-                        download_url: None,
-                        body: None,
-                    })
-                },
-                Err(e) => {
-                    log::error!("Update check failed: {}", e);
-                    Err(format!("Update check failed: {}", e))
-                }
-            }
+    match UpdateSource::find(&app).await {
+        Ok(Some(source)) => {
+            let scheduler_config = scheduler::UpdateSchedulerConfig::load().unwrap_or_default();
+            let version = source.version();
+            Ok(UpdateStatus {
+                available: true,
+                current_version,
+                skipped: scheduler_config.is_version_skipped(&version),
+                snoozed: scheduler_config.is_snoozed(),
+                latest_version: Some(version),
+                download_url: Some(source.download_url()),
+                body: Some(source.body()),
+            })
+        },
+        Ok(None) => {
+            Ok(UpdateStatus {
+                available: false,
+                current_version,
+                latest_version: None,
+                download_url: None,
+                body: None,
+                skipped: false,
+                snoozed: false,
+            })
         },
         Err(e) => {
-            log::error!("Failed to get updater: {}", e);
-            Err(format!("Failed to get updater: {}", e))
+            log::error!("{}", e);
+            Err(e)
         }
     }
 }
 
 pub async fn download_and_install(app: AppHandle) -> Result<(), String> {
-    match app.updater() {
-        Ok(updater) => {
-            match updater.check().await {
-                Ok(Some(update)) => {
-                    let mut downloaded = 0;
-
-                    match update.download_and_install(
-                        |chunk_length, content_length| {
-                            downloaded += chunk_length;
-                            let progress = if let Some(total) = content_length {
-                                (downloaded as f64 / total as f64) * 100.0
-                            } else {
-                                0.0
-                            };
-                            
-                            let _ = app.emit("update-progress", progress);
-                        },
-                        || {
-                            let _ = app.emit("update-completed", ());
-                        },
-                    ).await {
-                        Ok(_) => {
-                            // 更新安装成功后，延迟重启应用
-                            let app_clone = app.clone();
-                            tokio::spawn(async move {
-                                // 给前端一点时间显示完成状态
-                                tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
-                                let _ = app_clone.emit("update-restart", ());
-                                // 重启应用
-                                app_clone.restart();
-                            });
-                            Ok(())
-                        },
-                        Err(e) => {
-                            log::error!("Update installation failed: {}", e);
-                            Err(format!("Update installation failed: {}", e))
-                        }
-                    }
-                },
-                Ok(None) => {
-                    Err("No update available".to_string())
-                },
-                Err(e) => {
-                    log::error!("Update check failed: {}", e);
-                    Err(format!("Update check failed: {}", e))
-                }
-            }
+    match UpdateSource::find(&app).await {
+        Ok(Some(source)) => {
+            let bytes = source.download(&app).await.map_err(|e| {
+                log::error!("Update download failed: {}", e);
+                e
+            })?;
+            source.install(&app, bytes, true).map_err(|e| {
+                log::error!("Update installation failed: {}", e);
+                e
+            })
+        },
+        Ok(None) => {
+            Err("No update available".to_string())
         },
         Err(e) => {
-            log::error!("Failed to get updater: {}", e);
-            Err(format!("Failed to get updater: {}", e))
+            log::error!("{}", e);
+            Err(e)
         }
     }
 }
 
+/// 后台下载并校验更新，暂存到退出时或计划的时间再安装
+pub async fn download_and_stage(
+    app: AppHandle,
+    mode: scheduler::InstallMode,
+    scheduled_time: Option<String>,
+) -> Result<(), String> {
+    let source = UpdateSource::find(&app)
+        .await?
+        .ok_or_else(|| "No update available".to_string())?;
+    if staged::staged_version().as_deref() == Some(source.version().as_str()) {
+        return Ok(());
+    }
+    let bytes = source.download(&app).await?;
+    staged::stage(&app, source, bytes, mode, scheduled_time);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn check_update(app: AppHandle) -> Result<UpdateStatus, String> {
     check_for_updates(app).await
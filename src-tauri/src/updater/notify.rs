@@ -0,0 +1,152 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::UpdateStatus;
+use crate::i18n::{t, t_format};
+
+// 通知按钮对应的操作
+const ACTION_INSTALL: &str = "install";
+const ACTION_NOTES: &str = "notes";
+
+/// 发送带“立即安装”“查看更新说明”按钮的系统通知。
+/// 窗口隐藏时前端收不到 update-available 事件，点击按钮会显示主窗口并跳转到对应操作。
+pub fn update_available(app: &AppHandle, status: &UpdateStatus) {
+    let version = status.latest_version.clone().unwrap_or_default();
+    let title = t_format("update_available_title", &[&version]);
+    let body = t("update_available_body");
+
+    if let Err(e) = platform::show(app, status, &title, &body) {
+        // 不支持操作按钮时退回普通通知，点击后由用户自行打开应用
+        log::warn!("Failed to show actionable update notification: {}", e);
+        let _ = tauri_plugin_notification::NotificationExt::notification(app)
+            .builder()
+            .title(&title)
+            .body(&body)
+            .show();
+    }
+}
+
+/// 处理通知上的操作：显示主窗口并通知前端，“立即安装”同时开始下载安装
+fn handle_action(app: &AppHandle, status: &UpdateStatus, action: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+
+    let action = if action == ACTION_INSTALL { ACTION_INSTALL } else { ACTION_NOTES };
+    let _ = app.emit("update-notification-action", &serde_json::json!({
+        "action": action,
+        "status": status,
+    }));
+
+    if action == ACTION_INSTALL {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = super::download_and_install(app.clone()).await {
+                log::error!("Update installation from notification failed: {}", e);
+                let _ = app.emit("update-error", &e);
+            }
+        });
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    // 通知服务器（GNOME、KDE 等）支持操作按钮，等待用户点击需要单独的线程
+    pub fn show(app: &AppHandle, status: &UpdateStatus, title: &str, body: &str) -> Result<(), String> {
+        let handle = notify_rust::Notification::new()
+            .appname("FileSortify")
+            .summary(title)
+            .body(body)
+            .action(ACTION_INSTALL, &t("update_install_now"))
+            .action(ACTION_NOTES, &t("update_view_notes"))
+            .action("default", &t("update_view_notes"))
+            .show()
+            .map_err(|e| e.to_string())?;
+
+        let app = app.clone();
+        let status = status.clone();
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                // 关闭通知时为 __closed
+                if action != "__closed" {
+                    handle_action(&app, &status, action);
+                }
+            });
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+    use mac_notification_sys::{MainButton, Notification, NotificationResponse};
+
+    // 主按钮为“立即安装”，关闭按钮为“查看更新说明”；发送后会阻塞到用户响应
+    pub fn show(app: &AppHandle, status: &UpdateStatus, title: &str, body: &str) -> Result<(), String> {
+        let _ = mac_notification_sys::set_application(&app.config().identifier);
+
+        let app = app.clone();
+        let status = status.clone();
+        let (title, body) = (title.to_string(), body.to_string());
+        std::thread::spawn(move || {
+            let install = t("update_install_now");
+            let notes = t("update_view_notes");
+            let mut notification = Notification::new();
+            notification
+                .main_button(MainButton::SingleAction(&install))
+                .close_button(&notes);
+
+            match mac_notification_sys::send_notification(&title, None, &body, Some(&notification)) {
+                Ok(NotificationResponse::ActionButton(_)) => handle_action(&app, &status, ACTION_INSTALL),
+                Ok(NotificationResponse::CloseButton(_)) | Ok(NotificationResponse::Click) => {
+                    handle_action(&app, &status, ACTION_NOTES)
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to show update notification: {}", e),
+            }
+        });
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use tauri_winrt_notification::Toast;
+
+    pub fn show(app: &AppHandle, status: &UpdateStatus, title: &str, body: &str) -> Result<(), String> {
+        // 开发模式下应用没有注册 AppUserModelID，借用 PowerShell 的
+        let app_id = if cfg!(debug_assertions) {
+            Toast::POWERSHELL_APP_ID.to_string()
+        } else {
+            app.config().identifier.clone()
+        };
+
+        let handler_app = app.clone();
+        let status = status.clone();
+        Toast::new(&app_id)
+            .title(title)
+            .text1(body)
+            .add_button(&t("update_install_now"), ACTION_INSTALL)
+            .add_button(&t("update_view_notes"), ACTION_NOTES)
+            .on_activated(move |action| {
+                // 点击通知本身时没有 action
+                handle_action(&handler_app, &status, action.as_deref().unwrap_or(ACTION_NOTES));
+                Ok(())
+            })
+            .show()
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    pub fn show(_app: &AppHandle, _status: &UpdateStatus, _title: &str, _body: &str) -> Result<(), String> {
+        Err("Actionable notifications are not supported on this platform".to_string())
+    }
+}
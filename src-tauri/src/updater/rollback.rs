@@ -0,0 +1,480 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use super::delta;
+use crate::i18n::{t, t_format};
+
+// 更新前保留的安装（macOS 的 .app、Linux 的 AppImage）放在更新缓存目录中，version 文件记录它的版本
+#[cfg(target_os = "macos")]
+const KEPT_INSTALLATION_NAME: &str = "FileSortify.app";
+#[cfg(target_os = "linux")]
+const KEPT_INSTALLATION_NAME: &str = "FileSortify.AppImage";
+
+// 当前运行的 .app 目录（.../FileSortify.app/Contents/MacOS/FileSortify）
+#[cfg(target_os = "macos")]
+fn app_bundle() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    exe.ancestors()
+        .nth(3)
+        .filter(|bundle| bundle.extension().map_or(false, |ext| ext == "app"))
+        .map(PathBuf::from)
+        .ok_or_else(|| "Not running from an app bundle".to_string())
+}
+
+// 回滚目标：更新前保留的安装，或缓存中保存了签名的安装包
+enum RollbackTarget {
+    Installation { version: String, path: PathBuf },
+    Package(String),
+}
+
+impl RollbackTarget {
+    fn version(&self) -> &str {
+        match self {
+            RollbackTarget::Installation { version, .. } => version,
+            RollbackTarget::Package(version) => version,
+        }
+    }
+}
+
+// 可以回滚到的版本：更新前保留的安装，以及缓存中早于当前版本、并保存了签名的安装包里较新的一个。
+// 缓存的安装包回滚前会重新校验签名
+fn rollback_target(current_version: &str) -> Option<RollbackTarget> {
+    let package = delta::cached_versions()
+        .into_iter()
+        .filter(|version| compare_versions(version, current_version).is_lt())
+        .filter(|version| delta::signature_path(version).exists())
+        .max_by(|a, b| compare_versions(a, b))
+        .map(RollbackTarget::Package);
+    let installation = kept_installation()
+        .filter(|(version, _)| compare_versions(version, current_version).is_lt())
+        .map(|(version, path)| RollbackTarget::Installation { version, path });
+
+    match (installation, package) {
+        (Some(installation), Some(package)) => {
+            if compare_versions(package.version(), installation.version()).is_gt() {
+                Some(package)
+            } else {
+                Some(installation)
+            }
+        }
+        (installation, package) => installation.or(package),
+    }
+}
+
+// 按语义化版本比较，1.2.0-beta.1 早于 1.2.0，beta.2 早于 beta.10
+pub(super) fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> (Vec<u64>, Option<String>) {
+        // 构建元数据不参与比较
+        let v = v.trim_start_matches('v').split('+').next().unwrap_or_default();
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (v, None),
+        };
+        (core.split('.').map(|part| part.parse().unwrap_or(0)).collect(), pre)
+    };
+    let ((core_a, pre_a), (core_b, pre_b)) = (parse(a), parse(b));
+    core_a.cmp(&core_b).then_with(|| match (pre_a, pre_b) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(pre_a), Some(pre_b)) => compare_prerelease(&pre_a, &pre_b),
+    })
+}
+
+// 预发布标识逐段比较：数字按数值比较且早于非数字，其余按字符比较，前面各段相同时段数少的更早
+fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    let mut parts_a = a.split('.');
+    let mut parts_b = b.split('.');
+    loop {
+        let ordering = match (parts_a.next(), parts_b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(part_a), Some(part_b)) => match (part_a.parse::<u64>(), part_b.parse::<u64>()) {
+                (Ok(number_a), Ok(number_b)) => number_a.cmp(&number_b),
+                (Ok(_), Err(_)) => Ordering::Less,
+                (Err(_), Ok(_)) => Ordering::Greater,
+                (Err(_), Err(_)) => part_a.cmp(part_b),
+            },
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn kept_installation_dir() -> PathBuf {
+    delta::cache_dir().join("previous")
+}
+
+// 更新前保留的安装的版本和位置
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn kept_installation() -> Option<(String, PathBuf)> {
+    let dir = kept_installation_dir();
+    let version = std::fs::read_to_string(dir.join("version")).ok()?.trim().to_string();
+    let path = dir.join(KEPT_INSTALLATION_NAME);
+    path.exists().then_some((version, path))
+}
+
+// Windows 上回滚使用缓存的安装程序
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn kept_installation() -> Option<(String, PathBuf)> {
+    None
+}
+
+// 把 source 保留为回滚目标，替换之前保留的版本。move_source 为 true 时移动，跨磁盘时改为复制后删除
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn keep_installation(version: &str, source: &Path, move_source: bool) -> Result<(), String> {
+    let dir = kept_installation_dir();
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let kept = dir.join(KEPT_INSTALLATION_NAME);
+    if !(move_source && std::fs::rename(source, &kept).is_ok()) {
+        copy_installation(source, &kept)?;
+        if move_source {
+            remove_installation(source);
+        }
+    }
+    std::fs::write(dir.join("version"), version).map_err(|e| e.to_string())?;
+    log::info!("Kept version {} for rollback", version);
+    Ok(())
+}
+
+// ditto 保留代码签名和扩展属性
+#[cfg(target_os = "macos")]
+fn copy_installation(source: &Path, destination: &Path) -> Result<(), String> {
+    let status = std::process::Command::new("ditto")
+        .arg(source)
+        .arg(destination)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("ditto exited with {}", status));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn copy_installation(source: &Path, destination: &Path) -> Result<(), String> {
+    std::fs::copy(source, destination).map(|_| ()).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn remove_installation(path: &Path) {
+    let _ = std::fs::remove_dir_all(path);
+}
+
+#[cfg(target_os = "linux")]
+fn remove_installation(path: &Path) {
+    let _ = std::fs::remove_file(path);
+}
+
+/// 更新服务器的更新由 updater 插件直接替换安装，安装前复制一份当前版本，用于回滚
+#[cfg(target_os = "macos")]
+pub(super) fn keep_current_installation(version: &str) {
+    let result = app_bundle().and_then(|bundle| keep_installation(version, &bundle, false));
+    if let Err(e) = result {
+        log::warn!("Failed to keep version {} for rollback: {}", version, e);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub(super) fn keep_current_installation(version: &str) {
+    let result = std::env::var_os("APPIMAGE")
+        .map(PathBuf::from)
+        .ok_or_else(|| "Not running as an AppImage".to_string())
+        .and_then(|appimage| keep_installation(version, &appimage, false));
+    if let Err(e) = result {
+        log::warn!("Failed to keep version {} for rollback: {}", version, e);
+    }
+}
+
+// Windows 上回滚使用 cache_current_installer 缓存的安装程序
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub(super) fn keep_current_installation(_version: &str) {}
+
+/// Windows 上更新前缓存当前版本的安装程序，用于回滚。手动安装的版本没有缓存时从 GitHub 发布下载并校验
+#[cfg(target_os = "windows")]
+pub(super) async fn cache_current_installer(app: &AppHandle) {
+    let version = app.package_info().version.to_string();
+    if delta::package_path(&version).exists() && delta::signature_path(&version).exists() {
+        return;
+    }
+
+    let client = super::github::GitHubClient::for_app();
+    let release = match client.get_release_by_tag(&format!("v{}", version)).await {
+        Ok(release) => release,
+        Err(e) => {
+            log::warn!("Failed to find release {} for rollback: {}", version, e);
+            return;
+        }
+    };
+    let Some(asset) = client.get_installable_asset(&release).cloned() else {
+        log::warn!("Release {} has no installable asset for rollback", version);
+        return;
+    };
+    match client.download_verified_asset_in_background(app, &release, &asset).await {
+        Ok((bytes, signature)) => delta::store_rollback_package(&version, &bytes, &signature),
+        Err(e) => log::warn!("Failed to cache installer {} for rollback: {}", version, e),
+    }
+}
+
+// macOS 和 Linux 回滚到更新前保留的安装
+#[cfg(not(target_os = "windows"))]
+pub(super) async fn cache_current_installer(_app: &AppHandle) {}
+
+/// 安装完整的安装包。restart 为 true 时随后重启应用（Windows 上由安装程序负责重新启动），
+/// 应用退出时安装则传 false。keep_version 为当前版本时保留被替换的安装用于回滚，回滚时传 None
+#[cfg(target_os = "linux")]
+pub(super) fn install_package(
+    app: &AppHandle,
+    bytes: Vec<u8>,
+    restart: bool,
+    keep_version: Option<&str>,
+) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let appimage = std::env::var_os("APPIMAGE").map(PathBuf::from).ok_or("Not running as an AppImage")?;
+    let staging = appimage.with_extension("new");
+    std::fs::write(&staging, bytes).map_err(|e| e.to_string())?;
+    std::fs::set_permissions(&staging, std::fs::Permissions::from_mode(0o755)).map_err(|e| e.to_string())?;
+    if let Some(version) = keep_version {
+        if let Err(e) = keep_installation(version, &appimage, false) {
+            log::warn!("Failed to keep version {} for rollback: {}", version, e);
+        }
+    }
+    std::fs::rename(&staging, &appimage).map_err(|e| e.to_string())?;
+    if restart {
+        app.restart();
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+pub(super) fn install_package(
+    app: &AppHandle,
+    bytes: Vec<u8>,
+    restart: bool,
+    keep_version: Option<&str>,
+) -> Result<(), String> {
+    let bundle = app_bundle()?;
+    let staging = std::env::temp_dir().join(format!("filesortify-install-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&staging).map_err(|e| e.to_string())?;
+    let archive = staging.join("package.tar.gz");
+    std::fs::write(&archive, bytes).map_err(|e| e.to_string())?;
+
+    let status = std::process::Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(&staging)
+        .status()
+        .map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("tar exited with {}", status));
+    }
+
+    let extracted = std::fs::read_dir(&staging)
+        .map_err(|e| e.to_string())?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.extension().map_or(false, |ext| ext == "app"))
+        .ok_or("Package does not contain an app bundle")?;
+
+    // 先把当前版本移开，替换失败时还原
+    let previous = bundle.with_extension("app.old");
+    let _ = std::fs::remove_dir_all(&previous);
+    std::fs::rename(&bundle, &previous).map_err(|e| e.to_string())?;
+    if let Err(e) = std::fs::rename(&extracted, &bundle) {
+        let _ = std::fs::rename(&previous, &bundle);
+        return Err(e.to_string());
+    }
+    // 被替换的版本保留用于回滚
+    match keep_version {
+        Some(version) => {
+            if let Err(e) = keep_installation(version, &previous, true) {
+                log::warn!("Failed to keep version {} for rollback: {}", version, e);
+                let _ = std::fs::remove_dir_all(&previous);
+            }
+        }
+        None => {
+            let _ = std::fs::remove_dir_all(&previous);
+        }
+    }
+    let _ = std::fs::remove_dir_all(&staging);
+    if restart {
+        app.restart();
+    }
+    Ok(())
+}
+
+// 当前版本的安装程序由 cache_current_installer 缓存，不需要保留安装目录
+#[cfg(target_os = "windows")]
+pub(super) fn install_package(
+    app: &AppHandle,
+    bytes: Vec<u8>,
+    restart: bool,
+    _keep_version: Option<&str>,
+) -> Result<(), String> {
+    // MSI 是 OLE 复合文档，其余按 NSIS 安装程序处理
+    let is_msi = bytes.starts_with(&[0xD0, 0xCF, 0x11, 0xE0]);
+    let installer = std::env::temp_dir().join(if is_msi {
+        "FileSortify-update.msi"
+    } else {
+        "FileSortify-update-setup.exe"
+    });
+    std::fs::write(&installer, bytes).map_err(|e| e.to_string())?;
+
+    let mut command = if is_msi {
+        let mut command = std::process::Command::new("msiexec.exe");
+        command.arg("/i").arg(&installer).arg("/passive");
+        if restart {
+            command.args(["/promptrestart", "AUTOLAUNCHAPP=True"]);
+        }
+        command
+    } else {
+        let mut command = std::process::Command::new(&installer);
+        command.arg("/P");
+        if restart {
+            command.arg("/R");
+        }
+        command
+    };
+    command.spawn().map_err(|e| e.to_string())?;
+    // 安装程序需要替换正在运行的文件
+    if restart {
+        app.exit(0);
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(super) fn install_package(
+    _app: &AppHandle,
+    _bytes: Vec<u8>,
+    _restart: bool,
+    _keep_version: Option<&str>,
+) -> Result<(), String> {
+    Err("Installing packages is not supported on this platform".to_string())
+}
+
+// 换回更新前保留的 .app，保留的副本随后删除
+#[cfg(target_os = "macos")]
+fn restore_installation(app: &AppHandle, kept: &Path) -> Result<(), String> {
+    let bundle = app_bundle()?;
+    let replaced = bundle.with_extension("app.old");
+    let _ = std::fs::remove_dir_all(&replaced);
+    std::fs::rename(&bundle, &replaced).map_err(|e| e.to_string())?;
+    let restored = match std::fs::rename(kept, &bundle) {
+        Ok(()) => Ok(()),
+        Err(_) => copy_installation(kept, &bundle),
+    };
+    if let Err(e) = restored {
+        let _ = std::fs::rename(&replaced, &bundle);
+        return Err(e);
+    }
+    let _ = std::fs::remove_dir_all(&replaced);
+    let _ = std::fs::remove_dir_all(kept_installation_dir());
+    app.restart();
+}
+
+#[cfg(target_os = "linux")]
+fn restore_installation(app: &AppHandle, kept: &Path) -> Result<(), String> {
+    let bytes = std::fs::read(kept).map_err(|e| e.to_string())?;
+    install_package(app, bytes, false, None)?;
+    let _ = std::fs::remove_dir_all(kept_installation_dir());
+    app.restart();
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn restore_installation(_app: &AppHandle, _kept: &Path) -> Result<(), String> {
+    Err("No kept installation on this platform".to_string())
+}
+
+// 读取缓存的安装包。安装包可能在磁盘上被改动过，安装前重新校验签名
+fn load_package(app: &AppHandle, version: &str) -> Result<Vec<u8>, String> {
+    let bytes = std::fs::read(delta::package_path(version)).map_err(|e| e.to_string())?;
+    let signature = std::fs::read_to_string(delta::signature_path(version)).map_err(|e| e.to_string())?;
+    super::verify_signature(app, &bytes, signature.trim()).map_err(|e| {
+        log::error!("Signature verification failed for cached package {}: {}", version, e);
+        e
+    })?;
+    Ok(bytes)
+}
+
+// 回滚后不要马上又提示更新到出问题的版本
+fn announce_rollback(app: &AppHandle, current_version: &str, version: &str) {
+    if let Err(e) = super::scheduler::skip_update_version(current_version.to_string()) {
+        log::warn!("Failed to skip version {} after rollback: {}", current_version, e);
+    }
+    log::info!("Rolling back from {} to {}", current_version, version);
+    let _ = app.emit("update-rollback", version);
+}
+
+// Tauri命令：回滚到上一个版本，并跳过当前版本的更新提示
+#[tauri::command]
+pub async fn rollback_update(app: AppHandle) -> Result<String, String> {
+    let current_version = app.package_info().version.to_string();
+    let target = rollback_target(&current_version).ok_or_else(|| t("no_rollback_available"))?;
+    let result = match &target {
+        RollbackTarget::Installation { version, path } => {
+            announce_rollback(&app, &current_version, version);
+            restore_installation(&app, path)
+        }
+        RollbackTarget::Package(version) => {
+            let bytes = load_package(&app, version).map_err(|e| t_format("rollback_failed", &[&e]))?;
+            announce_rollback(&app, &current_version, version);
+            install_package(&app, bytes, true, None)
+        }
+    };
+    result.map_err(|e| {
+        log::error!("Rollback failed: {}", e);
+        t_format("rollback_failed", &[&e])
+    })?;
+    Ok(target.version().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_release_versions() {
+        assert!(compare_versions("1.2.0", "1.10.0").is_lt());
+        assert!(compare_versions("1.2.1", "1.2.0").is_gt());
+        assert!(compare_versions("v1.2.0", "1.2.0").is_eq());
+        assert!(compare_versions("1.2.0+build.5", "1.2.0").is_eq());
+    }
+
+    #[test]
+    fn prereleases_come_before_the_release() {
+        assert!(compare_versions("1.2.0-beta.1", "1.2.0").is_lt());
+        assert!(compare_versions("1.2.0", "1.2.0-rc.1").is_gt());
+        assert!(compare_versions("1.2.0-rc.1", "1.1.9").is_gt());
+    }
+
+    #[test]
+    fn compares_numeric_prerelease_identifiers_as_numbers() {
+        assert!(compare_versions("1.2.0-beta.2", "1.2.0-beta.10").is_lt());
+        assert!(compare_versions("1.2.0-beta.10", "1.2.0-beta.9").is_gt());
+    }
+
+    #[test]
+    fn follows_semver_prerelease_precedence() {
+        // semver.org 第 11 条中的示例，从早到晚
+        let versions = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ];
+        for pair in versions.windows(2) {
+            assert!(compare_versions(pair[0], pair[1]).is_lt(), "{} < {}", pair[0], pair[1]);
+        }
+    }
+}
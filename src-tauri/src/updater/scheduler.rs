@@ -6,12 +6,62 @@ use tauri::{AppHandle, Emitter};
 use tokio::time;
 use crate::i18n::{t, t_format};
 
+/// 自动下载的更新何时安装
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMode {
+    // 下载完成后立即安装并重启
+    Immediate,
+    // 退出应用时安装，不打断正在进行的监控
+    OnQuit,
+    // 在 scheduled_install_time 安装并重启
+    Scheduled,
+}
+
+impl Default for InstallMode {
+    fn default() -> Self {
+        InstallMode::Immediate
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateSchedulerConfig {
     pub enabled: bool,
     pub check_interval_hours: u64,
     pub auto_download: bool,
     pub auto_install: bool,
+    // 用户选择“跳过此版本”的版本号，不再提示
+    #[serde(default)]
+    pub skipped_versions: Vec<String>,
+    // “稍后提醒”：在此时间（RFC 3339）之前不提示更新
+    #[serde(default)]
+    pub snooze_until: Option<String>,
+    // 下载镜像，下载前探测延迟并选择最快的地址
+    #[serde(default)]
+    pub mirrors: Vec<String>,
+    // 免打扰时段（本地时间 HH:MM，可以跨午夜），期间不检查也不下载更新
+    #[serde(default)]
+    pub quiet_hours_start: Option<String>,
+    #[serde(default)]
+    pub quiet_hours_end: Option<String>,
+    // 按流量计费的网络上不自动下载更新（仍然提示有新版本）
+    #[serde(default = "default_skip_metered_downloads")]
+    pub skip_metered_downloads: bool,
+    // 上次成功检查更新的时间（RFC 3339），重启后据此决定何时再次检查
+    #[serde(default)]
+    pub last_check: Option<String>,
+    // 从 GitHub 检查更新时包含预发布版本
+    #[serde(default)]
+    pub include_prereleases: bool,
+    #[serde(default)]
+    pub install_mode: InstallMode,
+    // install_mode 为 scheduled 时的安装时间（本地时间 HH:MM）
+    #[serde(default)]
+    pub scheduled_install_time: Option<String>,
+}
+
+fn default_skip_metered_downloads() -> bool {
+    true
 }
 
 impl UpdateSchedulerConfig {
@@ -42,9 +92,82 @@ impl UpdateSchedulerConfig {
         Ok(())
     }
     
+    pub fn is_version_skipped(&self, version: &str) -> bool {
+        self.skipped_versions.iter().any(|v| v == version)
+    }
+    
+    pub fn is_snoozed(&self) -> bool {
+        self.snooze_until
+            .as_deref()
+            .and_then(|until| chrono::DateTime::parse_from_rfc3339(until).ok())
+            .map(|until| chrono::Utc::now() < until)
+            .unwrap_or(false)
+    }
+    
+    pub fn last_check_time(&self) -> Option<SystemTime> {
+        self.last_check
+            .as_deref()
+            .and_then(|time| chrono::DateTime::parse_from_rfc3339(time).ok())
+            .map(SystemTime::from)
+    }
+
+    /// 记录一次检查。重新读取配置文件再保存，避免覆盖其他地方的修改
+    pub fn record_check(time: SystemTime) {
+        let mut config = Self::load().unwrap_or_default();
+        config.last_check = Some(chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339());
+        if let Err(e) = config.save() {
+            log::warn!("Failed to save last update check time: {}", e);
+        }
+    }
+
+    // 距离下一次检查还需要等待的时间，从未检查过或已经到期时为 0
+    fn next_check_delay(&self) -> Duration {
+        let interval = Duration::from_secs(self.check_interval_hours * 3600);
+        self.last_check_time()
+            .and_then(|last| SystemTime::now().duration_since(last).ok())
+            .map(|elapsed| interval.saturating_sub(elapsed))
+            .unwrap_or(Duration::ZERO)
+    }
+
+    /// 当前处于免打扰时段时，返回距离时段结束的时间
+    pub fn quiet_hours_remaining(&self, now: &chrono::DateTime<chrono::Local>) -> Option<Duration> {
+        use chrono::{NaiveTime, Timelike};
+
+        let parse = |value: &Option<String>| value.as_deref().and_then(|v| NaiveTime::parse_from_str(v, "%H:%M").ok());
+        let (start, end) = (parse(&self.quiet_hours_start)?, parse(&self.quiet_hours_end)?);
+        let current = NaiveTime::from_hms_opt(now.hour(), now.minute(), now.second()).unwrap_or_default();
+        let quiet = if start <= end {
+            current >= start && current < end
+        } else {
+            current >= start || current < end
+        };
+        if !quiet {
+            return None;
+        }
+
+        // 跨午夜时 end - current 为负，加上一天
+        let mut remaining = end.signed_duration_since(current);
+        if remaining <= chrono::Duration::zero() {
+            remaining = remaining + chrono::Duration::days(1);
+        }
+        remaining.to_std().ok()
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.install_mode == InstallMode::Scheduled && self.scheduled_install_time.is_none() {
+            return Err(t("scheduled_install_time_required"));
+        }
+        for time in [&self.quiet_hours_start, &self.quiet_hours_end, &self.scheduled_install_time].into_iter().flatten() {
+            if chrono::NaiveTime::parse_from_str(time, "%H:%M").is_err() {
+                return Err(t_format("invalid_schedule_time", &[time]));
+            }
+        }
+        super::mirror::validate(&self.mirrors).map_err(|mirror| t_format("invalid_update_mirror", &[&mirror]))
+    }
+    
     fn get_config_path() -> PathBuf {
-        if let Some(config_dir) = dirs::config_dir() {
-            config_dir.join("fileSortify").join("update_scheduler.json")
+        if let Some(data_dir) = crate::app_paths::app_data_dir() {
+            data_dir.join("update_scheduler.json")
         } else {
             PathBuf::from("update_scheduler_config.json")
         }
@@ -58,6 +181,16 @@ impl Default for UpdateSchedulerConfig {
             check_interval_hours: 24, // 每24小时检查一次
             auto_download: false,
             auto_install: false,
+            skipped_versions: Vec::new(),
+            snooze_until: None,
+            mirrors: Vec::new(),
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            skip_metered_downloads: default_skip_metered_downloads(),
+            last_check: None,
+            include_prereleases: false,
+            install_mode: InstallMode::default(),
+            scheduled_install_time: None,
         }
     }
 }
@@ -70,8 +203,8 @@ pub struct UpdateScheduler {
 impl UpdateScheduler {
     pub fn new(config: UpdateSchedulerConfig) -> Self {
         Self {
+            last_check: config.last_check_time(),
             config,
-            last_check: None,
         }
     }
 
@@ -92,7 +225,9 @@ impl UpdateScheduler {
     }
 
     pub fn mark_checked(&mut self) {
-        self.last_check = Some(SystemTime::now());
+        let now = SystemTime::now();
+        self.last_check = Some(now);
+        UpdateSchedulerConfig::record_check(now);
     }
 
     pub fn start_background_task(config: UpdateSchedulerConfig, app: AppHandle) {
@@ -101,23 +236,53 @@ impl UpdateScheduler {
         }
 
         let interval = Duration::from_secs(config.check_interval_hours * 3600);
-        let auto_download = config.auto_download;
         
+        // 上次检查的时间保存在配置中，重启后不会立即重新检查
+        let delay = config.next_check_delay();
+        if !delay.is_zero() {
+            log::info!("Next update check in {:?}", delay);
+        }
+
         tokio::spawn(async move {
-            let mut interval_timer = time::interval(interval);
+            let mut interval_timer = time::interval_at(time::Instant::now() + delay, interval);
             
             loop {
                 interval_timer.tick().await;
+
+                // 读取最新配置，免打扰时段内推迟到时段结束
+                let config = UpdateSchedulerConfig::load().unwrap_or_else(|_| config.clone());
+                if let Some(remaining) = config.quiet_hours_remaining(&chrono::Local::now()) {
+                    log::info!("Update check deferred by quiet hours for {:?}", remaining);
+                    time::sleep(remaining).await;
+                }
                 
                 match super::check_for_updates(app.clone()).await {
                     Ok(update_status) => {
-                        if update_status.available {
-                            // 发送更新可用通知
+                        UpdateSchedulerConfig::record_check(SystemTime::now());
+
+                        // 用户跳过了该版本或选择了稍后提醒时不打扰
+                        if update_status.skipped || update_status.snoozed {
+                            log::info!("Update {:?} available but suppressed by user choice", update_status.latest_version);
+                        } else if update_status.available {
+                            // 发送更新可用通知，窗口隐藏时靠系统通知提醒
                             let _ = app.emit("update-available", &update_status);
+                            if notifications_enabled() {
+                                super::notify::update_available(&app, &update_status);
+                            }
                             
-                            // 如果启用自动下载
-                            if auto_download {
-                                if let Ok(_) = super::download_and_install(app.clone()).await {
+                            // 如果启用自动下载（按流量计费的网络上跳过）
+                            if config.auto_download {
+                                let metered = config.skip_metered_downloads
+                                    && tokio::task::spawn_blocking(crate::metered::is_metered).await.unwrap_or(false);
+                                if metered {
+                                    log::info!("Skipping automatic update download on a metered connection");
+                                } else if config.install_mode == InstallMode::Immediate {
+                                    if let Ok(_) = super::download_and_install(app.clone()).await {
+                                        let _ = app.emit("update-downloaded", ());
+                                    }
+                                } else if let Err(e) = super::download_and_stage(app.clone(), config.install_mode, config.scheduled_install_time.clone()).await {
+                                    log::error!("Background update download failed: {}", e);
+                                } else {
                                     let _ = app.emit("update-downloaded", ());
                                 }
                             }
@@ -136,6 +301,13 @@ impl UpdateScheduler {
     }
 }
 
+// 全局关闭通知时不发送系统通知
+fn notifications_enabled() -> bool {
+    crate::config::Config::load()
+        .map(|config| config.notification_enabled != Some(false))
+        .unwrap_or(true)
+}
+
 #[tauri::command]
 pub fn get_scheduler_config() -> Result<UpdateSchedulerConfig, String> {
     match UpdateSchedulerConfig::load() {
@@ -148,7 +320,12 @@ pub fn get_scheduler_config() -> Result<UpdateSchedulerConfig, String> {
 }
 
 #[tauri::command]
-pub fn update_scheduler_config(config: UpdateSchedulerConfig) -> Result<String, String> {
+pub fn update_scheduler_config(mut config: UpdateSchedulerConfig) -> Result<String, String> {
+    config.validate()?;
+    // 前端没有传上次检查时间时保留已保存的值
+    if config.last_check.is_none() {
+        config.last_check = UpdateSchedulerConfig::load().ok().and_then(|saved| saved.last_check);
+    }
     match config.save() {
         Ok(_) => {
             log::info!("Update scheduler config updated: {:?}", config);
@@ -159,4 +336,36 @@ pub fn update_scheduler_config(config: UpdateSchedulerConfig) -> Result<String,
             Err(t_format("update_scheduler_config_failed", &[&e.to_string()]))
         }
     }
-}
\ No newline at end of file
+}
+
+// Tauri命令：跳过某个版本，之后不再提示该版本的更新
+#[tauri::command]
+pub fn skip_update_version(version: String) -> Result<(), String> {
+    let mut config = UpdateSchedulerConfig::load()
+        .map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))?;
+    if !config.is_version_skipped(&version) {
+        config.skipped_versions.push(version);
+    }
+    config.save().map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))
+}
+
+// Tauri命令：稍后提醒，默认 24 小时内不再提示更新
+#[tauri::command]
+pub fn snooze_update(hours: Option<u64>) -> Result<String, String> {
+    let mut config = UpdateSchedulerConfig::load()
+        .map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))?;
+    let until = chrono::Utc::now() + chrono::Duration::hours(hours.unwrap_or(24).max(1) as i64);
+    config.snooze_until = Some(until.to_rfc3339());
+    config.save().map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))?;
+    Ok(until.to_rfc3339())
+}
+
+// Tauri命令：清除跳过的版本和稍后提醒
+#[tauri::command]
+pub fn reset_update_reminders() -> Result<(), String> {
+    let mut config = UpdateSchedulerConfig::load()
+        .map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))?;
+    config.skipped_versions.clear();
+    config.snooze_until = None;
+    config.save().map_err(|e| t_format("update_scheduler_config_failed", &[&e.to_string()]))
+}
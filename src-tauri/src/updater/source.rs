@@ -0,0 +1,140 @@
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::Update;
+
+use super::github::{GitHubAsset, GitHubClient, GitHubRelease};
+use super::{build_updater, delta, download, rollback, verify_signature};
+use crate::i18n::t_format;
+
+/// 找到的可用更新及其来源。
+/// 优先使用 tauri.conf.json 中配置的更新服务器，服务器不可达时改用 GitHub 发布。
+pub enum UpdateSource {
+    Endpoint(Update),
+    GitHub {
+        client: GitHubClient,
+        release: GitHubRelease,
+        asset: GitHubAsset,
+    },
+}
+
+impl UpdateSource {
+    /// 检查是否有新版本。更新服务器明确返回“没有更新”时不会再查询 GitHub
+    pub async fn find(app: &AppHandle) -> Result<Option<Self>, String> {
+        let endpoint_error = match build_updater(app) {
+            Ok(updater) => match updater.check().await {
+                Ok(update) => return Ok(update.map(UpdateSource::Endpoint)),
+                Err(e) => format!("Update check failed: {}", e),
+            },
+            Err(e) => format!("Failed to get updater: {}", e),
+        };
+
+        log::warn!("{}, falling back to GitHub releases", endpoint_error);
+        match Self::find_on_github(app, GitHubClient::for_app()).await {
+            Ok(source) => Ok(source),
+            Err(e) => {
+                log::error!("GitHub fallback failed: {}", e);
+                Err(endpoint_error)
+            }
+        }
+    }
+
+    /// 在指定仓库的最新发布中查找比当前版本新、且有可安装资源的更新
+    pub async fn find_on_github(app: &AppHandle, client: GitHubClient) -> Result<Option<Self>, String> {
+        let release = client.get_latest_release().await.map_err(|e| e.to_string())?;
+        let current_version = app.package_info().version.to_string();
+        let version = release.tag_name.trim_start_matches('v');
+        if rollback::compare_versions(version, &current_version).is_le() {
+            return Ok(None);
+        }
+
+        let asset = client
+            .get_installable_asset(&release)
+            .cloned()
+            .ok_or_else(|| t_format("no_installable_asset", &[&release.tag_name]))?;
+        Ok(Some(UpdateSource::GitHub { client, release, asset }))
+    }
+
+    pub fn version(&self) -> String {
+        match self {
+            UpdateSource::Endpoint(update) => update.version.clone(),
+            UpdateSource::GitHub { release, .. } => release.tag_name.trim_start_matches('v').to_string(),
+        }
+    }
+
+    pub fn body(&self) -> String {
+        match self {
+            UpdateSource::Endpoint(update) => update.body.clone().unwrap_or_default(),
+            UpdateSource::GitHub { release, .. } => release.body.clone(),
+        }
+    }
+
+    pub fn download_url(&self) -> String {
+        match self {
+            UpdateSource::Endpoint(update) => update.download_url.to_string(),
+            UpdateSource::GitHub { asset, .. } => asset.browser_download_url.clone(),
+        }
+    }
+
+    /// 下载并校验安装包，校验失败时不返回任何内容
+    pub async fn download(&self, app: &AppHandle) -> Result<Vec<u8>, String> {
+        let (bytes, signature) = match self {
+            UpdateSource::Endpoint(update) => {
+                // 优先使用增量补丁，不可用时下载完整安装包
+                let bytes = match delta::try_download(app, update).await {
+                    Some(bytes) => bytes,
+                    None => {
+                        // 支持暂停、继续和断点续传，自行下载后需要校验签名
+                        let bytes = download::fetch(app, update.download_url.as_str(), &update.version).await?;
+                        verify_signature(app, &bytes, &update.signature)
+                            .map_err(|e| format!("Update signature verification failed: {}", e))?;
+                        bytes
+                    }
+                };
+                (bytes, update.signature.clone())
+            }
+            UpdateSource::GitHub { client, release, asset } => {
+                client.download_verified_asset(app, release, asset).await?
+            }
+        };
+        delta::store_signature(&self.version(), &signature);
+        let _ = app.emit("update-completed", ());
+        // Windows 上回滚需要当前版本的安装程序
+        rollback::cache_current_installer(app).await;
+        Ok(bytes)
+    }
+
+    /// 安装已校验的安装包。restart 为 false 时只安装，用于应用退出时安装
+    pub fn install(&self, app: &AppHandle, bytes: Vec<u8>, restart: bool) -> Result<(), String> {
+        // 保存新版本的安装包，作为下一次增量更新的基准；缓存中的当前版本留作回滚
+        let current_version = app.package_info().version.to_string();
+        delta::store_package(&self.version(), &current_version, &bytes);
+
+        match self {
+            UpdateSource::Endpoint(update) => {
+                rollback::keep_current_installation(&current_version);
+                update
+                    .install(bytes)
+                    .map_err(|e| format!("Update installation failed: {}", e))?;
+                if !restart {
+                    return Ok(());
+                }
+                // 更新安装成功后，延迟重启应用
+                let app_clone = app.clone();
+                tokio::spawn(async move {
+                    // 给前端一点时间显示完成状态
+                    tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+                    let _ = app_clone.emit("update-restart", ());
+                    // 重启应用
+                    app_clone.restart();
+                });
+                Ok(())
+            }
+            UpdateSource::GitHub { asset, release, .. } => {
+                log::info!("Installing {} from GitHub release {}", asset.name, release.tag_name);
+                if restart {
+                    let _ = app.emit("update-restart", ());
+                }
+                rollback::install_package(app, bytes, restart, Some(&current_version))
+            }
+        }
+    }
+}
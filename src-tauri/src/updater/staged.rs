@@ -0,0 +1,100 @@
+use lazy_static::lazy_static;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use super::scheduler::InstallMode;
+use super::source::UpdateSource;
+use crate::i18n::t;
+
+/// 已下载并校验、等待安装的更新
+struct StagedUpdate {
+    source: UpdateSource,
+    bytes: Vec<u8>,
+}
+
+lazy_static! {
+    static ref STAGED: Mutex<Option<StagedUpdate>> = Mutex::new(None);
+}
+
+/// 暂存已下载的更新，按安装方式在退出时或计划的时间安装
+pub fn stage(app: &AppHandle, source: UpdateSource, bytes: Vec<u8>, mode: InstallMode, scheduled_time: Option<String>) {
+    let version = source.version();
+    *STAGED.lock().unwrap() = Some(StagedUpdate { source, bytes });
+    log::info!("Update {} downloaded, will be installed {:?}", version, mode);
+
+    let _ = app.emit("update-staged", &serde_json::json!({
+        "version": version,
+        "mode": mode,
+        "scheduled_time": scheduled_time,
+    }));
+
+    if mode == InstallMode::Scheduled {
+        if let Some(time) = scheduled_time {
+            schedule_install(app.clone(), version, time);
+        }
+    }
+}
+
+/// 等待安装的更新版本
+pub fn staged_version() -> Option<String> {
+    STAGED.lock().unwrap().as_ref().map(|staged| staged.source.version())
+}
+
+/// 安装暂存的更新，没有暂存的更新时返回 false
+pub fn install_staged(app: &AppHandle, restart: bool) -> Result<bool, String> {
+    let Some(staged) = STAGED.lock().unwrap().take() else {
+        return Ok(false);
+    };
+    log::info!("Installing staged update {}", staged.source.version());
+    staged.source.install(app, staged.bytes, restart)?;
+    Ok(true)
+}
+
+/// 应用退出时安装暂存的更新，不重新启动
+pub fn apply_on_exit(app: &AppHandle) {
+    if let Err(e) = install_staged(app, false) {
+        log::error!("Failed to install update on quit: {}", e);
+    }
+}
+
+// 到下一个 HH:MM（本地时间）时安装并重启
+fn schedule_install(app: AppHandle, version: String, time: String) {
+    let Ok(at) = chrono::NaiveTime::parse_from_str(&time, "%H:%M") else {
+        log::warn!("Invalid scheduled install time: {}", time);
+        return;
+    };
+
+    tauri::async_runtime::spawn(async move {
+        let now = chrono::Local::now();
+        let mut delay = at.signed_duration_since(now.time());
+        if delay <= chrono::Duration::zero() {
+            delay = delay + chrono::Duration::days(1);
+        }
+        log::info!("Staged update {} will be installed at {}", version, time);
+        tokio::time::sleep(delay.to_std().unwrap_or_default()).await;
+
+        // 期间可能已经在退出时或手动安装过，或者暂存了更新的版本
+        if staged_version().as_deref() != Some(version.as_str()) {
+            return;
+        }
+        if let Err(e) = install_staged(&app, true) {
+            log::error!("Scheduled update installation failed: {}", e);
+        }
+    });
+}
+
+// Tauri命令：获取等待安装的更新版本
+#[tauri::command]
+pub fn get_staged_update() -> Option<String> {
+    staged_version()
+}
+
+// Tauri命令：立即安装已下载的更新并重启
+#[tauri::command]
+pub async fn apply_staged_update(app: AppHandle) -> Result<(), String> {
+    if install_staged(&app, true)? {
+        Ok(())
+    } else {
+        Err(t("no_staged_update"))
+    }
+}
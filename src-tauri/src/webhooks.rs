@@ -0,0 +1,201 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::file_organizer::{FileOrganizedEvent, SessionKind, SessionSummary};
+use crate::i18n::{t, t_format};
+
+// 首次发送失败后最多重试的次数，间隔按 2、4、8、16 秒递增
+const MAX_RETRIES: u32 = 4;
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+// 错误事件容易连续出现，同一个地址每分钟最多发送一次
+const ERROR_THROTTLE_SECS: u64 = 60;
+
+/// 可以订阅的事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    FileOrganized,
+    Error,
+    SessionSummary,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::FileOrganized => "file_organized",
+            WebhookEvent::Error => "error",
+            WebhookEvent::SessionSummary => "session_summary",
+        }
+    }
+}
+
+/// 一个 Webhook 地址及其订阅的事件。事件以 JSON POST 发送，
+/// 同时带 text 和 content 字段，可以直接用于 Slack 和 Discord 的传入 Webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default = "default_events")]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_events() -> Vec<WebhookEvent> {
+    vec![WebhookEvent::FileOrganized, WebhookEvent::Error, WebhookEvent::SessionSummary]
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl WebhookConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let url = reqwest::Url::parse(self.url.trim()).map_err(|e| format!("Invalid webhook URL {}: {}", self.url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("Webhook URL must use http or https: {}", self.url));
+        }
+        Ok(())
+    }
+}
+
+pub fn validate_all(webhooks: &[WebhookConfig]) -> Result<(), String> {
+    webhooks.iter().try_for_each(WebhookConfig::validate)
+}
+
+lazy_static! {
+    static ref WEBHOOKS: RwLock<Vec<WebhookConfig>> = RwLock::new(Vec::new());
+    // 地址 -> 上次发送错误事件的时间
+    static ref LAST_ERROR_SENT: Mutex<HashMap<String, Instant>> = Mutex::new(HashMap::new());
+}
+
+/// 更新当前使用的 Webhook 列表（启动时和设置修改后调用）
+pub fn set(webhooks: &[WebhookConfig]) {
+    *WEBHOOKS.write().unwrap() = webhooks.to_vec();
+}
+
+pub fn file_organized(event: &FileOrganizedEvent) {
+    let text = t_format("webhook_file_organized", &[&event.actual_file_name, &event.category]);
+    publish(WebhookEvent::FileOrganized, text, serde_json::to_value(event).unwrap_or_default());
+}
+
+pub fn error(message: &str) {
+    let text = t_format("webhook_error", &[message]);
+    publish(WebhookEvent::Error, text, serde_json::json!({ "message": message }));
+}
+
+pub fn session_finished(summary: &SessionSummary) {
+    // 没有移动任何文件的监控不发送汇总
+    if summary.kind == SessionKind::Monitoring && summary.files_moved == 0 {
+        return;
+    }
+    let text = t_format(
+        "webhook_session_summary",
+        &[&summary.files_moved.to_string(), &summary.folder_path, &summary.unmatched_count.to_string()],
+    );
+    publish(WebhookEvent::SessionSummary, text, serde_json::to_value(summary).unwrap_or_default());
+}
+
+// 发送给所有订阅了该事件的地址，在后台发送，不阻塞整理
+fn publish(event: WebhookEvent, text: String, data: serde_json::Value) {
+    let mut targets: Vec<String> = WEBHOOKS
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|webhook| webhook.enabled && webhook.events.contains(&event))
+        .map(|webhook| webhook.url.trim().to_string())
+        .collect();
+    if event == WebhookEvent::Error {
+        let mut last_sent = LAST_ERROR_SENT.lock().unwrap();
+        targets.retain(|url| {
+            if last_sent.get(url).is_some_and(|sent| sent.elapsed() < Duration::from_secs(ERROR_THROTTLE_SECS)) {
+                return false;
+            }
+            last_sent.insert(url.clone(), Instant::now());
+            true
+        });
+    }
+    if targets.is_empty() {
+        return;
+    }
+
+    let body = payload(event, &text, data);
+    for url in targets {
+        let body = body.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = deliver(&url, &body).await {
+                log::warn!("Webhook delivery to {} failed: {}", url, e);
+            }
+        });
+    }
+}
+
+fn payload(event: WebhookEvent, text: &str, data: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "event": event.as_str(),
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+        "text": text,
+        "content": text,
+        "data": data,
+    })
+}
+
+// 网络错误、5xx 和 429 按指数退避重试，其他 4xx 说明地址或内容有问题，不再重试
+async fn deliver(url: &str, body: &serde_json::Value) -> Result<(), String> {
+    let client = crate::http::client();
+    let mut attempt = 0;
+    loop {
+        let result = client
+            .post(url)
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .json(body)
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => return Ok(()),
+            Ok(response) => {
+                let status = response.status();
+                if !status.is_server_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    return Err(format!("HTTP {}", status));
+                }
+                format!("HTTP {}", status)
+            }
+            Err(e) => e.to_string(),
+        };
+
+        if attempt >= MAX_RETRIES {
+            return Err(error);
+        }
+        attempt += 1;
+        log::debug!("Webhook delivery to {} failed ({}), retry {} of {}", url, error, attempt, MAX_RETRIES);
+        tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+    }
+}
+
+// Tauri命令：向指定地址发送一条测试消息，不重试
+#[tauri::command]
+pub async fn test_webhook(url: String) -> Result<(), String> {
+    let webhook = WebhookConfig {
+        url,
+        events: default_events(),
+        enabled: true,
+    };
+    webhook.validate()?;
+
+    let text = t("webhook_test_message");
+    let body = payload(WebhookEvent::SessionSummary, &text, serde_json::json!({ "test": true }));
+    let response = crate::http::client()
+        .post(webhook.url.trim())
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| t_format("webhook_test_failed", &[&e.to_string()]))?;
+    if !response.status().is_success() {
+        return Err(t_format("webhook_test_failed", &[&format!("HTTP {}", response.status())]));
+    }
+    crate::telemetry::record_feature("webhook_test");
+    Ok(())
+}
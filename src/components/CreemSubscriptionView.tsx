@@ -2,6 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
 import { useSubscriptionStore } from '../stores';
 import { useI18n } from '../contexts/I18nContext';
+import { errorMessage } from '../utils/tauri';
 
 interface UserPackage {
   id: string;
@@ -77,8 +78,8 @@ const CreemSubscriptionView: React.FC<CreemSubscriptionViewProps> = ({
       setCurrentSession(sessionId);
       startPolling();
     } catch (error) {
-      console.error('Failed to start purchase:', error);
-      alert(`${t('creemSubscription.purchaseFailed')}: ${error}`);
+      console.error('Failed to start purchase:', errorMessage(error));
+      alert(`${t('creemSubscription.purchaseFailed')}: ${errorMessage(error)}`);
     } finally {
       setIsLoading(false);
     }
@@ -116,7 +117,7 @@ const CreemSubscriptionView: React.FC<CreemSubscriptionViewProps> = ({
           }
         }
       } catch (error) {
-        console.error('Failed to check payment status:', error);
+        console.error('Failed to check payment status:', errorMessage(error));
         clearInterval(interval);
         setPollInterval(null);
         setIsPolling(false);
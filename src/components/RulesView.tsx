@@ -2,6 +2,7 @@ import React, { useState } from 'react';
 import { ask, message } from '@tauri-apps/plugin-dialog';
 import { Config, RulesTabType } from '../types';
 import { useConfigStore, useLoggerStore } from '../stores';
+import { categoryDisplayName } from '../stores/configStore';
 import { useI18n } from '../contexts/I18nContext';
 
 interface RulesViewProps {
@@ -61,8 +62,9 @@ const RulesView: React.FC<RulesViewProps> = ({ config, loading }) => {
   };
 
   const handleDeleteCategory = async (categoryName: string) => {
+    const displayName = categoryDisplayName(config, categoryName);
     const confirmed = await ask(
-      t('alerts.deleteCategoryConfirm', { category: categoryName }),
+      t('alerts.deleteCategoryConfirm', { category: displayName }),
       {
         title: t('common.confirm'),
         kind: 'warning',
@@ -75,7 +77,7 @@ const RulesView: React.FC<RulesViewProps> = ({ config, loading }) => {
 
     try {
       await deleteCategory(categoryName);
-      addLog(`✅ ${t('messages.categoryDeleted', { name: categoryName })}`);
+      addLog(`✅ ${t('messages.categoryDeleted', { name: displayName })}`);
     } catch (error) {
       addLog(
         `❌ ${t('errors.deleteCategoryFailed')}: ${
@@ -227,7 +229,9 @@ const RulesView: React.FC<RulesViewProps> = ({ config, loading }) => {
               if (extensions.length === 0) return null;
               return (
                 <div key={category} className='rule-card'>
-                  <div className='rule-card-header'>{category}</div>
+                  <div className='rule-card-header'>
+                    {categoryDisplayName(config, category)}
+                  </div>
                   <div className='rule-extensions'>{extensions.join(', ')}</div>
                 </div>
               );
@@ -286,7 +290,7 @@ const RulesView: React.FC<RulesViewProps> = ({ config, loading }) => {
                     return (
                       <div key={category} className='rule-item'>
                         <div className='rule-item-header'>
-                          <h4>{category}</h4>
+                          <h4>{categoryDisplayName(config, category)}</h4>
                           <button
                             className='delete-category-btn'
                             onClick={() => handleDeleteCategory(category)}
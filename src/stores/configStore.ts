@@ -5,6 +5,28 @@ import { tauriAPI } from '../utils/tauri';
 import { DEFAULT_CONFIG } from '../utils/defaultConfig';
 import { t } from '../contexts/I18nContext';
 
+// 内置分类的ID，显示名称按界面语言翻译
+const BUILTIN_CATEGORY_IDS = [
+  'images',
+  'documents',
+  'spreadsheets',
+  'presentations',
+  'audio',
+  'video',
+  'archives',
+  'programs',
+  'code',
+  'fonts',
+];
+
+// 分类的显示名称：内置分类使用翻译，自定义分类使用配置中的名称，都没有时显示ID
+export const categoryDisplayName = (config: Config, categoryId: string) => {
+  if (BUILTIN_CATEGORY_IDS.includes(categoryId)) {
+    return t(`categories.${categoryId}`);
+  }
+  return config.categoryNames?.[categoryId] ?? categoryId;
+};
+
 interface ConfigState {
   config: Config;
   loading: boolean;
@@ -95,8 +117,10 @@ export const useConfigStore = create<ConfigState>()(
         const newConfig = {
           ...config,
           categories: { ...config.categories },
+          categoryNames: { ...config.categoryNames },
         };
         delete newConfig.categories[categoryName];
+        delete newConfig.categoryNames[categoryName];
 
         await saveConfig(newConfig);
       },
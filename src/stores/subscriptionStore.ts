@@ -1,6 +1,7 @@
 import { create } from 'zustand';
 import { persist, createJSONStorage } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '../utils/tauri';
 
 interface PackageInfo {
     name: string;
@@ -32,7 +33,7 @@ export const useSubscriptionStore = create<SubscriptionState>()(
                     const packagesInfo = await invoke<PackagesResponse>('fetch_packages_from_server');
                     set({ packages: packagesInfo.packages });
                 } catch (error) {
-                    console.error('Failed to load packages from server:', error);
+                    console.error('Failed to load packages from server:', errorMessage(error));
                     // 如果服务端获取失败，回退到本地数据
                     try {
                         const localPackages = await invoke<PackagesResponse>('get_packages');
@@ -21,7 +21,8 @@ export interface PathConfig {
 }
 
 export interface Config {
-  categories: FileCategory; // 全局分类规则
+  categories: FileCategory; // 全局分类规则，键是分类ID
+  categoryNames?: Record<string, string>; // 分类ID -> 自定义分类的显示名称
   auto_organize?: boolean;
   notification_enabled?: boolean;
   rules?: any[];
@@ -55,6 +56,13 @@ export interface LogEntry {
   type?: 'info' | 'success' | 'error' | 'warning';
 }
 
+// 订阅和支付命令失败时返回的错误：code 是稳定的错误代码，message 已按当前语言翻译
+export interface CommandError {
+  code: string;
+  params?: Record<string, string>;
+  message: string;
+}
+
 export interface GeneralSettings {
   auto_start: boolean;
   theme: string;
@@ -1,6 +1,11 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
-import { Config, SubscriptionStatus, GeneralSettings } from '../types';
+import {
+  Config,
+  SubscriptionStatus,
+  GeneralSettings,
+  CommandError,
+} from '../types';
 
 // Check if we're running in Tauri environment
 function isTauriEnvironment(): boolean {
@@ -303,3 +308,14 @@ export class TauriAPI {
 }
 
 export const tauriAPI = TauriAPI.getInstance();
+
+// 命令失败时的错误信息：订阅命令返回 CommandError 对象，其他命令返回字符串
+export function errorMessage(error: unknown): string {
+  if (error instanceof Error) {
+    return error.message;
+  }
+  if (error && typeof error === 'object' && 'message' in error) {
+    return (error as CommandError).message;
+  }
+  return String(error);
+}